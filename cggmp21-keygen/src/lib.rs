@@ -15,11 +15,16 @@ extern crate std;
 
 pub mod progress;
 pub mod security_level;
+pub mod timeout;
 
 /// Non-threshold DKG specific types
 mod non_threshold;
+/// Non-threshold batch DKG specific types
+mod non_threshold_batch;
 /// Threshold DKG specific types
 mod threshold;
+/// Threshold batch DKG specific types
+mod threshold_batch;
 
 mod errors;
 mod execution_id;
@@ -28,14 +33,15 @@ mod utils;
 use alloc::vec::Vec;
 
 use digest::Digest;
-use generic_ec::Curve;
+use generic_ec::{Curve, NonZero, Scalar};
 use rand_core::{CryptoRng, RngCore};
-use round_based::{Mpc, MsgId, PartyIndex};
+use round_based::{Mpc, PartyIndex};
 
 #[doc(inline)]
 pub use key_share;
 
 use crate::progress::Tracer;
+use crate::timeout::{RoundTimeout, RoundTimer};
 use crate::{
     errors::IoError,
     key_share::{CoreKeyShare, InvalidCoreShare},
@@ -45,6 +51,11 @@ use crate::{
 pub use self::execution_id::ExecutionId;
 #[doc(no_inline)]
 pub use self::msg::{non_threshold::Msg as NonThresholdMsg, threshold::Msg as ThresholdMsg};
+#[doc(no_inline)]
+pub use self::msg::{
+    non_threshold_batch::Msg as NonThresholdBatchMsg, threshold_batch::Msg as ThresholdBatchMsg,
+};
+pub use self::utils::{AbortBlame, Aborted};
 
 /// Defines default choice for digest and security level used across the crate
 mod default_choice {
@@ -61,6 +72,20 @@ pub mod msg {
     /// Messages types related to threshold DKG protocol
     pub mod threshold {
         pub use crate::threshold::{
+            Msg, MsgComplaints, MsgComplaintsReliabilityCheck, MsgJustification,
+            MsgJustificationReliabilityCheck, MsgReliabilityCheck, MsgRound1, MsgRound2Broad,
+            MsgRound2Uni, MsgRound3,
+        };
+    }
+    /// Messages types related to non threshold batch DKG protocol
+    pub mod non_threshold_batch {
+        pub use crate::non_threshold_batch::{
+            Msg, MsgReliabilityCheck, MsgRound1, MsgRound2, MsgRound3,
+        };
+    }
+    /// Messages types related to threshold batch DKG protocol
+    pub mod threshold_batch {
+        pub use crate::threshold_batch::{
             Msg, MsgReliabilityCheck, MsgRound1, MsgRound2Broad, MsgRound2Uni, MsgRound3,
         };
     }
@@ -94,6 +119,10 @@ pub struct GenericKeygenBuilder<'a, E: Curve, M, L: SecurityLevel, D: Digest> {
     optional_t: M,
     execution_id: ExecutionId<'a>,
     tracer: Option<&'a mut dyn Tracer>,
+    round_timer: Option<&'a mut dyn RoundTimer>,
+    share_preimages: Option<Vec<NonZero<Scalar<E>>>>,
+    weights: Option<Vec<u16>>,
+    ranks: Option<Vec<u16>>,
     #[cfg(feature = "hd-wallet")]
     hd_enabled: bool,
     _params: core::marker::PhantomData<(E, L, D)>,
@@ -121,6 +150,10 @@ where
             reliable_broadcast_enforced: true,
             execution_id: eid,
             tracer: None,
+            round_timer: None,
+            share_preimages: None,
+            weights: None,
+            ranks: None,
             #[cfg(feature = "hd-wallet")]
             hd_enabled: true,
             _params: core::marker::PhantomData,
@@ -143,6 +176,10 @@ where
             reliable_broadcast_enforced: self.reliable_broadcast_enforced,
             execution_id: self.execution_id,
             tracer: self.tracer,
+            round_timer: self.round_timer,
+            share_preimages: self.share_preimages,
+            weights: self.weights,
+            ranks: self.ranks,
             #[cfg(feature = "hd-wallet")]
             hd_enabled: self.hd_enabled,
             _params: core::marker::PhantomData,
@@ -160,6 +197,10 @@ where
             reliable_broadcast_enforced: self.reliable_broadcast_enforced,
             execution_id: self.execution_id,
             tracer: self.tracer,
+            round_timer: self.round_timer,
+            share_preimages: self.share_preimages,
+            weights: self.weights,
+            ranks: self.ranks,
             #[cfg(feature = "hd-wallet")]
             hd_enabled: self.hd_enabled,
             _params: core::marker::PhantomData,
@@ -178,6 +219,10 @@ where
             reliable_broadcast_enforced: self.reliable_broadcast_enforced,
             execution_id: self.execution_id,
             tracer: self.tracer,
+            round_timer: self.round_timer,
+            share_preimages: self.share_preimages,
+            weights: self.weights,
+            ranks: self.ranks,
             #[cfg(feature = "hd-wallet")]
             hd_enabled: self.hd_enabled,
             _params: core::marker::PhantomData,
@@ -190,6 +235,16 @@ where
         self
     }
 
+    /// Sets a timer that limits how long each round of the protocol may take
+    ///
+    /// If some parties don't send their messages before the round deadline, protocol fails
+    /// with an error that lists them (see [`KeygenError::timed_out`]). See [`timeout`] module
+    /// for more details.
+    pub fn set_round_timer(mut self, timer: &'a mut dyn RoundTimer) -> Self {
+        self.round_timer = Some(timer);
+        self
+    }
+
     #[doc = include_str!("../docs/enforce_reliable_broadcast.md")]
     pub fn enforce_reliable_broadcast(self, enforce: bool) -> Self {
         Self {
@@ -220,6 +275,7 @@ where
     {
         non_threshold::run_keygen(
             self.tracer,
+            self.round_timer,
             self.i,
             self.n,
             self.reliable_broadcast_enforced,
@@ -248,6 +304,60 @@ where
     {
         round_based::state_machine::wrap_protocol(|party| self.start(rng, party))
     }
+
+    /// Starts batch key generation
+    ///
+    /// Generates `batch_size` independent keys in one protocol execution. Messages carry data
+    /// of all keys at once, so generating a batch takes the same amount of rounds as generating
+    /// a single key. All parties must use the same `batch_size`.
+    ///
+    /// Returns key shares in the same order for all parties: `k`-th key share of every party
+    /// corresponds to the same key.
+    pub async fn start_batch<R, M>(
+        self,
+        batch_size: u16,
+        rng: &mut R,
+        party: M,
+    ) -> Result<Vec<CoreKeyShare<E>>, KeygenError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = non_threshold_batch::Msg<E, L, D>>,
+    {
+        non_threshold_batch::run_batch_keygen(
+            self.tracer,
+            self.round_timer,
+            self.i,
+            self.n,
+            batch_size,
+            self.reliable_broadcast_enforced,
+            self.execution_id,
+            rng,
+            party,
+            #[cfg(feature = "hd-wallet")]
+            self.hd_enabled,
+        )
+        .await
+    }
+
+    /// Returns a state machine that can be used to carry out the batch key generation protocol
+    ///
+    /// See [`start_batch`](Self::start_batch) and [`round_based::state_machine`] for details.
+    #[cfg(feature = "state-machine")]
+    pub fn into_batch_state_machine<R>(
+        self,
+        batch_size: u16,
+        rng: &'a mut R,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<Vec<CoreKeyShare<E>>, KeygenError>,
+        Msg = non_threshold_batch::Msg<E, L, D>,
+    > + 'a
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(move |party| {
+            self.start_batch(batch_size, rng, party)
+        })
+    }
 }
 
 impl<'a, E, L, D> GenericKeygenBuilder<'a, E, WithThreshold, L, D>
@@ -256,6 +366,58 @@ where
     L: SecurityLevel,
     D: Digest + Clone + 'static,
 {
+    /// Specifies share preimages
+    ///
+    /// Key share of $j$-th party will be $x_j = F(I_j)$ where $F$ is the shared polynomial and
+    /// $I_j$ is $j$-th element of `preimages`. By default, $I_j = j + 1$. Custom preimages can
+    /// be used to tie shares to stable identities of parties.
+    ///
+    /// Preimages must be non-zero, pairwise distinct, and there must be exactly $n$ of them (or
+    /// as many as the total weight, if [weights](Self::set_weights) are specified), otherwise
+    /// protocol returns an error. All parties must provide the same preimages.
+    pub fn set_share_preimages(mut self, preimages: Vec<NonZero<Scalar<E>>>) -> Self {
+        self.share_preimages = Some(preimages);
+        self
+    }
+
+    /// Specifies weights of the parties
+    ///
+    /// Party with weight $w$ obtains $w$ key shares at distinct points, so it counts as $w$
+    /// signers: any set of parties with total weight of at least $t$ can sign. `weights[j]` is
+    /// weight of $j$-th party. By default, weight of every party is 1.
+    ///
+    /// Weights must be non-zero, there must be exactly $n$ of them, and their sum must fit
+    /// into `u16`, otherwise protocol returns an error. All parties must provide the same weights.
+    /// If [share preimages](Self::set_share_preimages) are specified, there must be as many of
+    /// them as the total weight, grouped by parties in order.
+    ///
+    /// Weighted keygen can't be carried out in [batch mode](Self::start_batch).
+    pub fn set_weights(mut self, weights: Vec<u16>) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Makes the key hierarchical by specifying ranks of key shares
+    ///
+    /// Key share of rank $r$ is value of $r$-th derivative of the shared polynomial (Tassa's
+    /// hierarchical threshold secret sharing). Key shares of lower rank belong to a higher tier:
+    /// set of key shares can sign only if it contains $t$ key shares such that $k$-th lowest rank
+    /// among them (counting from 0) doesn't exceed $k$. For instance, policy "any 3 signers, at
+    /// least one of which is from the compliance tier" is expressed by $t = 3$, rank 0 for parties
+    /// of the compliance tier and rank 1 for everyone else. See
+    /// [`VssSetup::ranks`](crate::key_share::VssSetup::ranks) for details.
+    ///
+    /// `ranks[j]` is rank of $j$-th key share (key share of $j$-th party, unless
+    /// [weights](Self::set_weights) are specified). Ranks must be less than $t$, and at least one
+    /// set of key shares must be able to sign, otherwise protocol returns an error. All parties
+    /// must provide the same ranks.
+    ///
+    /// Hierarchical keygen can't be carried out in [batch mode](Self::start_batch).
+    pub fn set_ranks(mut self, ranks: Vec<u16>) -> Self {
+        self.ranks = Some(ranks);
+        self
+    }
+
     /// Starts threshold key generation
     pub async fn start<R, M>(self, rng: &mut R, party: M) -> Result<CoreKeyShare<E>, KeygenError>
     where
@@ -264,10 +426,15 @@ where
     {
         threshold::run_threshold_keygen(
             self.tracer,
+            self.round_timer,
             self.i,
             self.optional_t.0,
             self.n,
+            self.weights,
+            self.ranks,
+            self.share_preimages,
             self.reliable_broadcast_enforced,
+            false,
             self.execution_id,
             rng,
             party,
@@ -275,6 +442,7 @@ where
             self.hd_enabled,
         )
         .await
+        .map(|(key_share, _)| key_share)
     }
 
     /// Returns a state machine that can be used to carry out the key generation protocol
@@ -293,6 +461,152 @@ where
     {
         round_based::state_machine::wrap_protocol(|party| self.start(rng, party))
     }
+
+    /// Starts robust threshold key generation
+    ///
+    /// Unlike [`start`](Self::start), party that sent invalid secret shares doesn't abort the
+    /// protocol. Instead, parties that received invalid shares publicly complain about the dealer,
+    /// and the dealer must reveal the shares it sent to the complaining parties. Dealers that
+    /// fail to reveal valid shares are disqualified: their contribution is excluded from the key,
+    /// and keygen completes among the qualified parties. Disqualified parties are listed in the
+    /// output.
+    ///
+    /// Robust keygen takes one more round than regular one, and one more round on top of that
+    /// if somebody complained. Shares revealed in justification round become known to everyone,
+    /// which doesn't compromise the key as long as complaining party is honest: the revealed
+    /// shares are only part of its key share.
+    ///
+    /// All honest parties must receive the same complaints and justifications, otherwise they
+    /// would disqualify different dealers. If [reliable broadcast](Self::enforce_reliable_broadcast)
+    /// is enforced, complaints and justifications are followed by an additional round each, in
+    /// which parties make sure they received the same messages, and abort otherwise. Reliable
+    /// broadcast must not be disabled unless the channel is reliable by itself.
+    ///
+    /// All parties must agree on whether keygen is robust.
+    pub async fn start_robust<R, M>(
+        self,
+        rng: &mut R,
+        party: M,
+    ) -> Result<RobustKeygenOutput<E>, KeygenError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = threshold::Msg<E, L, D>>,
+    {
+        threshold::run_threshold_keygen(
+            self.tracer,
+            self.round_timer,
+            self.i,
+            self.optional_t.0,
+            self.n,
+            self.weights,
+            self.ranks,
+            self.share_preimages,
+            self.reliable_broadcast_enforced,
+            true,
+            self.execution_id,
+            rng,
+            party,
+            #[cfg(feature = "hd-wallet")]
+            self.hd_enabled,
+        )
+        .await
+        .map(|(key_share, disqualified)| RobustKeygenOutput {
+            key_share,
+            disqualified,
+        })
+    }
+
+    /// Returns a state machine that can be used to carry out the robust key generation protocol
+    ///
+    /// See [`start_robust`](Self::start_robust) and [`round_based::state_machine`] for details.
+    #[cfg(feature = "state-machine")]
+    pub fn into_robust_state_machine<R>(
+        self,
+        rng: &'a mut R,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<RobustKeygenOutput<E>, KeygenError>,
+        Msg = threshold::Msg<E, L, D>,
+    > + 'a
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(|party| self.start_robust(rng, party))
+    }
+
+    /// Starts threshold batch key generation
+    ///
+    /// Generates `batch_size` independent keys in one protocol execution. Messages carry data
+    /// of all keys at once, so generating a batch takes the same amount of rounds as generating
+    /// a single key. All parties must use the same `batch_size`, and all keys are shared at
+    /// the same [share preimages](Self::set_share_preimages).
+    ///
+    /// Returns key shares in the same order for all parties: `k`-th key share of every party
+    /// corresponds to the same key.
+    pub async fn start_batch<R, M>(
+        self,
+        batch_size: u16,
+        rng: &mut R,
+        party: M,
+    ) -> Result<Vec<CoreKeyShare<E>>, KeygenError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = threshold_batch::Msg<E, L, D>>,
+    {
+        if self.weights.is_some() {
+            return Err(InvalidArgs::WeightedBatch.into());
+        }
+        if self.ranks.is_some() {
+            return Err(InvalidArgs::HierarchicalBatch.into());
+        }
+        threshold_batch::run_threshold_batch_keygen(
+            self.tracer,
+            self.round_timer,
+            self.i,
+            self.optional_t.0,
+            self.n,
+            batch_size,
+            self.share_preimages,
+            self.reliable_broadcast_enforced,
+            self.execution_id,
+            rng,
+            party,
+            #[cfg(feature = "hd-wallet")]
+            self.hd_enabled,
+        )
+        .await
+    }
+
+    /// Returns a state machine that can be used to carry out the batch key generation protocol
+    ///
+    /// See [`start_batch`](Self::start_batch) and [`round_based::state_machine`] for details.
+    #[cfg(feature = "state-machine")]
+    pub fn into_batch_state_machine<R>(
+        self,
+        batch_size: u16,
+        rng: &'a mut R,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<Vec<CoreKeyShare<E>>, KeygenError>,
+        Msg = threshold_batch::Msg<E, L, D>,
+    > + 'a
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(move |party| {
+            self.start_batch(batch_size, rng, party)
+        })
+    }
+}
+
+/// Output of [robust threshold keygen](GenericKeygenBuilder::start_robust)
+#[derive(Clone)]
+pub struct RobustKeygenOutput<E: Curve> {
+    /// Key share of local party
+    pub key_share: CoreKeyShare<E>,
+    /// Indexes of parties disqualified during keygen, sorted in ascending order
+    ///
+    /// Contribution of disqualified parties is excluded from the key. They still hold key shares,
+    /// as these are derived from contributions of the qualified parties.
+    pub disqualified: Vec<PartyIndex>,
 }
 
 /// Keygen protocol error
@@ -301,9 +615,31 @@ where
 #[displaydoc("keygen protocol is failed to complete")]
 pub struct KeygenError(#[cfg_attr(feature = "std", source)] Reason);
 
+impl KeygenError {
+    /// If protocol was aborted by malicious party, returns information about
+    /// the abort: its reason and parties to blame
+    pub fn aborted(&self) -> Option<&KeygenAborted> {
+        match &self.0 {
+            Reason::Aborted(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// If protocol failed because some parties didn't send their messages before round
+    /// deadline, returns the round and parties that stalled
+    pub fn timed_out(&self) -> Option<&RoundTimeout> {
+        match &self.0 {
+            Reason::Timeout(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 crate::errors::impl_from! {
     impl From for KeygenError {
         err: KeygenAborted => KeygenError(Reason::Aborted(err)),
+        err: RoundTimeout => KeygenError(Reason::Timeout(err)),
+        err: InvalidArgs => KeygenError(Reason::InvalidArgs(err)),
         err: IoError => KeygenError(Reason::IoError(err)),
         err: Bug => KeygenError(Reason::Bug(err)),
     }
@@ -312,11 +648,16 @@ crate::errors::impl_from! {
 #[derive(Debug, displaydoc::Display)]
 #[cfg_attr(feature = "std", derive(thiserror::Error))]
 enum Reason {
+    #[displaydoc("invalid arguments")]
+    InvalidArgs(#[cfg_attr(feature = "std", source)] InvalidArgs),
     /// Protocol was maliciously aborted by another party
     #[displaydoc("protocol was aborted by malicious party")]
     Aborted(#[cfg_attr(feature = "std", source)] KeygenAborted),
     #[displaydoc("i/o error")]
     IoError(#[cfg_attr(feature = "std", source)] IoError),
+    /// Some parties didn't send their messages before round deadline
+    #[displaydoc("round deadline expired")]
+    Timeout(#[cfg_attr(feature = "std", source)] RoundTimeout),
     /// Bug occurred
     #[displaydoc("bug occurred")]
     Bug(Bug),
@@ -328,25 +669,64 @@ impl From<KeygenAborted> for Reason {
     }
 }
 
-/// Error indicating that protocol was aborted by malicious party
-///
-/// It _can be_ cryptographically proven, but we do not support it yet.
-#[derive(Debug, displaydoc::Display)]
+/// Error indicating that keygen was aborted by malicious party
+pub type KeygenAborted = Aborted<KeygenAbortReason>;
+
+/// Reason for keygen abort: which exact check has failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, displaydoc::Display)]
 #[cfg_attr(feature = "std", derive(thiserror::Error))]
-enum KeygenAborted {
-    #[displaydoc("party decommitment doesn't match commitment: {0:?}")]
-    InvalidDecommitment(Vec<utils::AbortBlame>),
-    #[displaydoc("party provided invalid schnorr proof: {0:?}")]
-    InvalidSchnorrProof(Vec<utils::AbortBlame>),
-    #[displaydoc("party secret share is not consistent: {parties:?}")]
-    FeldmanVerificationFailed { parties: Vec<u16> },
-    #[displaydoc("party data size is not suitable for threshold parameters: {parties:?}")]
-    InvalidDataSize { parties: Vec<u16> },
+#[non_exhaustive]
+pub enum KeygenAbortReason {
+    /// Decommitment doesn't match commitment
+    #[displaydoc("party decommitment doesn't match commitment")]
+    InvalidDecommitment,
+    /// Party provided invalid schnorr proof
+    #[displaydoc("party provided invalid schnorr proof")]
+    InvalidSchnorrProof,
+    /// Secret share is not consistent with party's public polynomial
+    #[displaydoc("party secret share is not consistent")]
+    FeldmanVerificationFailed,
+    /// Party's data size doesn't match threshold parameters
+    #[displaydoc("party data size is not suitable for threshold parameters")]
+    InvalidDataSize,
+    /// Party claims it received different messages in round 1 than other parties
     #[displaydoc("round1 wasn't reliable")]
-    Round1NotReliable(Vec<(PartyIndex, MsgId)>),
+    Round1NotReliable,
+    /// Party claims it received different complaints than other parties (robust keygen)
+    #[displaydoc("complaints round wasn't reliable")]
+    ComplaintsNotReliable,
+    /// Party claims it received different justifications than other parties (robust keygen)
+    #[displaydoc("justification round wasn't reliable")]
+    JustificationNotReliable,
+    /// Party did not generate chain code
     #[cfg(feature = "hd-wallet")]
-    #[displaydoc("party did not generate chain code: {0:?}")]
-    MissingChainCode(Vec<utils::AbortBlame>),
+    #[displaydoc("party did not generate chain code")]
+    MissingChainCode,
+}
+
+#[derive(Debug, displaydoc::Display)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+enum InvalidArgs {
+    #[displaydoc("amount of share preimages doesn't match amount of parties")]
+    MismatchedAmountOfPreimages,
+    #[displaydoc("share preimages are not distinct")]
+    DuplicatedPreimage,
+    #[displaydoc("batch size must be at least 1")]
+    EmptyBatch,
+    #[displaydoc("amount of weights doesn't match amount of parties")]
+    MismatchedAmountOfWeights,
+    #[displaydoc("weight of a party is zero")]
+    ZeroWeight,
+    #[displaydoc("total weight of parties overflows u16")]
+    TotalWeightOverflow,
+    #[displaydoc("batch keygen doesn't support weights")]
+    WeightedBatch,
+    #[displaydoc("amount of ranks doesn't match amount of key shares")]
+    MismatchedAmountOfRanks,
+    #[displaydoc("ranks must be less than threshold, and at least one set of key shares must be able to sign")]
+    InvalidRanks,
+    #[displaydoc("batch keygen doesn't support ranks")]
+    HierarchicalBatch,
 }
 
 #[derive(Debug, displaydoc::Display)]
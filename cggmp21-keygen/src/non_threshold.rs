@@ -5,12 +5,13 @@ use generic_ec::{Curve, NonZero, Point, Scalar, SecretScalar};
 use generic_ec_zkp::schnorr_pok;
 use rand_core::{CryptoRng, RngCore};
 use round_based::{
-    rounds_router::simple_store::RoundInput, rounds_router::RoundsRouter, Delivery, Mpc, MpcParty,
-    Outgoing, ProtocolMessage, SinkExt,
+    rounds_router::simple_store::RoundInput, Delivery, Mpc, MpcParty, Outgoing, ProtocolMessage,
+    SinkExt,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::progress::Tracer;
+use crate::timeout::{RoundTimer, RoundsRouter};
 use crate::{
     errors::IoError,
     key_share::{CoreKeyShare, DirtyCoreKeyShare, DirtyKeyInfo, Validate},
@@ -18,7 +19,7 @@ use crate::{
     utils, ExecutionId,
 };
 
-use super::{Bug, KeygenAborted, KeygenError};
+use super::{Bug, KeygenAbortReason, KeygenAborted, KeygenError};
 
 macro_rules! prefixed {
     ($name:tt) => {
@@ -121,6 +122,7 @@ mod unambiguous {
 
 pub async fn run_keygen<E, R, M, L, D>(
     mut tracer: Option<&mut dyn Tracer>,
+    round_timer: Option<&mut dyn RoundTimer>,
     i: u16,
     n: u16,
     reliable_broadcast_enforced: bool,
@@ -142,12 +144,12 @@ where
     let MpcParty { delivery, .. } = party.into_party();
     let (incomings, mut outgoings) = delivery.split();
 
-    let mut rounds = RoundsRouter::<Msg<E, L, D>>::builder();
+    let mut rounds = RoundsRouter::<Msg<E, L, D>>::builder(i, n);
     let round1 = rounds.add_round(RoundInput::<MsgRound1<D>>::broadcast(i, n));
     let round1_sync = rounds.add_round(RoundInput::<MsgReliabilityCheck<D>>::broadcast(i, n));
     let round2 = rounds.add_round(RoundInput::<MsgRound2<E, L>>::broadcast(i, n));
     let round3 = rounds.add_round(RoundInput::<MsgRound3<E>>::broadcast(i, n));
-    let mut rounds = rounds.listen(incomings);
+    let mut rounds = rounds.listen(incomings, round_timer);
 
     // Round 1
     tracer.round_begins();
@@ -206,7 +208,7 @@ where
     tracer.receive_msgs();
     let commitments = rounds
         .complete(round1)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
@@ -233,7 +235,7 @@ where
         tracer.receive_msgs();
         let round1_hashes = rounds
             .complete(round1_sync)
-            .await
+            .await?
             .map_err(IoError::receive_message)?;
         tracer.msgs_received();
 
@@ -241,10 +243,14 @@ where
         let parties_have_different_hashes = round1_hashes
             .into_iter_indexed()
             .filter(|(_j, _msg_id, hash_j)| hash_j.0 != h_i)
-            .map(|(j, msg_id, _)| (j, msg_id))
+            .map(|(j, msg_id, _)| utils::AbortBlame::new(j, msg_id, msg_id))
             .collect::<Vec<_>>();
         if !parties_have_different_hashes.is_empty() {
-            return Err(KeygenAborted::Round1NotReliable(parties_have_different_hashes).into());
+            return Err(KeygenAborted::new(
+                KeygenAbortReason::Round1NotReliable,
+                parties_have_different_hashes,
+            )
+            .into());
         }
     }
 
@@ -261,7 +267,7 @@ where
     tracer.receive_msgs();
     let decommitments = rounds
         .complete(round2)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
@@ -275,7 +281,7 @@ where
         com.commitment != com_expected
     });
     if !blame.is_empty() {
-        return Err(KeygenAborted::InvalidDecommitment(blame).into());
+        return Err(KeygenAborted::new(KeygenAbortReason::InvalidDecommitment, blame).into());
     }
 
     #[cfg(feature = "hd-wallet")]
@@ -283,7 +289,7 @@ where
         tracer.stage("Calculate chain_code");
         let blame = utils::collect_simple_blame(&decommitments, |decom| decom.chain_code.is_none());
         if !blame.is_empty() {
-            return Err(KeygenAborted::MissingChainCode(blame).into());
+            return Err(KeygenAborted::new(KeygenAbortReason::MissingChainCode, blame).into());
         }
         Some(decommitments.iter_including_me(&my_decommitment).try_fold(
             hd_wallet::ChainCode::default(),
@@ -327,7 +333,7 @@ where
     tracer.receive_msgs();
     let sch_proofs = rounds
         .complete(round3)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
@@ -345,7 +351,7 @@ where
             .is_err()
     });
     if !blame.is_empty() {
-        return Err(KeygenAborted::InvalidSchnorrProof(blame).into());
+        return Err(KeygenAborted::new(KeygenAbortReason::InvalidSchnorrProof, blame).into());
     }
 
     tracer.protocol_ends();
@@ -370,6 +376,7 @@ where
             chain_code,
         },
         x: x_i,
+        extra_x: Vec::new(),
     }
     .validate()
     .map_err(|e| Bug::InvalidKeyShare(e.into_error()))?)
@@ -0,0 +1,439 @@
+use alloc::vec::Vec;
+
+use digest::Digest;
+use generic_ec::{Curve, NonZero, Point, Scalar, SecretScalar};
+use generic_ec_zkp::schnorr_pok;
+use rand_core::{CryptoRng, RngCore};
+use round_based::{
+    rounds_router::simple_store::RoundInput, Delivery, Mpc, MpcParty, Outgoing, ProtocolMessage,
+    SinkExt,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::progress::Tracer;
+use crate::timeout::{RoundTimer, RoundsRouter};
+use crate::{
+    errors::IoError,
+    key_share::{CoreKeyShare, DirtyCoreKeyShare, DirtyKeyInfo, Validate},
+    security_level::SecurityLevel,
+    utils, ExecutionId,
+};
+
+use super::{Bug, InvalidArgs, KeygenAbortReason, KeygenAborted, KeygenError};
+
+macro_rules! prefixed {
+    ($name:tt) => {
+        concat!("dfns.cggmp21.keygen.non_threshold_batch.", $name)
+    };
+}
+
+/// Message of batch key generation protocol
+#[derive(ProtocolMessage, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum Msg<E: Curve, L: SecurityLevel, D: Digest> {
+    /// Round 1 message
+    Round1(MsgRound1<D>),
+    /// Reliability check message (optional additional round)
+    ReliabilityCheck(MsgReliabilityCheck<D>),
+    /// Round 2 message
+    Round2(MsgRound2<E, L>),
+    /// Round 3 message
+    Round3(MsgRound3<E>),
+}
+
+/// Message from round 1
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[serde(bound = "")]
+#[udigest(bound = "")]
+#[udigest(tag = prefixed!("round1"))]
+pub struct MsgRound1<D: Digest> {
+    /// $V_i$, commitment to data of all keys being generated
+    #[udigest(as_bytes)]
+    pub commitment: digest::Output<D>,
+}
+/// Message from round 2
+#[serde_with::serde_as]
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[serde(bound = "")]
+#[udigest(bound = "")]
+#[udigest(tag = prefixed!("round2"))]
+pub struct MsgRound2<E: Curve, L: SecurityLevel> {
+    /// `rid_i`
+    #[serde_as(as = "utils::HexOrBin")]
+    #[udigest(as_bytes)]
+    pub rid: L::Rid,
+    /// $X_{i,k}$ for every key $k$
+    pub Xs: Vec<NonZero<Point<E>>>,
+    /// $A_{i,k}$ for every key $k$
+    pub sch_commits: Vec<schnorr_pok::Commit<E>>,
+    /// Party contribution to chain code of every key
+    #[cfg(feature = "hd-wallet")]
+    #[serde_as(as = "Option<Vec<utils::HexOrBin>>")]
+    #[udigest(as = Option<Vec<udigest::Bytes>>)]
+    pub chain_codes: Option<Vec<hd_wallet::ChainCode>>,
+    /// $u_i$
+    #[serde(with = "hex::serde")]
+    #[udigest(as_bytes)]
+    pub decommit: L::Rid,
+}
+/// Message from round 3
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound3<E: Curve> {
+    /// $\psi_{i,k}$ for every key $k$
+    pub sch_proofs: Vec<schnorr_pok::Proof<E>>,
+}
+/// Message parties exchange to ensure reliability of broadcast channel
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgReliabilityCheck<D: Digest>(pub digest::Output<D>);
+
+mod unambiguous {
+    use crate::{ExecutionId, SecurityLevel};
+    use generic_ec::Curve;
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("hash_commitment"))]
+    #[udigest(bound = "")]
+    pub struct HashCom<'a, E: Curve, L: SecurityLevel> {
+        pub sid: ExecutionId<'a>,
+        pub party_index: u16,
+        pub decommitment: &'a super::MsgRound2<E, L>,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("schnorr_pok"))]
+    #[udigest(bound = "")]
+    pub struct SchnorrPok<'a> {
+        pub sid: ExecutionId<'a>,
+        pub prover: u16,
+        pub key_index: u16,
+        #[udigest(as_bytes)]
+        pub rid: &'a [u8],
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("echo_round"))]
+    #[udigest(bound = "")]
+    pub struct Echo<'a, D: digest::Digest> {
+        pub sid: ExecutionId<'a>,
+        pub commitment: &'a super::MsgRound1<D>,
+    }
+}
+
+pub async fn run_batch_keygen<E, R, M, L, D>(
+    mut tracer: Option<&mut dyn Tracer>,
+    round_timer: Option<&mut dyn RoundTimer>,
+    i: u16,
+    n: u16,
+    batch_size: u16,
+    reliable_broadcast_enforced: bool,
+    sid: ExecutionId<'_>,
+    rng: &mut R,
+    party: M,
+    #[cfg(feature = "hd-wallet")] hd_enabled: bool,
+) -> Result<Vec<CoreKeyShare<E>>, KeygenError>
+where
+    E: Curve,
+    L: SecurityLevel,
+    D: Digest + Clone + 'static,
+    R: RngCore + CryptoRng,
+    M: Mpc<ProtocolMessage = Msg<E, L, D>>,
+{
+    tracer.protocol_begins();
+
+    if batch_size == 0 {
+        return Err(InvalidArgs::EmptyBatch.into());
+    }
+    let k = usize::from(batch_size);
+
+    tracer.stage("Setup networking");
+    let MpcParty { delivery, .. } = party.into_party();
+    let (incomings, mut outgoings) = delivery.split();
+
+    let mut rounds = RoundsRouter::<Msg<E, L, D>>::builder(i, n);
+    let round1 = rounds.add_round(RoundInput::<MsgRound1<D>>::broadcast(i, n));
+    let round1_sync = rounds.add_round(RoundInput::<MsgReliabilityCheck<D>>::broadcast(i, n));
+    let round2 = rounds.add_round(RoundInput::<MsgRound2<E, L>>::broadcast(i, n));
+    let round3 = rounds.add_round(RoundInput::<MsgRound3<E>>::broadcast(i, n));
+    let mut rounds = rounds.listen(incomings, round_timer);
+
+    // Round 1
+    tracer.round_begins();
+
+    tracer.stage("Sample x_i, rid_i, chain_code for every key");
+    let xs = (0..k)
+        .map(|_| NonZero::<SecretScalar<E>>::random(rng))
+        .collect::<Vec<_>>();
+    let Xs = xs
+        .iter()
+        .map(|x_i| Point::generator() * x_i)
+        .collect::<Vec<_>>();
+
+    let mut rid = L::Rid::default();
+    rng.fill_bytes(rid.as_mut());
+
+    #[cfg(feature = "hd-wallet")]
+    let chain_codes_local = if hd_enabled {
+        Some(
+            (0..k)
+                .map(|_| {
+                    let mut chain_code = hd_wallet::ChainCode::default();
+                    rng.fill_bytes(&mut chain_code);
+                    chain_code
+                })
+                .collect::<Vec<_>>(),
+        )
+    } else {
+        None
+    };
+
+    tracer.stage("Sample schnorr commitments");
+    let (sch_secrets, sch_commits): (Vec<_>, Vec<_>) = (0..k)
+        .map(|_| schnorr_pok::prover_commits_ephemeral_secret::<E, _>(rng))
+        .unzip();
+
+    tracer.stage("Commit to public data");
+    let my_decommitment = MsgRound2 {
+        rid,
+        Xs,
+        sch_commits,
+        #[cfg(feature = "hd-wallet")]
+        chain_codes: chain_codes_local,
+        decommit: {
+            let mut nonce = L::Rid::default();
+            rng.fill_bytes(nonce.as_mut());
+            nonce
+        },
+    };
+    let hash_commit = udigest::hash::<D>(&unambiguous::HashCom {
+        sid,
+        party_index: i,
+        decommitment: &my_decommitment,
+    });
+    let my_commitment = MsgRound1 {
+        commitment: hash_commit,
+    };
+
+    tracer.send_msg();
+    outgoings
+        .send(Outgoing::broadcast(Msg::Round1(my_commitment.clone())))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Round 2
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let commitments = rounds
+        .complete(round1)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    // Optional reliability check
+    if reliable_broadcast_enforced {
+        tracer.stage("Hash received msgs (reliability check)");
+        let h_i = udigest::hash_iter::<D>(
+            commitments
+                .iter_including_me(&my_commitment)
+                .map(|commitment| unambiguous::Echo { sid, commitment }),
+        );
+
+        tracer.send_msg();
+        outgoings
+            .send(Outgoing::broadcast(Msg::ReliabilityCheck(
+                MsgReliabilityCheck(h_i.clone()),
+            )))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.round_begins();
+
+        tracer.receive_msgs();
+        let round1_hashes = rounds
+            .complete(round1_sync)
+            .await?
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        tracer.stage("Assert other parties hashed messages (reliability check)");
+        let parties_have_different_hashes = round1_hashes
+            .into_iter_indexed()
+            .filter(|(_j, _msg_id, hash_j)| hash_j.0 != h_i)
+            .map(|(j, msg_id, _)| utils::AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        if !parties_have_different_hashes.is_empty() {
+            return Err(KeygenAborted::new(
+                KeygenAbortReason::Round1NotReliable,
+                parties_have_different_hashes,
+            )
+            .into());
+        }
+    }
+
+    tracer.send_msg();
+    outgoings
+        .send(Outgoing::broadcast(Msg::Round2(my_decommitment.clone())))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Round 3
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let decommitments = rounds
+        .complete(round2)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    tracer.stage("Validate decommitments");
+    let blame = utils::collect_blame(&commitments, &decommitments, |j, com, decom| {
+        let com_expected = udigest::hash::<D>(&unambiguous::HashCom {
+            sid,
+            party_index: j,
+            decommitment: decom,
+        });
+        com.commitment != com_expected
+    });
+    if !blame.is_empty() {
+        return Err(KeygenAborted::new(KeygenAbortReason::InvalidDecommitment, blame).into());
+    }
+
+    tracer.stage("Validate data size");
+    let blame = utils::collect_simple_blame(&decommitments, |decom| {
+        decom.Xs.len() != k || decom.sch_commits.len() != k
+    });
+    if !blame.is_empty() {
+        return Err(KeygenAborted::new(KeygenAbortReason::InvalidDataSize, blame).into());
+    }
+
+    #[cfg(feature = "hd-wallet")]
+    let chain_codes = if hd_enabled {
+        tracer.stage("Calculate chain_codes");
+        let blame = utils::collect_simple_blame(&decommitments, |decom| {
+            decom
+                .chain_codes
+                .as_ref()
+                .is_none_or(|codes| codes.len() != k)
+        });
+        if !blame.is_empty() {
+            return Err(KeygenAborted::new(KeygenAbortReason::MissingChainCode, blame).into());
+        }
+        let mut chain_codes = alloc::vec![hd_wallet::ChainCode::default(); k];
+        for decom in decommitments.iter_including_me(&my_decommitment) {
+            let codes = decom.chain_codes.as_ref().ok_or(Bug::NoChainCode)?;
+            for (acc, code) in chain_codes.iter_mut().zip(codes) {
+                *acc = utils::xor_array(*acc, code);
+            }
+        }
+        chain_codes.into_iter().map(Some).collect::<Vec<_>>()
+    } else {
+        alloc::vec![None; k]
+    };
+
+    tracer.stage("Calculate challege rid");
+    let rid = decommitments
+        .iter_including_me(&my_decommitment)
+        .map(|d| &d.rid)
+        .fold(L::Rid::default(), utils::xor_array);
+
+    tracer.stage("Prove knowledge of `x_i` for every key");
+    let sch_proofs = (0..batch_size)
+        .zip(&sch_secrets)
+        .zip(&xs)
+        .map(|((key, sch_secret), x_i)| {
+            let challenge = Scalar::from_hash::<D>(&unambiguous::SchnorrPok {
+                sid,
+                prover: i,
+                key_index: key,
+                rid: rid.as_ref(),
+            });
+            let challenge = schnorr_pok::Challenge { nonce: challenge };
+            schnorr_pok::prove(sch_secret, &challenge, x_i)
+        })
+        .collect::<Vec<_>>();
+
+    tracer.send_msg();
+    let my_sch_proof = MsgRound3 { sch_proofs };
+    outgoings
+        .send(Outgoing::broadcast(Msg::Round3(my_sch_proof.clone())))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Round 4
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let sch_proofs = rounds
+        .complete(round3)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    tracer.stage("Validate schnorr proofs");
+    let blame = utils::collect_blame(&decommitments, &sch_proofs, |_, _, proofs| {
+        proofs.sch_proofs.len() != k
+    });
+    if !blame.is_empty() {
+        return Err(KeygenAborted::new(KeygenAbortReason::InvalidDataSize, blame).into());
+    }
+    let blame = utils::collect_blame(&decommitments, &sch_proofs, |j, decom, proofs| {
+        (0..batch_size)
+            .zip(&proofs.sch_proofs)
+            .any(|(key, sch_proof)| {
+                let challenge = Scalar::from_hash::<D>(&unambiguous::SchnorrPok {
+                    sid,
+                    prover: j,
+                    key_index: key,
+                    rid: rid.as_ref(),
+                });
+                let challenge = schnorr_pok::Challenge { nonce: challenge };
+                sch_proof
+                    .verify(
+                        &decom.sch_commits[usize::from(key)],
+                        &challenge,
+                        &decom.Xs[usize::from(key)],
+                    )
+                    .is_err()
+            })
+    });
+    if !blame.is_empty() {
+        return Err(KeygenAborted::new(KeygenAbortReason::InvalidSchnorrProof, blame).into());
+    }
+
+    tracer.stage("Derive resulting public keys and other data");
+    let mut key_shares = Vec::with_capacity(k);
+    for (key, x_i) in xs.into_iter().enumerate() {
+        let public_shares = decommitments
+            .iter_including_me(&my_decommitment)
+            .map(|d| d.Xs[key])
+            .collect::<Vec<_>>();
+        let key_share = DirtyCoreKeyShare {
+            i,
+            key_info: DirtyKeyInfo {
+                curve: Default::default(),
+                shared_public_key: NonZero::from_point(public_shares.iter().sum())
+                    .ok_or(Bug::ZeroPk)?,
+                public_shares,
+                vss_setup: None,
+                #[cfg(feature = "hd-wallet")]
+                chain_code: chain_codes[key],
+            },
+            x: x_i,
+            extra_x: Vec::new(),
+        }
+        .validate()
+        .map_err(|e| Bug::InvalidKeyShare(e.into_error()))?;
+        key_shares.push(key_share);
+    }
+
+    tracer.protocol_ends();
+
+    Ok(key_shares)
+}
@@ -1,17 +1,19 @@
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
 
 use digest::Digest;
 use generic_ec::{Curve, NonZero, Point, Scalar, SecretScalar};
 use generic_ec_zkp::{polynomial::Polynomial, schnorr_pok};
 use rand_core::{CryptoRng, RngCore};
 use round_based::{
-    rounds_router::simple_store::RoundInput, rounds_router::RoundsRouter, Delivery, Mpc, MpcParty,
-    Outgoing, ProtocolMessage, SinkExt,
+    rounds_router::simple_store::RoundInput, Delivery, Mpc, MpcParty, Outgoing, ProtocolMessage,
+    SinkExt,
 };
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 
 use crate::progress::Tracer;
+use crate::timeout::{RoundTimer, RoundsRouter};
 use crate::{
     errors::IoError,
     key_share::{CoreKeyShare, DirtyCoreKeyShare, DirtyKeyInfo, Validate, VssSetup},
@@ -19,7 +21,7 @@ use crate::{
     utils, ExecutionId,
 };
 
-use super::{Bug, KeygenAborted, KeygenError};
+use super::{Bug, InvalidArgs, KeygenAbortReason, KeygenAborted, KeygenError};
 
 macro_rules! prefixed {
     ($name:tt) => {
@@ -41,6 +43,16 @@ pub enum Msg<E: Curve, L: SecurityLevel, D: Digest> {
     Round3(MsgRound3<E>),
     /// Reliability check message (optional additional round)
     ReliabilityCheck(MsgReliabilityCheck<D>),
+    /// Complaints about invalid shares (additional round of robust keygen)
+    Complaints(MsgComplaints),
+    /// Justification of accused parties (additional round of robust keygen, carried out only
+    /// if somebody complained)
+    Justification(MsgJustification<E>),
+    /// Reliability check of complaints (optional additional round of robust keygen)
+    ComplaintsReliabilityCheck(MsgComplaintsReliabilityCheck<D>),
+    /// Reliability check of justifications (optional additional round of robust keygen, carried
+    /// out only if somebody complained)
+    JustificationReliabilityCheck(MsgJustificationReliabilityCheck<D>),
 }
 
 /// Message from round 1
@@ -84,6 +96,9 @@ pub struct MsgRound2Broad<E: Curve, L: SecurityLevel> {
 pub struct MsgRound2Uni<E: Curve> {
     /// $\sigma_{i,j}$
     pub sigma: Scalar<E>,
+    /// $\sigma_{i,j}$ at the remaining points of $j$-th party, present if keygen is weighted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_sigmas: Vec<Scalar<E>>,
 }
 /// Message from round 3
 #[derive(Clone, Serialize, Deserialize)]
@@ -96,6 +111,31 @@ pub struct MsgRound3<E: Curve> {
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(bound = "")]
 pub struct MsgReliabilityCheck<D: Digest>(pub digest::Output<D>);
+/// Complaints of a party, broadcasted in robust keygen
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[udigest(tag = prefixed!("complaints"))]
+pub struct MsgComplaints {
+    /// Indexes of parties whose secret shares are not consistent with their public polynomial
+    pub accused: Vec<u16>,
+}
+/// Justification of a party, broadcasted in robust keygen
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[serde(bound = "")]
+#[udigest(bound = "")]
+#[udigest(tag = prefixed!("justification"))]
+pub struct MsgJustification<E: Curve> {
+    /// Secret shares $\sigma_{i,j}$ revealed to everyone: pairs of index $j$ of complaining party
+    /// and shares sent to it in round 2
+    pub revealed: Vec<(u16, Vec<Scalar<E>>)>,
+}
+/// Message parties exchange to ensure that all of them received the same complaints
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgComplaintsReliabilityCheck<D: Digest>(pub digest::Output<D>);
+/// Message parties exchange to ensure that all of them received the same justifications
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgJustificationReliabilityCheck<D: Digest>(pub digest::Output<D>);
 
 mod unambiguous {
     use generic_ec::{Curve, NonZero, Point};
@@ -130,19 +170,32 @@ mod unambiguous {
         pub sid: ExecutionId<'a>,
         pub commitment: &'a super::MsgRound1<D>,
     }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("echo_msg"))]
+    #[udigest(bound = "M: udigest::Digestable")]
+    pub struct EchoMsg<'a, M> {
+        pub sid: ExecutionId<'a>,
+        pub msg: &'a M,
+    }
 }
 
 pub async fn run_threshold_keygen<E, R, M, L, D>(
     mut tracer: Option<&mut dyn Tracer>,
+    round_timer: Option<&mut dyn RoundTimer>,
     i: u16,
     t: u16,
     n: u16,
+    weights: Option<Vec<u16>>,
+    ranks: Option<Vec<u16>>,
+    share_preimages: Option<Vec<NonZero<Scalar<E>>>>,
     reliable_broadcast_enforced: bool,
+    robust: bool,
     sid: ExecutionId<'_>,
     rng: &mut R,
     party: M,
     #[cfg(feature = "hd-wallet")] hd_enabled: bool,
-) -> Result<CoreKeyShare<E>, KeygenError>
+) -> Result<(CoreKeyShare<E>, Vec<u16>), KeygenError>
 where
     E: Curve,
     L: SecurityLevel,
@@ -152,17 +205,79 @@ where
 {
     tracer.protocol_begins();
 
+    tracer.stage("Retrieve weights");
+    let shares = match &weights {
+        Some(weights) => {
+            if weights.len() != usize::from(n) {
+                return Err(InvalidArgs::MismatchedAmountOfWeights.into());
+            }
+            if weights.contains(&0) {
+                return Err(InvalidArgs::ZeroWeight.into());
+            }
+            shares_positions(weights)
+        }
+        None => shares_positions(&vec![1; usize::from(n)]),
+    };
+    // Total weight is amount of key shares, it must fit into u16
+    let total_weight = u16::try_from(shares.last().map_or(0, |s| s.end))
+        .map_err(|_| InvalidArgs::TotalWeightOverflow)?;
+    let my_shares = shares[usize::from(i)].clone();
+
+    tracer.stage("Retrieve share preimages");
+    let I = match share_preimages {
+        Some(I) => {
+            if I.len() != usize::from(total_weight) {
+                return Err(InvalidArgs::MismatchedAmountOfPreimages.into());
+            }
+            if I.iter().enumerate().any(|(j, I_j)| I[..j].contains(I_j)) {
+                return Err(InvalidArgs::DuplicatedPreimage.into());
+            }
+            I
+        }
+        None => (1..=total_weight)
+            .map(|j| NonZero::from_scalar(Scalar::from(j)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Bug::NonZeroScalar)?,
+    };
+
+    tracer.stage("Retrieve ranks");
+    let share_ranks = match &ranks {
+        Some(ranks) => {
+            if ranks.len() != usize::from(total_weight) {
+                return Err(InvalidArgs::MismatchedAmountOfRanks.into());
+            }
+            // Pólya condition: there must be `t` key shares such that
+            // `k`-th lowest rank among them doesn't exceed `k`
+            let mut sorted_ranks = ranks.clone();
+            sorted_ranks.sort_unstable();
+            if sorted_ranks.iter().any(|r| *r >= t)
+                || (0..t).zip(&sorted_ranks).any(|(k, r)| *r > k)
+            {
+                return Err(InvalidArgs::InvalidRanks.into());
+            }
+            ranks.clone()
+        }
+        None => vec![0; usize::from(total_weight)],
+    };
+
     tracer.stage("Setup networking");
     let MpcParty { delivery, .. } = party.into_party();
     let (incomings, mut outgoings) = delivery.split();
 
-    let mut rounds = RoundsRouter::<Msg<E, L, D>>::builder();
+    let mut rounds = RoundsRouter::<Msg<E, L, D>>::builder(i, n);
     let round1 = rounds.add_round(RoundInput::<MsgRound1<D>>::broadcast(i, n));
     let round1_sync = rounds.add_round(RoundInput::<MsgReliabilityCheck<D>>::broadcast(i, n));
     let round2_broad = rounds.add_round(RoundInput::<MsgRound2Broad<E, L>>::broadcast(i, n));
     let round2_uni = rounds.add_round(RoundInput::<MsgRound2Uni<E>>::p2p(i, n));
+    let round_complaints = rounds.add_round(RoundInput::<MsgComplaints>::broadcast(i, n));
+    let round_complaints_sync = rounds.add_round(
+        RoundInput::<MsgComplaintsReliabilityCheck<D>>::broadcast(i, n),
+    );
+    let round_justification = rounds.add_round(RoundInput::<MsgJustification<E>>::broadcast(i, n));
+    let round_justification_sync =
+        rounds.add_round(RoundInput::<MsgJustificationReliabilityCheck<D>>::broadcast(i, n));
     let round3 = rounds.add_round(RoundInput::<MsgRound3<E>>::broadcast(i, n));
-    let mut rounds = rounds.listen(incomings);
+    let mut rounds = rounds.listen(incomings, round_timer);
 
     // Round 1
     tracer.round_begins();
@@ -175,13 +290,12 @@ where
 
     let f = Polynomial::<SecretScalar<E>>::sample(rng, usize::from(t) - 1);
     let F = &f * &Point::generator();
-    let sigmas = (0..n)
-        .map(|j| {
-            let x = Scalar::from(j + 1);
-            f.value(&x)
-        })
+    let sigmas = I
+        .iter()
+        .zip(&share_ranks)
+        .map(|(I_j, r_j)| derivative_value(&f, *r_j, I_j))
         .collect::<Vec<_>>();
-    debug_assert_eq!(sigmas.len(), usize::from(n));
+    debug_assert_eq!(sigmas.len(), usize::from(total_weight));
 
     #[cfg(feature = "hd-wallet")]
     let chain_code_local = if hd_enabled {
@@ -227,7 +341,7 @@ where
     tracer.receive_msgs();
     let commitments = rounds
         .complete(round1)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
@@ -254,7 +368,7 @@ where
         tracer.receive_msgs();
         let hashes = rounds
             .complete(round1_sync)
-            .await
+            .await?
             .map_err(IoError::receive_message)?;
         tracer.msgs_received();
 
@@ -262,10 +376,14 @@ where
         let parties_have_different_hashes = hashes
             .into_iter_indexed()
             .filter(|(_j, _msg_id, h_j)| h_i != h_j.0)
-            .map(|(j, msg_id, _)| (j, msg_id))
+            .map(|(j, msg_id, _)| utils::AbortBlame::new(j, msg_id, msg_id))
             .collect::<Vec<_>>();
         if !parties_have_different_hashes.is_empty() {
-            return Err(KeygenAborted::Round1NotReliable(parties_have_different_hashes).into());
+            return Err(KeygenAborted::new(
+                KeygenAbortReason::Round1NotReliable,
+                parties_have_different_hashes,
+            )
+            .into());
         }
     }
 
@@ -278,8 +396,10 @@ where
         .map_err(IoError::send_message)?;
 
     let messages = utils::iter_peers(i, n).map(|j| {
+        let shares_j = &shares[usize::from(j)];
         let message = MsgRound2Uni {
-            sigma: sigmas[usize::from(j)],
+            sigma: sigmas[shares_j.start],
+            extra_sigmas: sigmas[shares_j.start + 1..shares_j.end].to_vec(),
         };
         Outgoing::p2p(j, Msg::Round2Uni(message))
     });
@@ -295,11 +415,11 @@ where
     tracer.receive_msgs();
     let decommitments = rounds
         .complete(round2_broad)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     let sigmas_msg = rounds
         .complete(round2_uni)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
@@ -313,31 +433,255 @@ where
         com.commitment != com_expected
     });
     if !blame.is_empty() {
-        return Err(KeygenAborted::InvalidDecommitment(blame).into());
+        return Err(KeygenAborted::new(KeygenAbortReason::InvalidDecommitment, blame).into());
     }
 
     tracer.stage("Validate data size");
-    let blame = decommitments
-        .iter_indexed()
-        .filter(|(_, _, d)| d.F.degree() + 1 != usize::from(t))
-        .map(|t| t.0)
-        .collect::<Vec<_>>();
+    let mut blame =
+        utils::collect_simple_blame(&decommitments, |d| d.F.degree() + 1 != usize::from(t));
+    if !robust {
+        // In robust keygen, invalid shares are handled by complaints
+        blame.extend(utils::collect_simple_blame(&sigmas_msg, |s| {
+            s.extra_sigmas.len() + 1 != my_shares.len()
+        }));
+    }
     if !blame.is_empty() {
-        return Err(KeygenAborted::InvalidDataSize { parties: blame }.into());
+        return Err(KeygenAborted::new(KeygenAbortReason::InvalidDataSize, blame).into());
     }
 
-    tracer.stage("Validate Feldmann VSS");
-    let blame = decommitments
-        .iter_indexed()
-        .zip(sigmas_msg.iter())
-        .filter(|((_, _, d), s)| {
-            d.F.value::<_, Point<_>>(&Scalar::from(i + 1)) != Point::generator() * s.sigma
+    // Checks that `sigmas` are shares of `k`-th party on polynomial `F` committed by the dealer
+    let shares_are_valid = |F: &Polynomial<Point<E>>, k: u16, sigmas: &[Scalar<E>]| {
+        let positions = shares[usize::from(k)].clone();
+        sigmas.len() == positions.len()
+            && I[positions.clone()]
+                .iter()
+                .zip(&share_ranks[positions])
+                .zip(sigmas)
+                .all(|((I_k, r_k), sigma)| {
+                    derivative_value::<E, _, Point<E>>(F, *r_k, I_k) == Point::generator() * sigma
+                })
+    };
+    if !robust {
+        tracer.stage("Validate Feldmann VSS");
+        let blame = utils::collect_blame(&decommitments, &sigmas_msg, |_, d, s| {
+            let sigmas = core::iter::once(s.sigma)
+                .chain(s.extra_sigmas.iter().copied())
+                .collect::<Vec<_>>();
+            !shares_are_valid(&d.F, i, &sigmas)
+        });
+        if !blame.is_empty() {
+            return Err(
+                KeygenAborted::new(KeygenAbortReason::FeldmanVerificationFailed, blame).into(),
+            );
+        }
+    }
+
+    // `received_sigmas[j]` are shares that `j`-th party dealt to us
+    let mut received_sigmas = sigmas_msg
+        .into_iter_including_me(MsgRound2Uni {
+            sigma: sigmas[my_shares.start],
+            extra_sigmas: sigmas[my_shares.start + 1..my_shares.end].to_vec(),
+        })
+        .map(|s| {
+            core::iter::once(s.sigma)
+                .chain(s.extra_sigmas)
+                .collect::<Vec<_>>()
         })
-        .map(|t| t.0 .0)
         .collect::<Vec<_>>();
-    if !blame.is_empty() {
-        return Err(KeygenAborted::FeldmanVerificationFailed { parties: blame }.into());
+    let all_decommitments = decommitments
+        .iter_including_me(&my_decommitment)
+        .collect::<Vec<_>>();
+
+    // Parties whose contribution is excluded from the key
+    let mut disqualified = Vec::new();
+    if robust {
+        tracer.stage("Complain about invalid shares");
+        let my_complaints = MsgComplaints {
+            accused: utils::iter_peers(i, n)
+                .filter(|&j| {
+                    !shares_are_valid(
+                        &all_decommitments[usize::from(j)].F,
+                        i,
+                        &received_sigmas[usize::from(j)],
+                    )
+                })
+                .collect(),
+        };
+
+        tracer.send_msg();
+        outgoings
+            .send(Outgoing::broadcast(Msg::Complaints(my_complaints.clone())))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        // Round 3a: complaints
+        tracer.round_begins();
+
+        tracer.receive_msgs();
+        let complaints = rounds
+            .complete(round_complaints)
+            .await?
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        // Optional reliability check. Complaints decide which dealers are disqualified, so all
+        // parties must receive the same complaints
+        if reliable_broadcast_enforced {
+            tracer.stage("Hash received complaints (reliability check)");
+            let h_i = udigest::hash_iter::<D>(
+                complaints
+                    .iter_including_me(&my_complaints)
+                    .map(|msg| unambiguous::EchoMsg { sid, msg }),
+            );
+
+            tracer.send_msg();
+            outgoings
+                .send(Outgoing::broadcast(Msg::ComplaintsReliabilityCheck(
+                    MsgComplaintsReliabilityCheck(h_i.clone()),
+                )))
+                .await
+                .map_err(IoError::send_message)?;
+            tracer.msg_sent();
+
+            tracer.round_begins();
+
+            tracer.receive_msgs();
+            let hashes = rounds
+                .complete(round_complaints_sync)
+                .await?
+                .map_err(IoError::receive_message)?;
+            tracer.msgs_received();
+
+            tracer.stage("Assert other parties hashed complaints (reliability check)");
+            let parties_have_different_hashes = hashes
+                .into_iter_indexed()
+                .filter(|(_j, _msg_id, h_j)| h_i != h_j.0)
+                .map(|(j, msg_id, _)| utils::AbortBlame::new(j, msg_id, msg_id))
+                .collect::<Vec<_>>();
+            if !parties_have_different_hashes.is_empty() {
+                return Err(KeygenAborted::new(
+                    KeygenAbortReason::ComplaintsNotReliable,
+                    parties_have_different_hashes,
+                )
+                .into());
+            }
+        }
+
+        // Pairs `(k, j)` such that `k`-th party complained about shares dealt by `j`-th party
+        let mut complaints = complaints
+            .into_iter_including_me(my_complaints)
+            .zip(0u16..)
+            .flat_map(|(c, k)| c.accused.into_iter().map(move |j| (k, j)))
+            .filter(|&(k, j)| j < n && j != k)
+            .collect::<Vec<_>>();
+        complaints.sort_unstable();
+        complaints.dedup();
+
+        // Broadcast of complaints is reliable (either checked above, or guaranteed by the
+        // channel), so all honest parties received the same complaints and they either all
+        // carry out the justification round, or all skip it
+        if !complaints.is_empty() {
+            tracer.stage("Reveal shares dealt to complaining parties");
+            let my_justification = MsgJustification {
+                revealed: complaints
+                    .iter()
+                    .filter(|(_, j)| *j == i)
+                    .map(|&(k, _)| (k, sigmas[shares[usize::from(k)].clone()].to_vec()))
+                    .collect(),
+            };
+
+            tracer.send_msg();
+            outgoings
+                .send(Outgoing::broadcast(Msg::Justification(
+                    my_justification.clone(),
+                )))
+                .await
+                .map_err(IoError::send_message)?;
+            tracer.msg_sent();
+
+            // Round 3b: justification
+            tracer.round_begins();
+
+            tracer.receive_msgs();
+            let justifications = rounds
+                .complete(round_justification)
+                .await?
+                .map_err(IoError::receive_message)?;
+            tracer.msgs_received();
+
+            // Optional reliability check. Otherwise, a dealer could send valid justification to
+            // some parties and invalid to others, so that only some of them disqualify it
+            if reliable_broadcast_enforced {
+                tracer.stage("Hash received justifications (reliability check)");
+                let h_i = udigest::hash_iter::<D>(
+                    justifications
+                        .iter_including_me(&my_justification)
+                        .map(|msg| unambiguous::EchoMsg { sid, msg }),
+                );
+
+                tracer.send_msg();
+                outgoings
+                    .send(Outgoing::broadcast(Msg::JustificationReliabilityCheck(
+                        MsgJustificationReliabilityCheck(h_i.clone()),
+                    )))
+                    .await
+                    .map_err(IoError::send_message)?;
+                tracer.msg_sent();
+
+                tracer.round_begins();
+
+                tracer.receive_msgs();
+                let hashes = rounds
+                    .complete(round_justification_sync)
+                    .await?
+                    .map_err(IoError::receive_message)?;
+                tracer.msgs_received();
+
+                tracer.stage("Assert other parties hashed justifications (reliability check)");
+                let parties_have_different_hashes = hashes
+                    .into_iter_indexed()
+                    .filter(|(_j, _msg_id, h_j)| h_i != h_j.0)
+                    .map(|(j, msg_id, _)| utils::AbortBlame::new(j, msg_id, msg_id))
+                    .collect::<Vec<_>>();
+                if !parties_have_different_hashes.is_empty() {
+                    return Err(KeygenAborted::new(
+                        KeygenAbortReason::JustificationNotReliable,
+                        parties_have_different_hashes,
+                    )
+                    .into());
+                }
+            }
+            let justifications = justifications.into_vec_including_me(my_justification);
+
+            tracer.stage("Validate revealed shares");
+            for &(k, j) in &complaints {
+                let revealed = justifications[usize::from(j)]
+                    .revealed
+                    .iter()
+                    .find(|(k2, _)| *k2 == k)
+                    .map(|(_, sigmas)| sigmas)
+                    .filter(|sigmas| {
+                        shares_are_valid(&all_decommitments[usize::from(j)].F, k, sigmas)
+                    });
+                match revealed {
+                    Some(sigmas) if k == i => received_sigmas[usize::from(j)] = sigmas.clone(),
+                    Some(_) => (),
+                    None => disqualified.push(j),
+                }
+            }
+            disqualified.sort_unstable();
+            disqualified.dedup();
+        }
     }
+    let is_qualified = |j: u16| !disqualified.contains(&j);
+    // Decommitments of parties whose contribution is included into the key
+    let qualified_decommitments = all_decommitments
+        .iter()
+        .zip(0u16..)
+        .filter(|(_, j)| is_qualified(*j))
+        .map(|(d, _)| *d)
+        .collect::<Vec<_>>();
 
     tracer.stage("Compute rid");
     let rid = decommitments
@@ -349,9 +693,9 @@ where
         tracer.stage("Compute chain_code");
         let blame = utils::collect_simple_blame(&decommitments, |decom| decom.chain_code.is_none());
         if !blame.is_empty() {
-            return Err(KeygenAborted::MissingChainCode(blame).into());
+            return Err(KeygenAborted::new(KeygenAbortReason::MissingChainCode, blame).into());
         }
-        Some(decommitments.iter_including_me(&my_decommitment).try_fold(
+        Some(qualified_decommitments.iter().try_fold(
             hd_wallet::ChainCode::default(),
             |acc, decom| {
                 Ok::<_, Bug>(utils::xor_array(
@@ -364,32 +708,56 @@ where
         None
     };
     tracer.stage("Compute Ys");
-    let polynomial_sum = decommitments
-        .iter_including_me(&my_decommitment)
+    let polynomial_sum = qualified_decommitments
+        .iter()
         .map(|d| &d.F)
         .sum::<Polynomial<_>>();
-    let ys = (0..n)
-        .map(|l| polynomial_sum.value(&Scalar::from(l + 1)))
+    let ys = I
+        .iter()
+        .zip(&share_ranks)
+        .map(|(I_j, r_j)| derivative_value(&polynomial_sum, *r_j, I_j))
         .map(|y_j: Point<E>| NonZero::from_point(y_j).ok_or(Bug::ZeroShare))
         .collect::<Result<Vec<_>, _>>()?;
+    // Weighted party proves knowledge of sum of its shares, which is the same as proving
+    // knowledge of its only share when key isn't weighted
+    let party_y = |j: u16| {
+        NonZero::from_point(ys[shares[usize::from(j)].clone()].iter().sum::<Point<E>>())
+            .ok_or(Bug::ZeroShare)
+    };
+    let party_ys = (0..n).map(party_y).collect::<Result<Vec<_>, _>>()?;
+
     tracer.stage("Compute sigma");
-    let sigma: Scalar<E> = sigmas_msg.iter().map(|msg| msg.sigma).sum();
-    let mut sigma = sigma + sigmas[usize::from(i)];
-    let sigma = NonZero::from_secret_scalar(SecretScalar::new(&mut sigma)).ok_or(Bug::ZeroShare)?;
-    debug_assert_eq!(Point::generator() * &sigma, ys[usize::from(i)]);
+    let sigma = my_shares
+        .clone()
+        .enumerate()
+        .map(|(k, position)| {
+            let mut sigma: Scalar<E> = received_sigmas
+                .iter()
+                .zip(0u16..)
+                .filter(|(_, j)| is_qualified(*j))
+                .map(|(sigmas_j, _)| sigmas_j[k])
+                .sum();
+            let sigma =
+                NonZero::from_secret_scalar(SecretScalar::new(&mut sigma)).ok_or(Bug::ZeroShare)?;
+            debug_assert_eq!(Point::generator() * &sigma, ys[position]);
+            Ok(sigma)
+        })
+        .collect::<Result<Vec<_>, Bug>>()?;
+    let mut sigma_sum = sigma.iter().fold(Scalar::zero(), |acc, s| acc + s);
+    let sigma_sum = SecretScalar::new(&mut sigma_sum);
 
     tracer.stage("Calculate challenge");
     let challenge = Scalar::from_hash::<D>(&unambiguous::SchnorrPok {
         sid,
         prover: i,
         rid: rid.as_ref(),
-        y: ys[usize::from(i)],
+        y: party_ys[usize::from(i)],
         h: my_decommitment.sch_commit.0,
     });
     let challenge = schnorr_pok::Challenge { nonce: challenge };
 
     tracer.stage("Prove knowledge of `sigma_i`");
-    let z = schnorr_pok::prove(&r, &challenge, &sigma);
+    let z = schnorr_pok::prove(&r, &challenge, &sigma_sum);
 
     tracer.send_msg();
     let my_sch_proof = MsgRound3 { sch_proof: z };
@@ -405,42 +773,39 @@ where
     tracer.receive_msgs();
     let sch_proofs = rounds
         .complete(round3)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
     tracer.stage("Validate schnorr proofs");
     let blame = utils::collect_blame(&decommitments, &sch_proofs, |j, decom, sch_proof| {
+        if !is_qualified(j) {
+            // Disqualified parties may not know their key shares
+            return false;
+        }
         let challenge = Scalar::from_hash::<D>(&unambiguous::SchnorrPok {
             sid,
             prover: j,
             rid: rid.as_ref(),
-            y: ys[usize::from(j)],
+            y: party_ys[usize::from(j)],
             h: decom.sch_commit.0,
         });
         let challenge = schnorr_pok::Challenge { nonce: challenge };
         sch_proof
             .sch_proof
-            .verify(&decom.sch_commit, &challenge, &ys[usize::from(j)])
+            .verify(&decom.sch_commit, &challenge, &party_ys[usize::from(j)])
             .is_err()
     });
     if !blame.is_empty() {
-        return Err(KeygenAborted::InvalidSchnorrProof(blame).into());
+        return Err(KeygenAborted::new(KeygenAbortReason::InvalidSchnorrProof, blame).into());
     }
 
     tracer.stage("Derive resulting public key and other data");
-    let y: Point<E> = decommitments
-        .iter_including_me(&my_decommitment)
-        .map(|d| d.F.coefs()[0])
-        .sum();
-    let key_shares_indexes = (1..=n)
-        .map(|i| NonZero::from_scalar(Scalar::from(i)))
-        .collect::<Option<Vec<_>>>()
-        .ok_or(Bug::NonZeroScalar)?;
+    let y: Point<E> = qualified_decommitments.iter().map(|d| d.F.coefs()[0]).sum();
 
     tracer.protocol_ends();
 
-    Ok(DirtyCoreKeyShare {
+    let key_share = DirtyCoreKeyShare {
         i,
         key_info: DirtyKeyInfo {
             curve: Default::default(),
@@ -448,13 +813,48 @@ where
             public_shares: ys,
             vss_setup: Some(VssSetup {
                 min_signers: t,
-                I: key_shares_indexes,
+                I,
+                weights,
+                ranks,
             }),
             #[cfg(feature = "hd-wallet")]
             chain_code,
         },
-        x: sigma,
+        x: sigma[0].clone(),
+        extra_x: sigma[1..].to_vec(),
     }
     .validate()
-    .map_err(|err| Bug::InvalidKeyShare(err.into_error()))?)
+    .map_err(|err| Bug::InvalidKeyShare(err.into_error()))?;
+    Ok((key_share, disqualified))
+}
+
+/// Returns positions of key shares of every party: `j`-th party holds key shares at
+/// positions `shares[j]`
+fn shares_positions(weights: &[u16]) -> Vec<Range<usize>> {
+    weights
+        .iter()
+        .scan(0, |start, w| {
+            let shares = *start..*start + usize::from(*w);
+            *start = shares.end;
+            Some(shares)
+        })
+        .collect()
+}
+
+/// Evaluates `r`-th derivative of polynomial `f` at point `x`
+///
+/// Key shares of hierarchical keys are values of derivatives of the shared polynomial. Derivative
+/// of order 0 is the polynomial itself.
+fn derivative_value<E: Curve, C, O>(f: &Polynomial<C>, r: u16, x: &Scalar<E>) -> O
+where
+    for<'c> Scalar<E>: core::ops::Mul<&'c C, Output = O>,
+    O: core::iter::Sum,
+{
+    // Degree of polynomial is less than threshold, so amount of coefficients fits into `u16`
+    let t = u16::try_from(f.coefs().len()).unwrap_or(u16::MAX);
+    key_share::birkhoff::matrix_row(x, r, t)
+        .into_iter()
+        .zip(f.coefs())
+        .map(|(multiplier, c_m)| multiplier * c_m)
+        .sum()
 }
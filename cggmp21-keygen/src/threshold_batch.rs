@@ -0,0 +1,537 @@
+use alloc::vec::Vec;
+
+use digest::Digest;
+use generic_ec::{Curve, NonZero, Point, Scalar, SecretScalar};
+use generic_ec_zkp::{polynomial::Polynomial, schnorr_pok};
+use rand_core::{CryptoRng, RngCore};
+use round_based::{
+    rounds_router::simple_store::RoundInput, Delivery, Mpc, MpcParty, Outgoing, ProtocolMessage,
+    SinkExt,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use crate::progress::Tracer;
+use crate::timeout::{RoundTimer, RoundsRouter};
+use crate::{
+    errors::IoError,
+    key_share::{CoreKeyShare, DirtyCoreKeyShare, DirtyKeyInfo, Validate, VssSetup},
+    security_level::SecurityLevel,
+    utils, ExecutionId,
+};
+
+use super::{Bug, InvalidArgs, KeygenAbortReason, KeygenAborted, KeygenError};
+
+macro_rules! prefixed {
+    ($name:tt) => {
+        concat!("dfns.cggmp21.keygen.threshold_batch.", $name)
+    };
+}
+
+/// Message of batch key generation protocol
+#[derive(ProtocolMessage, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum Msg<E: Curve, L: SecurityLevel, D: Digest> {
+    /// Round 1 message
+    Round1(MsgRound1<D>),
+    /// Round 2a message
+    Round2Broad(MsgRound2Broad<E, L>),
+    /// Round 2b message
+    Round2Uni(MsgRound2Uni<E>),
+    /// Round 3 message
+    Round3(MsgRound3<E>),
+    /// Reliability check message (optional additional round)
+    ReliabilityCheck(MsgReliabilityCheck<D>),
+}
+
+/// Message from round 1
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[serde(bound = "")]
+#[udigest(bound = "")]
+#[udigest(tag = prefixed!("round1"))]
+pub struct MsgRound1<D: Digest> {
+    /// $V_i$, commitment to data of all keys being generated
+    #[udigest(as_bytes)]
+    pub commitment: digest::Output<D>,
+}
+/// Message from round 2 broadcasted to everyone
+#[serde_as]
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[serde(bound = "")]
+#[udigest(bound = "")]
+#[udigest(tag = prefixed!("round2_broad"))]
+pub struct MsgRound2Broad<E: Curve, L: SecurityLevel> {
+    /// `rid_i`
+    #[serde_as(as = "utils::HexOrBin")]
+    #[udigest(as_bytes)]
+    pub rid: L::Rid,
+    /// $\vec S_{i,k}$ for every key $k$
+    pub Fs: Vec<Polynomial<Point<E>>>,
+    /// $A_{i,k}$ for every key $k$
+    pub sch_commits: Vec<schnorr_pok::Commit<E>>,
+    /// Party contribution to chain code of every key
+    #[cfg(feature = "hd-wallet")]
+    #[serde_as(as = "Option<Vec<utils::HexOrBin>>")]
+    #[udigest(as = Option<Vec<udigest::Bytes>>)]
+    pub chain_codes: Option<Vec<hd_wallet::ChainCode>>,
+    /// $u_i$
+    #[serde(with = "hex::serde")]
+    #[udigest(as_bytes)]
+    pub decommit: L::Rid,
+}
+/// Message from round 2 unicasted to each party
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound2Uni<E: Curve> {
+    /// $\sigma_{i,j,k}$ for every key $k$
+    pub sigmas: Vec<Scalar<E>>,
+}
+/// Message from round 3
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound3<E: Curve> {
+    /// $\psi_{i,k}$ for every key $k$
+    pub sch_proofs: Vec<schnorr_pok::Proof<E>>,
+}
+/// Message parties exchange to ensure reliability of broadcast channel
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgReliabilityCheck<D: Digest>(pub digest::Output<D>);
+
+mod unambiguous {
+    use generic_ec::{Curve, NonZero, Point};
+
+    use crate::{ExecutionId, SecurityLevel};
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("hash_commitment"))]
+    #[udigest(bound = "")]
+    pub struct HashCom<'a, E: Curve, L: SecurityLevel> {
+        pub sid: ExecutionId<'a>,
+        pub party_index: u16,
+        pub decommitment: &'a super::MsgRound2Broad<E, L>,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("schnorr_pok"))]
+    #[udigest(bound = "")]
+    pub struct SchnorrPok<'a, E: Curve> {
+        pub sid: ExecutionId<'a>,
+        pub prover: u16,
+        pub key_index: u16,
+        #[udigest(as_bytes)]
+        pub rid: &'a [u8],
+        pub y: NonZero<Point<E>>,
+        pub h: Point<E>,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("echo_round"))]
+    #[udigest(bound = "")]
+    pub struct Echo<'a, D: digest::Digest> {
+        pub sid: ExecutionId<'a>,
+        pub commitment: &'a super::MsgRound1<D>,
+    }
+}
+
+pub async fn run_threshold_batch_keygen<E, R, M, L, D>(
+    mut tracer: Option<&mut dyn Tracer>,
+    round_timer: Option<&mut dyn RoundTimer>,
+    i: u16,
+    t: u16,
+    n: u16,
+    batch_size: u16,
+    share_preimages: Option<Vec<NonZero<Scalar<E>>>>,
+    reliable_broadcast_enforced: bool,
+    sid: ExecutionId<'_>,
+    rng: &mut R,
+    party: M,
+    #[cfg(feature = "hd-wallet")] hd_enabled: bool,
+) -> Result<Vec<CoreKeyShare<E>>, KeygenError>
+where
+    E: Curve,
+    L: SecurityLevel,
+    D: Digest + Clone + 'static,
+    R: RngCore + CryptoRng,
+    M: Mpc<ProtocolMessage = Msg<E, L, D>>,
+{
+    tracer.protocol_begins();
+
+    tracer.stage("Retrieve share preimages");
+    if batch_size == 0 {
+        return Err(InvalidArgs::EmptyBatch.into());
+    }
+    let I = match share_preimages {
+        Some(I) => {
+            if I.len() != usize::from(n) {
+                return Err(InvalidArgs::MismatchedAmountOfPreimages.into());
+            }
+            if I.iter().enumerate().any(|(j, I_j)| I[..j].contains(I_j)) {
+                return Err(InvalidArgs::DuplicatedPreimage.into());
+            }
+            I
+        }
+        None => (1..=n)
+            .map(|j| NonZero::from_scalar(Scalar::from(j)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Bug::NonZeroScalar)?,
+    };
+    let k = usize::from(batch_size);
+
+    tracer.stage("Setup networking");
+    let MpcParty { delivery, .. } = party.into_party();
+    let (incomings, mut outgoings) = delivery.split();
+
+    let mut rounds = RoundsRouter::<Msg<E, L, D>>::builder(i, n);
+    let round1 = rounds.add_round(RoundInput::<MsgRound1<D>>::broadcast(i, n));
+    let round1_sync = rounds.add_round(RoundInput::<MsgReliabilityCheck<D>>::broadcast(i, n));
+    let round2_broad = rounds.add_round(RoundInput::<MsgRound2Broad<E, L>>::broadcast(i, n));
+    let round2_uni = rounds.add_round(RoundInput::<MsgRound2Uni<E>>::p2p(i, n));
+    let round3 = rounds.add_round(RoundInput::<MsgRound3<E>>::broadcast(i, n));
+    let mut rounds = rounds.listen(incomings, round_timer);
+
+    // Round 1
+    tracer.round_begins();
+
+    tracer.stage("Sample rid_i, schnorr commitments, polynomials, chain_codes");
+    let mut rid = L::Rid::default();
+    rng.fill_bytes(rid.as_mut());
+
+    let (rs, hs): (Vec<_>, Vec<_>) = (0..k)
+        .map(|_| schnorr_pok::prover_commits_ephemeral_secret::<E, _>(rng))
+        .unzip();
+
+    let fs = (0..k)
+        .map(|_| Polynomial::<SecretScalar<E>>::sample(rng, usize::from(t) - 1))
+        .collect::<Vec<_>>();
+    let Fs = fs
+        .iter()
+        .map(|f| f * &Point::generator())
+        .collect::<Vec<_>>();
+    // `sigmas[j][k]` is share of `k`-th key dealt to `j`-th party
+    let sigmas = I
+        .iter()
+        .map(|I_j| fs.iter().map(|f| f.value(I_j)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    debug_assert_eq!(sigmas.len(), usize::from(n));
+
+    #[cfg(feature = "hd-wallet")]
+    let chain_codes_local = if hd_enabled {
+        Some(
+            (0..k)
+                .map(|_| {
+                    let mut chain_code = hd_wallet::ChainCode::default();
+                    rng.fill_bytes(&mut chain_code);
+                    chain_code
+                })
+                .collect::<Vec<_>>(),
+        )
+    } else {
+        None
+    };
+
+    tracer.stage("Commit to public data");
+    let my_decommitment = MsgRound2Broad {
+        rid,
+        Fs,
+        sch_commits: hs,
+        #[cfg(feature = "hd-wallet")]
+        chain_codes: chain_codes_local,
+        decommit: {
+            let mut nonce = L::Rid::default();
+            rng.fill_bytes(nonce.as_mut());
+            nonce
+        },
+    };
+    let hash_commit = udigest::hash::<D>(&unambiguous::HashCom {
+        sid,
+        party_index: i,
+        decommitment: &my_decommitment,
+    });
+
+    tracer.send_msg();
+    let my_commitment = MsgRound1 {
+        commitment: hash_commit,
+    };
+    outgoings
+        .send(Outgoing::broadcast(Msg::Round1(my_commitment.clone())))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Round 2
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let commitments = rounds
+        .complete(round1)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    // Optional reliability check
+    if reliable_broadcast_enforced {
+        tracer.stage("Hash received msgs (reliability check)");
+        let h_i = udigest::hash_iter::<D>(
+            commitments
+                .iter_including_me(&my_commitment)
+                .map(|commitment| unambiguous::Echo { sid, commitment }),
+        );
+
+        tracer.send_msg();
+        outgoings
+            .send(Outgoing::broadcast(Msg::ReliabilityCheck(
+                MsgReliabilityCheck(h_i.clone()),
+            )))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.round_begins();
+
+        tracer.receive_msgs();
+        let hashes = rounds
+            .complete(round1_sync)
+            .await?
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        tracer.stage("Assert other parties hashed messages (reliability check)");
+        let parties_have_different_hashes = hashes
+            .into_iter_indexed()
+            .filter(|(_j, _msg_id, h_j)| h_i != h_j.0)
+            .map(|(j, msg_id, _)| utils::AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        if !parties_have_different_hashes.is_empty() {
+            return Err(KeygenAborted::new(
+                KeygenAbortReason::Round1NotReliable,
+                parties_have_different_hashes,
+            )
+            .into());
+        }
+    }
+
+    tracer.send_msg();
+    outgoings
+        .feed(Outgoing::broadcast(Msg::Round2Broad(
+            my_decommitment.clone(),
+        )))
+        .await
+        .map_err(IoError::send_message)?;
+
+    let messages = utils::iter_peers(i, n).map(|j| {
+        let message = MsgRound2Uni {
+            sigmas: sigmas[usize::from(j)].clone(),
+        };
+        Outgoing::p2p(j, Msg::Round2Uni(message))
+    });
+    outgoings
+        .send_all(&mut futures_util::stream::iter(messages.map(Ok)))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Round 3
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let decommitments = rounds
+        .complete(round2_broad)
+        .await?
+        .map_err(IoError::receive_message)?;
+    let sigmas_msg = rounds
+        .complete(round2_uni)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    tracer.stage("Validate decommitments");
+    let blame = utils::collect_blame(&commitments, &decommitments, |j, com, decom| {
+        let com_expected = udigest::hash::<D>(&unambiguous::HashCom {
+            sid,
+            party_index: j,
+            decommitment: decom,
+        });
+        com.commitment != com_expected
+    });
+    if !blame.is_empty() {
+        return Err(KeygenAborted::new(KeygenAbortReason::InvalidDecommitment, blame).into());
+    }
+
+    tracer.stage("Validate data size");
+    let blame = utils::collect_blame(&decommitments, &sigmas_msg, |_, d, s| {
+        d.Fs.len() != k
+            || d.sch_commits.len() != k
+            || s.sigmas.len() != k
+            || d.Fs.iter().any(|F| F.degree() + 1 != usize::from(t))
+    });
+    if !blame.is_empty() {
+        return Err(KeygenAborted::new(KeygenAbortReason::InvalidDataSize, blame).into());
+    }
+
+    tracer.stage("Validate Feldmann VSS");
+    let blame = utils::collect_blame(&decommitments, &sigmas_msg, |_, d, s| {
+        d.Fs.iter().zip(&s.sigmas).any(|(F, sigma)| {
+            F.value::<_, Point<_>>(&I[usize::from(i)]) != Point::generator() * sigma
+        })
+    });
+    if !blame.is_empty() {
+        return Err(KeygenAborted::new(KeygenAbortReason::FeldmanVerificationFailed, blame).into());
+    }
+
+    tracer.stage("Compute rid");
+    let rid = decommitments
+        .iter_including_me(&my_decommitment)
+        .map(|d| &d.rid)
+        .fold(L::Rid::default(), utils::xor_array);
+    #[cfg(feature = "hd-wallet")]
+    let chain_codes = if hd_enabled {
+        tracer.stage("Compute chain_codes");
+        let blame = utils::collect_simple_blame(&decommitments, |decom| {
+            decom
+                .chain_codes
+                .as_ref()
+                .is_none_or(|codes| codes.len() != k)
+        });
+        if !blame.is_empty() {
+            return Err(KeygenAborted::new(KeygenAbortReason::MissingChainCode, blame).into());
+        }
+        let mut chain_codes = alloc::vec![hd_wallet::ChainCode::default(); k];
+        for decom in decommitments.iter_including_me(&my_decommitment) {
+            let codes = decom.chain_codes.as_ref().ok_or(Bug::NoChainCode)?;
+            for (acc, code) in chain_codes.iter_mut().zip(codes) {
+                *acc = utils::xor_array(*acc, code);
+            }
+        }
+        chain_codes.into_iter().map(Some).collect::<Vec<_>>()
+    } else {
+        alloc::vec![None; k]
+    };
+    tracer.stage("Compute Ys");
+    let polynomial_sums = (0..k)
+        .map(|key| {
+            decommitments
+                .iter_including_me(&my_decommitment)
+                .map(|d| &d.Fs[key])
+                .sum::<Polynomial<_>>()
+        })
+        .collect::<Vec<_>>();
+    let ys = polynomial_sums
+        .iter()
+        .map(|polynomial_sum| {
+            I.iter()
+                .map(|I_j| polynomial_sum.value(I_j))
+                .map(|y_j: Point<E>| NonZero::from_point(y_j).ok_or(Bug::ZeroShare))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    tracer.stage("Compute sigmas");
+    let sigmas = (0..k)
+        .map(|key| {
+            let sigma: Scalar<E> = sigmas_msg.iter().map(|msg| msg.sigmas[key]).sum();
+            let mut sigma = sigma + sigmas[usize::from(i)][key];
+            NonZero::from_secret_scalar(SecretScalar::new(&mut sigma)).ok_or(Bug::ZeroShare)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    debug_assert!(sigmas
+        .iter()
+        .zip(&ys)
+        .all(|(sigma, ys)| Point::generator() * sigma == ys[usize::from(i)]));
+
+    tracer.stage("Prove knowledge of `sigma_i` for every key");
+    let sch_proofs = (0..batch_size)
+        .zip(&rs)
+        .zip(&sigmas)
+        .map(|((key, r), sigma)| {
+            let challenge = Scalar::from_hash::<D>(&unambiguous::SchnorrPok {
+                sid,
+                prover: i,
+                key_index: key,
+                rid: rid.as_ref(),
+                y: ys[usize::from(key)][usize::from(i)],
+                h: my_decommitment.sch_commits[usize::from(key)].0,
+            });
+            let challenge = schnorr_pok::Challenge { nonce: challenge };
+            schnorr_pok::prove(r, &challenge, sigma)
+        })
+        .collect::<Vec<_>>();
+
+    tracer.send_msg();
+    let my_sch_proof = MsgRound3 { sch_proofs };
+    outgoings
+        .send(Outgoing::broadcast(Msg::Round3(my_sch_proof.clone())))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Output round
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let sch_proofs = rounds
+        .complete(round3)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    tracer.stage("Validate schnorr proofs");
+    let blame = utils::collect_blame(&decommitments, &sch_proofs, |_, _, proofs| {
+        proofs.sch_proofs.len() != k
+    });
+    if !blame.is_empty() {
+        return Err(KeygenAborted::new(KeygenAbortReason::InvalidDataSize, blame).into());
+    }
+    let blame = utils::collect_blame(&decommitments, &sch_proofs, |j, decom, proofs| {
+        (0..batch_size).zip(&proofs.sch_proofs).any(|(key, proof)| {
+            let y = ys[usize::from(key)][usize::from(j)];
+            let h = &decom.sch_commits[usize::from(key)];
+            let challenge = Scalar::from_hash::<D>(&unambiguous::SchnorrPok {
+                sid,
+                prover: j,
+                key_index: key,
+                rid: rid.as_ref(),
+                y,
+                h: h.0,
+            });
+            let challenge = schnorr_pok::Challenge { nonce: challenge };
+            proof.verify(h, &challenge, &y).is_err()
+        })
+    });
+    if !blame.is_empty() {
+        return Err(KeygenAborted::new(KeygenAbortReason::InvalidSchnorrProof, blame).into());
+    }
+
+    tracer.stage("Derive resulting public keys and other data");
+    let mut key_shares = Vec::with_capacity(k);
+    for (key, (ys, sigma)) in ys.into_iter().zip(sigmas).enumerate() {
+        let y: Point<E> = decommitments
+            .iter_including_me(&my_decommitment)
+            .map(|d| d.Fs[key].coefs()[0])
+            .sum();
+        let key_share = DirtyCoreKeyShare {
+            i,
+            key_info: DirtyKeyInfo {
+                curve: Default::default(),
+                shared_public_key: NonZero::from_point(y).ok_or(Bug::ZeroPk)?,
+                public_shares: ys,
+                vss_setup: Some(VssSetup {
+                    min_signers: t,
+                    I: I.clone(),
+                    weights: None,
+                    ranks: None,
+                }),
+                #[cfg(feature = "hd-wallet")]
+                chain_code: chain_codes[key],
+            },
+            x: sigma,
+            extra_x: Vec::new(),
+        }
+        .validate()
+        .map_err(|err| Bug::InvalidKeyShare(err.into_error()))?;
+        key_shares.push(key_share);
+    }
+
+    tracer.protocol_ends();
+
+    Ok(key_shares)
+}
@@ -0,0 +1,234 @@
+//! Per-round deadlines
+//!
+//! By default, protocols wait for messages of every round for as long as it takes: if some party
+//! goes silent, protocol execution never completes. Protocol builders accept a [`RoundTimer`]
+//! which sets a deadline for every round. If the deadline expires before all messages of the
+//! round are received, protocol fails with [`RoundTimeout`] error which tells which parties
+//! didn't send their messages.
+//!
+//! The crate doesn't depend on any async runtime, so timer needs to be provided by the caller.
+//! Any closure that returns a future can be used as a timer, for instance, with `tokio` runtime
+//! it could be `|| tokio::time::sleep(Duration::from_secs(30))`.
+//!
+//! Deadline applies to each round separately: timer is started when the protocol starts waiting
+//! for messages of the round.
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{
+    future::Future,
+    pin::{pin, Pin},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use futures_util::future::{select, Either};
+use round_based::{
+    rounds_router::{self, errors::CompleteRoundError, MessagesStore, Round},
+    Incoming, PartyIndex, ProtocolMessage, RoundMessage,
+};
+
+/// Sets a deadline for a protocol round
+///
+/// Implemented for any closure `FnMut() -> F` where `F` is a future: the round expires once
+/// the future is resolved. See [module level documentation](self) for more details.
+pub trait RoundTimer: Send + Sync {
+    /// Returns a future that resolves once the round is expired
+    ///
+    /// Called every time the protocol starts waiting for messages of the next round.
+    fn round_deadline(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+}
+
+impl<T, F> RoundTimer for T
+where
+    T: FnMut() -> F + Send + Sync,
+    F: Future<Output = ()> + Send + 'static,
+{
+    fn round_deadline(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(self())
+    }
+}
+
+/// Error indicating that round deadline expired before all messages of the round were received
+#[derive(Debug, Clone, displaydoc::Display)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+#[displaydoc("round {round} timed out; messages not received from parties: {missing_parties:?}")]
+pub struct RoundTimeout {
+    round: u16,
+    missing_parties: Vec<PartyIndex>,
+}
+
+impl RoundTimeout {
+    /// Number of the round that timed out
+    ///
+    /// Matches the round number assigned to the message in protocol message type
+    /// (see [`ProtocolMessage::round`]).
+    pub fn round(&self) -> u16 {
+        self.round
+    }
+
+    /// Indexes of parties whose messages for the round never arrived, sorted in ascending order
+    pub fn missing_parties(&self) -> &[PartyIndex] {
+        &self.missing_parties
+    }
+}
+
+/// Rounds router which enforces per-round deadlines
+///
+/// Wraps [`round_based::rounds_router::RoundsRouter`]. Keeps track of which parties sent
+/// messages at every round, so when round deadline expires, it can tell who is missing.
+pub struct RoundsRouter<'t, M, S = ()> {
+    inner: rounds_router::RoundsRouter<M, S>,
+    i: PartyIndex,
+    received: Vec<(u16, Arc<[AtomicBool]>)>,
+    timer: Option<&'t mut dyn RoundTimer>,
+}
+
+impl<M: ProtocolMessage + 'static> RoundsRouter<'static, M> {
+    /// Instantiates [`RoundsRouterBuilder`]
+    ///
+    /// Takes local party index $i$ and number of parties $n$
+    pub fn builder(i: PartyIndex, n: u16) -> RoundsRouterBuilder<M> {
+        RoundsRouterBuilder {
+            inner: rounds_router::RoundsRouter::builder(),
+            i,
+            n,
+            received: Vec::new(),
+        }
+    }
+}
+
+impl<M, S, E> RoundsRouter<'_, M, S>
+where
+    M: ProtocolMessage,
+    S: futures_util::Stream<Item = Result<Incoming<M>, E>> + Unpin,
+    E: core::error::Error,
+{
+    /// Completes specified round
+    ///
+    /// Same as [`round_based::rounds_router::RoundsRouter::complete`], but returns
+    /// `Err(RoundTimeout)` if round deadline expired before round is completed.
+    #[allow(clippy::type_complexity)]
+    pub async fn complete<R>(
+        &mut self,
+        round: Round<Tracked<R>>,
+    ) -> Result<Result<R::Output, CompleteRoundError<R::Error, E>>, RoundTimeout>
+    where
+        R: MessagesStore,
+        M: RoundMessage<R::Msg>,
+    {
+        let deadline = match self.timer.as_mut() {
+            Some(timer) => timer.round_deadline(),
+            None => return Ok(self.inner.complete(round).await),
+        };
+        let complete = pin!(self.inner.complete(round));
+        match select(complete, deadline).await {
+            Either::Left((output, _)) => Ok(output),
+            Either::Right(((), _)) => {
+                let round = <M as RoundMessage<R::Msg>>::ROUND;
+                let missing_parties = self
+                    .received
+                    .iter()
+                    .find(|(n, _)| *n == round)
+                    .map(|(_, received)| {
+                        (0..)
+                            .zip(received.iter())
+                            .filter(|(j, received)| {
+                                *j != self.i && !received.load(Ordering::Relaxed)
+                            })
+                            .map(|(j, _)| j)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                Err(RoundTimeout {
+                    round,
+                    missing_parties,
+                })
+            }
+        }
+    }
+}
+
+/// Builds [`RoundsRouter`]
+pub struct RoundsRouterBuilder<M> {
+    inner: rounds_router::RoundsRouterBuilder<M>,
+    i: PartyIndex,
+    n: u16,
+    received: Vec<(u16, Arc<[AtomicBool]>)>,
+}
+
+impl<M> RoundsRouterBuilder<M>
+where
+    M: ProtocolMessage + 'static,
+{
+    /// Registers new round
+    ///
+    /// ## Panics
+    /// Panics if round `R` was already registered
+    pub fn add_round<R>(&mut self, message_store: R) -> Round<Tracked<R>>
+    where
+        R: MessagesStore + Send + 'static,
+        R::Output: Send,
+        R::Error: Send,
+        M: RoundMessage<R::Msg>,
+    {
+        let received: Arc<[AtomicBool]> = (0..self.n).map(|_| AtomicBool::new(false)).collect();
+        self.received.push((M::ROUND, received.clone()));
+        self.inner.add_round(Tracked {
+            store: message_store,
+            received,
+        })
+    }
+
+    /// Builds [`RoundsRouter`]
+    ///
+    /// Takes a stream of incoming messages which will be routed between registered rounds, and
+    /// optional timer that sets deadline for every round
+    pub fn listen<'t, S, E>(
+        self,
+        incomings: S,
+        timer: Option<&'t mut dyn RoundTimer>,
+    ) -> RoundsRouter<'t, M, S>
+    where
+        S: futures_util::Stream<Item = Result<Incoming<M>, E>>,
+    {
+        RoundsRouter {
+            inner: self.inner.listen(incomings),
+            i: self.i,
+            received: self.received,
+            timer,
+        }
+    }
+}
+
+/// Messages store that remembers which parties sent a message
+///
+/// Output and errors are the same as of the wrapped store.
+pub struct Tracked<R> {
+    store: R,
+    received: Arc<[AtomicBool]>,
+}
+
+impl<R: MessagesStore> MessagesStore for Tracked<R> {
+    type Msg = R::Msg;
+    type Output = R::Output;
+    type Error = R::Error;
+
+    fn add_message(&mut self, msg: Incoming<Self::Msg>) -> Result<(), Self::Error> {
+        let sender = msg.sender;
+        self.store.add_message(msg)?;
+        if let Some(received) = self.received.get(usize::from(sender)) {
+            received.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    fn wants_more(&self) -> bool {
+        self.store.wants_more()
+    }
+
+    fn output(self) -> Result<Self::Output, Self> {
+        let received = self.received;
+        self.store
+            .output()
+            .map_err(|store| Self { store, received })
+    }
+}
@@ -18,14 +18,15 @@ where
     a
 }
 
+/// Party to blame for aborting the protocol
+///
 /// For some messages it is possible to precisely identify where the fault
-/// happened and which party is to blame. Use this struct to collect present the
-/// blame.
+/// happened and which party is to blame. Blame points to the faulty party
+/// and to the messages it sent which prove its misbehavior.
 ///
-/// In the future we might want to replace the data_message and proof_message
-/// with a generic vec of messages.
-#[derive(Debug)]
-#[allow(dead_code)] // removes false-positive warnings
+/// When misbehavior is evident from a single message, `data_message` and
+/// `proof_message` refer to the same message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct AbortBlame {
     /// Party which can be blamed for breaking the protocol
     pub faulty_party: PartyIndex,
@@ -36,6 +37,7 @@ pub struct AbortBlame {
 }
 
 impl AbortBlame {
+    /// Constructs a blame
     pub fn new(faulty_party: PartyIndex, data_message: MsgId, proof_message: MsgId) -> Self {
         Self {
             faulty_party,
@@ -45,6 +47,59 @@ impl AbortBlame {
     }
 }
 
+/// Error indicating that protocol was aborted by malicious party
+///
+/// Contains the reason of abort and the list of parties to blame along with
+/// messages which prove their misbehavior. `Reason` is a protocol-specific
+/// enum describing which exact check has failed.
+#[derive(Debug, Clone)]
+pub struct Aborted<Reason> {
+    reason: Reason,
+    parties: Vec<AbortBlame>,
+}
+
+impl<Reason: Copy> Aborted<Reason> {
+    /// Constructs an error from the reason of abort and the list of parties to blame
+    pub fn new(reason: Reason, parties: Vec<AbortBlame>) -> Self {
+        Self { reason, parties }
+    }
+
+    /// Reason for protocol abort: which exact check has failed
+    pub fn reason(&self) -> Reason {
+        self.reason
+    }
+
+    /// Parties to blame along with messages that prove their misbehavior
+    pub fn blame(&self) -> &[AbortBlame] {
+        &self.parties
+    }
+
+    /// Indexes of parties to blame, sorted and deduplicated
+    pub fn faulty_parties(&self) -> Vec<PartyIndex> {
+        let mut parties = self
+            .parties
+            .iter()
+            .map(|blame| blame.faulty_party)
+            .collect::<Vec<_>>();
+        parties.sort_unstable();
+        parties.dedup();
+        parties
+    }
+}
+
+impl<Reason: core::fmt::Display> core::fmt::Display for Aborted<Reason> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "protocol aborted; malicious parties: {:?}; reason: {}",
+            self.parties, self.reason
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Reason: core::fmt::Debug + core::fmt::Display> std::error::Error for Aborted<Reason> {}
+
 /// Filter returns `true` for every __faulty__ message pair
 pub fn collect_blame<D, P, F>(
     data_messages: &RoundMsgs<D>,
@@ -69,7 +124,6 @@ where
 
 /// Filter returns `true` for every __faulty__ message. Data and proof are set
 /// to the same message.
-#[cfg(feature = "hd-wallet")]
 pub fn collect_simple_blame<D, F>(messages: &RoundMsgs<D>, mut filter: F) -> Vec<AbortBlame>
 where
     F: FnMut(&D) -> bool,
@@ -0,0 +1,421 @@
+//! Chain code generation protocol
+//!
+//! Makes an existing key HD-capable. Keys generated without HD wallets support don't have a
+//! chain code, so child keys can't be derived from them. In this protocol, all `n` key co-holders
+//! jointly sample a chain code: each party commits to its random contribution, then reveals it,
+//! and the resulting chain code is XOR of all contributions. Shared public key and key shares stay
+//! the same.
+//!
+//! Protocol outputs core key share ([`IncompleteKeyShare`]) with the chain code set. Aux info
+//! doesn't depend on the chain code, so aux info of the original [`KeyShare`](crate::KeyShare) can
+//! be used to complete the resulting key share.
+
+use digest::Digest;
+use futures::SinkExt;
+use generic_ec::Curve;
+use rand_core::{CryptoRng, RngCore};
+use round_based::{
+    rounds_router::{simple_store::RoundInput, RoundsRouter},
+    Delivery, Mpc, MpcParty, Outgoing, ProtocolMessage,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    errors::IoError,
+    key_share::{AnyKeyShare, IncompleteKeyShare, InvalidIncompleteKeyShare, Validate},
+    progress::Tracer,
+    utils::{self, AbortBlame, Aborted},
+    ExecutionId,
+};
+
+macro_rules! prefixed {
+    ($name:tt) => {
+        concat!("dfns.cggmp21.chain_code_gen.", $name)
+    };
+}
+
+/// Message of chain code generation protocol
+#[derive(ProtocolMessage, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum Msg<D: Digest> {
+    /// Round 1 message
+    Round1(MsgRound1<D>),
+    /// Round 2 message
+    Round2(MsgRound2),
+    /// Reliability check message (optional additional round)
+    ReliabilityCheck(MsgReliabilityCheck<D>),
+}
+
+/// Message from round 1
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[serde(bound = "")]
+#[udigest(bound = "")]
+#[udigest(tag = prefixed!("round1"))]
+pub struct MsgRound1<D: Digest> {
+    /// $V_i$
+    #[udigest(as_bytes)]
+    pub commitment: digest::Output<D>,
+}
+/// Message from round 2
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[udigest(tag = prefixed!("round2"))]
+pub struct MsgRound2 {
+    /// Party contribution to chain code
+    #[serde(with = "hex::serde")]
+    #[udigest(as_bytes)]
+    pub chain_code: hd_wallet::ChainCode,
+    /// $u_i$
+    #[serde(with = "hex::serde")]
+    #[udigest(as_bytes)]
+    pub decommit: [u8; 32],
+}
+/// Message parties exchange to ensure reliability of broadcast channel
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgReliabilityCheck<D: Digest>(pub digest::Output<D>);
+
+mod unambiguous {
+    use crate::ExecutionId;
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("hash_commitment"))]
+    pub struct HashCom<'a> {
+        pub sid: ExecutionId<'a>,
+        pub party_index: u16,
+        pub decommitment: &'a super::MsgRound2,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("echo_round"))]
+    #[udigest(bound = "")]
+    pub struct Echo<'a, D: digest::Digest> {
+        pub sid: ExecutionId<'a>,
+        pub commitment: &'a super::MsgRound1<D>,
+    }
+}
+
+/// Chain code generation protocol builder
+pub struct ChainCodeGenBuilder<'a, E: Curve, D = crate::default_choice::Digest> {
+    eid: ExecutionId<'a>,
+    key_share: &'a IncompleteKeyShare<E>,
+    tracer: Option<&'a mut dyn Tracer>,
+    enforce_reliable_broadcast: bool,
+    _digest: std::marker::PhantomData<D>,
+}
+
+impl<'a, E, D> ChainCodeGenBuilder<'a, E, D>
+where
+    E: Curve,
+    D: Digest + Clone + 'static,
+{
+    /// Constructs a builder
+    ///
+    /// Takes key share of local party. Key must not have a chain code yet.
+    pub fn new(eid: ExecutionId<'a>, key_share: &'a impl AnyKeyShare<E>) -> Self {
+        Self {
+            eid,
+            key_share: key_share.as_ref(),
+            tracer: None,
+            enforce_reliable_broadcast: true,
+            _digest: std::marker::PhantomData,
+        }
+    }
+
+    /// Specifies another hash function to use
+    pub fn set_digest<D2>(self) -> ChainCodeGenBuilder<'a, E, D2>
+    where
+        D2: Digest + Clone + 'static,
+    {
+        ChainCodeGenBuilder {
+            eid: self.eid,
+            key_share: self.key_share,
+            tracer: self.tracer,
+            enforce_reliable_broadcast: self.enforce_reliable_broadcast,
+            _digest: std::marker::PhantomData,
+        }
+    }
+
+    /// Sets a tracer that tracks progress of protocol execution
+    pub fn set_progress_tracer(mut self, tracer: &'a mut dyn Tracer) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    #[doc = include_str!("../docs/enforce_reliable_broadcast.md")]
+    pub fn enforce_reliable_broadcast(self, v: bool) -> Self {
+        Self {
+            enforce_reliable_broadcast: v,
+            ..self
+        }
+    }
+
+    /// Carries out the chain code generation protocol
+    ///
+    /// Returns key share of local party with the chain code set
+    pub async fn start<R, M>(
+        self,
+        rng: &mut R,
+        party: M,
+    ) -> Result<IncompleteKeyShare<E>, ChainCodeGenError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = Msg<D>>,
+    {
+        run_chain_code_gen(
+            self.tracer,
+            self.key_share,
+            self.enforce_reliable_broadcast,
+            self.eid,
+            rng,
+            party,
+        )
+        .await
+    }
+
+    /// Returns a state machine that can be used to carry out the chain code generation protocol
+    ///
+    /// See [`round_based::state_machine`] for details on how that can be done.
+    #[cfg(feature = "state-machine")]
+    pub fn into_state_machine<R>(
+        self,
+        rng: &'a mut R,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<IncompleteKeyShare<E>, ChainCodeGenError>,
+        Msg = Msg<D>,
+    > + 'a
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(|party| self.start(rng, party))
+    }
+}
+
+async fn run_chain_code_gen<E, D, R, M>(
+    mut tracer: Option<&mut dyn Tracer>,
+    key_share: &IncompleteKeyShare<E>,
+    reliable_broadcast_enforced: bool,
+    sid: ExecutionId<'_>,
+    rng: &mut R,
+    party: M,
+) -> Result<IncompleteKeyShare<E>, ChainCodeGenError>
+where
+    E: Curve,
+    D: Digest + Clone + 'static,
+    R: RngCore + CryptoRng,
+    M: Mpc<ProtocolMessage = Msg<D>>,
+{
+    tracer.protocol_begins();
+
+    tracer.stage("Validate arguments");
+    if key_share.is_hd_wallet() {
+        return Err(InvalidArgs::AlreadyHdWallet.into());
+    }
+    let i = key_share.i;
+    let n = key_share.n();
+
+    tracer.stage("Setup networking");
+    let MpcParty { delivery, .. } = party.into_party();
+    let (incomings, mut outgoings) = delivery.split();
+
+    let mut rounds = RoundsRouter::<Msg<D>>::builder();
+    let round1 = rounds.add_round(RoundInput::<MsgRound1<D>>::broadcast(i, n));
+    let round1_sync = rounds.add_round(RoundInput::<MsgReliabilityCheck<D>>::broadcast(i, n));
+    let round2 = rounds.add_round(RoundInput::<MsgRound2>::broadcast(i, n));
+    let mut rounds = rounds.listen(incomings);
+
+    // Round 1
+    tracer.round_begins();
+
+    tracer.stage("Sample chain_code_i and commit to it");
+    let mut chain_code = hd_wallet::ChainCode::default();
+    rng.fill_bytes(&mut chain_code);
+    let my_decommitment = MsgRound2 {
+        chain_code,
+        decommit: {
+            let mut nonce = [0u8; 32];
+            rng.fill_bytes(&mut nonce);
+            nonce
+        },
+    };
+    let hash_commit = udigest::hash::<D>(&unambiguous::HashCom {
+        sid,
+        party_index: i,
+        decommitment: &my_decommitment,
+    });
+
+    tracer.send_msg();
+    let my_commitment = MsgRound1 {
+        commitment: hash_commit,
+    };
+    outgoings
+        .send(Outgoing::broadcast(Msg::Round1(my_commitment.clone())))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Round 2
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let commitments = rounds
+        .complete(round1)
+        .await
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    // Optional reliability check
+    if reliable_broadcast_enforced {
+        tracer.stage("Hash received msgs (reliability check)");
+        let h_i = udigest::hash_iter::<D>(
+            commitments
+                .iter_including_me(&my_commitment)
+                .map(|commitment| unambiguous::Echo { sid, commitment }),
+        );
+
+        tracer.send_msg();
+        outgoings
+            .send(Outgoing::broadcast(Msg::ReliabilityCheck(
+                MsgReliabilityCheck(h_i.clone()),
+            )))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.round_begins();
+
+        tracer.receive_msgs();
+        let hashes = rounds
+            .complete(round1_sync)
+            .await
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        tracer.stage("Assert other parties hashed messages (reliability check)");
+        let parties_have_different_hashes = hashes
+            .into_iter_indexed()
+            .filter(|(_j, _msg_id, h_j)| h_i != h_j.0)
+            .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        if !parties_have_different_hashes.is_empty() {
+            return Err(ChainCodeGenAborted::new(
+                ChainCodeGenAbortReason::Round1NotReliable,
+                parties_have_different_hashes,
+            )
+            .into());
+        }
+    }
+
+    tracer.send_msg();
+    outgoings
+        .send(Outgoing::broadcast(Msg::Round2(my_decommitment.clone())))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Output
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let decommitments = rounds
+        .complete(round2)
+        .await
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    tracer.stage("Validate decommitments");
+    let blame = utils::collect_blame(&commitments, &decommitments, |j, com, decom| {
+        let com_expected = udigest::hash::<D>(&unambiguous::HashCom {
+            sid,
+            party_index: j,
+            decommitment: decom,
+        });
+        com.commitment != com_expected
+    });
+    if !blame.is_empty() {
+        return Err(
+            ChainCodeGenAborted::new(ChainCodeGenAbortReason::InvalidDecommitment, blame).into(),
+        );
+    }
+
+    tracer.stage("Compute chain_code");
+    let chain_code = decommitments
+        .iter_including_me(&my_decommitment)
+        .map(|d| d.chain_code)
+        .fold(hd_wallet::ChainCode::default(), utils::xor_array);
+
+    let mut key_share = key_share.clone().into_inner();
+    key_share.key_info.chain_code = Some(chain_code);
+    let key_share = key_share
+        .validate()
+        .map_err(|err| Bug::InvalidShareGenerated(err.into_error()))?;
+
+    tracer.protocol_ends();
+    Ok(key_share)
+}
+
+/// Error of chain code generation protocol
+#[derive(Debug, Error)]
+#[error("chain code generation protocol failed to complete")]
+pub struct ChainCodeGenError(#[source] Reason);
+
+impl ChainCodeGenError {
+    /// If protocol was aborted by malicious party, returns information about
+    /// the abort: its reason and parties to blame
+    pub fn aborted(&self) -> Option<&ChainCodeGenAborted> {
+        match &self.0 {
+            Reason::Aborted(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+crate::errors::impl_from! {
+    impl From for ChainCodeGenError {
+        err: InvalidArgs => ChainCodeGenError(Reason::InvalidArgs(err)),
+        err: ChainCodeGenAborted => ChainCodeGenError(Reason::Aborted(err)),
+        err: IoError => ChainCodeGenError(Reason::IoError(err)),
+        err: Bug => ChainCodeGenError(Reason::Bug(err)),
+    }
+}
+
+#[derive(Debug, Error)]
+enum Reason {
+    #[error("invalid arguments")]
+    InvalidArgs(#[source] InvalidArgs),
+    /// Protocol was maliciously aborted by another party
+    #[error("protocol was aborted by malicious party")]
+    Aborted(#[source] ChainCodeGenAborted),
+    #[error("i/o error")]
+    IoError(#[source] IoError),
+    #[error("bug occurred")]
+    Bug(#[source] Bug),
+}
+
+#[derive(Debug, Error)]
+enum InvalidArgs {
+    #[error("key already has a chain code")]
+    AlreadyHdWallet,
+}
+
+/// Unexpected error in operation not caused by other parties
+#[derive(Debug, Error)]
+enum Bug {
+    #[error("resulting key share is not valid")]
+    InvalidShareGenerated(#[source] InvalidIncompleteKeyShare),
+}
+
+/// Error indicating that chain code generation was aborted by malicious party
+pub type ChainCodeGenAborted = Aborted<ChainCodeGenAbortReason>;
+
+/// Reason for chain code generation abort: which exact check has failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum ChainCodeGenAbortReason {
+    /// Decommitment doesn't match commitment
+    #[error("decommitment doesn't match commitment")]
+    InvalidDecommitment,
+    /// Party claims it received different messages in round 1 than other parties
+    #[error("round 1 was not reliable")]
+    Round1NotReliable,
+}
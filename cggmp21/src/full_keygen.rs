@@ -0,0 +1,426 @@
+//! Key generation combined with aux info generation
+//!
+//! Obtaining a complete [`KeyShare`] normally requires carrying out two protocols one after
+//! another: [keygen](crate::keygen()) and [aux info generation](crate::aux_info_gen), and then
+//! joining their outputs via [`KeyShare::from_parts`]. This module runs both protocols at once:
+//! messages of both protocols are sent over the same channel, and rounds of one protocol don't
+//! wait for rounds of another one. It takes as many communication rounds as the longest of the
+//! two protocols, and only one execution id is needed.
+//!
+//! Execution ids of sub-protocols are derived from the execution id provided by the caller.
+
+use digest::Digest;
+use futures::TryFutureExt;
+use rand_core::{CryptoRng, RngCore};
+use round_based::{Mpc, MpcParty, ProtocolMessage};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    errors::IoError,
+    key_refresh::{AuxInfoGenerationBuilder, PregeneratedPrimes},
+    key_share::{AuxInfo, InvalidKeyShare},
+    keygen::{KeygenBuilder, NonThreshold, WithThreshold},
+    security_level::SecurityLevel,
+    ExecutionId, IncompleteKeyShare, KeyRefreshError, KeyShare, KeygenError,
+};
+
+mod mux;
+
+macro_rules! prefixed {
+    ($name:tt) => {
+        concat!("dfns.cggmp21.full_keygen.", $name)
+    };
+}
+
+/// Message of combined protocol: message of either keygen or aux info generation
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Msg<K, A> {
+    /// Keygen message
+    Keygen(K),
+    /// Aux info generation message
+    AuxInfo(A),
+}
+
+/// Messages are not routed by round at the top level, so round of the message
+/// is index of sub-protocol it belongs to
+impl<K, A> ProtocolMessage for Msg<K, A> {
+    fn round(&self) -> u16 {
+        match self {
+            Msg::Keygen(_) => 0,
+            Msg::AuxInfo(_) => 1,
+        }
+    }
+}
+
+/// Message of non-threshold keygen combined with aux info generation
+pub type NonThresholdMsg<E, L, D> =
+    Msg<crate::keygen::NonThresholdMsg<E, L, D>, crate::key_refresh::AuxOnlyMsg<D, L>>;
+/// Message of threshold keygen combined with aux info generation
+pub type ThresholdMsg<E, L, D> =
+    Msg<crate::keygen::ThresholdMsg<E, L, D>, crate::key_refresh::AuxOnlyMsg<D, L>>;
+
+mod unambiguous {
+    use crate::ExecutionId;
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("subprotocol"))]
+    pub struct Subprotocol<'a> {
+        pub sid: ExecutionId<'a>,
+        pub subprotocol: &'static str,
+    }
+}
+
+/// Combined keygen entry point. You can call [`set_threshold`] to make it into a threshold DKG
+///
+/// [`set_threshold`]: GenericFullKeygenBuilder::set_threshold
+pub type FullKeygenBuilder<
+    'a,
+    E,
+    L = crate::default_choice::SecurityLevel,
+    D = crate::default_choice::Digest,
+> = GenericFullKeygenBuilder<'a, E, NonThreshold, L, D>;
+
+/// Combined threshold keygen builder
+pub type ThresholdFullKeygenBuilder<
+    'a,
+    E,
+    L = crate::default_choice::SecurityLevel,
+    D = crate::default_choice::Digest,
+> = GenericFullKeygenBuilder<'a, E, WithThreshold, L, D>;
+
+/// Builder of keygen combined with aux info generation, with choice for threshold or
+/// non-threshold variant
+pub struct GenericFullKeygenBuilder<'a, E, M, L: SecurityLevel, D> {
+    eid: ExecutionId<'a>,
+    pregenerated: PregeneratedPrimes<L>,
+    params: Params,
+    _params: std::marker::PhantomData<(E, M, D)>,
+}
+
+/// Parameters of sub-protocols
+#[derive(Clone, Copy)]
+struct Params {
+    i: u16,
+    n: u16,
+    /// Threshold, only used in threshold keygen
+    t: u16,
+    enforce_reliable_broadcast: bool,
+    precompute_multiexp_tables: bool,
+    precompute_crt: bool,
+    #[cfg(feature = "hd-wallet")]
+    hd_enabled: bool,
+}
+
+impl Params {
+    fn keygen<'e, E, L, D>(self, eid: ExecutionId<'e>) -> KeygenBuilder<'e, E, L, D>
+    where
+        E: generic_ec::Curve,
+        L: SecurityLevel,
+        D: Digest + Clone + 'static,
+    {
+        let builder = KeygenBuilder::<E, L, D>::new(eid, self.i, self.n)
+            .enforce_reliable_broadcast(self.enforce_reliable_broadcast);
+        #[cfg(feature = "hd-wallet")]
+        let builder = builder.hd_wallet(self.hd_enabled);
+        builder
+    }
+
+    fn aux_info_gen<'e, L, D>(
+        self,
+        eid: ExecutionId<'e>,
+        pregenerated: PregeneratedPrimes<L>,
+    ) -> AuxInfoGenerationBuilder<'e, L, D>
+    where
+        L: SecurityLevel,
+        D: Digest,
+    {
+        AuxInfoGenerationBuilder::<L, D>::new_aux_gen(eid, self.i, self.n, pregenerated)
+            .enforce_reliable_broadcast(self.enforce_reliable_broadcast)
+            .precompute_multiexp_tables(self.precompute_multiexp_tables)
+            .precompute_crt(self.precompute_crt)
+    }
+}
+
+impl<'a, E, L> FullKeygenBuilder<'a, E, L>
+where
+    E: generic_ec::Curve,
+    L: SecurityLevel,
+{
+    /// Constructs [`FullKeygenBuilder`]
+    ///
+    /// Takes local party index $i$, number of parties $n$, and pregenerated primes
+    /// that will be used to generate aux info
+    pub fn new(eid: ExecutionId<'a>, i: u16, n: u16, pregenerated: PregeneratedPrimes<L>) -> Self {
+        Self {
+            eid,
+            pregenerated,
+            params: Params {
+                i,
+                n,
+                t: n,
+                enforce_reliable_broadcast: true,
+                precompute_multiexp_tables: false,
+                precompute_crt: false,
+                #[cfg(feature = "hd-wallet")]
+                hd_enabled: true,
+            },
+            _params: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'a, E, M, L, D> GenericFullKeygenBuilder<'a, E, M, L, D>
+where
+    E: generic_ec::Curve,
+    L: SecurityLevel,
+    D: Digest<OutputSize = digest::typenum::U32> + Clone + 'static,
+{
+    /// Specifies to generate key shares for a threshold scheme
+    pub fn set_threshold(self, t: u16) -> GenericFullKeygenBuilder<'a, E, WithThreshold, L, D> {
+        GenericFullKeygenBuilder {
+            eid: self.eid,
+            pregenerated: self.pregenerated,
+            params: Params { t, ..self.params },
+            _params: std::marker::PhantomData,
+        }
+    }
+
+    /// Specifies another hash function to use
+    pub fn set_digest<D2>(self) -> GenericFullKeygenBuilder<'a, E, M, L, D2>
+    where
+        D2: Digest<OutputSize = digest::typenum::U32> + Clone + 'static,
+    {
+        GenericFullKeygenBuilder {
+            eid: self.eid,
+            pregenerated: self.pregenerated,
+            params: self.params,
+            _params: std::marker::PhantomData,
+        }
+    }
+
+    #[doc = include_str!("../docs/enforce_reliable_broadcast.md")]
+    pub fn enforce_reliable_broadcast(mut self, v: bool) -> Self {
+        self.params.enforce_reliable_broadcast = v;
+        self
+    }
+
+    /// Precomputes multiexponentiation tables for output aux data
+    ///
+    /// See [`GenericKeyRefreshBuilder::precompute_multiexp_tables`](crate::key_refresh::GenericKeyRefreshBuilder::precompute_multiexp_tables)
+    pub fn precompute_multiexp_tables(mut self, v: bool) -> Self {
+        self.params.precompute_multiexp_tables = v;
+        self
+    }
+
+    /// Precomputes CRT parameters
+    ///
+    /// See [`GenericKeyRefreshBuilder::precompute_crt`](crate::key_refresh::GenericKeyRefreshBuilder::precompute_crt)
+    pub fn precompute_crt(mut self, v: bool) -> Self {
+        self.params.precompute_crt = v;
+        self
+    }
+
+    #[cfg(feature = "hd-wallet")]
+    /// Specifies whether HD derivation is enabled for a key
+    pub fn hd_wallet(mut self, v: bool) -> Self {
+        self.params.hd_enabled = v;
+        self
+    }
+
+    /// Derives execution ids of keygen and aux info generation
+    fn subprotocol_eids(&self) -> (digest::Output<D>, digest::Output<D>) {
+        let eid = |subprotocol| {
+            udigest::hash::<D>(&unambiguous::Subprotocol {
+                sid: self.eid,
+                subprotocol,
+            })
+        };
+        (eid("keygen"), eid("aux_info_gen"))
+    }
+}
+
+impl<'a, E, L, D> GenericFullKeygenBuilder<'a, E, NonThreshold, L, D>
+where
+    E: generic_ec::Curve,
+    L: SecurityLevel,
+    D: Digest<OutputSize = digest::typenum::U32> + Clone + 'static,
+{
+    /// Starts key generation
+    pub async fn start<R, M>(self, rng: &mut R, party: M) -> Result<KeyShare<E, L>, FullKeygenError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = NonThresholdMsg<E, L, D>>,
+    {
+        let (keygen_eid, aux_info_eid) = self.subprotocol_eids();
+        let mut keygen_rng = mux::SharedRng::new(rng);
+        let mut aux_info_rng = keygen_rng.clone();
+
+        let keygen_channel = mux::Channel::new();
+        let keygen = self
+            .params
+            .keygen(ExecutionId::new(&keygen_eid))
+            .start(
+                &mut keygen_rng,
+                MpcParty::connected(keygen_channel.delivery()),
+            )
+            .map_err(FullKeygenError::from);
+        let aux_info_channel = mux::Channel::new();
+        let aux_info = self
+            .params
+            .aux_info_gen::<L, D>(ExecutionId::new(&aux_info_eid), self.pregenerated)
+            .start(
+                &mut aux_info_rng,
+                MpcParty::connected(aux_info_channel.delivery()),
+            )
+            .map_err(FullKeygenError::from);
+
+        let MpcParty { delivery, .. } = party.into_party();
+        let (core_share, aux_info) = mux::join(
+            delivery,
+            (keygen_channel, keygen),
+            (aux_info_channel, aux_info),
+        )
+        .await?;
+        complete_key_share(core_share, aux_info)
+    }
+
+    /// Returns a state machine that can be used to carry out the combined keygen protocol
+    ///
+    /// See [`round_based::state_machine`] for details on how that can be done.
+    #[cfg(feature = "state-machine")]
+    pub fn into_state_machine<R>(
+        self,
+        rng: &'a mut R,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<KeyShare<E, L>, FullKeygenError>,
+        Msg = NonThresholdMsg<E, L, D>,
+    > + 'a
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(|party| self.start(rng, party))
+    }
+}
+
+impl<'a, E, L, D> GenericFullKeygenBuilder<'a, E, WithThreshold, L, D>
+where
+    E: generic_ec::Curve,
+    L: SecurityLevel,
+    D: Digest<OutputSize = digest::typenum::U32> + Clone + 'static,
+{
+    /// Starts threshold key generation
+    pub async fn start<R, M>(self, rng: &mut R, party: M) -> Result<KeyShare<E, L>, FullKeygenError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = ThresholdMsg<E, L, D>>,
+    {
+        let (keygen_eid, aux_info_eid) = self.subprotocol_eids();
+        let mut keygen_rng = mux::SharedRng::new(rng);
+        let mut aux_info_rng = keygen_rng.clone();
+
+        let keygen_channel = mux::Channel::new();
+        let keygen = self
+            .params
+            .keygen(ExecutionId::new(&keygen_eid))
+            .set_threshold(self.params.t)
+            .start(
+                &mut keygen_rng,
+                MpcParty::connected(keygen_channel.delivery()),
+            )
+            .map_err(FullKeygenError::from);
+        let aux_info_channel = mux::Channel::new();
+        let aux_info = self
+            .params
+            .aux_info_gen::<L, D>(ExecutionId::new(&aux_info_eid), self.pregenerated)
+            .start(
+                &mut aux_info_rng,
+                MpcParty::connected(aux_info_channel.delivery()),
+            )
+            .map_err(FullKeygenError::from);
+
+        let MpcParty { delivery, .. } = party.into_party();
+        let (core_share, aux_info) = mux::join(
+            delivery,
+            (keygen_channel, keygen),
+            (aux_info_channel, aux_info),
+        )
+        .await?;
+        complete_key_share(core_share, aux_info)
+    }
+
+    /// Returns a state machine that can be used to carry out the combined threshold keygen
+    /// protocol
+    ///
+    /// See [`round_based::state_machine`] for details on how that can be done.
+    #[cfg(feature = "state-machine")]
+    pub fn into_state_machine<R>(
+        self,
+        rng: &'a mut R,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<KeyShare<E, L>, FullKeygenError>,
+        Msg = ThresholdMsg<E, L, D>,
+    > + 'a
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(|party| self.start(rng, party))
+    }
+}
+
+/// Joins outputs of sub-protocols into a complete key share
+fn complete_key_share<E, L>(
+    core_share: IncompleteKeyShare<E>,
+    aux_info: AuxInfo<L>,
+) -> Result<KeyShare<E, L>, FullKeygenError>
+where
+    E: generic_ec::Curve,
+    L: SecurityLevel,
+{
+    KeyShare::from_parts((core_share, aux_info))
+        .map_err(|err| FullKeygenError(Reason::InvalidKeyShare(err.into_error())))
+}
+
+/// Error of combined keygen protocol
+#[derive(Debug, Error)]
+#[error("keygen combined with aux info generation failed to complete")]
+pub struct FullKeygenError(#[source] Reason);
+
+impl FullKeygenError {
+    /// If keygen failed, returns its error
+    pub fn keygen(&self) -> Option<&KeygenError> {
+        match &self.0 {
+            Reason::Keygen(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// If aux info generation failed, returns its error
+    pub fn aux_info_gen(&self) -> Option<&KeyRefreshError> {
+        match &self.0 {
+            Reason::AuxInfoGen(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+crate::errors::impl_from! {
+    impl From for FullKeygenError {
+        err: KeygenError => FullKeygenError(Reason::Keygen(err)),
+        err: KeyRefreshError => FullKeygenError(Reason::AuxInfoGen(err)),
+        err: IoError => FullKeygenError(Reason::IoError(err)),
+    }
+}
+
+#[derive(Debug, Error)]
+enum Reason {
+    #[error("keygen failed")]
+    Keygen(#[source] KeygenError),
+    #[error("aux info generation failed")]
+    AuxInfoGen(#[source] KeyRefreshError),
+    #[error("i/o error")]
+    IoError(#[source] IoError),
+    /// Outputs of sub-protocols are inconsistent, which should never happen
+    #[error("resulting key share is not valid")]
+    InvalidKeyShare(#[source] InvalidKeyShare),
+}
@@ -0,0 +1,225 @@
+//! Carries out keygen and aux info generation over a single channel
+//!
+//! Each sub-protocol is given its own in-memory channel. [`join`] polls sub-protocols until
+//! both of them are waiting for incoming messages, sends everything they produced to the
+//! underlying channel, then receives one message and passes it to the sub-protocol it belongs
+//! to. Messages are received only once sub-protocols have nothing to send, so it also works
+//! on top of [`round_based::state_machine`] delivery that can't send and receive at the same
+//! time.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll};
+
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use rand_core::{CryptoRng, RngCore};
+use round_based::{Delivery, Incoming, Outgoing};
+
+use super::Msg;
+use crate::errors::IoError;
+
+/// In-memory channel between sub-protocol and [`join`]
+pub struct Channel<M>(Arc<Mutex<Queues<M>>>);
+
+struct Queues<M> {
+    incoming: VecDeque<Incoming<M>>,
+    outgoing: VecDeque<Outgoing<M>>,
+}
+
+impl<M> Channel<M> {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(Queues {
+            incoming: VecDeque::new(),
+            outgoing: VecDeque::new(),
+        })))
+    }
+
+    /// Returns delivery that needs to be used by sub-protocol
+    pub fn delivery(&self) -> (Receiver<M>, Sender<M>) {
+        (Receiver(self.0.clone()), Sender(self.0.clone()))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Queues<M>> {
+        lock(&self.0)
+    }
+}
+
+/// Incoming messages of sub-protocol
+pub struct Receiver<M>(Arc<Mutex<Queues<M>>>);
+
+/// Outgoing messages of sub-protocol
+///
+/// Always ready to accept a message. Messages are buffered until [`join`] sends them.
+pub struct Sender<M>(Arc<Mutex<Queues<M>>>);
+
+impl<M> Stream for Receiver<M> {
+    type Item = Result<Incoming<M>, std::convert::Infallible>;
+
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // `join` polls sub-protocol once message is received, so waker doesn't need to be
+        // registered
+        match lock(&self.0).incoming.pop_front() {
+            Some(incoming) => Poll::Ready(Some(Ok(incoming))),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<M> Sink<Outgoing<M>> for Sender<M> {
+    type Error = std::convert::Infallible;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, outgoing: Outgoing<M>) -> Result<(), Self::Error> {
+        lock(&self.0).outgoing.push_back(outgoing);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Carries out both sub-protocols until both of them complete or any of them fails
+///
+/// `keygen` and `aux_info` must use deliveries of `keygen_channel` and `aux_info_channel`
+/// respectively.
+pub async fn join<K, A, D, T1, T2, Err>(
+    delivery: D,
+    (keygen_channel, keygen): (Channel<K>, impl Future<Output = Result<T1, Err>>),
+    (aux_info_channel, aux_info): (Channel<A>, impl Future<Output = Result<T2, Err>>),
+) -> Result<(T1, T2), Err>
+where
+    D: Delivery<Msg<K, A>>,
+    Err: From<IoError>,
+{
+    let (incomings, outgoings) = delivery.split();
+    let mut incomings = pin!(incomings);
+    let mut outgoings = pin!(outgoings);
+    let mut keygen = pin!(keygen);
+    let mut aux_info = pin!(aux_info);
+    let mut keygen_output = None;
+    let mut aux_info_output = None;
+
+    loop {
+        if keygen_output.is_none() {
+            if let Poll::Ready(output) = futures::poll!(keygen.as_mut()) {
+                keygen_output = Some(output?);
+            }
+        }
+        if aux_info_output.is_none() {
+            if let Poll::Ready(output) = futures::poll!(aux_info.as_mut()) {
+                aux_info_output = Some(output?);
+            }
+        }
+
+        let outgoing_msgs = keygen_channel
+            .lock()
+            .outgoing
+            .drain(..)
+            .map(|outgoing| outgoing.map(Msg::Keygen))
+            .chain(
+                aux_info_channel
+                    .lock()
+                    .outgoing
+                    .drain(..)
+                    .map(|outgoing| outgoing.map(Msg::AuxInfo)),
+            )
+            .collect::<Vec<_>>();
+        let has_outgoing_msgs = !outgoing_msgs.is_empty();
+        for outgoing in outgoing_msgs {
+            outgoings
+                .feed(outgoing)
+                .await
+                .map_err(IoError::send_message)?;
+        }
+        if has_outgoing_msgs {
+            outgoings.flush().await.map_err(IoError::send_message)?;
+        }
+
+        match (keygen_output, aux_info_output) {
+            (Some(keygen_output), Some(aux_info_output)) => {
+                return Ok((keygen_output, aux_info_output))
+            }
+            outputs => (keygen_output, aux_info_output) = outputs,
+        }
+
+        let incoming = incomings
+            .next()
+            .await
+            .ok_or(IoError::ReceiveMessageEof)?
+            .map_err(|err| IoError::ReceiveMessage(Box::new(err)))?;
+        let Incoming {
+            id,
+            sender,
+            msg_type,
+            msg,
+        } = incoming;
+        // Messages of sub-protocol that is already finished are ignored
+        match msg {
+            Msg::Keygen(msg) if keygen_output.is_none() => {
+                keygen_channel.lock().incoming.push_back(Incoming {
+                    id,
+                    sender,
+                    msg_type,
+                    msg,
+                })
+            }
+            Msg::AuxInfo(msg) if aux_info_output.is_none() => {
+                aux_info_channel.lock().incoming.push_back(Incoming {
+                    id,
+                    sender,
+                    msg_type,
+                    msg,
+                })
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Randomness source shared between sub-protocols
+pub struct SharedRng<R>(Arc<Mutex<R>>);
+
+impl<R> SharedRng<R> {
+    pub fn new(rng: R) -> Self {
+        Self(Arc::new(Mutex::new(rng)))
+    }
+}
+
+impl<R> Clone for SharedRng<R> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<R: RngCore> RngCore for SharedRng<R> {
+    fn next_u32(&mut self) -> u32 {
+        lock(&self.0).next_u32()
+    }
+    fn next_u64(&mut self) -> u64 {
+        lock(&self.0).next_u64()
+    }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        lock(&self.0).fill_bytes(dest)
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        lock(&self.0).try_fill_bytes(dest)
+    }
+}
+
+impl<R: CryptoRng> CryptoRng for SharedRng<R> {}
+
+/// Mutex is never held across an await point, and its state stays valid even if the holder
+/// panicked, so poisoning is ignored
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
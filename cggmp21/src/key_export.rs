@@ -0,0 +1,702 @@
+//! Encrypted key export protocol
+//!
+//! Allows to export the shared secret key to a designated recipient without reconstructing it
+//! on any of the signers. At least `min_signers` parties holding key shares (exporters) encrypt
+//! their Lagrange-weighted secret shares $\lambda_k x_k$ to the recipient's public key using
+//! ECIES over the same curve, and prove knowledge of the encrypted share with respect to
+//! `public_shares`. Recipient decrypts the shares, checks each of them against
+//! $\lambda_k X_k$, and sums them up to obtain the secret key. Exporters learn nothing.
+//!
+//! Proof of knowledge doesn't bind the ciphertext to the encrypted share, so if the recipient
+//! blames an exporter for [invalid ciphertext](ExportAbortReason::InvalidCiphertext), it also
+//! provides a [verifiable decryption](VerifiableDecryption) of that ciphertext which can be
+//! published and checked by anyone (see [`KeyExportError::invalid_ciphertexts`]).
+//!
+//! Only recipient learns the key, which makes it a single point of failure. That's why
+//! the protocol is only available with `spof` feature.
+
+use digest::Digest;
+use futures::SinkExt;
+use generic_ec::{Curve, NonZero, Point, Scalar, SecretScalar};
+use generic_ec_zkp::{dlog_eq, polynomial::lagrange_coefficient_at_zero, schnorr_pok};
+use rand_core::{CryptoRng, RngCore};
+use round_based::{
+    rounds_router::{simple_store::RoundInput, RoundsRouter},
+    Delivery, Mpc, MpcParty, Outgoing, PartyIndex, ProtocolMessage,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    errors::IoError,
+    key_share::{AnyKeyShare, DirtyIncompleteKeyShare, DirtyKeyInfo, VssSetup},
+    progress::Tracer,
+    utils::{AbortBlame, Aborted},
+    ExecutionId,
+};
+
+macro_rules! prefixed {
+    ($name:tt) => {
+        concat!("dfns.cggmp21.key_export.", $name)
+    };
+}
+
+/// Message of key export protocol
+#[derive(ProtocolMessage, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum Msg<E: Curve> {
+    /// Round 1 message sent by each exporter to recipient
+    Round1(MsgRound1<E>),
+}
+
+/// Message from round 1 sent by each exporter to recipient
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound1<E: Curve> {
+    /// $R_k = r_k \cdot G$, ephemeral public key of ECIES
+    pub R: Point<E>,
+    /// $c_k = \lambda_k x_k + H(r_k \cdot Y)$, encrypted Lagrange-weighted secret share
+    pub ciphertext: Scalar<E>,
+    /// Commitment of Schnorr proof of knowledge of $\lambda_k x_k$
+    pub sch_commit: schnorr_pok::Commit<E>,
+    /// Schnorr proof of knowledge of $\lambda_k x_k$
+    pub sch_proof: schnorr_pok::Proof<E>,
+}
+
+mod unambiguous {
+    use generic_ec::{Curve, Point, Scalar};
+
+    use crate::ExecutionId;
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("ecies_kdf"))]
+    #[udigest(bound = "")]
+    pub struct Kdf<'a, E: Curve> {
+        pub sid: ExecutionId<'a>,
+        pub sender: u16,
+        pub R: Point<E>,
+        pub shared_secret: Point<E>,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("schnorr_pok"))]
+    #[udigest(bound = "")]
+    pub struct SchnorrPok<'a, E: Curve> {
+        pub sid: ExecutionId<'a>,
+        pub prover: u16,
+        pub R: Point<E>,
+        pub ciphertext: Scalar<E>,
+        pub h: Point<E>,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("verifiable_decryption"))]
+    pub struct VerifiableDecryption<'a> {
+        pub sid: ExecutionId<'a>,
+        pub exporter: u16,
+    }
+}
+
+/// Verifiable decryption of exporter's ciphertext
+///
+/// Recipient produces it for every exporter blamed for
+/// [invalid ciphertext](ExportAbortReason::InvalidCiphertext). It reveals ECIES shared secret
+/// $S_k = y \cdot R_k$ along with a proof that $\log_G Y = \log_{R_k} S_k$, so anyone can
+/// decrypt the ciphertext and make sure it doesn't match $\lambda_k X_k$. Revealing $S_k$
+/// doesn't reveal the decryption key $y$.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct VerifiableDecryption<E: Curve> {
+    /// Index of exporter whose ciphertext was decrypted
+    pub exporter: PartyIndex,
+    /// $S_k = y \cdot R_k$, ECIES shared secret
+    pub shared_secret: Point<E>,
+    /// Proof that $\log_G Y = \log_{R_k} S_k$
+    pub proof: dlog_eq::non_interactive::Proof<E>,
+}
+
+impl<E: Curve> VerifiableDecryption<E> {
+    /// Verifies that `msg` sent by the exporter indeed contains invalid ciphertext
+    ///
+    /// `eid`, `exporters`, `key_info`, and `recipient_key` must be the same as used in
+    /// the protocol, and `msg` must be the message that exporter sent to the recipient.
+    /// `D` must be the hash function used in the protocol.
+    ///
+    /// Returns `Ok(())` if the proof is valid and the decrypted share doesn't match
+    /// $\lambda_k X_k$, i.e. the exporter is indeed faulty.
+    pub fn verify<D: Digest>(
+        &self,
+        eid: ExecutionId,
+        exporters: &[u16],
+        key_info: &DirtyKeyInfo<E>,
+        recipient_key: NonZero<Point<E>>,
+        msg: &MsgRound1<E>,
+    ) -> Result<(), InvalidVerifiableDecryption> {
+        let lambdas = lagrange_coefficients(exporters, key_info)
+            .map_err(InvalidVerifiableDecryptionReason::InvalidArgs)?;
+        let k = usize::from(self.exporter);
+        let (Some(j), Some(lambda_k)) = (exporters.get(k), lambdas.get(k)) else {
+            return Err(InvalidVerifiableDecryptionReason::UnknownExporter.into());
+        };
+
+        let data = dlog_eq::non_interactive::Data {
+            gen1: Point::generator().into(),
+            prod1: *recipient_key,
+            gen2: msg.R,
+            prod2: self.shared_secret,
+        };
+        let shared_state = unambiguous::VerifiableDecryption {
+            sid: eid,
+            exporter: self.exporter,
+        };
+        dlog_eq::non_interactive::verify::<E, D>(&shared_state, data, self.proof)
+            .map_err(|_| InvalidVerifiableDecryptionReason::InvalidProof)?;
+
+        let mask = Scalar::from_hash::<D>(&unambiguous::Kdf {
+            sid: eid,
+            sender: self.exporter,
+            R: msg.R,
+            shared_secret: self.shared_secret,
+        });
+        let share = msg.ciphertext - mask;
+        if Point::generator() * share == key_info.public_shares[usize::from(*j)] * lambda_k {
+            return Err(InvalidVerifiableDecryptionReason::ValidCiphertext.into());
+        }
+        Ok(())
+    }
+}
+
+/// Key export protocol builder
+pub struct KeyExportBuilder<'a, E: Curve, D = crate::default_choice::Digest> {
+    eid: ExecutionId<'a>,
+    i: PartyIndex,
+    exporters: &'a [u16],
+    key_info: &'a DirtyKeyInfo<E>,
+    recipient_key: NonZero<Point<E>>,
+    key_share: Option<&'a DirtyIncompleteKeyShare<E>>,
+    decryption_key: Option<NonZero<SecretScalar<E>>>,
+    tracer: Option<&'a mut dyn Tracer>,
+    _digest: std::marker::PhantomData<D>,
+}
+
+impl<'a, E, D> KeyExportBuilder<'a, E, D>
+where
+    E: Curve,
+    D: Digest + Clone + 'static,
+{
+    /// Constructs a builder
+    ///
+    /// Protocol is carried out by `exporters.len() + 1` parties: exporters have indexes
+    /// `0..exporters.len()`, and recipient has index `exporters.len()`.
+    ///
+    /// * `i` is index of local party in the protocol
+    /// * `exporters` are indexes of exporters' key shares, `exporters[k]` is index of key share
+    ///   held by party `k`
+    /// * `key_info` is public information about the key
+    /// * `recipient_key` is public key of the recipient the secret key is encrypted to
+    ///
+    /// Exporters must provide their key share via [`set_key_share`](Self::set_key_share),
+    /// recipient must provide its secret key via [`set_decryption_key`](Self::set_decryption_key).
+    pub fn new(
+        eid: ExecutionId<'a>,
+        i: PartyIndex,
+        exporters: &'a [u16],
+        key_info: &'a DirtyKeyInfo<E>,
+        recipient_key: NonZero<Point<E>>,
+    ) -> Self {
+        Self {
+            eid,
+            i,
+            exporters,
+            key_info,
+            recipient_key,
+            key_share: None,
+            decryption_key: None,
+            tracer: None,
+            _digest: std::marker::PhantomData,
+        }
+    }
+
+    /// Sets key share held by local party
+    ///
+    /// Must be set if local party is an exporter
+    pub fn set_key_share(self, key_share: &'a impl AnyKeyShare<E>) -> Self {
+        Self {
+            key_share: Some(key_share.as_ref()),
+            ..self
+        }
+    }
+
+    /// Sets secret key corresponding to `recipient_key`
+    ///
+    /// Must be set if local party is the recipient
+    pub fn set_decryption_key(self, decryption_key: NonZero<SecretScalar<E>>) -> Self {
+        Self {
+            decryption_key: Some(decryption_key),
+            ..self
+        }
+    }
+
+    /// Specifies another hash function to use
+    pub fn set_digest<D2>(self) -> KeyExportBuilder<'a, E, D2>
+    where
+        D2: Digest + Clone + 'static,
+    {
+        KeyExportBuilder {
+            eid: self.eid,
+            i: self.i,
+            exporters: self.exporters,
+            key_info: self.key_info,
+            recipient_key: self.recipient_key,
+            key_share: self.key_share,
+            decryption_key: self.decryption_key,
+            tracer: self.tracer,
+            _digest: std::marker::PhantomData,
+        }
+    }
+
+    /// Sets a tracer that tracks progress of protocol execution
+    pub fn set_progress_tracer(mut self, tracer: &'a mut dyn Tracer) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    /// Carries out the key export protocol
+    ///
+    /// Returns `Some(secret_key)` if local party is the recipient, and `None` if local party
+    /// is an exporter.
+    pub async fn start<R, M>(
+        self,
+        rng: &mut R,
+        party: M,
+    ) -> Result<Option<NonZero<SecretScalar<E>>>, KeyExportError<E>>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = Msg<E>>,
+    {
+        run_export::<E, D, _, _>(
+            self.tracer,
+            self.i,
+            self.exporters,
+            self.key_info,
+            self.recipient_key,
+            self.key_share,
+            self.decryption_key,
+            self.eid,
+            rng,
+            party,
+        )
+        .await
+    }
+
+    /// Returns a state machine that can be used to carry out the key export protocol
+    ///
+    /// See [`round_based::state_machine`] for details on how that can be done.
+    #[cfg(feature = "state-machine")]
+    pub fn into_state_machine<R>(
+        self,
+        rng: &'a mut R,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<Option<NonZero<SecretScalar<E>>>, KeyExportError<E>>,
+        Msg = Msg<E>,
+    > + 'a
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(|party| self.start(rng, party))
+    }
+}
+
+async fn run_export<E, D, R, M>(
+    mut tracer: Option<&mut dyn Tracer>,
+    i: PartyIndex,
+    exporters: &[u16],
+    key_info: &DirtyKeyInfo<E>,
+    recipient_key: NonZero<Point<E>>,
+    key_share: Option<&DirtyIncompleteKeyShare<E>>,
+    decryption_key: Option<NonZero<SecretScalar<E>>>,
+    sid: ExecutionId<'_>,
+    rng: &mut R,
+    party: M,
+) -> Result<Option<NonZero<SecretScalar<E>>>, KeyExportError<E>>
+where
+    E: Curve,
+    D: Digest + Clone + 'static,
+    R: RngCore + CryptoRng,
+    M: Mpc<ProtocolMessage = Msg<E>>,
+{
+    tracer.protocol_begins();
+
+    tracer.stage("Validate arguments");
+    let m = u16::try_from(exporters.len()).map_err(|_| InvalidArgs::TooManyParties)?;
+    let n = m.checked_add(1).ok_or(InvalidArgs::TooManyParties)?;
+    if i >= n {
+        return Err(InvalidArgs::IndexOutOfBounds.into());
+    }
+    let is_exporter = i < m;
+
+    let lambdas = lagrange_coefficients(exporters, key_info)?;
+    // Expected λ_k X_k for every exporter
+    let expected_shares = exporters
+        .iter()
+        .zip(&lambdas)
+        .map(|(j, lambda_j)| key_info.public_shares[usize::from(*j)] * lambda_j)
+        .collect::<Vec<_>>();
+
+    tracer.stage("Setup networking");
+    let MpcParty { delivery, .. } = party.into_party();
+    let (incomings, mut outgoings) = delivery.split();
+
+    if is_exporter {
+        let share = key_share.ok_or(InvalidArgs::MissingKeyShare)?;
+        if share.i != exporters[usize::from(i)]
+            || share.key_info.shared_public_key != key_info.shared_public_key
+            || share.key_info.public_shares != key_info.public_shares
+        {
+            return Err(InvalidArgs::MismatchedKeyShare.into());
+        }
+        if decryption_key.is_some() {
+            return Err(InvalidArgs::UnexpectedDecryptionKey.into());
+        }
+
+        // Round 1
+        tracer.round_begins();
+
+        tracer.stage("Encrypt λ_i x_i to recipient");
+        let mut secret = *(lambdas[usize::from(i)] * &share.x);
+        let secret = SecretScalar::new(&mut secret);
+        let r = SecretScalar::<E>::random(rng);
+        let R = Point::generator() * &r;
+        let mask = Scalar::from_hash::<D>(&unambiguous::Kdf {
+            sid,
+            sender: i,
+            R,
+            shared_secret: *recipient_key * &r,
+        });
+        let ciphertext = secret.as_ref() + mask;
+
+        tracer.stage("Prove knowledge of λ_i x_i");
+        let (sch_secret, sch_commit) = schnorr_pok::prover_commits_ephemeral_secret::<E, _>(rng);
+        let challenge = Scalar::from_hash::<D>(&unambiguous::SchnorrPok {
+            sid,
+            prover: i,
+            R,
+            ciphertext,
+            h: sch_commit.0,
+        });
+        let challenge = schnorr_pok::Challenge { nonce: challenge };
+        let sch_proof = schnorr_pok::prove(&sch_secret, &challenge, &secret);
+
+        tracer.send_msg();
+        outgoings
+            .send(Outgoing::p2p(
+                m,
+                Msg::Round1(MsgRound1 {
+                    R,
+                    ciphertext,
+                    sch_commit,
+                    sch_proof,
+                }),
+            ))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.protocol_ends();
+        return Ok(None);
+    }
+
+    let decryption_key = decryption_key.ok_or(InvalidArgs::MissingDecryptionKey)?;
+    if Point::generator() * &decryption_key != recipient_key {
+        return Err(InvalidArgs::MismatchedDecryptionKey.into());
+    }
+
+    let mut rounds = RoundsRouter::<Msg<E>>::builder();
+    let round1 = rounds.add_round(RoundInput::<MsgRound1<E>>::p2p(i, n));
+    let mut rounds = rounds.listen(incomings);
+
+    // Output
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let ciphertexts = rounds
+        .complete(round1)
+        .await
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    tracer.stage("Validate schnorr proofs");
+    let blame = ciphertexts
+        .iter_indexed()
+        .filter(|(k, _, msg)| {
+            let challenge = Scalar::from_hash::<D>(&unambiguous::SchnorrPok {
+                sid,
+                prover: *k,
+                R: msg.R,
+                ciphertext: msg.ciphertext,
+                h: msg.sch_commit.0,
+            });
+            let challenge = schnorr_pok::Challenge { nonce: challenge };
+            msg.sch_proof
+                .verify(
+                    &msg.sch_commit,
+                    &challenge,
+                    &expected_shares[usize::from(*k)],
+                )
+                .is_err()
+        })
+        .map(|(k, msg_id, _)| AbortBlame::new(k, msg_id, msg_id))
+        .collect::<Vec<_>>();
+    if !blame.is_empty() {
+        return Err(ExportAborted::new(ExportAbortReason::InvalidSchnorrProof, blame).into());
+    }
+
+    tracer.stage("Decrypt λ_k x_k");
+    let shared_secrets = ciphertexts
+        .iter()
+        .map(|msg| msg.R * &decryption_key)
+        .collect::<Vec<_>>();
+    let shares = ciphertexts
+        .iter()
+        .zip(&shared_secrets)
+        .zip(0..)
+        .map(|((msg, shared_secret), k)| {
+            let mask = Scalar::from_hash::<D>(&unambiguous::Kdf {
+                sid,
+                sender: k,
+                R: msg.R,
+                shared_secret: *shared_secret,
+            });
+            msg.ciphertext - mask
+        })
+        .collect::<Vec<_>>();
+
+    tracer.stage("Validate λ_k x_k G = λ_k X_k");
+    let invalid = ciphertexts
+        .iter_indexed()
+        .zip(shares.iter().zip(&shared_secrets))
+        .filter(|((k, _, _), (share, _))| {
+            Point::generator() * *share != expected_shares[usize::from(*k)]
+        })
+        .collect::<Vec<_>>();
+    if !invalid.is_empty() {
+        tracer.stage("Prove correctness of decryption of invalid ciphertexts");
+        let mut blame = Vec::with_capacity(invalid.len());
+        let mut invalid_ciphertexts = Vec::with_capacity(invalid.len());
+        for ((k, msg_id, msg), (_, shared_secret)) in invalid {
+            let data =
+                dlog_eq::non_interactive::Data::from_secret_key(decryption_key.as_ref(), msg.R);
+            let proof = dlog_eq::non_interactive::prove::<E, D>(
+                rng,
+                &unambiguous::VerifiableDecryption { sid, exporter: k },
+                decryption_key.as_ref(),
+                data,
+            );
+            blame.push(AbortBlame::new(k, msg_id, msg_id));
+            invalid_ciphertexts.push(VerifiableDecryption {
+                exporter: k,
+                shared_secret: *shared_secret,
+                proof,
+            });
+        }
+        return Err(KeyExportError(Reason::Aborted(
+            ExportAborted::new(ExportAbortReason::InvalidCiphertext, blame),
+            invalid_ciphertexts,
+        )));
+    }
+
+    tracer.stage("Compute x = Σ_k λ_k x_k");
+    let mut x = shares.iter().sum::<Scalar<E>>();
+    let x = NonZero::from_secret_scalar(SecretScalar::new(&mut x)).ok_or(Bug::ZeroKey)?;
+    if Point::generator() * &x != key_info.shared_public_key {
+        return Err(Bug::MismatchedPublicKey.into());
+    }
+
+    tracer.protocol_ends();
+    Ok(Some(x))
+}
+
+/// Computes Lagrange coefficients $\lambda_k$ such that shared secret key is equal to
+/// $\sum_k \lambda_k x_k$, where $x_k$ is secret share of `exporters[k]`
+fn lagrange_coefficients<E: Curve>(
+    exporters: &[u16],
+    key_info: &DirtyKeyInfo<E>,
+) -> Result<Vec<NonZero<Scalar<E>>>, InvalidArgs> {
+    let key_n = key_info.public_shares.len();
+    if exporters.iter().any(|j| usize::from(*j) >= key_n)
+        || (1..exporters.len()).any(|k| exporters[..k].contains(&exporters[k]))
+    {
+        return Err(InvalidArgs::InvalidIndexes);
+    }
+    match &key_info.vss_setup {
+        Some(VssSetup {
+            min_signers,
+            I,
+            weights,
+            ranks,
+        }) => {
+            if weights.is_some() {
+                return Err(InvalidArgs::WeightedKey);
+            }
+            if ranks.is_some() {
+                return Err(InvalidArgs::HierarchicalKey);
+            }
+            if exporters.len() < usize::from(*min_signers) {
+                return Err(InvalidArgs::NotEnoughExporters);
+            }
+            let I = exporters
+                .iter()
+                .map(|j| I.get(usize::from(*j)).ok_or(InvalidArgs::InvalidIndexes))
+                .collect::<Result<Vec<_>, _>>()?;
+            (0..I.len())
+                .map(|k| lagrange_coefficient_at_zero(k, &I).ok_or(InvalidArgs::InvalidKeyInfo))
+                .collect()
+        }
+        None => {
+            if exporters.len() != key_n {
+                return Err(InvalidArgs::NotEnoughExporters);
+            }
+            Ok(vec![NonZero::<Scalar<E>>::one(); key_n])
+        }
+    }
+}
+
+/// Error of key export protocol
+#[derive(Debug, Error)]
+#[error("key export protocol failed to complete")]
+pub struct KeyExportError<E: Curve>(#[source] Reason<E>);
+
+impl<E: Curve> KeyExportError<E> {
+    /// If protocol was aborted by malicious party, returns information about
+    /// the abort: its reason and parties to blame
+    pub fn aborted(&self) -> Option<&ExportAborted> {
+        match &self.0 {
+            Reason::Aborted(err, _) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// If recipient aborted the protocol due to [invalid ciphertexts](ExportAbortReason::InvalidCiphertext),
+    /// returns verifiable decryption of every blamed exporter's ciphertext
+    ///
+    /// Recipient may publish them to prove that blamed exporters are indeed faulty,
+    /// see [`VerifiableDecryption::verify`]. Returns an empty slice otherwise.
+    pub fn invalid_ciphertexts(&self) -> &[VerifiableDecryption<E>] {
+        match &self.0 {
+            Reason::Aborted(_, invalid_ciphertexts) => invalid_ciphertexts,
+            _ => &[],
+        }
+    }
+}
+
+impl<E: Curve> From<InvalidArgs> for KeyExportError<E> {
+    fn from(err: InvalidArgs) -> Self {
+        KeyExportError(Reason::InvalidArgs(err))
+    }
+}
+
+impl<E: Curve> From<ExportAborted> for KeyExportError<E> {
+    fn from(err: ExportAborted) -> Self {
+        KeyExportError(Reason::Aborted(err, vec![]))
+    }
+}
+
+impl<E: Curve> From<IoError> for KeyExportError<E> {
+    fn from(err: IoError) -> Self {
+        KeyExportError(Reason::IoError(err))
+    }
+}
+
+impl<E: Curve> From<Bug> for KeyExportError<E> {
+    fn from(err: Bug) -> Self {
+        KeyExportError(Reason::Bug(err))
+    }
+}
+
+#[derive(Debug, Error)]
+enum Reason<E: Curve> {
+    #[error("invalid arguments")]
+    InvalidArgs(#[source] InvalidArgs),
+    /// Protocol was maliciously aborted by another party
+    #[error("protocol was aborted by malicious party")]
+    Aborted(#[source] ExportAborted, Vec<VerifiableDecryption<E>>),
+    #[error("i/o error")]
+    IoError(#[source] IoError),
+    #[error("bug occurred")]
+    Bug(#[source] Bug),
+}
+
+#[derive(Debug, Error)]
+enum InvalidArgs {
+    #[error("too many parties")]
+    TooManyParties,
+    #[error("party index `i` is out of bounds")]
+    IndexOutOfBounds,
+    #[error("key share indexes of exporters are out of bounds or not unique")]
+    InvalidIndexes,
+    #[error("not enough exporters to export the key")]
+    NotEnoughExporters,
+    #[error("exporter didn't provide its key share")]
+    MissingKeyShare,
+    #[error("key share doesn't match provided key info or exporter index")]
+    MismatchedKeyShare,
+    #[error("recipient didn't provide its decryption key")]
+    MissingDecryptionKey,
+    #[error("decryption key is provided by party which is not the recipient")]
+    UnexpectedDecryptionKey,
+    #[error("decryption key doesn't match recipient public key")]
+    MismatchedDecryptionKey,
+    #[error("weighted keys can't be exported")]
+    WeightedKey,
+    #[error("hierarchical keys can't be exported")]
+    HierarchicalKey,
+    #[error("key info contains duplicated VSS indexes")]
+    InvalidKeyInfo,
+}
+
+/// Unexpected error in operation not caused by other parties
+#[derive(Debug, Error)]
+enum Bug {
+    #[error("exported key is zero although public key is not")]
+    ZeroKey,
+    #[error("exported key doesn't match public key although we checked exporters' data")]
+    MismatchedPublicKey,
+}
+
+/// Error indicating that key export was aborted by malicious party
+pub type ExportAborted = Aborted<ExportAbortReason>;
+
+/// Reason for key export abort: which exact check has failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum ExportAbortReason {
+    /// Exporter provided invalid proof of knowledge of its Lagrange-weighted secret share
+    #[error("provided invalid schnorr proof")]
+    InvalidSchnorrProof,
+    /// Share decrypted by recipient doesn't match Lagrange-weighted public share of exporter
+    #[error("decrypted share doesn't match λ_k X_k")]
+    InvalidCiphertext,
+}
+
+/// Error indicating that [`VerifiableDecryption`] doesn't prove that exporter is faulty
+#[derive(Debug, Error)]
+#[error("verifiable decryption is invalid")]
+pub struct InvalidVerifiableDecryption(#[source] InvalidVerifiableDecryptionReason);
+
+crate::errors::impl_from! {
+    impl From for InvalidVerifiableDecryption {
+        err: InvalidVerifiableDecryptionReason => InvalidVerifiableDecryption(err),
+    }
+}
+
+#[derive(Debug, Error)]
+enum InvalidVerifiableDecryptionReason {
+    #[error("invalid arguments")]
+    InvalidArgs(#[source] InvalidArgs),
+    #[error("exporter index is out of bounds")]
+    UnknownExporter,
+    #[error("proof of correct decryption is invalid")]
+    InvalidProof,
+    #[error("ciphertext decrypts to valid share")]
+    ValidCiphertext,
+}
@@ -0,0 +1,603 @@
+//! Distributed key import protocol
+//!
+//! Allows to import an existing secret key into a committee of `n` parties without a trusted
+//! dealer that learns everyone's key shares. Owner of the key takes part in the protocol as
+//! a dealer: it shares the key via Feldman VSS with threshold `t`, proves knowledge of the key,
+//! and sends each member of the committee its secret share. Members verify received share
+//! against public commitments. Apart from the owner, every party learns only its own share.
+//!
+//! Import only outputs core key share ([`IncompleteKeyShare`]), which is indistinguishable
+//! from output of threshold [keygen](crate::keygen). Members of the committee need to carry out
+//! [aux info generation](crate::aux_info_gen) to obtain a complete [`KeyShare`](crate::KeyShare).
+
+use digest::Digest;
+use futures::SinkExt;
+use generic_ec::{Curve, NonZero, Point, Scalar, SecretScalar};
+use generic_ec_zkp::{polynomial::Polynomial, schnorr_pok};
+use rand_core::{CryptoRng, RngCore};
+use round_based::{
+    rounds_router::{simple_store::RoundInput, RoundsRouter},
+    Delivery, Mpc, MpcParty, Outgoing, PartyIndex, ProtocolMessage,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    errors::IoError,
+    key_share::{
+        DirtyIncompleteKeyShare, DirtyKeyInfo, IncompleteKeyShare, InvalidIncompleteKeyShare,
+        Validate, VssSetup,
+    },
+    progress::Tracer,
+    utils::{self, AbortBlame, Aborted},
+    ExecutionId,
+};
+
+macro_rules! prefixed {
+    ($name:tt) => {
+        concat!("dfns.cggmp21.key_import.", $name)
+    };
+}
+
+/// Message of key import protocol
+#[derive(ProtocolMessage, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum Msg<E: Curve, D: Digest> {
+    /// Round 1 message broadcasted to everyone
+    Round1Broad(MsgRound1Broad<E>),
+    /// Round 1 message unicasted to each party
+    Round1Uni(MsgRound1Uni<E>),
+    /// Reliability check message (optional additional round)
+    ReliabilityCheck(MsgReliabilityCheck<D>),
+}
+
+/// Message from round 1 broadcasted to everyone
+///
+/// All fields are `None` if sender is not the key owner
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[serde(bound = "")]
+#[udigest(bound = "")]
+#[udigest(tag = prefixed!("round1_broad"))]
+pub struct MsgRound1Broad<E: Curve> {
+    /// $F(x)$, commitment to polynomial $f(x)$ such that $f(0)$ is the imported secret key
+    pub F: Option<Polynomial<Point<E>>>,
+    /// Commitment of Schnorr proof of knowledge of $f(0)$
+    pub sch_commit: Option<schnorr_pok::Commit<E>>,
+    /// Schnorr proof of knowledge of $f(0)$
+    pub sch_proof: Option<schnorr_pok::Proof<E>>,
+    /// Chain code associated with the imported key
+    #[cfg(feature = "hd-wallet")]
+    #[serde(default)]
+    #[udigest(as = Option<udigest::Bytes>)]
+    pub chain_code: Option<hd_wallet::ChainCode>,
+}
+/// Message from round 1 unicasted to each party
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound1Uni<E: Curve> {
+    /// $\sigma_j = f(I_j)$
+    ///
+    /// `None` if sender is not the key owner
+    pub sigma: Option<Scalar<E>>,
+}
+/// Message parties exchange to ensure reliability of broadcast channel
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgReliabilityCheck<D: Digest>(pub digest::Output<D>);
+
+mod unambiguous {
+    use generic_ec::{Curve, Point};
+
+    use crate::ExecutionId;
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("schnorr_pok"))]
+    #[udigest(bound = "")]
+    pub struct SchnorrPok<'a, E: Curve> {
+        pub sid: ExecutionId<'a>,
+        pub F: &'a generic_ec_zkp::polynomial::Polynomial<Point<E>>,
+        pub h: Point<E>,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("echo_round"))]
+    #[udigest(bound = "")]
+    pub struct Echo<'a, E: Curve> {
+        pub sid: ExecutionId<'a>,
+        pub msg: &'a super::MsgRound1Broad<E>,
+    }
+}
+
+/// Key import protocol builder
+pub struct KeyImportBuilder<'a, E: Curve, D = crate::default_choice::Digest> {
+    eid: ExecutionId<'a>,
+    i: PartyIndex,
+    n: u16,
+    t: u16,
+    secret_key: Option<NonZero<SecretScalar<E>>>,
+    #[cfg(feature = "hd-wallet")]
+    chain_code: Option<hd_wallet::ChainCode>,
+    tracer: Option<&'a mut dyn Tracer>,
+    enforce_reliable_broadcast: bool,
+    _digest: std::marker::PhantomData<D>,
+}
+
+impl<'a, E, D> KeyImportBuilder<'a, E, D>
+where
+    E: Curve,
+    D: Digest + Clone + 'static,
+{
+    /// Constructs a builder
+    ///
+    /// Protocol is carried out by `n + 1` parties: members of the committee have indexes
+    /// `0..n` and obtain key shares with the same indexes, and owner of the key has index `n`.
+    ///
+    /// * `i` is index of local party in the protocol
+    /// * `n` is number of parties in the committee
+    /// * `t` is threshold of the imported key
+    ///
+    /// Key owner must provide the key via [`set_secret_key`](Self::set_secret_key).
+    pub fn new(eid: ExecutionId<'a>, i: PartyIndex, n: u16, t: u16) -> Self {
+        Self {
+            eid,
+            i,
+            n,
+            t,
+            secret_key: None,
+            #[cfg(feature = "hd-wallet")]
+            chain_code: None,
+            tracer: None,
+            enforce_reliable_broadcast: true,
+            _digest: std::marker::PhantomData,
+        }
+    }
+
+    /// Sets secret key being imported
+    ///
+    /// Must be set by the key owner, and must not be set by members of the committee
+    pub fn set_secret_key(self, sk: NonZero<SecretScalar<E>>) -> Self {
+        Self {
+            secret_key: Some(sk),
+            ..self
+        }
+    }
+
+    /// Sets chain code associated with the key being imported
+    ///
+    /// Can only be set by the key owner. If set, imported key shares support HD derivation.
+    #[cfg(feature = "hd-wallet")]
+    pub fn set_chain_code(self, chain_code: hd_wallet::ChainCode) -> Self {
+        Self {
+            chain_code: Some(chain_code),
+            ..self
+        }
+    }
+
+    /// Specifies another hash function to use
+    pub fn set_digest<D2>(self) -> KeyImportBuilder<'a, E, D2>
+    where
+        D2: Digest + Clone + 'static,
+    {
+        KeyImportBuilder {
+            eid: self.eid,
+            i: self.i,
+            n: self.n,
+            t: self.t,
+            secret_key: self.secret_key,
+            #[cfg(feature = "hd-wallet")]
+            chain_code: self.chain_code,
+            tracer: self.tracer,
+            enforce_reliable_broadcast: self.enforce_reliable_broadcast,
+            _digest: std::marker::PhantomData,
+        }
+    }
+
+    /// Sets a tracer that tracks progress of protocol execution
+    pub fn set_progress_tracer(mut self, tracer: &'a mut dyn Tracer) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    #[doc = include_str!("../docs/enforce_reliable_broadcast.md")]
+    pub fn enforce_reliable_broadcast(self, v: bool) -> Self {
+        Self {
+            enforce_reliable_broadcast: v,
+            ..self
+        }
+    }
+
+    /// Carries out the key import protocol
+    ///
+    /// Returns `Some(key_share)` if local party is a member of the committee, and `None`
+    /// if local party is the key owner.
+    pub async fn start<R, M>(
+        self,
+        rng: &mut R,
+        party: M,
+    ) -> Result<Option<IncompleteKeyShare<E>>, KeyImportError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = Msg<E, D>>,
+    {
+        run_import(
+            self.tracer,
+            self.i,
+            self.n,
+            self.t,
+            self.secret_key,
+            #[cfg(feature = "hd-wallet")]
+            self.chain_code,
+            self.enforce_reliable_broadcast,
+            self.eid,
+            rng,
+            party,
+        )
+        .await
+    }
+
+    /// Returns a state machine that can be used to carry out the key import protocol
+    ///
+    /// See [`round_based::state_machine`] for details on how that can be done.
+    #[cfg(feature = "state-machine")]
+    pub fn into_state_machine<R>(
+        self,
+        rng: &'a mut R,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<Option<IncompleteKeyShare<E>>, KeyImportError>,
+        Msg = Msg<E, D>,
+    > + 'a
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(|party| self.start(rng, party))
+    }
+}
+
+async fn run_import<E, D, R, M>(
+    mut tracer: Option<&mut dyn Tracer>,
+    i: PartyIndex,
+    n: u16,
+    t: u16,
+    secret_key: Option<NonZero<SecretScalar<E>>>,
+    #[cfg(feature = "hd-wallet")] chain_code: Option<hd_wallet::ChainCode>,
+    reliable_broadcast_enforced: bool,
+    sid: ExecutionId<'_>,
+    rng: &mut R,
+    party: M,
+) -> Result<Option<IncompleteKeyShare<E>>, KeyImportError>
+where
+    E: Curve,
+    D: Digest + Clone + 'static,
+    R: RngCore + CryptoRng,
+    M: Mpc<ProtocolMessage = Msg<E, D>>,
+{
+    tracer.protocol_begins();
+
+    tracer.stage("Validate arguments");
+    let owner = n;
+    let n_parties = n.checked_add(1).ok_or(InvalidArgs::TooManyParties)?;
+    if i > owner {
+        return Err(InvalidArgs::IndexOutOfBounds.into());
+    }
+    if !(2 <= t && t <= n) {
+        return Err(InvalidArgs::InvalidThreshold.into());
+    }
+    match (i == owner, &secret_key) {
+        (true, None) => return Err(InvalidArgs::MissingSecretKey.into()),
+        (false, Some(_)) => return Err(InvalidArgs::UnexpectedSecretKey.into()),
+        _ => (),
+    }
+    #[cfg(feature = "hd-wallet")]
+    if i != owner && chain_code.is_some() {
+        return Err(InvalidArgs::UnexpectedChainCode.into());
+    }
+    let I = (1..=n)
+        .map(|j| NonZero::from_scalar(Scalar::from(j)).ok_or(Bug::ZeroIndex))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    tracer.stage("Setup networking");
+    let MpcParty { delivery, .. } = party.into_party();
+    let (incomings, mut outgoings) = delivery.split();
+
+    let mut rounds = RoundsRouter::<Msg<E, D>>::builder();
+    let round1_broad = rounds.add_round(RoundInput::<MsgRound1Broad<E>>::broadcast(i, n_parties));
+    let round1_uni = rounds.add_round(RoundInput::<MsgRound1Uni<E>>::p2p(i, n_parties));
+    let round1_sync = rounds.add_round(RoundInput::<MsgReliabilityCheck<D>>::broadcast(
+        i, n_parties,
+    ));
+    let mut rounds = rounds.listen(incomings);
+
+    // Round 1
+    tracer.round_begins();
+
+    tracer.stage("Sample polynomial f such that f(0) = sk");
+    let f = secret_key.as_ref().map(|sk| {
+        Polynomial::<SecretScalar<E>>::sample_with_const_term(
+            rng,
+            usize::from(t) - 1,
+            SecretScalar::clone(sk),
+        )
+    });
+    let F = f.as_ref().map(|f| f * &Point::generator());
+
+    tracer.stage("Prove knowledge of sk");
+    let sch_proof = secret_key.as_ref().zip(F.as_ref()).map(|(sk, F)| {
+        let (r, h) = schnorr_pok::prover_commits_ephemeral_secret::<E, _>(rng);
+        let challenge = Scalar::from_hash::<D>(&unambiguous::SchnorrPok { sid, F, h: h.0 });
+        let challenge = schnorr_pok::Challenge { nonce: challenge };
+        let z = schnorr_pok::prove(&r, &challenge, sk);
+        (h, z)
+    });
+    let (sch_commit, sch_proof) = sch_proof.unzip();
+
+    let my_msg = MsgRound1Broad {
+        F,
+        sch_commit,
+        sch_proof,
+        #[cfg(feature = "hd-wallet")]
+        chain_code,
+    };
+
+    tracer.send_msg();
+    outgoings
+        .feed(Outgoing::broadcast(Msg::Round1Broad(my_msg.clone())))
+        .await
+        .map_err(IoError::send_message)?;
+    let messages = utils::iter_peers(i, n_parties).map(|j| {
+        let sigma = f
+            .as_ref()
+            .zip(I.get(usize::from(j)))
+            .map(|(f, I_j)| f.value(I_j));
+        Outgoing::p2p(j, Msg::Round1Uni(MsgRound1Uni { sigma }))
+    });
+    outgoings
+        .send_all(&mut futures::stream::iter(messages.map(Ok)))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    tracer.receive_msgs();
+    let msgs_broad = rounds
+        .complete(round1_broad)
+        .await
+        .map_err(IoError::receive_message)?;
+    let msgs_uni = rounds
+        .complete(round1_uni)
+        .await
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    // Optional reliability check
+    if reliable_broadcast_enforced {
+        tracer.round_begins();
+
+        tracer.stage("Hash received msgs (reliability check)");
+        let h_i = udigest::hash_iter::<D>(
+            msgs_broad
+                .iter_including_me(&my_msg)
+                .map(|msg| unambiguous::Echo { sid, msg }),
+        );
+
+        tracer.send_msg();
+        outgoings
+            .send(Outgoing::broadcast(Msg::ReliabilityCheck(
+                MsgReliabilityCheck(h_i.clone()),
+            )))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.receive_msgs();
+        let hashes = rounds
+            .complete(round1_sync)
+            .await
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        tracer.stage("Assert other parties hashed messages (reliability check)");
+        let parties_have_different_hashes = hashes
+            .into_iter_indexed()
+            .filter(|(_j, _msg_id, h_j)| h_i != h_j.0)
+            .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        if !parties_have_different_hashes.is_empty() {
+            return Err(ImportAborted::new(
+                ImportAbortReason::Round1NotReliable,
+                parties_have_different_hashes,
+            )
+            .into());
+        }
+    }
+
+    if i == owner {
+        // Key owner doesn't receive a key share
+        tracer.protocol_ends();
+        return Ok(None);
+    }
+
+    tracer.stage("Validate data size");
+    let blame = utils::collect_blame(&msgs_broad, &msgs_uni, |j, msg, sigma| {
+        j == owner
+            && (msg.F.as_ref().is_none_or(|F| {
+                F.degree() >= usize::from(t)
+                    || F.value::<_, Point<E>>(&Scalar::zero()) == Point::zero()
+            }) || msg.sch_commit.is_none()
+                || msg.sch_proof.is_none()
+                || sigma.sigma.is_none())
+    });
+    if !blame.is_empty() {
+        return Err(ImportAborted::new(ImportAbortReason::InvalidDataSize, blame).into());
+    }
+
+    tracer.stage("Validate schnorr proof");
+    let blame = utils::collect_blame(&msgs_broad, &msgs_uni, |j, msg, _| {
+        let (Some(F), Some(h), Some(proof)) = (&msg.F, &msg.sch_commit, &msg.sch_proof) else {
+            return false;
+        };
+        if j != owner {
+            return false;
+        }
+        let challenge = Scalar::from_hash::<D>(&unambiguous::SchnorrPok { sid, F, h: h.0 });
+        let challenge = schnorr_pok::Challenge { nonce: challenge };
+        let X = F.value::<_, Point<E>>(&Scalar::zero());
+        proof.verify(h, &challenge, &X).is_err()
+    });
+    if !blame.is_empty() {
+        return Err(ImportAborted::new(ImportAbortReason::InvalidSchnorrProof, blame).into());
+    }
+
+    tracer.stage("Validate Feldmann VSS");
+    let blame = utils::collect_blame(&msgs_broad, &msgs_uni, |j, msg, sigma| {
+        match (&msg.F, sigma.sigma) {
+            (Some(F), Some(sigma)) if j == owner => {
+                F.value::<_, Point<E>>(&I[usize::from(i)]) != Point::generator() * sigma
+            }
+            _ => false,
+        }
+    });
+    if !blame.is_empty() {
+        return Err(ImportAborted::new(ImportAbortReason::FeldmanVerificationFailed, blame).into());
+    }
+
+    tracer.stage("Compute key share");
+    let (msg, sigma) = msgs_broad
+        .iter_indexed()
+        .zip(msgs_uni.iter_indexed())
+        .find(|((j, _, _), _)| *j == owner)
+        .map(|((_, _, msg), (_, _, sigma))| (msg, sigma))
+        .ok_or(Bug::MissingOwnerData)?;
+    let (F, mut x) = msg
+        .F
+        .as_ref()
+        .zip(sigma.sigma)
+        .ok_or(Bug::MissingOwnerData)?;
+    let x = SecretScalar::new(&mut x);
+    let shared_public_key =
+        NonZero::from_point(F.value::<_, Point<E>>(&Scalar::zero())).ok_or(Bug::ZeroPublicKey)?;
+
+    let public_shares = I
+        .iter()
+        .map(|I_j| NonZero::from_point(F.value::<_, Point<E>>(I_j)))
+        .collect::<Option<Vec<_>>>();
+    let (Some(public_shares), Some(x)) = (public_shares, NonZero::from_secret_scalar(x)) else {
+        let blame = utils::collect_blame(&msgs_broad, &msgs_uni, |j, _, _| j == owner);
+        return Err(ImportAborted::new(ImportAbortReason::ZeroShare, blame).into());
+    };
+
+    let key_share = DirtyIncompleteKeyShare {
+        i,
+        key_info: DirtyKeyInfo {
+            curve: Default::default(),
+            shared_public_key,
+            public_shares,
+            vss_setup: Some(VssSetup {
+                min_signers: t,
+                I,
+                weights: None,
+                ranks: None,
+            }),
+            #[cfg(feature = "hd-wallet")]
+            chain_code: msg.chain_code,
+        },
+        x,
+        extra_x: Vec::new(),
+    }
+    .validate()
+    .map_err(|err| Bug::InvalidShareGenerated(err.into_error()))?;
+
+    tracer.protocol_ends();
+    Ok(Some(key_share))
+}
+
+/// Error of key import protocol
+#[derive(Debug, Error)]
+#[error("key import protocol failed to complete")]
+pub struct KeyImportError(#[source] Reason);
+
+impl KeyImportError {
+    /// If protocol was aborted by malicious party, returns information about
+    /// the abort: its reason and parties to blame
+    pub fn aborted(&self) -> Option<&ImportAborted> {
+        match &self.0 {
+            Reason::Aborted(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+crate::errors::impl_from! {
+    impl From for KeyImportError {
+        err: InvalidArgs => KeyImportError(Reason::InvalidArgs(err)),
+        err: ImportAborted => KeyImportError(Reason::Aborted(err)),
+        err: IoError => KeyImportError(Reason::IoError(err)),
+        err: Bug => KeyImportError(Reason::Bug(err)),
+    }
+}
+
+#[derive(Debug, Error)]
+enum Reason {
+    #[error("invalid arguments")]
+    InvalidArgs(#[source] InvalidArgs),
+    /// Protocol was maliciously aborted by another party
+    #[error("protocol was aborted by malicious party")]
+    Aborted(#[source] ImportAborted),
+    #[error("i/o error")]
+    IoError(#[source] IoError),
+    #[error("bug occurred")]
+    Bug(#[source] Bug),
+}
+
+#[derive(Debug, Error)]
+enum InvalidArgs {
+    #[error("too many parties")]
+    TooManyParties,
+    #[error("party index `i` is out of bounds")]
+    IndexOutOfBounds,
+    #[error("threshold must satisfy 2 <= t <= n")]
+    InvalidThreshold,
+    #[error("key owner didn't provide the secret key")]
+    MissingSecretKey,
+    #[error("secret key is provided by party which is not the key owner")]
+    UnexpectedSecretKey,
+    #[cfg(feature = "hd-wallet")]
+    #[error("chain code is provided by party which is not the key owner")]
+    UnexpectedChainCode,
+}
+
+/// Unexpected error in operation not caused by other parties
+#[derive(Debug, Error)]
+enum Bug {
+    #[error("key share index is zero")]
+    ZeroIndex,
+    #[error("owner data is missing although we checked that it's present")]
+    MissingOwnerData,
+    #[error("public key is zero although we checked that it's not")]
+    ZeroPublicKey,
+    #[error("resulting key share is not valid")]
+    InvalidShareGenerated(#[source] InvalidIncompleteKeyShare),
+}
+
+/// Error indicating that key import was aborted by malicious party
+pub type ImportAborted = Aborted<ImportAbortReason>;
+
+/// Reason for key import abort: which exact check has failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum ImportAbortReason {
+    /// Key owner didn't send its polynomial, proof or share, or polynomial doesn't match
+    /// the threshold, or commits to zero key
+    #[error("key owner data is missing or doesn't match threshold")]
+    InvalidDataSize,
+    /// Key owner provided invalid proof of knowledge of the key
+    #[error("provided invalid schnorr proof")]
+    InvalidSchnorrProof,
+    /// Secret share sent by key owner is not consistent with its polynomial
+    #[error("secret share is not consistent")]
+    FeldmanVerificationFailed,
+    /// Key owner dealt zero secret share
+    #[error("dealt secret share is zero")]
+    ZeroShare,
+    /// Party claims it received different messages in round 1 than other parties
+    #[error("round 1 was not reliable")]
+    Round1NotReliable,
+}
@@ -4,6 +4,8 @@
 mod aux_only;
 /// Non-threshold key refresh specific types
 mod non_threshold;
+/// Threshold key refresh specific types
+mod threshold;
 
 use digest::Digest;
 use generic_ec::Curve;
@@ -16,13 +18,17 @@ use crate::{
     key_share::{AnyKeyShare, AuxInfo, DirtyIncompleteKeyShare, KeyShare},
     progress::Tracer,
     security_level::SecurityLevel,
-    utils::AbortBlame,
+    timeout::{RoundTimeout, RoundTimer},
+    utils::Aborted,
     ExecutionId,
 };
 use crate::{fast_paillier, rug::Integer};
 
 #[doc(no_inline)]
-pub use self::msg::{aux_only::Msg as AuxOnlyMsg, non_threshold::Msg as NonThresholdMsg};
+pub use self::msg::{
+    aux_only::Msg as AuxOnlyMsg, non_threshold::Msg as NonThresholdMsg,
+    threshold::Msg as ThresholdMsg,
+};
 
 #[doc = include_str!("../docs/mpc_message.md")]
 pub mod msg {
@@ -38,6 +44,12 @@ pub mod msg {
             Msg, MsgReliabilityCheck, MsgRound1, MsgRound2, MsgRound3,
         };
     }
+    /// Messages types related to threshold key refresh protocol
+    pub mod threshold {
+        pub use crate::key_refresh::threshold::{
+            Msg, MsgReliabilityCheck, MsgRound1, MsgRound2, MsgRound3,
+        };
+    }
 }
 
 /// To speed up computations, it's possible to supply data to the algorithm
@@ -91,6 +103,14 @@ pub type KeyRefreshBuilder<
     D = crate::default_choice::Digest,
 > = GenericKeyRefreshBuilder<'a, RefreshShare<'a, E>, L, D>;
 
+/// A variant of [`GenericKeyRefreshBuilder`] that performs threshold key refresh
+pub type ThresholdKeyRefreshBuilder<
+    'a,
+    E,
+    L = crate::default_choice::SecurityLevel,
+    D = crate::default_choice::Digest,
+> = GenericKeyRefreshBuilder<'a, RefreshThresholdShare<'a, E>, L, D>;
+
 /// A variant of [`GenericKeyRefreshBuilder`] that only generates auxiliary info
 /// and doesn't require key shares
 pub type AuxInfoGenerationBuilder<
@@ -109,6 +129,7 @@ where
     execution_id: ExecutionId<'a>,
     pregenerated: PregeneratedPrimes<L>,
     tracer: Option<&'a mut dyn Tracer>,
+    round_timer: Option<&'a mut dyn RoundTimer>,
     enforce_reliable_broadcast: bool,
     precompute_multiexp_tables: bool,
     precompute_crt: bool,
@@ -117,6 +138,8 @@ where
 
 /// A marker for [`KeyRefreshBuilder`]
 pub struct RefreshShare<'a, E: Curve>(&'a DirtyIncompleteKeyShare<E>);
+/// A marker for [`ThresholdKeyRefreshBuilder`]
+pub struct RefreshThresholdShare<'a, E: Curve>(&'a DirtyIncompleteKeyShare<E>);
 /// A marker for [`AuxInfoGenerationBuilder`]
 pub struct AuxOnly {
     i: u16,
@@ -142,6 +165,7 @@ where
             execution_id: eid,
             pregenerated,
             tracer: None,
+            round_timer: None,
             enforce_reliable_broadcast: true,
             precompute_multiexp_tables: false,
             precompute_crt: false,
@@ -164,6 +188,7 @@ where
             self.execution_id,
             self.pregenerated,
             self.tracer,
+            self.round_timer,
             self.enforce_reliable_broadcast,
             self.precompute_multiexp_tables,
             self.precompute_crt,
@@ -193,6 +218,80 @@ where
     }
 }
 
+impl<'a, E, L, D> ThresholdKeyRefreshBuilder<'a, E, L, D>
+where
+    E: Curve,
+    L: SecurityLevel,
+    D: Digest,
+{
+    /// Build threshold key refresh operation. Start it with [`start`](Self::start).
+    ///
+    /// Key share must be a threshold key share, i.e. it must have [`VssSetup`](crate::key_share::VssSetup).
+    ///
+    /// PregeneratedPrimes can be obtained with [`PregeneratedPrimes::generate`]
+    pub fn new(
+        eid: ExecutionId<'a>,
+        key_share: &'a impl AnyKeyShare<E>,
+        pregenerated: PregeneratedPrimes<L>,
+    ) -> Self {
+        Self {
+            target: RefreshThresholdShare(key_share.as_ref()),
+            execution_id: eid,
+            pregenerated,
+            tracer: None,
+            round_timer: None,
+            enforce_reliable_broadcast: true,
+            precompute_multiexp_tables: false,
+            precompute_crt: false,
+            _digest: std::marker::PhantomData,
+        }
+    }
+
+    /// Carry out the threshold refresh procedure. Takes a lot of time
+    pub async fn start<R, M>(self, rng: &mut R, party: M) -> Result<KeyShare<E, L>, KeyRefreshError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = ThresholdMsg<E, D, L>>,
+        E: Curve,
+        L: SecurityLevel,
+        D: Digest<OutputSize = digest::typenum::U32> + Clone + 'static,
+    {
+        threshold::run_threshold_refresh(
+            rng,
+            party,
+            self.execution_id,
+            self.pregenerated,
+            self.tracer,
+            self.round_timer,
+            self.enforce_reliable_broadcast,
+            self.precompute_multiexp_tables,
+            self.precompute_crt,
+            self.target.0,
+        )
+        .await
+    }
+
+    /// Returns a state machine that can be used to carry out the threshold key refresh protocol
+    ///
+    /// See [`round_based::state_machine`] for details on how that can be done.
+    #[cfg(feature = "state-machine")]
+    pub fn into_state_machine<R>(
+        self,
+        rng: &'a mut R,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<KeyShare<E, L>, KeyRefreshError>,
+        Msg = ThresholdMsg<E, D, L>,
+    > + 'a
+    where
+        R: RngCore + CryptoRng,
+        E: Curve,
+        L: SecurityLevel,
+        D: Digest<OutputSize = digest::typenum::U32> + Clone + 'static,
+    {
+        round_based::state_machine::wrap_protocol(|party| self.start(rng, party))
+    }
+}
+
 impl<'a, L, D> AuxInfoGenerationBuilder<'a, L, D>
 where
     L: SecurityLevel,
@@ -212,6 +311,7 @@ where
             execution_id: eid,
             pregenerated,
             tracer: None,
+            round_timer: None,
             enforce_reliable_broadcast: true,
             precompute_multiexp_tables: false,
             precompute_crt: false,
@@ -235,6 +335,7 @@ where
             self.execution_id,
             self.pregenerated,
             self.tracer,
+            self.round_timer,
             self.enforce_reliable_broadcast,
             self.precompute_multiexp_tables,
             self.precompute_crt,
@@ -274,6 +375,7 @@ where
             execution_id: self.execution_id,
             pregenerated: self.pregenerated,
             tracer: self.tracer,
+            round_timer: self.round_timer,
             enforce_reliable_broadcast: self.enforce_reliable_broadcast,
             precompute_multiexp_tables: self.precompute_multiexp_tables,
             precompute_crt: self.precompute_crt,
@@ -287,6 +389,16 @@ where
         self
     }
 
+    /// Sets a timer that limits how long each round of the protocol may take
+    ///
+    /// If some parties don't send their messages before the round deadline, protocol fails
+    /// with an error that lists them (see [`KeyRefreshError::timed_out`]). See
+    /// [`timeout`](crate::timeout) module for more details.
+    pub fn set_round_timer(mut self, timer: &'a mut dyn RoundTimer) -> Self {
+        self.round_timer = Some(timer);
+        self
+    }
+
     #[doc = include_str!("../docs/enforce_reliable_broadcast.md")]
     pub fn enforce_reliable_broadcast(self, v: bool) -> Self {
         Self {
@@ -323,25 +435,64 @@ where
 #[error("key refresh protocol failed to complete")]
 pub struct KeyRefreshError(#[source] Reason);
 
+impl KeyRefreshError {
+    /// If protocol was aborted by malicious party, returns information about
+    /// the abort: its reason and parties to blame
+    pub fn aborted(&self) -> Option<&ProtocolAborted> {
+        match &self.0 {
+            Reason::Aborted(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// If protocol failed because some parties didn't send their messages before round
+    /// deadline, returns the round and parties that stalled
+    pub fn timed_out(&self) -> Option<&RoundTimeout> {
+        match &self.0 {
+            Reason::Timeout(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 crate::errors::impl_from! {
     impl From for KeyRefreshError {
+        err: InvalidArgs => KeyRefreshError(Reason::InvalidArgs(err)),
         err: ProtocolAborted => KeyRefreshError(Reason::Aborted(err)),
         err: IoError => KeyRefreshError(Reason::IoError(err)),
+        err: RoundTimeout => KeyRefreshError(Reason::Timeout(err)),
         err: Bug => KeyRefreshError(Reason::InternalError(err)),
     }
 }
 
 #[derive(Debug, Error)]
 enum Reason {
+    #[error("invalid arguments")]
+    InvalidArgs(#[source] InvalidArgs),
     /// Protocol was maliciously aborted by another party
     #[error("protocol was aborted by malicious party")]
     Aborted(#[source] ProtocolAborted),
     #[error("i/o error")]
     IoError(#[source] IoError),
+    /// Some parties didn't send their messages before round deadline
+    #[error("round deadline expired")]
+    Timeout(#[source] RoundTimeout),
     #[error("internal error")]
     InternalError(#[from] Bug),
 }
 
+#[derive(Debug, Error)]
+enum InvalidArgs {
+    #[error("key share is a threshold key share, use threshold key refresh instead")]
+    ThresholdKeyShare,
+    #[error("key share is not a threshold key share, use non-threshold key refresh instead")]
+    NonThresholdKeyShare,
+    #[error("weighted key shares can't be refreshed")]
+    WeightedKey,
+    #[error("hierarchical key shares can't be refreshed")]
+    HierarchicalKey,
+}
+
 /// Unexpected error in operation not caused by other parties
 #[derive(Debug, Error)]
 enum Bug {
@@ -369,63 +520,41 @@ enum Bug {
     ZeroShare,
 }
 
-/// Error indicating that protocol was aborted by malicious party
-///
-/// It _can be_ cryptographically proven, but we do not support it yet.
-#[derive(Debug, Error)]
-#[error("Protocol aborted; malicious parties: {parties:?}; reason: {reason}")]
-struct ProtocolAborted {
-    pub reason: ProtocolAbortReason,
-    pub parties: Vec<AbortBlame>,
-}
+/// Error indicating that key refresh was aborted by malicious party
+pub type ProtocolAborted = Aborted<ProtocolAbortReason>;
 
 /// Reason for protocol abort: which exact check has failed
-#[derive(Debug, Error)]
-enum ProtocolAbortReason {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum ProtocolAbortReason {
+    /// Decommitment doesn't match commitment
     #[error("decommitment doesn't match commitment")]
     InvalidDecommitment,
+    /// Party provided invalid schnorr proof
     #[error("provided invalid schnorr proof")]
     InvalidSchnorrProof,
+    /// Party provided invalid $\Pi^\text{mod}$ proof
     #[error("provided invalid proof for Rmod")]
     InvalidModProof,
+    /// Party provided invalid $\Pi^\text{fac}$ proof
     #[error("provided invalid proof for Rfac")]
     InvalidFacProof,
+    /// Party provided invalid ring-pedersen parameters
     #[error("N, s and t parameters are invalid")]
     InvalidRingPedersenParameters,
+    /// Party's public shares are malformed
     #[error("X is malformed")]
     InvalidX,
+    /// Secret share sent by party doesn't correspond to its public commitment
     #[error("x doesn't correspond to X")]
     InvalidXShare,
+    /// Party sent a message with missing data
     #[error("party sent a message with missing data")]
     InvalidDataSize,
+    /// Party's message could not be decrypted
     #[error("party message could not be decrypted")]
     PaillierDec,
+    /// Party claims it received different messages in round 1 than other parties
     #[error("round 1 was not reliable")]
     Round1NotReliable,
 }
-
-macro_rules! make_factory {
-    ($function:ident, $reason:ident) => {
-        fn $function(parties: Vec<AbortBlame>) -> Self {
-            Self {
-                reason: ProtocolAbortReason::$reason,
-                parties,
-            }
-        }
-    };
-}
-impl ProtocolAborted {
-    make_factory!(invalid_decommitment, InvalidDecommitment);
-    make_factory!(invalid_schnorr_proof, InvalidSchnorrProof);
-    make_factory!(invalid_mod_proof, InvalidModProof);
-    make_factory!(invalid_fac_proof, InvalidFacProof);
-    make_factory!(
-        invalid_ring_pedersen_parameters,
-        InvalidRingPedersenParameters
-    );
-    make_factory!(invalid_x, InvalidX);
-    make_factory!(invalid_x_share, InvalidXShare);
-    make_factory!(invalid_data_size, InvalidDataSize);
-    make_factory!(paillier_dec, PaillierDec);
-    make_factory!(round1_not_reliable, Round1NotReliable);
-}
@@ -8,8 +8,7 @@ use paillier_zk::{
 };
 use rand_core::{CryptoRng, RngCore};
 use round_based::{
-    rounds_router::{simple_store::RoundInput, RoundsRouter},
-    Delivery, Mpc, MpcParty, Outgoing, ProtocolMessage,
+    rounds_router::simple_store::RoundInput, Delivery, Mpc, MpcParty, Outgoing, ProtocolMessage,
 };
 use serde::{Deserialize, Serialize};
 
@@ -18,13 +17,14 @@ use crate::{
     key_share::{AuxInfo, DirtyAuxInfo, PartyAux, Validate},
     progress::Tracer,
     security_level::SecurityLevel,
+    timeout::{RoundTimer, RoundsRouter},
     utils,
     utils::{collect_blame, AbortBlame},
     zk::ring_pedersen_parameters as π_prm,
     ExecutionId,
 };
 
-use super::{Bug, KeyRefreshError, PregeneratedPrimes, ProtocolAborted};
+use super::{Bug, KeyRefreshError, PregeneratedPrimes, ProtocolAbortReason, ProtocolAborted};
 
 macro_rules! prefixed {
     ($name:tt) => {
@@ -161,6 +161,7 @@ pub async fn run_aux_gen<R, M, L, D>(
     sid: ExecutionId<'_>,
     pregenerated: PregeneratedPrimes<L>,
     mut tracer: Option<&mut dyn Tracer>,
+    round_timer: Option<&mut dyn RoundTimer>,
     reliable_broadcast_enforced: bool,
     compute_multiexp_table: bool,
     compute_crt: bool,
@@ -179,12 +180,12 @@ where
     let MpcParty { delivery, .. } = party.into_party();
     let (incomings, mut outgoings) = delivery.split();
 
-    let mut rounds = RoundsRouter::<Msg<D, L>>::builder();
+    let mut rounds = RoundsRouter::<Msg<D, L>>::builder(i, n);
     let round1 = rounds.add_round(RoundInput::<MsgRound1<D>>::broadcast(i, n));
     let round1_sync = rounds.add_round(RoundInput::<MsgReliabilityCheck<D>>::broadcast(i, n));
     let round2 = rounds.add_round(RoundInput::<MsgRound2<L>>::broadcast(i, n));
     let round3 = rounds.add_round(RoundInput::<MsgRound3>::p2p(i, n));
-    let mut rounds = rounds.listen(incomings);
+    let mut rounds = rounds.listen(incomings, round_timer);
 
     // Round 1
     tracer.round_begins();
@@ -258,7 +259,7 @@ where
     tracer.receive_msgs();
     let commitments = rounds
         .complete(round1)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
@@ -285,7 +286,7 @@ where
         tracer.receive_msgs();
         let hashes = rounds
             .complete(round1_sync)
-            .await
+            .await?
             .map_err(IoError::receive_message)?;
         tracer.msgs_received();
 
@@ -296,7 +297,11 @@ where
             .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
             .collect::<Vec<_>>();
         if !parties_have_different_hashes.is_empty() {
-            return Err(ProtocolAborted::round1_not_reliable(parties_have_different_hashes).into());
+            return Err(ProtocolAborted::new(
+                ProtocolAbortReason::Round1NotReliable,
+                parties_have_different_hashes,
+            )
+            .into());
         }
     }
 
@@ -313,7 +318,7 @@ where
     tracer.receive_msgs();
     let decommitments = rounds
         .complete(round2)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
@@ -328,7 +333,7 @@ where
         com_expected != comm.commitment
     });
     if !blame.is_empty() {
-        return Err(ProtocolAborted::invalid_decommitment(blame).into());
+        return Err(ProtocolAborted::new(ProtocolAbortReason::InvalidDecommitment, blame).into());
     }
     // validate parameters and param_proofs
     tracer.stage("Validate П_prm (ψ_i)");
@@ -350,7 +355,11 @@ where
         }
     });
     if !blame.is_empty() {
-        return Err(ProtocolAborted::invalid_ring_pedersen_parameters(blame).into());
+        return Err(ProtocolAborted::new(
+            ProtocolAbortReason::InvalidRingPedersenParameters,
+            blame,
+        )
+        .into());
     }
 
     tracer.stage("Add together shared random bytes");
@@ -434,7 +443,7 @@ where
     tracer.receive_msgs();
     let shares_msg_b = rounds
         .complete(round3)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
@@ -462,7 +471,7 @@ where
         },
     );
     if !blame.is_empty() {
-        return Err(ProtocolAborted::invalid_mod_proof(blame).into());
+        return Err(ProtocolAborted::new(ProtocolAbortReason::InvalidModProof, blame).into());
     }
 
     tracer.stage("Validate ф_j (П_fac)");
@@ -503,7 +512,7 @@ where
         },
     );
     if !blame.is_empty() {
-        return Err(ProtocolAborted::invalid_fac_proof(blame).into());
+        return Err(ProtocolAborted::new(ProtocolAbortReason::InvalidFacProof, blame).into());
     }
 
     // verifications passed, compute final key shares
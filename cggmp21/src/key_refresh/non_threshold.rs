@@ -11,13 +11,12 @@ use paillier_zk::{
 };
 use rand_core::{CryptoRng, RngCore};
 use round_based::ProtocolMessage;
-use round_based::{
-    rounds_router::{simple_store::RoundInput, RoundsRouter},
-    Delivery, Mpc, MpcParty, Outgoing,
-};
+use round_based::{rounds_router::simple_store::RoundInput, Delivery, Mpc, MpcParty, Outgoing};
 use serde::{Deserialize, Serialize};
 
-use super::{Bug, KeyRefreshError, PregeneratedPrimes, ProtocolAborted};
+use super::{
+    Bug, InvalidArgs, KeyRefreshError, PregeneratedPrimes, ProtocolAbortReason, ProtocolAborted,
+};
 use crate::{
     errors::IoError,
     key_share::{
@@ -25,6 +24,7 @@ use crate::{
     },
     progress::Tracer,
     security_level::{SecurityLevel, M},
+    timeout::{RoundTimer, RoundsRouter},
     utils,
     utils::{
         but_nth, collect_blame, collect_simple_blame, iter_peers, scalar_to_bignumber, xor_array,
@@ -189,6 +189,7 @@ pub async fn run_refresh<R, M, E, L, D>(
     sid: ExecutionId<'_>,
     pregenerated: PregeneratedPrimes<L>,
     mut tracer: Option<&mut dyn Tracer>,
+    round_timer: Option<&mut dyn RoundTimer>,
     reliable_broadcast_enforced: bool,
     build_multiexp_tables: bool,
     build_crt: bool,
@@ -206,17 +207,20 @@ where
     tracer.stage("Retrieve auxiliary data");
     let i = core_share.i;
     let n = u16::try_from(core_share.public_shares.len()).map_err(|_| Bug::TooManyParties)?;
+    if core_share.vss_setup.is_some() {
+        return Err(InvalidArgs::ThresholdKeyShare.into());
+    }
 
     tracer.stage("Setup networking");
     let MpcParty { delivery, .. } = party.into_party();
     let (incomings, mut outgoings) = delivery.split();
 
-    let mut rounds = RoundsRouter::<Msg<E, D, L>>::builder();
+    let mut rounds = RoundsRouter::<Msg<E, D, L>>::builder(i, n);
     let round1 = rounds.add_round(RoundInput::<MsgRound1<D>>::broadcast(i, n));
     let round1_sync = rounds.add_round(RoundInput::<MsgReliabilityCheck<D>>::broadcast(i, n));
     let round2 = rounds.add_round(RoundInput::<MsgRound2<E, L>>::broadcast(i, n));
     let round3 = rounds.add_round(RoundInput::<MsgRound3<E>>::p2p(i, n));
-    let mut rounds = rounds.listen(incomings);
+    let mut rounds = rounds.listen(incomings, round_timer);
 
     // Round 1
     tracer.round_begins();
@@ -317,7 +321,7 @@ where
     tracer.receive_msgs();
     let commitments = rounds
         .complete(round1)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
@@ -344,7 +348,7 @@ where
         tracer.receive_msgs();
         let hashes = rounds
             .complete(round1_sync)
-            .await
+            .await?
             .map_err(IoError::receive_message)?;
         tracer.msgs_received();
 
@@ -355,7 +359,11 @@ where
             .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
             .collect::<Vec<_>>();
         if !parties_have_different_hashes.is_empty() {
-            return Err(ProtocolAborted::round1_not_reliable(parties_have_different_hashes).into());
+            return Err(ProtocolAborted::new(
+                ProtocolAbortReason::Round1NotReliable,
+                parties_have_different_hashes,
+            )
+            .into());
         }
     }
 
@@ -372,7 +380,7 @@ where
     tracer.receive_msgs();
     let decommitments = rounds
         .complete(round2)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
@@ -387,7 +395,7 @@ where
         com_expected != comm.commitment
     });
     if !blame.is_empty() {
-        return Err(ProtocolAborted::invalid_decommitment(blame).into());
+        return Err(ProtocolAborted::new(ProtocolAbortReason::InvalidDecommitment, blame).into());
     }
     // Validate parties didn't skip any data
     tracer.stage("Validate data sizes");
@@ -396,7 +404,7 @@ where
         decommitment.Xs.len() != n || decommitment.sch_commits_a.len() != n
     });
     if !blame.is_empty() {
-        return Err(ProtocolAborted::invalid_data_size(blame).into());
+        return Err(ProtocolAborted::new(ProtocolAbortReason::InvalidDataSize, blame).into());
     }
     // validate parameters and param_proofs
     tracer.stage("Validate П_prm (ψ_i)");
@@ -418,7 +426,11 @@ where
         }
     });
     if !blame.is_empty() {
-        return Err(ProtocolAborted::invalid_ring_pedersen_parameters(blame).into());
+        return Err(ProtocolAborted::new(
+            ProtocolAbortReason::InvalidRingPedersenParameters,
+            blame,
+        )
+        .into());
     }
     // validate Xs add to zero
     tracer.stage("Validate X_i");
@@ -426,7 +438,7 @@ where
         d.Xs.iter().sum::<Point<E>>() != Point::zero()
     });
     if !blame.is_empty() {
-        return Err(ProtocolAborted::invalid_x(blame).into());
+        return Err(ProtocolAborted::new(ProtocolAbortReason::InvalidX, blame).into());
     }
 
     tracer.stage("Compute paillier encryption keys");
@@ -539,7 +551,7 @@ where
     tracer.receive_msgs();
     let shares_msg_b = rounds
         .complete(round3)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
@@ -556,7 +568,7 @@ where
             Ok::<_, AbortBlame>(bigint.to_scalar())
         }));
     if !blame.is_empty() {
-        return Err(ProtocolAborted::paillier_dec(blame).into());
+        return Err(ProtocolAborted::new(ProtocolAbortReason::PaillierDec, blame).into());
     }
     debug_assert_eq!(shares.len(), usize::from(n) - 1);
 
@@ -576,7 +588,7 @@ where
         })
         .collect::<Vec<_>>();
     if !blame.is_empty() {
-        return Err(ProtocolAborted::invalid_x_share(blame).into());
+        return Err(ProtocolAborted::new(ProtocolAbortReason::InvalidXShare, blame).into());
     }
     // It is possible at this point to report a bad party to others, but we
     // don't implement it now
@@ -614,7 +626,7 @@ where
         },
     )?;
     if !blame.is_empty() {
-        return Err(ProtocolAborted::invalid_schnorr_proof(blame).into());
+        return Err(ProtocolAborted::new(ProtocolAbortReason::InvalidSchnorrProof, blame).into());
     }
 
     tracer.stage("Validate ψ_j (П_mod)");
@@ -641,7 +653,7 @@ where
         },
     );
     if !blame.is_empty() {
-        return Err(ProtocolAborted::invalid_mod_proof(blame).into());
+        return Err(ProtocolAborted::new(ProtocolAbortReason::InvalidModProof, blame).into());
     }
 
     tracer.stage("Validate ф_j (П_fac)");
@@ -682,7 +694,7 @@ where
         },
     );
     if !blame.is_empty() {
-        return Err(ProtocolAborted::invalid_fac_proof(blame).into());
+        return Err(ProtocolAborted::new(ProtocolAbortReason::InvalidFacProof, blame).into());
     }
 
     // verifications passed, compute final key shares
@@ -0,0 +1,850 @@
+//! Key resharing protocol
+//!
+//! Allows to change the set of parties sharing the key and the threshold without changing the
+//! shared public key (and chain code, if HD wallets support is enabled).
+//!
+//! Parties of the old committee (dealers) deal fresh VSS shares of their Lagrange-weighted
+//! secret shares to the members of the new committee. Each member of the new committee
+//! verifies the received shares against public commitments and obtains a key share with
+//! new `n` and `t`. Parties that leave the committee do not obtain anything.
+//!
+//! Resharing only outputs core key share ([`IncompleteKeyShare`]). Members of the new
+//! committee need to carry out [aux info generation](crate::aux_info_gen) to obtain a
+//! complete [`KeyShare`](crate::KeyShare).
+//!
+//! [`ThresholdConversionBuilder`] is a special case of resharing that turns n-out-of-n key
+//! (generated by non-threshold keygen) into t-out-of-n key held by the same parties.
+
+use digest::Digest;
+use futures::SinkExt;
+use generic_ec::{Curve, NonZero, Point, Scalar, SecretScalar};
+use generic_ec_zkp::polynomial::{lagrange_coefficient_at_zero, Polynomial};
+use rand_core::{CryptoRng, RngCore};
+use round_based::{
+    rounds_router::{simple_store::RoundInput, RoundsRouter},
+    Delivery, Mpc, MpcParty, Outgoing, PartyIndex, ProtocolMessage,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    errors::IoError,
+    key_share::{
+        AnyKeyShare, DirtyIncompleteKeyShare, DirtyKeyInfo, IncompleteKeyShare,
+        InvalidIncompleteKeyShare, Validate, VssSetup,
+    },
+    progress::Tracer,
+    utils::{self, AbortBlame, Aborted},
+    ExecutionId,
+};
+
+macro_rules! prefixed {
+    ($name:tt) => {
+        concat!("dfns.cggmp21.key_resharing.", $name)
+    };
+}
+
+/// Message of key resharing protocol
+#[derive(ProtocolMessage, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum Msg<E: Curve, D: Digest> {
+    /// Round 1 message
+    Round1(MsgRound1<D>),
+    /// Round 2 message broadcasted to everyone
+    Round2Broad(MsgRound2Broad<E>),
+    /// Round 2 message unicasted to each party
+    Round2Uni(MsgRound2Uni<E>),
+    /// Reliability check message (optional additional round)
+    ReliabilityCheck(MsgReliabilityCheck<D>),
+}
+
+/// Message from round 1
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[serde(bound = "")]
+#[udigest(bound = "")]
+#[udigest(tag = prefixed!("round1"))]
+pub struct MsgRound1<D: Digest> {
+    /// $V_i$
+    #[udigest(as_bytes)]
+    pub commitment: digest::Output<D>,
+}
+/// Message from round 2 broadcasted to everyone
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[serde(bound = "")]
+#[udigest(bound = "")]
+#[udigest(tag = prefixed!("round2_broad"))]
+pub struct MsgRound2Broad<E: Curve> {
+    /// $F_i(x)$, commitment to polynomial $f_i(x)$ such that $f_i(0) = \lambda_i \cdot x_i$
+    ///
+    /// `None` if party is not a dealer
+    pub F: Option<Polynomial<Point<E>>>,
+    /// $u_i$
+    #[serde(with = "hex::serde")]
+    #[udigest(as_bytes)]
+    pub decommit: [u8; 32],
+}
+/// Message from round 2 unicasted to each party
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound2Uni<E: Curve> {
+    /// $\sigma_{i,j} = f_i(I_j)$
+    ///
+    /// `None` if sender is not a dealer or recipient is not a member of the new committee
+    pub sigma: Option<Scalar<E>>,
+}
+/// Message parties exchange to ensure reliability of broadcast channel
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgReliabilityCheck<D: Digest>(pub digest::Output<D>);
+
+mod unambiguous {
+    use generic_ec::Curve;
+
+    use crate::ExecutionId;
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("hash_commitment"))]
+    #[udigest(bound = "")]
+    pub struct HashCom<'a, E: Curve> {
+        pub sid: ExecutionId<'a>,
+        pub party_index: u16,
+        pub decommitment: &'a super::MsgRound2Broad<E>,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("echo_round"))]
+    #[udigest(bound = "")]
+    pub struct Echo<'a, D: digest::Digest> {
+        pub sid: ExecutionId<'a>,
+        pub commitment: &'a super::MsgRound1<D>,
+    }
+}
+
+/// Party taking part in key resharing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Participant {
+    /// Index of party's key share in the old committee
+    ///
+    /// `Some(_)` if party holds a share of the key and acts as a dealer, `None` otherwise
+    pub old_index: Option<u16>,
+    /// Whether party receives a key share in the new committee
+    ///
+    /// Members of the new committee are assigned new key share indexes in order in which
+    /// they appear in the list of participants
+    pub new_member: bool,
+}
+
+/// Key resharing protocol builder
+pub struct KeyResharingBuilder<'a, E: Curve, D = crate::default_choice::Digest> {
+    eid: ExecutionId<'a>,
+    i: PartyIndex,
+    participants: &'a [Participant],
+    new_t: u16,
+    old_key_info: &'a DirtyKeyInfo<E>,
+    old_key_share: Option<&'a DirtyIncompleteKeyShare<E>>,
+    tracer: Option<&'a mut dyn Tracer>,
+    enforce_reliable_broadcast: bool,
+    _digest: std::marker::PhantomData<D>,
+}
+
+impl<'a, E, D> KeyResharingBuilder<'a, E, D>
+where
+    E: Curve,
+    D: Digest + Clone + 'static,
+{
+    /// Constructs a builder
+    ///
+    /// * `i` is index of local party in the list of `participants`
+    /// * `participants` lists all parties of the protocol along with their roles
+    /// * `new_t` is threshold of the new committee
+    /// * `old_key_info` is public information about the key being reshared
+    ///
+    /// Dealers must provide their key share via [`set_old_key_share`](Self::set_old_key_share).
+    pub fn new(
+        eid: ExecutionId<'a>,
+        i: PartyIndex,
+        participants: &'a [Participant],
+        new_t: u16,
+        old_key_info: &'a DirtyKeyInfo<E>,
+    ) -> Self {
+        Self {
+            eid,
+            i,
+            participants,
+            new_t,
+            old_key_info,
+            old_key_share: None,
+            tracer: None,
+            enforce_reliable_broadcast: true,
+            _digest: std::marker::PhantomData,
+        }
+    }
+
+    /// Sets key share of the old committee held by local party
+    ///
+    /// Must be set if local party is a dealer
+    pub fn set_old_key_share(self, key_share: &'a impl AnyKeyShare<E>) -> Self {
+        Self {
+            old_key_share: Some(key_share.as_ref()),
+            ..self
+        }
+    }
+
+    /// Specifies another hash function to use
+    pub fn set_digest<D2>(self) -> KeyResharingBuilder<'a, E, D2>
+    where
+        D2: Digest + Clone + 'static,
+    {
+        KeyResharingBuilder {
+            eid: self.eid,
+            i: self.i,
+            participants: self.participants,
+            new_t: self.new_t,
+            old_key_info: self.old_key_info,
+            old_key_share: self.old_key_share,
+            tracer: self.tracer,
+            enforce_reliable_broadcast: self.enforce_reliable_broadcast,
+            _digest: std::marker::PhantomData,
+        }
+    }
+
+    /// Sets a tracer that tracks progress of protocol execution
+    pub fn set_progress_tracer(mut self, tracer: &'a mut dyn Tracer) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    #[doc = include_str!("../docs/enforce_reliable_broadcast.md")]
+    pub fn enforce_reliable_broadcast(self, v: bool) -> Self {
+        Self {
+            enforce_reliable_broadcast: v,
+            ..self
+        }
+    }
+
+    /// Carries out the key resharing protocol
+    ///
+    /// Returns `Some(key_share)` if local party is a member of the new committee, and
+    /// `None` otherwise.
+    pub async fn start<R, M>(
+        self,
+        rng: &mut R,
+        party: M,
+    ) -> Result<Option<IncompleteKeyShare<E>>, KeyResharingError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = Msg<E, D>>,
+    {
+        run_resharing(
+            self.tracer,
+            self.i,
+            self.participants,
+            self.new_t,
+            self.old_key_info,
+            self.old_key_share,
+            self.enforce_reliable_broadcast,
+            self.eid,
+            rng,
+            party,
+        )
+        .await
+    }
+
+    /// Returns a state machine that can be used to carry out the key resharing protocol
+    ///
+    /// See [`round_based::state_machine`] for details on how that can be done.
+    #[cfg(feature = "state-machine")]
+    pub fn into_state_machine<R>(
+        self,
+        rng: &'a mut R,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<Option<IncompleteKeyShare<E>>, KeyResharingError>,
+        Msg = Msg<E, D>,
+    > + 'a
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(|party| self.start(rng, party))
+    }
+}
+
+/// Builder of protocol converting n-out-of-n key into t-out-of-n key
+///
+/// All `n` parties holding additive key shares re-share them with a polynomial of degree $t-1$.
+/// Every party keeps its index, and obtains a key share of t-out-of-n key with the same shared
+/// public key (and chain code, if HD wallets support is enabled).
+pub struct ThresholdConversionBuilder<'a, E: Curve, D = crate::default_choice::Digest> {
+    eid: ExecutionId<'a>,
+    key_share: &'a DirtyIncompleteKeyShare<E>,
+    t: u16,
+    tracer: Option<&'a mut dyn Tracer>,
+    enforce_reliable_broadcast: bool,
+    _digest: std::marker::PhantomData<D>,
+}
+
+impl<'a, E, D> ThresholdConversionBuilder<'a, E, D>
+where
+    E: Curve,
+    D: Digest + Clone + 'static,
+{
+    /// Constructs a builder
+    ///
+    /// Takes n-out-of-n key share to be converted and threshold `t` of resulting key
+    pub fn new(eid: ExecutionId<'a>, key_share: &'a impl AnyKeyShare<E>, t: u16) -> Self {
+        Self {
+            eid,
+            key_share: key_share.as_ref(),
+            t,
+            tracer: None,
+            enforce_reliable_broadcast: true,
+            _digest: std::marker::PhantomData,
+        }
+    }
+
+    /// Specifies another hash function to use
+    pub fn set_digest<D2>(self) -> ThresholdConversionBuilder<'a, E, D2>
+    where
+        D2: Digest + Clone + 'static,
+    {
+        ThresholdConversionBuilder {
+            eid: self.eid,
+            key_share: self.key_share,
+            t: self.t,
+            tracer: self.tracer,
+            enforce_reliable_broadcast: self.enforce_reliable_broadcast,
+            _digest: std::marker::PhantomData,
+        }
+    }
+
+    /// Sets a tracer that tracks progress of protocol execution
+    pub fn set_progress_tracer(mut self, tracer: &'a mut dyn Tracer) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    #[doc = include_str!("../docs/enforce_reliable_broadcast.md")]
+    pub fn enforce_reliable_broadcast(self, v: bool) -> Self {
+        Self {
+            enforce_reliable_broadcast: v,
+            ..self
+        }
+    }
+
+    /// Carries out the conversion protocol
+    ///
+    /// Returns key share of t-out-of-n key. Aux info doesn't depend on the key, so aux info
+    /// of the original key share can be used to complete the resulting one.
+    pub async fn start<R, M>(
+        self,
+        rng: &mut R,
+        party: M,
+    ) -> Result<IncompleteKeyShare<E>, KeyResharingError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = Msg<E, D>>,
+    {
+        if self.key_share.key_info.vss_setup.is_some() {
+            return Err(InvalidArgs::ThresholdKey.into());
+        }
+        let n = u16::try_from(self.key_share.key_info.public_shares.len())
+            .map_err(|_| InvalidArgs::TooManyParties)?;
+        // Every party deals its additive share and receives a share of the new key
+        let participants = (0..n)
+            .map(|j| Participant {
+                old_index: Some(j),
+                new_member: true,
+            })
+            .collect::<Vec<_>>();
+
+        run_resharing(
+            self.tracer,
+            self.key_share.i,
+            &participants,
+            self.t,
+            &self.key_share.key_info,
+            Some(self.key_share),
+            self.enforce_reliable_broadcast,
+            self.eid,
+            rng,
+            party,
+        )
+        .await?
+        .ok_or_else(|| Bug::NotANewMember.into())
+    }
+
+    /// Returns a state machine that can be used to carry out the conversion protocol
+    ///
+    /// See [`round_based::state_machine`] for details on how that can be done.
+    #[cfg(feature = "state-machine")]
+    pub fn into_state_machine<R>(
+        self,
+        rng: &'a mut R,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<IncompleteKeyShare<E>, KeyResharingError>,
+        Msg = Msg<E, D>,
+    > + 'a
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(|party| self.start(rng, party))
+    }
+}
+
+async fn run_resharing<E, D, R, M>(
+    mut tracer: Option<&mut dyn Tracer>,
+    i: PartyIndex,
+    participants: &[Participant],
+    new_t: u16,
+    old_key_info: &DirtyKeyInfo<E>,
+    old_key_share: Option<&DirtyIncompleteKeyShare<E>>,
+    reliable_broadcast_enforced: bool,
+    sid: ExecutionId<'_>,
+    rng: &mut R,
+    party: M,
+) -> Result<Option<IncompleteKeyShare<E>>, KeyResharingError>
+where
+    E: Curve,
+    D: Digest + Clone + 'static,
+    R: RngCore + CryptoRng,
+    M: Mpc<ProtocolMessage = Msg<E, D>>,
+{
+    tracer.protocol_begins();
+
+    tracer.stage("Validate arguments");
+    let n = u16::try_from(participants.len()).map_err(|_| InvalidArgs::TooManyParties)?;
+    let me = participants
+        .get(usize::from(i))
+        .ok_or(InvalidArgs::IndexOutOfBounds)?;
+
+    // Indexes of new committee members among participants
+    let new_committee = (0..n)
+        .filter(|j| participants[usize::from(*j)].new_member)
+        .collect::<Vec<_>>();
+    let new_n = u16::try_from(new_committee.len()).map_err(|_| InvalidArgs::TooManyParties)?;
+    if !(2 <= new_t && new_t <= new_n) {
+        return Err(InvalidArgs::InvalidThreshold.into());
+    }
+    let new_I = (1..=new_n)
+        .map(|j| NonZero::from_scalar(Scalar::from(j)).ok_or(Bug::ZeroIndex))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Indexes of dealers among participants
+    let dealers = (0..n)
+        .filter(|j| participants[usize::from(*j)].old_index.is_some())
+        .collect::<Vec<_>>();
+    let old_indexes = participants
+        .iter()
+        .flat_map(|p| p.old_index)
+        .collect::<Vec<_>>();
+    let old_n = old_key_info.public_shares.len();
+    if old_indexes.iter().any(|j| usize::from(*j) >= old_n)
+        || (1..old_indexes.len()).any(|k| old_indexes[..k].contains(&old_indexes[k]))
+    {
+        return Err(InvalidArgs::InvalidOldIndexes.into());
+    }
+    // Lagrange coefficients λ_j such that shared secret key is equal to Σ λ_j x_j
+    let lambdas = match &old_key_info.vss_setup {
+        Some(VssSetup {
+            min_signers,
+            I,
+            weights,
+            ranks,
+        }) => {
+            if weights.is_some() {
+                return Err(InvalidArgs::WeightedKey.into());
+            }
+            if ranks.is_some() {
+                return Err(InvalidArgs::HierarchicalKey.into());
+            }
+            if old_indexes.len() < usize::from(*min_signers) {
+                return Err(InvalidArgs::NotEnoughDealers.into());
+            }
+            let I = old_indexes
+                .iter()
+                .map(|j| I.get(usize::from(*j)).ok_or(InvalidArgs::InvalidOldIndexes))
+                .collect::<Result<Vec<_>, _>>()?;
+            (0..I.len())
+                .map(|j| lagrange_coefficient_at_zero(j, &I).ok_or(Bug::LagrangeCoef))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        None => {
+            if old_indexes.len() != old_n {
+                return Err(InvalidArgs::NotEnoughDealers.into());
+            }
+            vec![NonZero::<Scalar<E>>::one(); old_n]
+        }
+    };
+    // Expected F_j(0) = λ_j X_j for every dealer
+    let expected_consts = old_indexes
+        .iter()
+        .zip(&lambdas)
+        .map(|(j, lambda_j)| old_key_info.public_shares[usize::from(*j)] * lambda_j)
+        .collect::<Vec<_>>();
+
+    let my_secret = match (me.old_index, old_key_share) {
+        (Some(old_i), Some(share)) => {
+            if share.i != old_i
+                || share.key_info.shared_public_key != old_key_info.shared_public_key
+                || share.key_info.public_shares != old_key_info.public_shares
+            {
+                return Err(InvalidArgs::MismatchedKeyShare.into());
+            }
+            let k = dealers
+                .iter()
+                .position(|j| *j == i)
+                .ok_or(Bug::NotADealer)?;
+            let mut secret = *(lambdas[k] * &share.x);
+            Some(SecretScalar::new(&mut secret))
+        }
+        (Some(_), None) => return Err(InvalidArgs::MissingKeyShare.into()),
+        (None, _) => None,
+    };
+
+    tracer.stage("Setup networking");
+    let MpcParty { delivery, .. } = party.into_party();
+    let (incomings, mut outgoings) = delivery.split();
+
+    let mut rounds = RoundsRouter::<Msg<E, D>>::builder();
+    let round1 = rounds.add_round(RoundInput::<MsgRound1<D>>::broadcast(i, n));
+    let round1_sync = rounds.add_round(RoundInput::<MsgReliabilityCheck<D>>::broadcast(i, n));
+    let round2_broad = rounds.add_round(RoundInput::<MsgRound2Broad<E>>::broadcast(i, n));
+    let round2_uni = rounds.add_round(RoundInput::<MsgRound2Uni<E>>::p2p(i, n));
+    let mut rounds = rounds.listen(incomings);
+
+    // Round 1
+    tracer.round_begins();
+
+    tracer.stage("Sample polynomial f_i such that f_i(0) = λ_i x_i");
+    let f = my_secret.map(|secret| {
+        Polynomial::<SecretScalar<E>>::sample_with_const_term(rng, usize::from(new_t) - 1, secret)
+    });
+    let my_decommitment = MsgRound2Broad {
+        F: f.as_ref().map(|f| f * &Point::generator()),
+        decommit: {
+            let mut nonce = [0u8; 32];
+            rng.fill_bytes(&mut nonce);
+            nonce
+        },
+    };
+    let hash_commit = udigest::hash::<D>(&unambiguous::HashCom {
+        sid,
+        party_index: i,
+        decommitment: &my_decommitment,
+    });
+
+    tracer.send_msg();
+    let my_commitment = MsgRound1 {
+        commitment: hash_commit,
+    };
+    outgoings
+        .send(Outgoing::broadcast(Msg::Round1(my_commitment.clone())))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Round 2
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let commitments = rounds
+        .complete(round1)
+        .await
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    // Optional reliability check
+    if reliable_broadcast_enforced {
+        tracer.stage("Hash received msgs (reliability check)");
+        let h_i = udigest::hash_iter::<D>(
+            commitments
+                .iter_including_me(&my_commitment)
+                .map(|commitment| unambiguous::Echo { sid, commitment }),
+        );
+
+        tracer.send_msg();
+        outgoings
+            .send(Outgoing::broadcast(Msg::ReliabilityCheck(
+                MsgReliabilityCheck(h_i.clone()),
+            )))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.round_begins();
+
+        tracer.receive_msgs();
+        let hashes = rounds
+            .complete(round1_sync)
+            .await
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        tracer.stage("Assert other parties hashed messages (reliability check)");
+        let parties_have_different_hashes = hashes
+            .into_iter_indexed()
+            .filter(|(_j, _msg_id, h_j)| h_i != h_j.0)
+            .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        if !parties_have_different_hashes.is_empty() {
+            return Err(ResharingAborted::new(
+                ResharingAbortReason::Round1NotReliable,
+                parties_have_different_hashes,
+            )
+            .into());
+        }
+    }
+
+    tracer.send_msg();
+    outgoings
+        .feed(Outgoing::broadcast(Msg::Round2Broad(
+            my_decommitment.clone(),
+        )))
+        .await
+        .map_err(IoError::send_message)?;
+    let messages = utils::iter_peers(i, n).map(|j| {
+        let new_j = new_committee.iter().position(|m| *m == j);
+        let sigma = f
+            .as_ref()
+            .zip(new_j)
+            .map(|(f, new_j)| f.value(&new_I[new_j]));
+        Outgoing::p2p(j, Msg::Round2Uni(MsgRound2Uni { sigma }))
+    });
+    outgoings
+        .send_all(&mut futures::stream::iter(messages.map(Ok)))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Output
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let decommitments = rounds
+        .complete(round2_broad)
+        .await
+        .map_err(IoError::receive_message)?;
+    let sigmas_msg = rounds
+        .complete(round2_uni)
+        .await
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    let Some(new_i) = new_committee.iter().position(|j| *j == i) else {
+        // Local party is not a member of the new committee
+        tracer.protocol_ends();
+        return Ok(None);
+    };
+
+    tracer.stage("Validate decommitments");
+    let blame = utils::collect_blame(&commitments, &decommitments, |j, com, decom| {
+        let com_expected = udigest::hash::<D>(&unambiguous::HashCom {
+            sid,
+            party_index: j,
+            decommitment: decom,
+        });
+        com.commitment != com_expected
+    });
+    if !blame.is_empty() {
+        return Err(ResharingAborted::new(ResharingAbortReason::InvalidDecommitment, blame).into());
+    }
+
+    tracer.stage("Validate data size");
+    let is_dealer = |j: PartyIndex| participants[usize::from(j)].old_index.is_some();
+    let blame = utils::collect_blame(&decommitments, &sigmas_msg, |j, decom, sigma| {
+        is_dealer(j)
+            && decom
+                .F
+                .as_ref()
+                .zip(sigma.sigma)
+                .is_none_or(|(F, _)| F.degree() >= usize::from(new_t))
+    });
+    if !blame.is_empty() {
+        return Err(ResharingAborted::new(ResharingAbortReason::InvalidDataSize, blame).into());
+    }
+
+    tracer.stage("Validate F_j(0) = λ_j X_j");
+    let blame = decommitments
+        .iter_indexed()
+        .filter_map(|(j, msg_id, decom)| {
+            let k = dealers.iter().position(|d| *d == j)?;
+            let F = decom.F.as_ref()?;
+            (F.value::<_, Point<E>>(&Scalar::zero()) != expected_consts[k])
+                .then(|| AbortBlame::new(j, msg_id, msg_id))
+        })
+        .collect::<Vec<_>>();
+    if !blame.is_empty() {
+        return Err(ResharingAborted::new(ResharingAbortReason::InvalidConstantTerm, blame).into());
+    }
+
+    tracer.stage("Validate Feldmann VSS");
+    let blame = utils::collect_blame(&decommitments, &sigmas_msg, |j, decom, sigma| {
+        match (&decom.F, sigma.sigma) {
+            (Some(F), Some(sigma)) if is_dealer(j) => {
+                F.value::<_, Point<E>>(&new_I[new_i]) != Point::generator() * sigma
+            }
+            _ => false,
+        }
+    });
+    if !blame.is_empty() {
+        return Err(
+            ResharingAborted::new(ResharingAbortReason::FeldmanVerificationFailed, blame).into(),
+        );
+    }
+
+    tracer.stage("Compute new key share");
+    let my_sigma = MsgRound2Uni {
+        sigma: f.as_ref().map(|f| f.value(&new_I[new_i])),
+    };
+    let dealt = decommitments
+        .iter_including_me(&my_decommitment)
+        .zip(sigmas_msg.iter_including_me(&my_sigma))
+        .zip(participants)
+        .filter(|(_, p)| p.old_index.is_some())
+        .map(|((decom, sigma), _)| match (&decom.F, sigma.sigma) {
+            (Some(F), Some(sigma)) => Ok((F, sigma)),
+            _ => Err(Bug::MissingDealerData),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let F = dealt
+        .iter()
+        .map(|(F_j, _)| *F_j)
+        .sum::<Polynomial<Point<E>>>();
+    let mut x = dealt.iter().map(|(_, sigma_j)| sigma_j).sum::<Scalar<E>>();
+    let x = NonZero::from_secret_scalar(SecretScalar::new(&mut x)).ok_or(Bug::ZeroShare)?;
+
+    if F.value::<_, Point<E>>(&Scalar::zero()) != *old_key_info.shared_public_key {
+        return Err(Bug::MismatchedPublicKey.into());
+    }
+    let public_shares = new_I
+        .iter()
+        .map(|I_j| NonZero::from_point(F.value::<_, Point<E>>(I_j)).ok_or(Bug::ZeroShare))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let key_share = DirtyIncompleteKeyShare {
+        i: u16::try_from(new_i).map_err(|_| InvalidArgs::TooManyParties)?,
+        key_info: DirtyKeyInfo {
+            public_shares,
+            vss_setup: Some(VssSetup {
+                min_signers: new_t,
+                I: new_I,
+                weights: None,
+                ranks: None,
+            }),
+            ..old_key_info.clone()
+        },
+        x,
+        extra_x: Vec::new(),
+    }
+    .validate()
+    .map_err(|err| Bug::InvalidShareGenerated(err.into_error()))?;
+
+    tracer.protocol_ends();
+    Ok(Some(key_share))
+}
+
+/// Error of key resharing protocol
+#[derive(Debug, Error)]
+#[error("key resharing protocol failed to complete")]
+pub struct KeyResharingError(#[source] Reason);
+
+impl KeyResharingError {
+    /// If protocol was aborted by malicious party, returns information about
+    /// the abort: its reason and parties to blame
+    pub fn aborted(&self) -> Option<&ResharingAborted> {
+        match &self.0 {
+            Reason::Aborted(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+crate::errors::impl_from! {
+    impl From for KeyResharingError {
+        err: InvalidArgs => KeyResharingError(Reason::InvalidArgs(err)),
+        err: ResharingAborted => KeyResharingError(Reason::Aborted(err)),
+        err: IoError => KeyResharingError(Reason::IoError(err)),
+        err: Bug => KeyResharingError(Reason::Bug(err)),
+    }
+}
+
+#[derive(Debug, Error)]
+enum Reason {
+    #[error("invalid arguments")]
+    InvalidArgs(#[source] InvalidArgs),
+    /// Protocol was maliciously aborted by another party
+    #[error("protocol was aborted by malicious party")]
+    Aborted(#[source] ResharingAborted),
+    #[error("i/o error")]
+    IoError(#[source] IoError),
+    #[error("bug occurred")]
+    Bug(#[source] Bug),
+}
+
+#[derive(Debug, Error)]
+enum InvalidArgs {
+    #[error("too many parties")]
+    TooManyParties,
+    #[error("party index `i` is out of bounds")]
+    IndexOutOfBounds,
+    #[error("new threshold must satisfy 2 <= t <= n")]
+    InvalidThreshold,
+    #[error("old indexes of dealers are out of bounds or not unique")]
+    InvalidOldIndexes,
+    #[error("not enough dealers to reshare the key")]
+    NotEnoughDealers,
+    #[error("dealer didn't provide its key share")]
+    MissingKeyShare,
+    #[error("key share doesn't match provided key info or old index")]
+    MismatchedKeyShare,
+    #[error("weighted keys can't be reshared")]
+    WeightedKey,
+    #[error("hierarchical keys can't be reshared")]
+    HierarchicalKey,
+    #[error("key is already threshold")]
+    ThresholdKey,
+}
+
+/// Unexpected error in operation not caused by other parties
+#[derive(Debug, Error)]
+enum Bug {
+    #[error("key share index is zero")]
+    ZeroIndex,
+    #[error("lagrange coefficient is undefined")]
+    LagrangeCoef,
+    #[error("local party is a dealer but is not in the list of dealers")]
+    NotADealer,
+    #[error("local party is a member of the new committee but didn't obtain a key share")]
+    NotANewMember,
+    #[error("dealer data is missing although we checked that it's present")]
+    MissingDealerData,
+    #[error("resulting public key doesn't match the old one although we checked dealers' data")]
+    MismatchedPublicKey,
+    #[error("key share is zero - probability of that is negligible")]
+    ZeroShare,
+    #[error("resulting key share is not valid")]
+    InvalidShareGenerated(#[source] InvalidIncompleteKeyShare),
+}
+
+/// Error indicating that resharing was aborted by malicious party
+pub type ResharingAborted = Aborted<ResharingAbortReason>;
+
+/// Reason for resharing abort: which exact check has failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum ResharingAbortReason {
+    /// Decommitment doesn't match commitment
+    #[error("decommitment doesn't match commitment")]
+    InvalidDecommitment,
+    /// Dealer didn't send its polynomial or share, or polynomial degree is too high
+    #[error("dealer data is missing or doesn't match new threshold")]
+    InvalidDataSize,
+    /// Dealer's polynomial doesn't commit to its (Lagrange-weighted) secret share
+    #[error("F_j(0) != λ_j X_j")]
+    InvalidConstantTerm,
+    /// Secret share sent by dealer is not consistent with its polynomial
+    #[error("dealer secret share is not consistent")]
+    FeldmanVerificationFailed,
+    /// Party claims it received different messages in round 1 than other parties
+    #[error("round 1 was not reliable")]
+    Round1NotReliable,
+}
@@ -250,7 +250,12 @@ impl<E: Curve, L: SecurityLevel> DirtyKeyShare<E, L> {
         core: &DirtyIncompleteKeyShare<E>,
         aux: &DirtyAuxInfo<L>,
     ) -> Result<(), InvalidKeyShare> {
-        if core.public_shares.len() != aux.parties.len() {
+        // Weighted key has more public shares than parties, aux data is per party
+        let n = match core.vss_setup.as_ref().and_then(|s| s.weights.as_ref()) {
+            Some(weights) => weights.len(),
+            None => core.public_shares.len(),
+        };
+        if n != aux.parties.len() {
             return Err(InvalidKeyShareReason::AuxLen.into());
         }
 
@@ -306,24 +311,15 @@ impl<E: Curve, L: SecurityLevel> ops::Deref for DirtyKeyShare<E, L> {
 pub trait AnyKeyShare<E: Curve>: AsRef<IncompleteKeyShare<E>> {
     /// Returns amount of key co-holders
     fn n(&self) -> u16 {
-        #[allow(clippy::expect_used)]
-        self.as_ref()
-            .public_shares
-            .len()
-            .try_into()
-            .expect("valid key share is guaranteed to have amount of signers fitting into u16")
+        self.as_ref().n()
     }
 
     /// Returns threshold
     ///
     /// Threshold is an amount of signers required to cooperate in order to sign a message
-    /// and/or generate presignature
+    /// and/or generate presignature. If key is weighted, it's minimal total weight of signers.
     fn min_signers(&self) -> u16 {
-        self.as_ref()
-            .vss_setup
-            .as_ref()
-            .map(|s| s.min_signers)
-            .unwrap_or_else(|| self.n())
+        self.as_ref().min_signers()
     }
 
     /// Returns public key shared by signers
@@ -14,9 +14,12 @@
 //!
 //! This crate implements:
 //! * Threshold (i.e., t-out-of-n) and non-threshold (i.e., n-out-of-n) key generation
-//! * (3+1)-round general threshold and non-threshold signing
+//! * (3+1)-round general threshold and non-threshold signing with identifiable abort
+//! * (5+1)-round general threshold and non-threshold signing with cheap identification of faulty parties
 //! * Auxiliary info generation protocol
-//! * Key refresh for non-threshold keys
+//! * Key refresh for non-threshold and threshold keys
+//! * Key resharing that changes the set of parties and the threshold
+//! * Recovery of a lost key share with help of other parties
 //! * HD-wallets support based on [slip10] standard (compatible with [bip32]) \
 //!   Requires `hd-wallets` feature
 //!
@@ -26,11 +29,6 @@
 //! * [Secret key reconstruction](crate::key_share::reconstruct_secret_key) (exporting key from TSS)
 //! * [Trusted dealer](crate::trusted_dealer) (importing key into TSS)
 //!
-//! This crate **does not** (currently) support:
-//! * Key refresh for threshold keys (i.e., t-out-of-n)
-//! * Identifiable abort
-//! * The (5+1)-round signing protocol
-//!
 //! Our implementation has been audited by Kudelski. Report can be found [here][report].
 //!
 //! > About notion of threshold and non-threshold keys: originally, CGGMP21 paper does not have support of
@@ -184,9 +182,10 @@
 //!
 //! ### Signing
 //! Once signers have a set of "completed" key shares, they can sign or generate presignatures.
-//! In either case, exactly the threshold number (i.e., t) of signers must take part in the protocol.
+//! In either case, at least the threshold number (i.e., t) of signers must take part in the protocol.
 //! As in the DKG protocol, each signer needs to be assigned a unique index, now in the range from 0
-//! to t-1. But the signers also need to know which index each signer occupied at the time of keygen.
+//! to |S|-1, where |S| is the amount of signers. But the signers also need to know which index each
+//! signer occupied at the time of keygen.
 //!
 //! In the example below, we do a full signing:
 //! ```rust,no_run
@@ -220,13 +219,31 @@
 //!
 //! Alternatively, you can generate a presignature and later use it to sign:
 //! 1. Use [`SigningBuilder::generate_presignature`] to run the presignature generation protocol
-//! 2. Later, when a signing request is received, each signer issues a partial signature using
-//!    [`Presignature::issue_partial_signature`]
-//! 3. A threshold number of partial signatures can be combined using [`PartialSignature::combine`] to
-//!    obtain a full signature
+//! 2. Move the presignature into a [`PresignatureStore`](signing::store::PresignatureStore). Presignature
+//!    can't be used to sign by itself: the store is the only way to issue a partial signature.
+//! 3. Later, when a signing request is received, each signer issues a partial signature using
+//!    [`PresignatureStore::consume`](signing::store::PresignatureStore::consume). It checks that the
+//!    presignature was generated with the same key share and aux info.
+//! 4. Partial signatures of all signers that generated the presignature can be combined using
+//!    [`PartialSignature::combine`] to obtain a full signature. If some of signers may be
+//!    dishonest, use [`PartialSignature::combine_verified`] instead: it checks every partial
+//!    signature against [public data](signing::Presignature::public_data) of the presignature
+//!    and reports which ones are invalid.
 //!
 //! **Never reuse presignatures!** If you use the same presignature to sign two different messages,
-//! the private key may be leaked.
+//! the private key may be leaked. The store enforces that: it marks presignature consumed before
+//! issuing a partial signature, and refuses to issue a second one. Presignatures can be kept in memory,
+//! or on disk with `presignature-file-store` feature.
+//!
+//! If you need many presignatures, [`SigningBuilder::generate_presignatures`] generates a batch of them
+//! in a single protocol run, so round-trip latency and per-message overhead are paid once.
+//! Similarly, [`SigningBuilder::sign_batch`] signs many messages at once.
+//!
+//! ### Choosing signing protocol
+//! By default, signing is carried out using (3+1)-round protocol. Calling [`set_five_rounds_protocol`](SigningBuilder::set_five_rounds_protocol)
+//! switches to (5+1)-round protocol: it takes two more rounds to generate a presignature, but if
+//! protocol is aborted, identification of faulty parties is much cheaper (in most cases, it doesn't
+//! require any ZK proofs). All signers must use the same protocol.
 //!
 //! ## Sync API
 //! Every protocol is defined as async function. If you need to run a protocol in non-async environment,
@@ -258,7 +275,13 @@
 //!
 //! Such use-cases contradict to nature of MPC so we don't include those primitives by default.
 //! However, you may opt for them by enabling `spof` feature, then you can use [`trusted_dealer`]
-//! for key import and [`key_share::reconstruct_secret_key`] for key export.
+//! for key import and [`key_share::reconstruct_secret_key`] for key export. To export the key
+//! without reconstructing it on any of the signers, use [`key_export`] that encrypts the key
+//! to a designated recipient.
+//!
+//! If owner of the key can take part in the protocol, prefer [`key_import`] which is available
+//! without `spof` feature: the owner deals key shares itself, so no other process ever learns
+//! more than one share.
 //!
 //! ## Differences between the implementation and CGGMP21
 //! [CGGMP21] only defines a non-threshold protocol. To support general thresholds,
@@ -309,7 +332,7 @@ pub use {
 };
 
 #[doc(inline)]
-pub use cggmp21_keygen::{keygen, progress, ExecutionId};
+pub use cggmp21_keygen::{keygen, progress, timeout, AbortBlame, Aborted, ExecutionId};
 
 use generic_ec::{coords::HasAffineX, Curve, Point};
 use key_share::AnyKeyShare;
@@ -318,14 +341,22 @@ use security_level::SecurityLevel;
 use signing::SigningBuilder;
 
 mod errors;
+pub mod full_keygen;
+pub mod key_import;
 pub mod key_refresh;
+pub mod key_resharing;
 pub mod key_share;
 pub mod security_level;
+pub mod share_recovery;
 pub mod signing;
 pub mod supported_curves;
 mod utils;
 mod zk;
 
+#[cfg(feature = "hd-wallet")]
+pub mod chain_code_gen;
+#[cfg(feature = "spof")]
+pub mod key_export;
 #[cfg(feature = "spof")]
 pub mod trusted_dealer;
 
@@ -339,12 +370,14 @@ mod default_choice {
 pub mod keygen {
     #[doc(inline)]
     pub use cggmp21_keygen::{
-        msg, GenericKeygenBuilder, KeygenBuilder, KeygenError, NonThreshold,
-        ThresholdKeygenBuilder, WithThreshold,
+        msg, GenericKeygenBuilder, KeygenAbortReason, KeygenAborted, KeygenBuilder, KeygenError,
+        NonThreshold, RobustKeygenOutput, ThresholdKeygenBuilder, WithThreshold,
     };
 
     pub use msg::non_threshold::Msg as NonThresholdMsg;
+    pub use msg::non_threshold_batch::Msg as NonThresholdBatchMsg;
     pub use msg::threshold::Msg as ThresholdMsg;
+    pub use msg::threshold_batch::Msg as ThresholdBatchMsg;
 }
 
 pub use self::{
@@ -379,10 +412,31 @@ where
     key_refresh::GenericKeyRefreshBuilder::new_aux_gen(eid, i, n, pregenerated)
 }
 
+/// Protocol for generating a key along with aux info
+///
+/// Carries out [`keygen`] and [`aux_info_gen`] at once, and outputs complete [`KeyShare`].
+/// Messages of both protocols are sent over the same channel, so it takes as many rounds as
+/// the longest of them. Call [`set_threshold`](full_keygen::GenericFullKeygenBuilder::set_threshold)
+/// to make it into a threshold DKG.
+///
+/// PregeneratedPrimes can be obtained with [`key_refresh::PregeneratedPrimes::generate`]
+pub fn full_keygen<E, L>(
+    eid: ExecutionId,
+    i: u16,
+    n: u16,
+    pregenerated: key_refresh::PregeneratedPrimes<L>,
+) -> full_keygen::FullKeygenBuilder<E, L>
+where
+    E: Curve,
+    L: SecurityLevel,
+{
+    full_keygen::FullKeygenBuilder::new(eid, i, n, pregenerated)
+}
+
 /// Protocol for performing key refresh. Can be used to perform initial refresh
 /// with aux info generation, or for a refresh of a complete key share.
 ///
-/// Doesn't work with general-threshold key shares at this point.
+/// Doesn't work with threshold key shares, use [`threshold_key_refresh`] for them.
 ///
 /// PregeneratedPrimes can be obtained with [`key_refresh::PregeneratedPrimes::generate`]
 pub fn key_refresh<'a, E, L>(
@@ -397,6 +451,158 @@ where
     key_refresh::KeyRefreshBuilder::new(eid, key_share, pregenerated)
 }
 
+/// Protocol for performing threshold key refresh
+///
+/// Re-randomizes VSS shares of threshold key share while keeping shared public key
+/// the same, and regenerates aux info. Can be used to perform initial refresh with
+/// aux info generation, or for a refresh of a complete key share.
+///
+/// All `n` parties holding key shares must take part in the protocol.
+///
+/// PregeneratedPrimes can be obtained with [`key_refresh::PregeneratedPrimes::generate`]
+pub fn threshold_key_refresh<'a, E, L>(
+    eid: ExecutionId<'a>,
+    key_share: &'a impl AnyKeyShare<E>,
+    pregenerated: key_refresh::PregeneratedPrimes<L>,
+) -> key_refresh::ThresholdKeyRefreshBuilder<'a, E, L>
+where
+    E: Curve,
+    L: SecurityLevel,
+{
+    key_refresh::ThresholdKeyRefreshBuilder::new(eid, key_share, pregenerated)
+}
+
+/// Protocol for resharing the key to a new set of parties with a new threshold
+///
+/// Parties of the old committee deal fresh shares of the key to the members of the new committee,
+/// shared public key (and chain code, if HD wallets support is enabled) stays the same.
+/// `participants` lists all parties of the protocol along with their roles, local party has
+/// index `i` in this list. Dealers must provide their key share via
+/// [`set_old_key_share`](key_resharing::KeyResharingBuilder::set_old_key_share).
+///
+/// Members of the new committee obtain [`IncompleteKeyShare`], parties that leave the committee
+/// obtain nothing. Use [`aux_info_gen`] to complete the new key shares.
+pub fn key_resharing<'a, E>(
+    eid: ExecutionId<'a>,
+    i: PartyIndex,
+    participants: &'a [key_resharing::Participant],
+    new_t: u16,
+    old_key_info: &'a key_share::DirtyKeyInfo<E>,
+) -> key_resharing::KeyResharingBuilder<'a, E>
+where
+    E: Curve,
+{
+    key_resharing::KeyResharingBuilder::new(eid, i, participants, new_t, old_key_info)
+}
+
+/// Protocol for converting n-out-of-n key into t-out-of-n key
+///
+/// Keys generated by non-threshold [`keygen`] are shared additively, so all `n` parties are
+/// required to sign. This protocol re-shares the key with threshold `t`: every party keeps its
+/// index, shared public key (and chain code, if HD wallets support is enabled) stays the same.
+/// All `n` parties must take part in the protocol.
+///
+/// Parties obtain [`IncompleteKeyShare`]. Aux info doesn't depend on the key, so aux info of
+/// the original [`KeyShare`] can be used to complete the resulting key share.
+pub fn convert_to_threshold<'a, E>(
+    eid: ExecutionId<'a>,
+    key_share: &'a impl AnyKeyShare<E>,
+    t: u16,
+) -> key_resharing::ThresholdConversionBuilder<'a, E>
+where
+    E: Curve,
+{
+    key_resharing::ThresholdConversionBuilder::new(eid, key_share, t)
+}
+
+/// Protocol for adding a chain code to an existing key
+///
+/// Keys generated without HD wallets support don't have a chain code, so child keys can't be
+/// derived from them. In this protocol, all `n` key co-holders jointly sample a chain code,
+/// shared public key and key shares stay the same.
+///
+/// Parties obtain [`IncompleteKeyShare`] with the chain code set. Aux info doesn't depend on
+/// the chain code, so aux info of the original [`KeyShare`] can be used to complete the
+/// resulting key share.
+#[cfg(feature = "hd-wallet")]
+pub fn chain_code_gen<'a, E>(
+    eid: ExecutionId<'a>,
+    key_share: &'a impl AnyKeyShare<E>,
+) -> chain_code_gen::ChainCodeGenBuilder<'a, E>
+where
+    E: Curve,
+{
+    chain_code_gen::ChainCodeGenBuilder::new(eid, key_share)
+}
+
+/// Protocol for importing an existing key into a committee of `n` parties
+///
+/// Owner of the key shares it among members of the committee with threshold `t`, so that
+/// nobody but the owner ever learns more than one key share. Members of the committee have
+/// indexes `0..n` in the protocol, and the key owner has index `n`. Key owner must provide
+/// the key via [`set_secret_key`](key_import::KeyImportBuilder::set_secret_key).
+///
+/// Members of the committee obtain [`IncompleteKeyShare`], the key owner obtains nothing.
+/// Use [`aux_info_gen`] to complete the key shares.
+pub fn key_import<'a, E>(
+    eid: ExecutionId<'a>,
+    i: PartyIndex,
+    n: u16,
+    t: u16,
+) -> key_import::KeyImportBuilder<'a, E>
+where
+    E: Curve,
+{
+    key_import::KeyImportBuilder::new(eid, i, n, t)
+}
+
+/// Protocol for exporting the key encrypted to a designated recipient
+///
+/// At least `min_signers` parties holding key shares (exporters) encrypt their Lagrange-weighted
+/// secret shares to `recipient_key`, so that only the recipient learns the secret key. Exporters
+/// have indexes `0..exporters.len()` in the protocol, `exporters[k]` is index of key share held
+/// by party `k`, and the recipient has index `exporters.len()`. Exporters must provide their
+/// key share via [`set_key_share`](key_export::KeyExportBuilder::set_key_share), recipient
+/// must provide its secret key via
+/// [`set_decryption_key`](key_export::KeyExportBuilder::set_decryption_key).
+#[cfg(feature = "spof")]
+pub fn key_export<'a, E>(
+    eid: ExecutionId<'a>,
+    i: PartyIndex,
+    exporters: &'a [u16],
+    key_info: &'a key_share::DirtyKeyInfo<E>,
+    recipient_key: generic_ec::NonZero<Point<E>>,
+) -> key_export::KeyExportBuilder<'a, E>
+where
+    E: Curve,
+{
+    key_export::KeyExportBuilder::new(eid, i, exporters, key_info, recipient_key)
+}
+
+/// Protocol for recovering a lost key share
+///
+/// At least `min_signers` parties holding key shares (helpers) jointly recover key share
+/// with index `lost_index` for the party that lost it, without revealing the secret key
+/// or their own secret shares. Helpers have indexes `0..helpers.len()` in the protocol,
+/// `helpers[k]` is index of key share held by party `k`, and recovering party has index
+/// `helpers.len()`. Helpers must provide their key share via
+/// [`set_key_share`](share_recovery::ShareRecoveryBuilder::set_key_share).
+///
+/// Only works with threshold keys. Recovering party obtains [`IncompleteKeyShare`], and needs
+/// to run [`aux_info_gen`] to obtain a complete key share.
+pub fn share_recovery<'a, E>(
+    eid: ExecutionId<'a>,
+    i: PartyIndex,
+    helpers: &'a [u16],
+    lost_index: u16,
+    key_info: &'a key_share::DirtyKeyInfo<E>,
+) -> share_recovery::ShareRecoveryBuilder<'a, E>
+where
+    E: Curve,
+{
+    share_recovery::ShareRecoveryBuilder::new(eid, i, helpers, lost_index, key_info)
+}
+
 /// Protocol for generating a signature or presignature
 pub fn signing<'r, E, L>(
     eid: ExecutionId<'r>,
@@ -445,10 +651,20 @@ mod tests {
 
         crate::key_refresh::msg::aux_only::Msg<D, L>,
         crate::key_refresh::msg::non_threshold::Msg<E, D, L>,
+        crate::key_refresh::msg::threshold::Msg<E, D, L>,
+        crate::key_resharing::Msg<E, D>,
+        crate::share_recovery::Msg<E, D>,
 
         crate::signing::msg::Msg<E, D>,
-        crate::signing::Presignature<E>,
+        crate::signing::msg::MsgRound3Blame<E>,
+        crate::signing::msg::MsgRound4Blame<E>,
+        crate::signing::msg::five_rounds::Msg<E, D>,
+        crate::signing::msg::five_rounds::MsgRevealDelta,
+        crate::signing::msg::five_rounds::MsgChiBlame<E>,
+        crate::signing::msg::batch::Msg<E, D>,
+        crate::signing::store::StoredPresignature<E>,
         crate::signing::PartialSignature<E>,
+        crate::signing::PresignaturePublicData<E>,
         crate::signing::Signature<E>,
     }
 }
@@ -0,0 +1,630 @@
+//! Lost share recovery protocol
+//!
+//! Allows a party that lost its key share to recover it with help of other parties. At least
+//! `min_signers` parties holding valid key shares (helpers) jointly reconstruct secret share
+//! $x_j = F(I_j)$ of the recovering party $j$. Each helper splits its Lagrange-weighted secret
+//! share into random additive pieces and sends them to other helpers, so the recovering party
+//! learns only sums of the pieces that add up to $x_j$. Neither the recovering party nor any of
+//! the helpers learns the secret key or secret shares of other parties.
+//!
+//! Recovery only outputs core key share ([`IncompleteKeyShare`]). Recovering party needs to carry
+//! out [aux info generation](crate::aux_info_gen) to obtain a complete [`KeyShare`](crate::KeyShare).
+
+use digest::Digest;
+use futures::SinkExt;
+use generic_ec::{Curve, NonZero, Point, Scalar, SecretScalar};
+use generic_ec_zkp::polynomial::lagrange_coefficient;
+use rand_core::{CryptoRng, RngCore};
+use round_based::{
+    rounds_router::{simple_store::RoundInput, RoundsRouter},
+    Delivery, Mpc, MpcParty, Outgoing, PartyIndex, ProtocolMessage,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    errors::IoError,
+    key_share::{
+        AnyKeyShare, DirtyIncompleteKeyShare, DirtyKeyInfo, IncompleteKeyShare,
+        InvalidIncompleteKeyShare, Validate, VssSetup,
+    },
+    progress::Tracer,
+    utils::{self, AbortBlame, Aborted},
+    ExecutionId,
+};
+
+macro_rules! prefixed {
+    ($name:tt) => {
+        concat!("dfns.cggmp21.share_recovery.", $name)
+    };
+}
+
+/// Message of share recovery protocol
+#[derive(ProtocolMessage, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum Msg<E: Curve, D: Digest> {
+    /// Round 1 message broadcasted by helpers
+    Round1Broad(MsgRound1Broad<E>),
+    /// Round 1 message sent by each helper to other helpers
+    Round1Uni(MsgRound1Uni<E>),
+    /// Round 2 message sent by each helper to recovering party
+    Round2(MsgRound2<E>),
+    /// Reliability check message (optional additional round)
+    ReliabilityCheck(MsgReliabilityCheck<D>),
+}
+
+/// Message from round 1 broadcasted by helpers
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[serde(bound = "")]
+#[udigest(bound = "")]
+#[udigest(tag = prefixed!("round1"))]
+pub struct MsgRound1Broad<E: Curve> {
+    /// $\Delta_{i,k} = \delta_{i,k} \cdot G$ for every helper $k$
+    pub Deltas: Vec<Point<E>>,
+}
+/// Message from round 1 sent by each helper to other helpers
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound1Uni<E: Curve> {
+    /// $\delta_{i,k}$, such that $\sum_k \delta_{i,k} = \lambda_i \cdot x_i$
+    pub delta: Scalar<E>,
+}
+/// Message from round 2 sent by each helper to recovering party
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound2<E: Curve> {
+    /// $\sigma_k = \sum_i \delta_{i,k}$
+    pub sigma: Scalar<E>,
+}
+/// Message parties exchange to ensure reliability of broadcast channel
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgReliabilityCheck<D: Digest>(pub digest::Output<D>);
+
+mod unambiguous {
+    use generic_ec::Curve;
+
+    use crate::ExecutionId;
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("echo_round"))]
+    #[udigest(bound = "")]
+    pub struct Echo<'a, E: Curve> {
+        pub sid: ExecutionId<'a>,
+        pub msg: &'a super::MsgRound1Broad<E>,
+    }
+}
+
+/// Share recovery protocol builder
+pub struct ShareRecoveryBuilder<'a, E: Curve, D = crate::default_choice::Digest> {
+    eid: ExecutionId<'a>,
+    i: PartyIndex,
+    helpers: &'a [u16],
+    lost_index: u16,
+    key_info: &'a DirtyKeyInfo<E>,
+    key_share: Option<&'a DirtyIncompleteKeyShare<E>>,
+    tracer: Option<&'a mut dyn Tracer>,
+    enforce_reliable_broadcast: bool,
+    _digest: std::marker::PhantomData<D>,
+}
+
+impl<'a, E, D> ShareRecoveryBuilder<'a, E, D>
+where
+    E: Curve,
+    D: Digest + Clone + 'static,
+{
+    /// Constructs a builder
+    ///
+    /// Protocol is carried out by `helpers.len() + 1` parties: helpers have indexes
+    /// `0..helpers.len()`, and recovering party has index `helpers.len()`.
+    ///
+    /// * `i` is index of local party in the protocol
+    /// * `helpers` are indexes of helpers' key shares, `helpers[k]` is index of key share
+    ///   held by party `k`
+    /// * `lost_index` is index of key share being recovered
+    /// * `key_info` is public information about the key
+    ///
+    /// Helpers must provide their key share via [`set_key_share`](Self::set_key_share).
+    pub fn new(
+        eid: ExecutionId<'a>,
+        i: PartyIndex,
+        helpers: &'a [u16],
+        lost_index: u16,
+        key_info: &'a DirtyKeyInfo<E>,
+    ) -> Self {
+        Self {
+            eid,
+            i,
+            helpers,
+            lost_index,
+            key_info,
+            key_share: None,
+            tracer: None,
+            enforce_reliable_broadcast: true,
+            _digest: std::marker::PhantomData,
+        }
+    }
+
+    /// Sets key share held by local party
+    ///
+    /// Must be set if local party is a helper
+    pub fn set_key_share(self, key_share: &'a impl AnyKeyShare<E>) -> Self {
+        Self {
+            key_share: Some(key_share.as_ref()),
+            ..self
+        }
+    }
+
+    /// Specifies another hash function to use
+    pub fn set_digest<D2>(self) -> ShareRecoveryBuilder<'a, E, D2>
+    where
+        D2: Digest + Clone + 'static,
+    {
+        ShareRecoveryBuilder {
+            eid: self.eid,
+            i: self.i,
+            helpers: self.helpers,
+            lost_index: self.lost_index,
+            key_info: self.key_info,
+            key_share: self.key_share,
+            tracer: self.tracer,
+            enforce_reliable_broadcast: self.enforce_reliable_broadcast,
+            _digest: std::marker::PhantomData,
+        }
+    }
+
+    /// Sets a tracer that tracks progress of protocol execution
+    pub fn set_progress_tracer(mut self, tracer: &'a mut dyn Tracer) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    #[doc = include_str!("../docs/enforce_reliable_broadcast.md")]
+    pub fn enforce_reliable_broadcast(self, v: bool) -> Self {
+        Self {
+            enforce_reliable_broadcast: v,
+            ..self
+        }
+    }
+
+    /// Carries out the share recovery protocol
+    ///
+    /// Returns `Some(key_share)` if local party is the recovering party, and `None` if
+    /// local party is a helper.
+    pub async fn start<R, M>(
+        self,
+        rng: &mut R,
+        party: M,
+    ) -> Result<Option<IncompleteKeyShare<E>>, ShareRecoveryError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = Msg<E, D>>,
+    {
+        run_recovery(
+            self.tracer,
+            self.i,
+            self.helpers,
+            self.lost_index,
+            self.key_info,
+            self.key_share,
+            self.enforce_reliable_broadcast,
+            self.eid,
+            rng,
+            party,
+        )
+        .await
+    }
+
+    /// Returns a state machine that can be used to carry out the share recovery protocol
+    ///
+    /// See [`round_based::state_machine`] for details on how that can be done.
+    #[cfg(feature = "state-machine")]
+    pub fn into_state_machine<R>(
+        self,
+        rng: &'a mut R,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<Option<IncompleteKeyShare<E>>, ShareRecoveryError>,
+        Msg = Msg<E, D>,
+    > + 'a
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(|party| self.start(rng, party))
+    }
+}
+
+async fn run_recovery<E, D, R, M>(
+    mut tracer: Option<&mut dyn Tracer>,
+    i: PartyIndex,
+    helpers: &[u16],
+    lost_index: u16,
+    key_info: &DirtyKeyInfo<E>,
+    key_share: Option<&DirtyIncompleteKeyShare<E>>,
+    reliable_broadcast_enforced: bool,
+    sid: ExecutionId<'_>,
+    rng: &mut R,
+    party: M,
+) -> Result<Option<IncompleteKeyShare<E>>, ShareRecoveryError>
+where
+    E: Curve,
+    D: Digest + Clone + 'static,
+    R: RngCore + CryptoRng,
+    M: Mpc<ProtocolMessage = Msg<E, D>>,
+{
+    tracer.protocol_begins();
+
+    tracer.stage("Validate arguments");
+    let m = u16::try_from(helpers.len()).map_err(|_| InvalidArgs::TooManyParties)?;
+    let n = m.checked_add(1).ok_or(InvalidArgs::TooManyParties)?;
+    if i >= n {
+        return Err(InvalidArgs::IndexOutOfBounds.into());
+    }
+    let is_helper = i < m;
+
+    let VssSetup {
+        min_signers,
+        I,
+        weights,
+        ranks,
+    } = key_info
+        .vss_setup
+        .as_ref()
+        .ok_or(InvalidArgs::NonThresholdKey)?;
+    if weights.is_some() {
+        return Err(InvalidArgs::WeightedKey.into());
+    }
+    if ranks.is_some() {
+        return Err(InvalidArgs::HierarchicalKey.into());
+    }
+    if helpers.len() < usize::from(*min_signers) {
+        return Err(InvalidArgs::NotEnoughHelpers.into());
+    }
+    let key_n = key_info.public_shares.len();
+    if usize::from(lost_index) >= key_n
+        || helpers
+            .iter()
+            .any(|h| usize::from(*h) >= key_n || *h == lost_index)
+        || (1..helpers.len()).any(|k| helpers[..k].contains(&helpers[k]))
+    {
+        return Err(InvalidArgs::InvalidIndexes.into());
+    }
+    let I_j = I
+        .get(usize::from(lost_index))
+        .ok_or(InvalidArgs::InvalidIndexes)?;
+    let I_helpers = helpers
+        .iter()
+        .map(|h| I.get(usize::from(*h)).ok_or(InvalidArgs::InvalidIndexes))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Lagrange coefficients λ_k such that x_j = Σ λ_k x_k
+    let lambdas = (0..I_helpers.len())
+        .map(|k| lagrange_coefficient(**I_j, k, &I_helpers).ok_or(Bug::LagrangeCoef))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Expected Σ_k Δ_{h,k} = λ_h X_h for every helper
+    let expected_sums = helpers
+        .iter()
+        .zip(&lambdas)
+        .map(|(h, lambda_h)| key_info.public_shares[usize::from(*h)] * lambda_h)
+        .collect::<Vec<_>>();
+
+    let my_secret = if is_helper {
+        let share = key_share.ok_or(InvalidArgs::MissingKeyShare)?;
+        if share.i != helpers[usize::from(i)]
+            || share.key_info.shared_public_key != key_info.shared_public_key
+            || share.key_info.public_shares != key_info.public_shares
+        {
+            return Err(InvalidArgs::MismatchedKeyShare.into());
+        }
+        let mut secret = *(lambdas[usize::from(i)] * &share.x);
+        Some(SecretScalar::new(&mut secret))
+    } else {
+        None
+    };
+
+    tracer.stage("Setup networking");
+    let MpcParty { delivery, .. } = party.into_party();
+    let (incomings, mut outgoings) = delivery.split();
+
+    // Round 1 messages are sent by helpers only, round 2 messages are sent to recovering party only
+    let mut rounds = RoundsRouter::<Msg<E, D>>::builder();
+    let round1_broad = rounds.add_round(RoundInput::<MsgRound1Broad<E>>::broadcast(
+        i,
+        if is_helper { m } else { n },
+    ));
+    let round1_uni = is_helper.then(|| rounds.add_round(RoundInput::<MsgRound1Uni<E>>::p2p(i, m)));
+    let round1_sync = rounds.add_round(RoundInput::<MsgReliabilityCheck<D>>::broadcast(i, n));
+    let round2 = (!is_helper).then(|| rounds.add_round(RoundInput::<MsgRound2<E>>::p2p(i, n)));
+    let mut rounds = rounds.listen(incomings);
+
+    // Round 1
+    tracer.round_begins();
+
+    let mut my_deltas = None;
+    if let Some(secret) = &my_secret {
+        tracer.stage("Split λ_i x_i into additive shares δ_{i,k}");
+        let mut deltas = (0..m)
+            .map(|k| {
+                if k == i {
+                    Scalar::zero()
+                } else {
+                    Scalar::random(rng)
+                }
+            })
+            .collect::<Vec<_>>();
+        deltas[usize::from(i)] = secret.as_ref() - deltas.iter().sum::<Scalar<E>>();
+        let my_msg = MsgRound1Broad {
+            Deltas: deltas.iter().map(|d| Point::generator() * d).collect(),
+        };
+
+        tracer.send_msg();
+        outgoings
+            .feed(Outgoing::broadcast(Msg::Round1Broad(my_msg.clone())))
+            .await
+            .map_err(IoError::send_message)?;
+        let messages = utils::iter_peers(i, m).map(|k| {
+            Outgoing::p2p(
+                k,
+                Msg::Round1Uni(MsgRound1Uni {
+                    delta: deltas[usize::from(k)],
+                }),
+            )
+        });
+        outgoings
+            .send_all(&mut futures::stream::iter(messages.map(Ok)))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        my_deltas = Some((my_msg, deltas[usize::from(i)]));
+    }
+
+    // Round 2
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let commitments = rounds
+        .complete(round1_broad)
+        .await
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    // Optional reliability check
+    if reliable_broadcast_enforced {
+        tracer.stage("Hash received msgs (reliability check)");
+        let h_i = match &my_deltas {
+            Some((my_msg, _)) => udigest::hash_iter::<D>(
+                commitments
+                    .iter_including_me(my_msg)
+                    .map(|msg| unambiguous::Echo { sid, msg }),
+            ),
+            None => udigest::hash_iter::<D>(
+                commitments.iter().map(|msg| unambiguous::Echo { sid, msg }),
+            ),
+        };
+
+        tracer.send_msg();
+        outgoings
+            .send(Outgoing::broadcast(Msg::ReliabilityCheck(
+                MsgReliabilityCheck(h_i.clone()),
+            )))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.round_begins();
+
+        tracer.receive_msgs();
+        let hashes = rounds
+            .complete(round1_sync)
+            .await
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        tracer.stage("Assert other parties hashed messages (reliability check)");
+        let parties_have_different_hashes = hashes
+            .into_iter_indexed()
+            .filter(|(_j, _msg_id, h_j)| h_i != h_j.0)
+            .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        if !parties_have_different_hashes.is_empty() {
+            return Err(RecoveryAborted::new(
+                RecoveryAbortReason::Round1NotReliable,
+                parties_have_different_hashes,
+            )
+            .into());
+        }
+    }
+
+    tracer.stage("Validate data size");
+    let blame = utils::collect_simple_blame(&commitments, |msg| msg.Deltas.len() != helpers.len());
+    if !blame.is_empty() {
+        return Err(RecoveryAborted::new(RecoveryAbortReason::InvalidDataSize, blame).into());
+    }
+
+    tracer.stage("Validate Σ_k Δ_{h,k} = λ_h X_h");
+    let blame = commitments
+        .iter_indexed()
+        .filter(|(h, _, msg)| msg.Deltas.iter().sum::<Point<E>>() != expected_sums[usize::from(*h)])
+        .map(|(h, msg_id, _)| AbortBlame::new(h, msg_id, msg_id))
+        .collect::<Vec<_>>();
+    if !blame.is_empty() {
+        return Err(RecoveryAborted::new(RecoveryAbortReason::InvalidCommitments, blame).into());
+    }
+
+    if let (Some(round1_uni), Some((_, my_delta))) = (round1_uni, &my_deltas) {
+        tracer.receive_msgs();
+        let deltas = rounds
+            .complete(round1_uni)
+            .await
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        tracer.stage("Validate δ_{h,i} G = Δ_{h,i}");
+        let blame = utils::collect_blame(&commitments, &deltas, |_, commitment, delta| {
+            Point::generator() * delta.delta != commitment.Deltas[usize::from(i)]
+        });
+        if !blame.is_empty() {
+            return Err(RecoveryAborted::new(RecoveryAbortReason::InvalidDelta, blame).into());
+        }
+
+        tracer.stage("Compute σ_i = Σ_h δ_{h,i}");
+        let sigma = deltas
+            .iter_including_me(&MsgRound1Uni { delta: *my_delta })
+            .map(|d| d.delta)
+            .sum::<Scalar<E>>();
+
+        tracer.send_msg();
+        outgoings
+            .send(Outgoing::p2p(m, Msg::Round2(MsgRound2 { sigma })))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.protocol_ends();
+        return Ok(None);
+    }
+
+    // Output
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let sigmas = rounds
+        .complete(round2.ok_or(Bug::NoRound2)?)
+        .await
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    tracer.stage("Validate σ_k G = Σ_h Δ_{h,k}");
+    let blame = sigmas
+        .iter_indexed()
+        .filter(|(k, _, msg)| {
+            let expected = commitments
+                .iter()
+                .map(|c| c.Deltas[usize::from(*k)])
+                .sum::<Point<E>>();
+            Point::generator() * msg.sigma != expected
+        })
+        .map(|(k, msg_id, _)| AbortBlame::new(k, msg_id, msg_id))
+        .collect::<Vec<_>>();
+    if !blame.is_empty() {
+        return Err(RecoveryAborted::new(RecoveryAbortReason::InvalidSigma, blame).into());
+    }
+
+    tracer.stage("Compute x_j = Σ_k σ_k");
+    let mut x = sigmas.iter().map(|msg| msg.sigma).sum::<Scalar<E>>();
+    let x = NonZero::from_secret_scalar(SecretScalar::new(&mut x)).ok_or(Bug::ZeroShare)?;
+    if Point::generator() * &x != key_info.public_shares[usize::from(lost_index)] {
+        return Err(Bug::MismatchedPublicShare.into());
+    }
+
+    let key_share = DirtyIncompleteKeyShare {
+        i: lost_index,
+        key_info: key_info.clone(),
+        x,
+        extra_x: Vec::new(),
+    }
+    .validate()
+    .map_err(|err| Bug::InvalidShareGenerated(err.into_error()))?;
+
+    tracer.protocol_ends();
+    Ok(Some(key_share))
+}
+
+/// Error of share recovery protocol
+#[derive(Debug, Error)]
+#[error("share recovery protocol failed to complete")]
+pub struct ShareRecoveryError(#[source] Reason);
+
+impl ShareRecoveryError {
+    /// If protocol was aborted by malicious party, returns information about
+    /// the abort: its reason and parties to blame
+    pub fn aborted(&self) -> Option<&RecoveryAborted> {
+        match &self.0 {
+            Reason::Aborted(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+crate::errors::impl_from! {
+    impl From for ShareRecoveryError {
+        err: InvalidArgs => ShareRecoveryError(Reason::InvalidArgs(err)),
+        err: RecoveryAborted => ShareRecoveryError(Reason::Aborted(err)),
+        err: IoError => ShareRecoveryError(Reason::IoError(err)),
+        err: Bug => ShareRecoveryError(Reason::Bug(err)),
+    }
+}
+
+#[derive(Debug, Error)]
+enum Reason {
+    #[error("invalid arguments")]
+    InvalidArgs(#[source] InvalidArgs),
+    /// Protocol was maliciously aborted by another party
+    #[error("protocol was aborted by malicious party")]
+    Aborted(#[source] RecoveryAborted),
+    #[error("i/o error")]
+    IoError(#[source] IoError),
+    #[error("bug occurred")]
+    Bug(#[source] Bug),
+}
+
+#[derive(Debug, Error)]
+enum InvalidArgs {
+    #[error("too many parties")]
+    TooManyParties,
+    #[error("party index `i` is out of bounds")]
+    IndexOutOfBounds,
+    #[error("share recovery is only possible for threshold keys")]
+    NonThresholdKey,
+    #[error("not enough helpers to recover the share")]
+    NotEnoughHelpers,
+    #[error("key share indexes are out of bounds, not unique, or helpers include lost index")]
+    InvalidIndexes,
+    #[error("helper didn't provide its key share")]
+    MissingKeyShare,
+    #[error("key share doesn't match provided key info or helper index")]
+    MismatchedKeyShare,
+    #[error("share recovery isn't supported for weighted keys")]
+    WeightedKey,
+    #[error("share recovery isn't supported for hierarchical keys")]
+    HierarchicalKey,
+}
+
+/// Unexpected error in operation not caused by other parties
+#[derive(Debug, Error)]
+enum Bug {
+    #[error("lagrange coefficient is undefined")]
+    LagrangeCoef,
+    #[error("recovering party didn't register round 2")]
+    NoRound2,
+    #[error("key share is zero - probability of that is negligible")]
+    ZeroShare,
+    #[error("recovered share doesn't match public share although we checked helpers' data")]
+    MismatchedPublicShare,
+    #[error("resulting key share is not valid")]
+    InvalidShareGenerated(#[source] InvalidIncompleteKeyShare),
+}
+
+/// Error indicating that share recovery was aborted by malicious party
+pub type RecoveryAborted = Aborted<RecoveryAbortReason>;
+
+/// Reason for share recovery abort: which exact check has failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum RecoveryAbortReason {
+    /// Helper sent wrong number of commitments
+    #[error("helper sent wrong number of commitments")]
+    InvalidDataSize,
+    /// Helper's commitments don't sum up to its Lagrange-weighted public share
+    #[error("Σ_k Δ_{{h,k}} != λ_h X_h")]
+    InvalidCommitments,
+    /// Additive share sent by helper is not consistent with its commitment
+    #[error("δ_{{h,i}} G != Δ_{{h,i}}")]
+    InvalidDelta,
+    /// Sum of additive shares sent by helper to recovering party is not consistent
+    /// with commitments
+    #[error("σ_k G != Σ_h Δ_{{h,k}}")]
+    InvalidSigma,
+    /// Party claims it received different messages in round 1 than other parties
+    #[error("round 1 was not reliable")]
+    Round1NotReliable,
+}
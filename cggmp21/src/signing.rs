@@ -2,8 +2,10 @@
 
 use digest::Digest;
 use futures::SinkExt;
-use generic_ec::{coords::AlwaysHasAffineX, Curve, NonZero, Point, Scalar, SecretScalar};
-use generic_ec_zkp::polynomial::lagrange_coefficient_at_zero;
+use generic_ec::{
+    coords::{AlwaysHasAffineX, Coordinate, HasAffineXAndParity, Parity},
+    Curve, NonZero, Point, Scalar, SecretScalar,
+};
 use paillier_zk::rug::Complete;
 use paillier_zk::{fast_paillier, rug::Integer};
 use paillier_zk::{
@@ -13,16 +15,21 @@ use paillier_zk::{
 };
 use rand_core::{CryptoRng, RngCore};
 use round_based::{
-    rounds_router::{simple_store::RoundInput, RoundsRouter},
-    runtime::AsyncRuntime,
-    Delivery, Mpc, MpcParty, MsgId, Outgoing, PartyIndex,
+    rounds_router::simple_store::RoundInput, runtime::AsyncRuntime, Delivery, Mpc, MpcParty,
+    Outgoing, PartyIndex,
 };
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::errors::IoError;
-use crate::key_share::{KeyShare, PartyAux, VssSetup};
+use crate::key_share::{KeyShare, PartyAux};
 use crate::progress::Tracer;
+use crate::timeout::{RoundTimeout, RoundTimer, RoundsRouter};
+use crate::utils::{AbortBlame, Aborted};
+use crate::zk::{
+    paillier_decryption_modulo_q as pi_dec, paillier_multiplication as pi_mul,
+    paillier_multiplication_vs_group as pi_mul_star,
+};
 use crate::{key_share::InvalidKeyShare, security_level::SecurityLevel, utils, ExecutionId};
 
 use self::msg::*;
@@ -69,21 +76,91 @@ impl<E: Curve> DataToSign<E> {
 
 /// Presignature, can be used to issue a [partial signature](PartialSignature) without interacting with other signers
 ///
-/// [Threshold](crate::key_share::AnyKeyShare::min_signers) amount of partial signatures (from different signers) can be [combined](PartialSignature::combine) into regular signature
-#[derive(Clone, Serialize, Deserialize)]
-#[serde(bound = "")]
+/// Presignature is opaque: it can't be cloned, serialized or used to sign directly. It must be
+/// moved into a [presignature store](store::PresignatureStore) which issues at most one partial
+/// signature from it.
+///
+/// Partial signatures from all signers that generated the presignature can be [combined](PartialSignature::combine) into regular signature
 pub struct Presignature<E: Curve> {
     /// $R$ component of presignature
-    pub R: NonZero<Point<E>>,
+    R: NonZero<Point<E>>,
     /// $k$ component of presignaure
-    pub k: SecretScalar<E>,
+    k: SecretScalar<E>,
     /// $\chi$ component of presignature
-    pub chi: SecretScalar<E>,
+    chi: SecretScalar<E>,
+    /// Index of the signer (at keygen) that owns the presignature
+    signer: PartyIndex,
+    /// Public data binding the presignature to the key and signers that generated it
+    metadata: PresignatureMetadata,
+    /// Commitments to presignature shares of every signer, ordered the same way as
+    /// [`metadata.signers`](PresignatureMetadata::signers)
+    ///
+    /// Used to verify partial signatures individually, see [`PartialSignature::combine_verified`]
+    commitments: Vec<SignerCommitment<E>>,
+    /// Whether HD derivation path was applied to the presignature
+    ///
+    /// Derivation path can be applied only once, otherwise the presignature would be shifted twice
+    derived: bool,
+}
+
+/// Public commitment to presignature share of one signer
+///
+/// Partial signature $\sigma_j$ issued by signer $j$ for message $m$ is valid iff
+/// $\sigma_j \cdot R = m \cdot (k_j \cdot R) + r \cdot (\chi_j \cdot R)$
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct SignerCommitment<E: Curve> {
+    /// $k_j \cdot R$
+    pub k_R: Point<E>,
+    /// $\chi_j \cdot R$
+    pub chi_R: Point<E>,
+}
+
+/// Public part of presignature
+///
+/// It's the same for all honest signers that generated the presignature and carries no sensitive
+/// information. Obtained via [`Presignature::public_data`]. Combiner of partial signatures must
+/// take it from its own presignature, see [`PartialSignature::combine_verified`].
+/// Partial signatures issued with a child key are verified against public data with the same
+/// [derivation path](Self::set_derivation_path_with_algo) set.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PresignaturePublicData<E: Curve> {
+    /// $R$ component of presignature
+    pub R: NonZero<Point<E>>,
+    /// Commitments to presignature shares of every signer
+    pub commitments: Vec<SignerCommitment<E>>,
+    /// Presignature metadata
+    pub metadata: PresignatureMetadata,
+}
+
+/// Public data binding a presignature to the key and signers that generated it
+///
+/// Metadata is the same for all signers that took part in presignature generation. It's checked
+/// by [`PresignatureStore::consume`](store::PresignatureStore::consume) and [`PartialSignature::combine`], so that
+/// presignature paired with a wrong key share, or generated before a key refresh, is rejected
+/// instead of producing an invalid signature.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresignatureMetadata {
+    /// Indexes of signers (at keygen) that generated the presignature
+    pub signers: Vec<PartyIndex>,
+    /// Fingerprint of the public key info: shared public key, public shares, VSS setup and
+    /// chain code
+    #[serde(with = "hex::serde")]
+    pub key_fingerprint: [u8; 32],
+    /// Fingerprint of the aux info: Paillier keys and ring-Pedersen parameters of all signers
+    ///
+    /// Changes every time the key is refreshed.
+    #[serde(with = "hex::serde")]
+    pub aux_fingerprint: [u8; 32],
+    /// Execution id of presignature generation protocol
+    #[serde(with = "hex::serde")]
+    pub execution_id: Vec<u8>,
 }
 
 /// Partial signature issued by signer for given message
 ///
-/// Can be obtained using [`Presignature::issue_partial_signature`]. Partial signature doesn't carry any sensitive inforamtion.
+/// Can be obtained using [`PresignatureStore::consume`](store::PresignatureStore::consume). Partial signature doesn't carry any sensitive inforamtion.
 ///
 /// Threshold amount of partial signatures can be combined into a regular signature using [`PartialSignature::combine`]
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -93,16 +170,120 @@ pub struct PartialSignature<E: Curve> {
     pub r: Scalar<E>,
     /// $\sigma$ component of partial signature
     pub sigma: Scalar<E>,
+    /// Index of the signer (at keygen) that issued the partial signature
+    signer: PartyIndex,
+    /// Metadata of the presignature partial signature was issued from
+    metadata: PresignatureMetadata,
+    /// Recovery id of presignature $R$, before low-s normalization
+    recovery_id: RecoveryId,
 }
 
 /// ECDSA signature
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Debug)]
+///
+/// Equality and ordering of signatures take into account $r, s$ only and ignore
+/// [recovery id](Self::recovery_id): it's auxiliary information that doesn't affect validity of
+/// the signature. Signature with recovery id is equal to the same signature without it.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
 #[serde(bound = "")]
 pub struct Signature<E: Curve> {
     /// $r$ component of signature
     pub r: NonZero<Scalar<E>>,
     /// $s$ component of signature
     pub s: NonZero<Scalar<E>>,
+    /// Recovery id, see [`Signature::recovery_id`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recovery_id: Option<RecoveryId>,
+}
+
+/// Compares $r, s$ only, recovery id is ignored
+impl<E: Curve> PartialEq for Signature<E> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.r, self.s) == (other.r, other.s)
+    }
+}
+
+impl<E: Curve> Eq for Signature<E> {}
+
+impl<E: Curve> PartialOrd for Signature<E> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders by $r, s$ only, recovery id is ignored
+impl<E: Curve> Ord for Signature<E> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (self.r, self.s).cmp(&(other.r, other.s))
+    }
+}
+
+/// Recovery id of ECDSA signature
+///
+/// Bit 0 is set if $y$ coordinate of $R$ is odd, bit 1 is set if $x$ coordinate of $R$ is
+/// greater than or equal to curve order (i.e. $r \ne x$). Ethereum's `v` is `27 + recovery_id`
+/// (or `35 + 2 * chain_id + recovery_id` with EIP-155).
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub struct RecoveryId(u8);
+
+impl RecoveryId {
+    /// Constructs recovery id from parity of $y$ coordinate of $R$ and whether its $x$
+    /// coordinate was reduced modulo curve order
+    pub fn new(is_y_odd: bool, is_x_reduced: bool) -> Self {
+        Self(u8::from(is_y_odd) | (u8::from(is_x_reduced) << 1))
+    }
+
+    /// Parses recovery id from byte
+    ///
+    /// Returns `None` if `byte > 3`
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        (byte <= 3).then_some(Self(byte))
+    }
+
+    /// Returns recovery id as byte in range `0..=3`
+    pub fn to_byte(self) -> u8 {
+        self.0
+    }
+
+    /// Indicates whether $y$ coordinate of $R$ is odd
+    pub fn is_y_odd(self) -> bool {
+        self.0 & 1 == 1
+    }
+
+    /// Indicates whether $x$ coordinate of $R$ was reduced modulo curve order
+    pub fn is_x_reduced(self) -> bool {
+        self.0 & 2 == 2
+    }
+
+    /// Computes recovery id of $R$
+    ///
+    /// Parity of $y$ is taken from compressed point encoding, which is SEC1 for all supported curves
+    fn of<E: Curve>(R: &NonZero<Point<E>>) -> Self
+    where
+        NonZero<Point<E>>: AlwaysHasAffineX<E>,
+    {
+        let is_y_odd = R.to_bytes(true).as_bytes().first() == Some(&0x03);
+        let is_x_reduced = Scalar::<E>::from_be_bytes(R.x().as_be_bytes()).is_err();
+        Self::new(is_y_odd, is_x_reduced)
+    }
+
+    /// Recovery id of $-R$
+    fn negate(self) -> Self {
+        Self(self.0 ^ 1)
+    }
+}
+
+impl TryFrom<u8> for RecoveryId {
+    type Error = InvalidRecoveryId;
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        Self::from_byte(byte).ok_or(InvalidRecoveryId)
+    }
+}
+
+impl From<RecoveryId> for u8 {
+    fn from(id: RecoveryId) -> Self {
+        id.to_byte()
+    }
 }
 
 macro_rules! prefixed {
@@ -111,6 +292,11 @@ macro_rules! prefixed {
     };
 }
 
+mod batch;
+mod der;
+mod five_rounds;
+pub mod store;
+
 #[doc = include_str!("../docs/mpc_message.md")]
 pub mod msg {
     use digest::Digest;
@@ -126,6 +312,10 @@ pub mod msg {
     use serde::{Deserialize, Serialize};
 
     use crate::utils;
+    use crate::zk::{
+        paillier_decryption_modulo_q as pi_dec, paillier_multiplication as pi_mul,
+        paillier_multiplication_vs_group as pi_mul_star,
+    };
 
     /// Signing protocol message
     ///
@@ -140,12 +330,24 @@ pub mod msg {
         Round1b(MsgRound1b),
         /// Round 2 message
         Round2(MsgRound2<E>),
-        /// Round 3 message
-        Round3(MsgRound3<E>),
+        /// Round 3a message
+        Round3a(MsgRound3a<E>),
+        /// Round 3b message
+        Round3b(MsgRound3b<E>),
         /// Round 4 message
         Round4(MsgRound4<E>),
         /// Reliability check message (optional additional round)
         ReliabilityCheck(MsgReliabilityCheck<D>),
+        /// Reliability check of round 3a messages (optional additional round)
+        Round3aReliabilityCheck(MsgRound3aReliabilityCheck<D>),
+        /// Reliability check of round 4 messages (optional additional round)
+        Round4ReliabilityCheck(MsgRound4ReliabilityCheck<D>),
+        /// Message proving correctness of $\delta_i$ (sent only if $\Delta \ne \delta \cdot G$)
+        Round3Blame(MsgRound3Blame<E>),
+        /// Message proving correctness of $S_i$ (sent only if $\sum_j S_j \ne \delta \cdot X$)
+        ChiBlame(MsgChiBlame<E>),
+        /// Message proving correctness of $\sigma_i$ (sent only if signature is invalid)
+        Round4Blame(MsgRound4Blame<E>),
     }
 
     /// Message from round 1a
@@ -189,33 +391,126 @@ pub mod msg {
         pub psi_prime: (pi_log::Commitment<E>, pi_log::Proof),
     }
 
-    /// Message from round 3
-    #[derive(Clone, Serialize, Deserialize)]
+    /// Message from round 3a
+    ///
+    /// Sent over broadcast channel, so all signers agree on whether $\Delta = \delta \cdot G$
+    #[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+    #[udigest(tag = prefixed!("round3"))]
+    #[udigest(bound = "")]
     #[serde(bound = "")]
-    pub struct MsgRound3<E: Curve> {
+    pub struct MsgRound3a<E: Curve> {
         /// $\delta_i$
         pub delta: Scalar<E>,
         /// $\Delta_i$
         pub Delta: Point<E>,
+        /// $S_i = \chi_i \cdot \Gamma$
+        pub S: Point<E>,
+    }
+
+    /// Message from round 3b
+    #[derive(Clone, Serialize, Deserialize)]
+    #[serde(bound = "")]
+    pub struct MsgRound3b<E: Curve> {
         /// $\psi''_{j,i}$
         pub psi_prime_prime: (pi_log::Commitment<E>, pi_log::Proof),
     }
 
     /// Message from round 4
-    #[derive(Clone, Serialize, Deserialize)]
+    #[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+    #[udigest(tag = prefixed!("round4"))]
+    #[udigest(bound = "")]
     #[serde(bound = "")]
     pub struct MsgRound4<E: Curve> {
         /// $\sigma_i$
         pub sigma: Scalar<E>,
     }
 
+    /// Message from identification round that follows round 3
+    ///
+    /// Sent when $\Delta \ne \delta \cdot G$ to convince other parties that $\delta_i$
+    /// was computed honestly.
+    #[derive(Clone, Serialize, Deserialize)]
+    #[serde(bound = "")]
+    pub struct MsgRound3Blame<E: Curve> {
+        /// $H_i$, encryption of $\gamma_i \cdot k_i$
+        pub H: fast_paillier::Ciphertext,
+        /// $\psi^{mul}_i$, proof that $H_i$ is well-formed
+        pub psi_mul: (pi_mul::Commitment, pi_mul::Proof),
+        /// $\psi^{dec}_{j,i}$, proof that $\delta_i$ is decryption of $C_i$ modulo $q$
+        pub psi_dec: (pi_dec::Commitment<E>, pi_dec::Proof),
+        /// Round 2 messages received by $i$-th party from other signers
+        pub round2_msgs: Vec<MsgRound2<E>>,
+    }
+
+    /// Message from identification round that follows round 3
+    ///
+    /// Sent when $\sum_j S_j \ne \delta \cdot X$ to convince other parties that $S_i$
+    /// was computed honestly.
+    #[derive(Clone, Serialize, Deserialize)]
+    #[serde(bound = "")]
+    pub struct MsgChiBlame<E: Curve> {
+        /// $\hat H_i$, encryption of $x_i \cdot k_i$
+        pub hat_H: fast_paillier::Ciphertext,
+        /// $\psi^{mul*}_{j,i}$, proof that $\hat H_i$ is well-formed
+        pub psi_mul_star: (pi_mul_star::Commitment<E>, pi_mul_star::Proof),
+        /// $\psi^{log}_{j,i}$, proof that $S_i = y \cdot \Gamma$ where $y$ is plaintext of $\hat C_i$
+        pub psi_log: (pi_log::Commitment<E>, pi_log::Proof),
+        /// Round 2 messages received by $i$-th party from other signers
+        pub round2_msgs: Vec<MsgRound2<E>>,
+    }
+
+    /// Message from identification round that follows round 4
+    ///
+    /// Sent when resulting signature is invalid to convince other parties that
+    /// $\sigma_i$ was computed honestly.
+    #[derive(Clone, Serialize, Deserialize)]
+    #[serde(bound = "")]
+    pub struct MsgRound4Blame<E: Curve> {
+        /// $\hat H_i$, encryption of $x_i \cdot k_i$
+        pub hat_H: fast_paillier::Ciphertext,
+        /// $\psi^{mul*}_{j,i}$, proof that $\hat H_i$ is well-formed
+        pub psi_mul_star: (pi_mul_star::Commitment<E>, pi_mul_star::Proof),
+        /// $\psi^{dec}_{j,i}$, proof that $\sigma_i$ is decryption of $S_i$ modulo $q$
+        pub psi_dec: (pi_dec::Commitment<E>, pi_dec::Proof),
+        /// Round 2 messages received by $i$-th party from other signers
+        pub round2_msgs: Vec<MsgRound2<E>>,
+    }
+
     /// Message from auxiliary round for reliability check
     #[derive(Clone, Serialize, Deserialize)]
     #[serde(bound = "")]
     pub struct MsgReliabilityCheck<D: Digest>(pub digest::Output<D>);
+
+    /// Message from auxiliary round for reliability check of round 3a messages
+    #[derive(Clone, Serialize, Deserialize)]
+    #[serde(bound = "")]
+    pub struct MsgRound3aReliabilityCheck<D: Digest>(pub digest::Output<D>);
+
+    /// Message from auxiliary round for reliability check of round 4 messages
+    #[derive(Clone, Serialize, Deserialize)]
+    #[serde(bound = "")]
+    pub struct MsgRound4ReliabilityCheck<D: Digest>(pub digest::Output<D>);
+
+    /// Messages types related to (5+1)-round signing protocol
+    pub mod five_rounds {
+        pub use crate::signing::five_rounds::{
+            Msg, MsgChiBlame, MsgRevealDelta, MsgRound1a, MsgRound1b, MsgRound2Broad, MsgRound2Uni,
+            MsgRound3, MsgRound4, MsgRound5, MsgRound6,
+        };
+    }
+
+    /// Messages types related to batched presignature generation
+    pub mod batch {
+        pub use crate::signing::batch::{
+            Msg, MsgChiBlame, MsgRound1a, MsgRound1b, MsgRound2, MsgRound3Blame, MsgRound3a,
+            MsgRound3b,
+        };
+    }
 }
 
 mod unambiguous {
+    use generic_ec::{NonZero, Point, Scalar};
+
     use crate::ExecutionId;
 
     #[derive(udigest::Digestable)]
@@ -241,18 +536,111 @@ mod unambiguous {
         pub prime_prime: bool,
     }
 
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("proof_mul"))]
+    pub struct ProofMul<'a> {
+        pub sid: ExecutionId<'a>,
+        pub prover: u16,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("proof_mul_star"))]
+    pub struct ProofMulStar<'a> {
+        pub sid: ExecutionId<'a>,
+        pub prover: u16,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("proof_log_chi"))]
+    pub struct ProofLogChi<'a> {
+        pub sid: ExecutionId<'a>,
+        pub prover: u16,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("proof_dec"))]
+    pub struct ProofDec<'a> {
+        pub sid: ExecutionId<'a>,
+        pub prover: u16,
+        pub hat: bool,
+    }
+
     #[derive(udigest::Digestable)]
     #[udigest(tag = prefixed!("echo_round"))]
     pub struct Echo<'a> {
         pub sid: ExecutionId<'a>,
         pub ciphertexts: &'a super::MsgRound1a,
     }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("echo_msg"))]
+    #[udigest(bound = "M: udigest::Digestable")]
+    pub struct EchoMsg<'a, M> {
+        pub sid: ExecutionId<'a>,
+        pub round: u16,
+        pub msg: &'a M,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("key_fingerprint"))]
+    #[udigest(bound = "")]
+    pub struct KeyFingerprint<'a, E: generic_ec::Curve> {
+        pub shared_public_key: &'a NonZero<Point<E>>,
+        pub public_shares: &'a [NonZero<Point<E>>],
+        pub min_signers: Option<u16>,
+        pub I: Option<&'a [NonZero<Scalar<E>>]>,
+        pub weights: Option<&'a [u16]>,
+        pub ranks: Option<&'a [u16]>,
+        #[udigest(as = Option<udigest::Bytes>)]
+        pub chain_code: Option<[u8; 32]>,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("aux_fingerprint"))]
+    pub struct AuxFingerprint<'a> {
+        pub parties: Vec<PartyAuxFingerprint<'a>>,
+    }
+
+    #[derive(udigest::Digestable)]
+    pub struct PartyAuxFingerprint<'a> {
+        #[udigest(as = &crate::utils::encoding::Integer)]
+        pub N: &'a paillier_zk::rug::Integer,
+        #[udigest(as = &crate::utils::encoding::Integer)]
+        pub s: &'a paillier_zk::rug::Integer,
+        #[udigest(as = &crate::utils::encoding::Integer)]
+        pub t: &'a paillier_zk::rug::Integer,
+    }
 }
 
 /// Signing entry point
-pub struct SigningBuilder<
+///
+/// Runs (3+1)-round signing protocol. Use [`set_five_rounds_protocol`](Self::set_five_rounds_protocol)
+/// to switch to (5+1)-round signing protocol.
+pub type SigningBuilder<
+    'r,
+    E,
+    L = crate::default_choice::SecurityLevel,
+    D = crate::default_choice::Digest,
+> = GenericSigningBuilder<'r, E, ThreeRounds, L, D>;
+
+/// Entry point of (5+1)-round signing protocol
+///
+/// Obtained by calling [`SigningBuilder::set_five_rounds_protocol`]
+pub type FiveRoundsSigningBuilder<
+    'r,
+    E,
+    L = crate::default_choice::SecurityLevel,
+    D = crate::default_choice::Digest,
+> = GenericSigningBuilder<'r, E, FiveRounds, L, D>;
+
+/// Signing entry point with choice of signing protocol
+///
+/// Use [`SigningBuilder`] or [`FiveRoundsSigningBuilder`] type aliases instead of referring
+/// to this type directly.
+pub struct GenericSigningBuilder<
     'r,
     E,
+    M,
     L = crate::default_choice::SecurityLevel,
     D = crate::default_choice::Digest,
 > where
@@ -265,14 +653,28 @@ pub struct SigningBuilder<
     key_share: &'r KeyShare<E, L>,
     execution_id: ExecutionId<'r>,
     tracer: Option<&'r mut dyn Tracer>,
+    round_timer: Option<&'r mut dyn RoundTimer>,
     enforce_reliable_broadcast: bool,
+    _protocol: std::marker::PhantomData<M>,
     _digest: std::marker::PhantomData<D>,
 
     #[cfg(feature = "hd-wallet")]
     additive_shift: Option<Scalar<E>>,
 }
 
-impl<'r, E, L, D> SigningBuilder<'r, E, L, D>
+/// Indicates (3+1)-round signing protocol
+///
+/// Presignature generation takes 3 rounds (plus two optional rounds for reliability check). Identification
+/// of faulty parties (if protocol is aborted) requires additional round with expensive ZK proofs.
+pub struct ThreeRounds;
+/// Indicates (5+1)-round signing protocol
+///
+/// Presignature generation takes 5 rounds. In exchange, identification of faulty parties is cheap:
+/// it doesn't require ZK proofs if $\delta$ doesn't match, and if partial signature is invalid,
+/// faulty party is identified without any additional communication.
+pub struct FiveRounds;
+
+impl<'r, E, L, D> GenericSigningBuilder<'r, E, ThreeRounds, L, D>
 where
     E: Curve,
     NonZero<Point<E>>: AlwaysHasAffineX<E>,
@@ -292,37 +694,15 @@ where
             key_share: secret_key_share,
             execution_id: eid,
             tracer: None,
+            round_timer: None,
             enforce_reliable_broadcast: true,
+            _protocol: std::marker::PhantomData,
             _digest: std::marker::PhantomData,
             #[cfg(feature = "hd-wallet")]
             additive_shift: None,
         }
     }
 
-    /// Specifies another hash function to use
-    pub fn set_digest<D2>(self) -> SigningBuilder<'r, E, L, D2>
-    where
-        D2: Digest,
-    {
-        SigningBuilder {
-            i: self.i,
-            parties_indexes_at_keygen: self.parties_indexes_at_keygen,
-            key_share: self.key_share,
-            tracer: self.tracer,
-            enforce_reliable_broadcast: self.enforce_reliable_broadcast,
-            execution_id: self.execution_id,
-            _digest: std::marker::PhantomData,
-            #[cfg(feature = "hd-wallet")]
-            additive_shift: self.additive_shift,
-        }
-    }
-
-    /// Specifies a tracer that tracks progress of protocol execution
-    pub fn set_progress_tracer(mut self, tracer: &'r mut dyn Tracer) -> Self {
-        self.tracer = Some(tracer);
-        self
-    }
-
     #[doc = include_str!("../docs/enforce_reliable_broadcast.md")]
     pub fn enforce_reliable_broadcast(self, v: bool) -> Self {
         Self {
@@ -331,68 +711,27 @@ where
         }
     }
 
-    /// Specifies HD derivation path
-    ///
-    /// Note: when generating a presignature, derivation path doesn't need to be known in advance. Instead
-    /// of using this method, [`Presignature::set_derivation_path`] could be used to set derivation path
-    /// after presignature was generated.
-    ///
-    /// ## Example
-    /// Set derivation path to m/1/999
-    ///
-    /// ```rust,no_run
-    /// # let eid = cggmp21::ExecutionId::new(b"protocol nonce");
-    /// # let (i, parties_indexes_at_keygen, key_share): (u16, Vec<u16>, cggmp21::KeyShare<cggmp21::supported_curves::Secp256k1>)
-    /// # = unimplemented!();
-    /// cggmp21::signing(eid, i, &parties_indexes_at_keygen, &key_share)
-    ///     .set_derivation_path([1, 999])?
-    /// # ; Ok::<_, Box<dyn std::error::Error>>(())
-    /// ```
+    /// Switches to (5+1)-round signing protocol
     ///
-    /// ## Derivation algorithm
-    /// This method uses [`hd_wallet::Slip10`] derivation algorithm, which can only be used with secp256k1
-    /// and secp256r1 curves. If you need to use another one, see
-    /// [`set_derivation_path_with_algo`](Self::set_derivation_path_with_algo)
-    #[cfg(all(feature = "hd-wallet", feature = "hd-slip10"))]
-    pub fn set_derivation_path<Index>(
-        self,
-        path: impl IntoIterator<Item = Index>,
-    ) -> Result<
-        Self,
-        crate::key_share::HdError<<Index as TryInto<hd_wallet::NonHardenedIndex>>::Error>,
-    >
-    where
-        hd_wallet::Slip10: hd_wallet::HdWallet<E>,
-        hd_wallet::NonHardenedIndex: TryFrom<Index>,
-    {
-        self.set_derivation_path_with_algo::<hd_wallet::Slip10, _>(path)
-    }
-
-    /// Specifies HD derivation path, using HD derivation algorithm [`hd_wallet::HdWallet`]
+    /// (5+1)-round protocol takes more rounds to generate a presignature, but identification of faulty
+    /// parties is much cheaper. See [`FiveRounds`] for details. All signers must use the same protocol.
     ///
-    /// Note: when generating a presignature, derivation path doesn't need to be known in advance. Instead
-    /// of using this method, [`Presignature::set_derivation_path`] could be used to set derivation path
-    /// after presignature was generated.
-    #[cfg(feature = "hd-wallet")]
-    pub fn set_derivation_path_with_algo<Hd: hd_wallet::HdWallet<E>, Index>(
-        mut self,
-        path: impl IntoIterator<Item = Index>,
-    ) -> Result<
-        Self,
-        crate::key_share::HdError<<Index as TryInto<hd_wallet::NonHardenedIndex>>::Error>,
-    >
-    where
-        hd_wallet::NonHardenedIndex: TryFrom<Index>,
-    {
-        use crate::key_share::HdError;
-        let public_key = self
-            .key_share
-            .extended_public_key()
-            .ok_or(HdError::DisabledHd)?;
-        self.additive_shift = Some(
-            derive_additive_shift::<E, Hd, _>(public_key, path).map_err(HdError::InvalidPath)?,
-        );
-        Ok(self)
+    /// Reliable broadcast is always enforced in (5+1)-round protocol as identification of faulty
+    /// parties relies on it.
+    pub fn set_five_rounds_protocol(self) -> FiveRoundsSigningBuilder<'r, E, L, D> {
+        GenericSigningBuilder {
+            i: self.i,
+            parties_indexes_at_keygen: self.parties_indexes_at_keygen,
+            key_share: self.key_share,
+            tracer: self.tracer,
+            round_timer: self.round_timer,
+            enforce_reliable_broadcast: true,
+            execution_id: self.execution_id,
+            _protocol: std::marker::PhantomData,
+            _digest: std::marker::PhantomData,
+            #[cfg(feature = "hd-wallet")]
+            additive_shift: self.additive_shift,
+        }
     }
 
     /// Starts presignature generation protocol
@@ -407,6 +746,7 @@ where
     {
         match signing_t_out_of_n(
             self.tracer,
+            self.round_timer,
             rng,
             party,
             self.execution_id,
@@ -457,6 +797,7 @@ where
     {
         match signing_t_out_of_n(
             self.tracer,
+            self.round_timer,
             rng,
             party,
             self.execution_id,
@@ -496,23 +837,366 @@ where
             self.sign(rng, party, message_to_sign)
         })
     }
-}
 
-/// t-out-of-n signing
-///
-/// CGGMP paper doesn't support threshold signing out of the box. However, threshold signing
-/// can be easily implemented on top of CGGMP's [`signing_n_out_of_n`] by converting polynomial
-/// (VSS) key shares into additive (by multiplying at lagrange coefficient) and calling
-/// t-out-of-t protocol. The trick is described in more details in the spec.
-async fn signing_t_out_of_n<M, E, L, D, R>(
-    mut tracer: Option<&mut dyn Tracer>,
-    rng: &mut R,
-    party: M,
-    sid: ExecutionId<'_>,
-    i: PartyIndex,
-    key_share: &KeyShare<E, L>,
-    S: &[PartyIndex],
-    message_to_sign: Option<DataToSign<E>>,
+    /// Starts batched presignature generation protocol
+    ///
+    /// Generates `batch_size` presignatures in a single protocol run. Messages of all instances
+    /// are sent together, so round-trip latency and per-message overhead are paid once per
+    /// batch. All signers must use the same `batch_size`.
+    ///
+    /// Note that batched protocol uses different message type, see [`msg::batch::Msg`].
+    pub async fn generate_presignatures<R, M>(
+        self,
+        rng: &mut R,
+        party: M,
+        batch_size: usize,
+    ) -> Result<Vec<Presignature<E>>, SigningError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = msg::batch::Msg<E, D>>,
+    {
+        match batch::presignatures_t_out_of_n(
+            self.tracer,
+            self.round_timer,
+            rng,
+            party,
+            self.execution_id,
+            self.i,
+            self.key_share,
+            self.parties_indexes_at_keygen,
+            batch_size,
+            None,
+            self.enforce_reliable_broadcast,
+            #[cfg(feature = "hd-wallet")]
+            self.additive_shift,
+            #[cfg(not(feature = "hd-wallet"))]
+            None,
+        )
+        .await?
+        {
+            batch::BatchOutput::Presignatures(presigs) => Ok(presigs),
+            batch::BatchOutput::Signatures(_) => Err(Bug::UnexpectedProtocolOutput.into()),
+        }
+    }
+
+    /// Returns a state machine that can be used to carry out the batched presignature generation
+    /// protocol
+    ///
+    /// See [`round_based::state_machine`] for details on how that can be done.
+    #[cfg(feature = "state-machine")]
+    pub fn generate_presignatures_sync<R>(
+        self,
+        rng: &'r mut R,
+        batch_size: usize,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<Vec<Presignature<E>>, SigningError>,
+        Msg = msg::batch::Msg<E, D>,
+    > + 'r
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(move |party| {
+            self.generate_presignatures(rng, party, batch_size)
+        })
+    }
+
+    /// Starts signing protocol for a batch of messages
+    ///
+    /// Generates a presignature per message and signs all of them in a single protocol run, so
+    /// round-trip latency is paid once per batch. Returns signatures in the same order as
+    /// `messages_to_sign`. All signers must provide the same messages in the same order.
+    ///
+    /// Note that batched protocol uses different message type, see [`msg::batch::Msg`].
+    pub async fn sign_batch<R, M>(
+        self,
+        rng: &mut R,
+        party: M,
+        messages_to_sign: &[DataToSign<E>],
+    ) -> Result<Vec<Signature<E>>, SigningError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = msg::batch::Msg<E, D>>,
+    {
+        match batch::presignatures_t_out_of_n(
+            self.tracer,
+            self.round_timer,
+            rng,
+            party,
+            self.execution_id,
+            self.i,
+            self.key_share,
+            self.parties_indexes_at_keygen,
+            messages_to_sign.len(),
+            Some(messages_to_sign),
+            self.enforce_reliable_broadcast,
+            #[cfg(feature = "hd-wallet")]
+            self.additive_shift,
+            #[cfg(not(feature = "hd-wallet"))]
+            None,
+        )
+        .await?
+        {
+            batch::BatchOutput::Signatures(sigs) => Ok(sigs),
+            batch::BatchOutput::Presignatures(_) => Err(Bug::UnexpectedProtocolOutput.into()),
+        }
+    }
+
+    /// Returns a state machine that can be used to carry out the signing protocol for a batch
+    /// of messages
+    ///
+    /// See [`round_based::state_machine`] for details on how that can be done.
+    #[cfg(feature = "state-machine")]
+    pub fn sign_batch_sync<R>(
+        self,
+        rng: &'r mut R,
+        messages_to_sign: &'r [DataToSign<E>],
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<Vec<Signature<E>>, SigningError>,
+        Msg = msg::batch::Msg<E, D>,
+    > + 'r
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(move |party| {
+            self.sign_batch(rng, party, messages_to_sign)
+        })
+    }
+}
+
+impl<'r, E, L, D> GenericSigningBuilder<'r, E, FiveRounds, L, D>
+where
+    E: Curve,
+    NonZero<Point<E>>: AlwaysHasAffineX<E>,
+    L: SecurityLevel,
+    D: Digest<OutputSize = digest::typenum::U32> + Clone + 'static,
+{
+    /// Starts presignature generation protocol
+    pub async fn generate_presignature<R, M>(
+        self,
+        rng: &mut R,
+        party: M,
+    ) -> Result<Presignature<E>, SigningError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = five_rounds::Msg<E, D>>,
+    {
+        match five_rounds::signing_t_out_of_n(
+            self.tracer,
+            self.round_timer,
+            rng,
+            party,
+            self.execution_id,
+            self.i,
+            self.key_share,
+            self.parties_indexes_at_keygen,
+            None,
+            #[cfg(feature = "hd-wallet")]
+            self.additive_shift,
+            #[cfg(not(feature = "hd-wallet"))]
+            None,
+        )
+        .await?
+        {
+            ProtocolOutput::Presignature(presig) => Ok(presig),
+            ProtocolOutput::Signature(_) => Err(Bug::UnexpectedProtocolOutput.into()),
+        }
+    }
+
+    /// Returns a state machine that can be used to carry out the presignature generation protocol
+    ///
+    /// See [`round_based::state_machine`] for details on how that can be done.
+    #[cfg(feature = "state-machine")]
+    pub fn generate_presignature_sync<R>(
+        self,
+        rng: &'r mut R,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<Presignature<E>, SigningError>,
+        Msg = five_rounds::Msg<E, D>,
+    > + 'r
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(|party| self.generate_presignature(rng, party))
+    }
+
+    /// Starts signing protocol
+    pub async fn sign<R, M>(
+        self,
+        rng: &mut R,
+        party: M,
+        message_to_sign: DataToSign<E>,
+    ) -> Result<Signature<E>, SigningError>
+    where
+        R: RngCore + CryptoRng,
+        M: Mpc<ProtocolMessage = five_rounds::Msg<E, D>>,
+    {
+        match five_rounds::signing_t_out_of_n(
+            self.tracer,
+            self.round_timer,
+            rng,
+            party,
+            self.execution_id,
+            self.i,
+            self.key_share,
+            self.parties_indexes_at_keygen,
+            Some(message_to_sign),
+            #[cfg(feature = "hd-wallet")]
+            self.additive_shift,
+            #[cfg(not(feature = "hd-wallet"))]
+            None,
+        )
+        .await?
+        {
+            ProtocolOutput::Signature(sig) => Ok(sig),
+            ProtocolOutput::Presignature(_) => Err(Bug::UnexpectedProtocolOutput.into()),
+        }
+    }
+
+    /// Returns a state machine that can be used to carry out the signing protocol
+    ///
+    /// See [`round_based::state_machine`] for details on how that can be done.
+    #[cfg(feature = "state-machine")]
+    pub fn sign_sync<R>(
+        self,
+        rng: &'r mut R,
+        message_to_sign: DataToSign<E>,
+    ) -> impl round_based::state_machine::StateMachine<
+        Output = Result<Signature<E>, SigningError>,
+        Msg = five_rounds::Msg<E, D>,
+    > + 'r
+    where
+        R: RngCore + CryptoRng,
+    {
+        round_based::state_machine::wrap_protocol(move |party| {
+            self.sign(rng, party, message_to_sign)
+        })
+    }
+}
+
+impl<'r, E, M, L, D> GenericSigningBuilder<'r, E, M, L, D>
+where
+    E: Curve,
+    NonZero<Point<E>>: AlwaysHasAffineX<E>,
+    L: SecurityLevel,
+    D: Digest<OutputSize = digest::typenum::U32> + Clone + 'static,
+{
+    /// Specifies another hash function to use
+    pub fn set_digest<D2>(self) -> GenericSigningBuilder<'r, E, M, L, D2>
+    where
+        D2: Digest,
+    {
+        GenericSigningBuilder {
+            i: self.i,
+            parties_indexes_at_keygen: self.parties_indexes_at_keygen,
+            key_share: self.key_share,
+            tracer: self.tracer,
+            round_timer: self.round_timer,
+            enforce_reliable_broadcast: self.enforce_reliable_broadcast,
+            execution_id: self.execution_id,
+            _protocol: std::marker::PhantomData,
+            _digest: std::marker::PhantomData,
+            #[cfg(feature = "hd-wallet")]
+            additive_shift: self.additive_shift,
+        }
+    }
+
+    /// Specifies a tracer that tracks progress of protocol execution
+    pub fn set_progress_tracer(mut self, tracer: &'r mut dyn Tracer) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    /// Sets a timer that limits how long each round of the protocol may take
+    ///
+    /// If some signers don't send their messages before the round deadline, protocol fails
+    /// with an error that lists them (see [`SigningError::timed_out`]). See
+    /// [`timeout`](crate::timeout) module for more details.
+    pub fn set_round_timer(mut self, timer: &'r mut dyn RoundTimer) -> Self {
+        self.round_timer = Some(timer);
+        self
+    }
+
+    /// Specifies HD derivation path
+    ///
+    /// Note: when generating a presignature, derivation path doesn't need to be known in advance. Instead
+    /// of using this method, [`Presignature::set_derivation_path`] could be used to set derivation path
+    /// after presignature was generated.
+    ///
+    /// ## Example
+    /// Set derivation path to m/1/999
+    ///
+    /// ```rust,no_run
+    /// # let eid = cggmp21::ExecutionId::new(b"protocol nonce");
+    /// # let (i, parties_indexes_at_keygen, key_share): (u16, Vec<u16>, cggmp21::KeyShare<cggmp21::supported_curves::Secp256k1>)
+    /// # = unimplemented!();
+    /// cggmp21::signing(eid, i, &parties_indexes_at_keygen, &key_share)
+    ///     .set_derivation_path([1, 999])?
+    /// # ; Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// ## Derivation algorithm
+    /// This method uses [`hd_wallet::Slip10`] derivation algorithm, which can only be used with secp256k1
+    /// and secp256r1 curves. If you need to use another one, see
+    /// [`set_derivation_path_with_algo`](Self::set_derivation_path_with_algo)
+    #[cfg(all(feature = "hd-wallet", feature = "hd-slip10"))]
+    pub fn set_derivation_path<Index>(
+        self,
+        path: impl IntoIterator<Item = Index>,
+    ) -> Result<
+        Self,
+        crate::key_share::HdError<<Index as TryInto<hd_wallet::NonHardenedIndex>>::Error>,
+    >
+    where
+        hd_wallet::Slip10: hd_wallet::HdWallet<E>,
+        hd_wallet::NonHardenedIndex: TryFrom<Index>,
+    {
+        self.set_derivation_path_with_algo::<hd_wallet::Slip10, _>(path)
+    }
+
+    /// Specifies HD derivation path, using HD derivation algorithm [`hd_wallet::HdWallet`]
+    ///
+    /// Note: when generating a presignature, derivation path doesn't need to be known in advance. Instead
+    /// of using this method, [`Presignature::set_derivation_path`] could be used to set derivation path
+    /// after presignature was generated.
+    #[cfg(feature = "hd-wallet")]
+    pub fn set_derivation_path_with_algo<Hd: hd_wallet::HdWallet<E>, Index>(
+        mut self,
+        path: impl IntoIterator<Item = Index>,
+    ) -> Result<
+        Self,
+        crate::key_share::HdError<<Index as TryInto<hd_wallet::NonHardenedIndex>>::Error>,
+    >
+    where
+        hd_wallet::NonHardenedIndex: TryFrom<Index>,
+    {
+        use crate::key_share::HdError;
+        let public_key = self
+            .key_share
+            .extended_public_key()
+            .ok_or(HdError::DisabledHd)?;
+        self.additive_shift = Some(
+            derive_additive_shift::<E, Hd, _>(public_key, path).map_err(HdError::InvalidPath)?,
+        );
+        Ok(self)
+    }
+}
+
+/// t-out-of-n signing
+///
+/// CGGMP paper doesn't support threshold signing out of the box. However, threshold signing
+/// can be easily implemented on top of CGGMP's [`signing_n_out_of_n`] by converting polynomial
+/// (VSS) key shares into additive (by multiplying at lagrange coefficient) and calling
+/// t-out-of-t protocol. The trick is described in more details in the spec.
+async fn signing_t_out_of_n<M, E, L, D, R>(
+    mut tracer: Option<&mut dyn Tracer>,
+    round_timer: Option<&mut dyn RoundTimer>,
+    rng: &mut R,
+    party: M,
+    sid: ExecutionId<'_>,
+    i: PartyIndex,
+    key_share: &KeyShare<E, L>,
+    S: &[PartyIndex],
+    message_to_sign: Option<DataToSign<E>>,
     enforce_reliable_broadcast: bool,
     additive_shift: Option<Scalar<E>>,
 ) -> Result<ProtocolOutput<E>, SigningError>
@@ -526,7 +1210,62 @@ where
 {
     tracer.protocol_begins();
     tracer.stage("Map t-out-of-n protocol to t-out-of-t");
+    let TOutOfT { t, x_i, X, pk, R } =
+        map_t_out_of_n_to_t_out_of_t(key_share, i, S, additive_shift)?;
+    let (p_i, q_i) = (&key_share.aux.p, &key_share.aux.q);
+    let metadata = PresignatureMetadata::new(key_share, S, sid);
 
+    // t-out-of-t signing
+    signing_n_out_of_n::<_, _, L, _, _>(
+        tracer,
+        round_timer,
+        rng,
+        party,
+        sid,
+        i,
+        t,
+        &x_i,
+        &X,
+        pk,
+        p_i,
+        q_i,
+        &R,
+        metadata,
+        additive_shift.is_some(),
+        message_to_sign,
+        enforce_reliable_broadcast,
+    )
+    .await
+}
+
+/// Signing data mapped to t-out-of-t setting
+struct TOutOfT<E: Curve> {
+    /// Amount of signers
+    t: u16,
+    /// Additive share of the signer
+    x_i: NonZero<SecretScalar<E>>,
+    /// Additive public shares of all signers
+    X: Vec<NonZero<Point<E>>>,
+    /// Public key (with additive shift applied)
+    pk: Point<E>,
+    /// Auxiliary data of all signers
+    R: Vec<PartyAux>,
+}
+
+/// Converts polynomial (VSS) key share into additive share of t-out-of-t signing and
+/// applies additive shift (if any)
+///
+/// Any set of at least `min_signers` signers can take part. If there are more than
+/// `min_signers` of them, Lagrange interpolation is carried out over all of `S`, so all
+/// signers in `S` contribute to the signature and output `t` equals to `S.len()`.
+///
+/// Used by both (3+1)-round and (5+1)-round signing protocols
+fn map_t_out_of_n_to_t_out_of_t<E: Curve, L: SecurityLevel>(
+    key_share: &KeyShare<E, L>,
+    i: PartyIndex,
+    S: &[PartyIndex],
+    additive_shift: Option<Scalar<E>>,
+) -> Result<TOutOfT<E>, SigningError> {
     // Validate arguments
     let n: u16 = key_share
         .aux
@@ -540,31 +1279,73 @@ where
         .as_ref()
         .map(|s| s.min_signers)
         .unwrap_or(n);
-    if S.len() != usize::from(t) {
+    if S.iter().any(|&S_j| S_j >= n) {
+        return Err(InvalidArgs::InvalidS.into());
+    }
+    if (1..S.len()).any(|j| S[..j].contains(&S[j])) {
+        return Err(InvalidArgs::DuplicatedS.into());
+    }
+    // Signer of weighted key counts as many signers as its weight is
+    let weight_of_S = S
+        .iter()
+        .map(|&S_j| key_share.core.weight(S_j).map(usize::from))
+        .sum::<Option<usize>>()
+        .ok_or(InvalidArgs::InvalidS)?;
+    if weight_of_S < usize::from(t) {
         return Err(InvalidArgs::MismatchedAmountOfParties.into());
     }
+    // S has no duplicates and all its elements are less than n, so it fits into u16
+    let t = u16::try_from(S.len()).map_err(|_| Bug::PartiesNumberExceedsU16)?;
     if !(i < t) {
         return Err(InvalidArgs::SignerIndexOutOfBounds.into());
     }
-    if S.iter().any(|&S_j| S_j >= n) {
-        return Err(InvalidArgs::InvalidS.into());
-    }
 
     // Assemble x_i and \vec X
-    let (mut x_i, mut X) = if let Some(VssSetup { I, .. }) = &key_share.core.vss_setup {
-        // For t-out-of-n keys generated via VSS DKG scheme
-        let I = utils::subset(S, I).ok_or(Bug::Subset)?;
-        let X = utils::subset(S, &key_share.core.public_shares).ok_or(Bug::Subset)?;
-
-        let lambda_i = lagrange_coefficient_at_zero(usize::from(i), &I).ok_or(Bug::LagrangeCoef)?;
-        let x_i = (lambda_i * &key_share.core.x).into_secret();
+    let (mut x_i, mut X) = if key_share.core.vss_setup.is_some() {
+        // For t-out-of-n keys generated via VSS DKG scheme. Signer of weighted key holds
+        // several key shares, so interpolation is carried out over key shares of all signers,
+        // and additive share of the signer is a sum of its key shares times their interpolation
+        // coefficients. Hierarchical keys are interpolated via Birkhoff interpolation, which
+        // fails if signers don't satisfy the access structure.
+        let shares = S
+            .iter()
+            .map(|&S_j| key_share.core.shares_of(S_j))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Bug::Subset)?;
+        let lambda = key_share
+            .core
+            .interpolation_coefficients(S)
+            .ok_or(InvalidArgs::UnauthorizedSigners)?;
+        // `lambda[positions[j]]` are interpolation coefficients of key shares of j-th signer
+        let positions = shares
+            .iter()
+            .scan(0, |start, s| {
+                let positions = *start..*start + s.len();
+                *start = positions.end;
+                Some(positions)
+            })
+            .collect::<Vec<_>>();
 
-        let lambda = (0..t).map(|j| lagrange_coefficient_at_zero(usize::from(j), &I));
-        let X = lambda
-            .zip(&X)
-            .map(|(lambda_j, X_j)| Some(lambda_j? * X_j))
+        let mut x_i = lambda[positions[usize::from(i)].clone()]
+            .iter()
+            .zip(key_share.core.secret_shares())
+            .fold(Scalar::zero(), |acc, (lambda_k, x_k)| acc + lambda_k * x_k);
+        let x_i = NonZero::from_secret_scalar(SecretScalar::new(&mut x_i))
+            .ok_or(Bug::ZeroAdditiveShare)?;
+
+        let X = shares
+            .into_iter()
+            .zip(&positions)
+            .map(|(s, p)| {
+                let X_j = key_share.core.public_shares[s]
+                    .iter()
+                    .zip(&lambda[p.clone()])
+                    .map(|(X_k, lambda_k)| lambda_k * X_k)
+                    .sum::<Point<E>>();
+                NonZero::from_point(X_j)
+            })
             .collect::<Option<Vec<_>>>()
-            .ok_or(Bug::LagrangeCoef)?;
+            .ok_or(Bug::ZeroAdditiveShare)?;
 
         (x_i, X)
     } else {
@@ -590,27 +1371,15 @@ where
     );
 
     // Assemble rest of the data
-    let (p_i, q_i) = (&key_share.aux.p, &key_share.aux.q);
     let R = utils::subset(S, &key_share.aux.parties).ok_or(Bug::Subset)?;
 
-    // t-out-of-t signing
-    signing_n_out_of_n::<_, _, L, _, _>(
-        tracer,
-        rng,
-        party,
-        sid,
-        i,
+    Ok(TOutOfT {
         t,
-        &x_i,
-        &X,
-        key_share.core.shared_public_key + Shift,
-        p_i,
-        q_i,
-        &R,
-        message_to_sign,
-        enforce_reliable_broadcast,
-    )
-    .await
+        x_i,
+        X,
+        pk: key_share.core.shared_public_key + Shift,
+        R,
+    })
 }
 
 /// Original CGGMP n-out-of-n signing
@@ -619,6 +1388,7 @@ where
 /// reliability check, fixed some typos in CGGMP, etc. Differences are covered in the specs.
 async fn signing_n_out_of_n<M, E, L, D, R>(
     mut tracer: Option<&mut dyn Tracer>,
+    round_timer: Option<&mut dyn RoundTimer>,
     rng: &mut R,
     party: M,
     sid: ExecutionId<'_>,
@@ -630,6 +1400,8 @@ async fn signing_n_out_of_n<M, E, L, D, R>(
     p_i: &Integer,
     q_i: &Integer,
     R: &[PartyAux],
+    metadata: PresignatureMetadata,
+    derived: bool,
     message_to_sign: Option<DataToSign<E>>,
     enforce_reliable_broadcast: bool,
 ) -> Result<ProtocolOutput<E>, SigningError>
@@ -657,14 +1429,21 @@ where
     let security_params = crate::utils::SecurityParams::new::<L>();
 
     tracer.stage("Setup networking");
-    let mut rounds = RoundsRouter::<Msg<E, D>>::builder();
+    let mut rounds = RoundsRouter::<Msg<E, D>>::builder(i, n);
     let round1a = rounds.add_round(RoundInput::<MsgRound1a>::broadcast(i, n));
     let round1b = rounds.add_round(RoundInput::<MsgRound1b>::p2p(i, n));
     let round1a_sync = rounds.add_round(RoundInput::<MsgReliabilityCheck<D>>::broadcast(i, n));
     let round2 = rounds.add_round(RoundInput::<MsgRound2<E>>::p2p(i, n));
-    let round3 = rounds.add_round(RoundInput::<MsgRound3<E>>::p2p(i, n));
+    let round3a = rounds.add_round(RoundInput::<MsgRound3a<E>>::broadcast(i, n));
+    let round3b = rounds.add_round(RoundInput::<MsgRound3b<E>>::p2p(i, n));
+    let round3a_sync =
+        rounds.add_round(RoundInput::<MsgRound3aReliabilityCheck<D>>::broadcast(i, n));
     let round4 = rounds.add_round(RoundInput::<MsgRound4<E>>::broadcast(i, n));
-    let mut rounds = rounds.listen(incomings);
+    let round4_sync = rounds.add_round(RoundInput::<MsgRound4ReliabilityCheck<D>>::broadcast(i, n));
+    let round3_blame = rounds.add_round(RoundInput::<MsgRound3Blame<E>>::p2p(i, n));
+    let chi_blame = rounds.add_round(RoundInput::<MsgChiBlame<E>>::p2p(i, n));
+    let round4_blame = rounds.add_round(RoundInput::<MsgRound4Blame<E>>::p2p(i, n));
+    let mut rounds = rounds.listen(incomings, round_timer);
 
     // Round 1
     tracer.round_begins();
@@ -733,11 +1512,11 @@ where
     // Contains G_j, K_j sent by other parties
     let ciphertexts = rounds
         .complete(round1a)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     let psi0 = rounds
         .complete(round1b)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
@@ -767,17 +1546,21 @@ where
         tracer.receive_msgs();
         let round1a_hashes = rounds
             .complete(round1a_sync)
-            .await
+            .await?
             .map_err(IoError::receive_message)?;
         tracer.msgs_received();
         tracer.stage("Assert other parties hashed messages (reliability check)");
         let parties_have_different_hashes = round1a_hashes
             .into_iter_indexed()
             .filter(|(_j, _msg_id, hash)| hash.0 != h_i)
-            .map(|(j, msg_id, _)| (j, msg_id))
+            .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
             .collect::<Vec<_>>();
         if !parties_have_different_hashes.is_empty() {
-            return Err(SigningAborted::Round1aNotReliable(parties_have_different_hashes).into());
+            return Err(SigningAborted::new(
+                SigningAbortReason::Round1aNotReliable,
+                parties_have_different_hashes,
+            )
+            .into());
         }
     }
 
@@ -802,12 +1585,14 @@ where
             )
             .is_err()
             {
-                faulty_parties.push((j, msg1_id, msg2_id))
+                faulty_parties.push(AbortBlame::new(j, msg1_id, msg2_id))
             }
         }
 
         if !faulty_parties.is_empty() {
-            return Err(SigningAborted::EncProofOfK(faulty_parties).into());
+            return Err(
+                SigningAborted::new(SigningAbortReason::EncProofOfK, faulty_parties).into(),
+            );
         }
     }
     runtime.yield_now().await;
@@ -818,6 +1603,8 @@ where
 
     let mut beta_sum = Scalar::zero();
     let mut hat_beta_sum = Scalar::zero();
+    // Messages sent in round 2 are kept as they might be needed to identify faulty parties
+    let mut sent_round2 = Vec::with_capacity(usize::from(n) - 1);
     for (j, _, ciphertext_j) in ciphertexts.iter_indexed() {
         tracer.stage("Sample random r, hat_r, s, hat_s, beta, hat_beta");
         let R_j = &R[usize::from(j)];
@@ -953,21 +1740,21 @@ where
         .map_err(|e| Bug::PiLog(BugSource::psi_prime, e))?;
         runtime.yield_now().await;
 
+        let msg = MsgRound2 {
+            Gamma: Gamma_i,
+            D: D_ji,
+            F: F_ji,
+            hat_D: hat_D_ji,
+            hat_F: hat_F_ji,
+            psi: psi_ji,
+            hat_psi: hat_psi_ji,
+            psi_prime: psi_prime_ji,
+        };
+        sent_round2.push(msg.clone());
+
         tracer.send_msg();
         outgoings
-            .feed(Outgoing::p2p(
-                j,
-                Msg::Round2(MsgRound2 {
-                    Gamma: Gamma_i,
-                    D: D_ji,
-                    F: F_ji,
-                    hat_D: hat_D_ji,
-                    hat_F: hat_F_ji,
-                    psi: psi_ji,
-                    hat_psi: hat_psi_ji,
-                    psi_prime: psi_prime_ji,
-                }),
-            ))
+            .feed(Outgoing::p2p(j, Msg::Round2(msg)))
             .await
             .map_err(IoError::send_message)?;
         tracer.msg_sent();
@@ -983,7 +1770,7 @@ where
     tracer.receive_msgs();
     let round2_msgs = rounds
         .complete(round2)
-        .await
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
@@ -1061,18 +1848,13 @@ where
         .err();
 
         if psi_invalid.is_some() || hat_psi_invalid.is_some() || psi_prime_invalid.is_some() {
-            faulty_parties.push((
-                j,
-                ciphertext_msg_id,
-                msg_id,
-                (psi_invalid, hat_psi_invalid, psi_prime_invalid),
-            ))
+            faulty_parties.push(AbortBlame::new(j, ciphertext_msg_id, msg_id))
         }
         runtime.yield_now().await;
     }
 
     if !faulty_parties.is_empty() {
-        return Err(SigningAborted::InvalidPsi(faulty_parties).into());
+        return Err(SigningAborted::new(SigningAbortReason::InvalidPsi, faulty_parties).into());
     }
 
     // Step 2
@@ -1103,8 +1885,21 @@ where
 
     let delta_i = gamma_i.as_ref() * k_i.as_ref() + alpha_sum + beta_sum;
     let chi_i = x_i * k_i.as_ref() + hat_alpha_sum + hat_beta_sum;
+    let S_i = Gamma * chi_i;
     runtime.yield_now().await;
 
+    let my_round3a_msg = MsgRound3a {
+        delta: delta_i,
+        Delta: Delta_i,
+        S: S_i,
+    };
+    tracer.send_msg();
+    outgoings
+        .feed(Outgoing::broadcast(Msg::Round3a(my_round3a_msg.clone())))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
     for j in utils::iter_peers(i, n) {
         tracer.stage("Prove psi_prime_prime");
         let R_j = &R[usize::from(j)];
@@ -1134,11 +1929,7 @@ where
         outgoings
             .feed(Outgoing::p2p(
                 j,
-                Msg::Round3(MsgRound3 {
-                    delta: delta_i,
-                    Delta: Delta_i,
-                    psi_prime_prime,
-                }),
+                Msg::Round3b(MsgRound3b { psi_prime_prime }),
             ))
             .await
             .map_err(IoError::send_message)?;
@@ -1154,15 +1945,63 @@ where
     // Step 1
     tracer.receive_msgs();
     let round3_msgs = rounds
-        .complete(round3)
-        .await
+        .complete(round3a)
+        .await?
+        .map_err(IoError::receive_message)?;
+    let psi_prime_prime = rounds
+        .complete(round3b)
+        .await?
         .map_err(IoError::receive_message)?;
     tracer.msgs_received();
 
+    // Reliability check (if enabled). All signers need to agree on δ_j, Δ_j, otherwise
+    // only some of them would proceed to identification of faulty parties
+    if enforce_reliable_broadcast {
+        tracer.stage("Hash received msgs (reliability check)");
+        let h_i = udigest::hash_iter::<D>(
+            round3_msgs
+                .iter_including_me(&my_round3a_msg)
+                .map(|msg| unambiguous::EchoMsg { sid, round: 3, msg }),
+        );
+
+        tracer.send_msg();
+        outgoings
+            .send(Outgoing::broadcast(Msg::Round3aReliabilityCheck(
+                MsgRound3aReliabilityCheck(h_i),
+            )))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.round_begins();
+
+        tracer.receive_msgs();
+        let round3a_hashes = rounds
+            .complete(round3a_sync)
+            .await?
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+        tracer.stage("Assert other parties hashed messages (reliability check)");
+        let parties_have_different_hashes = round3a_hashes
+            .into_iter_indexed()
+            .filter(|(_j, _msg_id, hash)| hash.0 != h_i)
+            .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        if !parties_have_different_hashes.is_empty() {
+            return Err(SigningAborted::new(
+                SigningAbortReason::BroadcastNotReliable { round: 3 },
+                parties_have_different_hashes,
+            )
+            .into());
+        }
+    }
+
     tracer.stage("Validate psi_prime_prime");
     let mut faulty_parties = vec![];
-    for ((j, msg_id, msg_j), (_, ciphertext_id, ciphertext_j)) in
-        round3_msgs.iter_indexed().zip(ciphertexts.iter_indexed())
+    for (((j, msg_id, msg_j), (_, proof_id, proof_j)), ciphertext_j) in round3_msgs
+        .iter_indexed()
+        .zip(psi_prime_prime.iter_indexed())
+        .zip(ciphertexts.iter())
     {
         let R_j = &R[usize::from(j)];
         let enc_j = fast_paillier::EncryptionKey::from_n(R_j.N.clone());
@@ -1182,19 +2021,21 @@ where
             },
             &R_i.into(),
             data,
-            &msg_j.psi_prime_prime.0,
+            &proof_j.psi_prime_prime.0,
             &security_params.pi_log,
-            &msg_j.psi_prime_prime.1,
+            &proof_j.psi_prime_prime.1,
         )
         .is_err()
         {
-            faulty_parties.push((j, ciphertext_id, msg_id))
+            faulty_parties.push(AbortBlame::new(j, msg_id, proof_id))
         }
     }
     runtime.yield_now().await;
 
     if !faulty_parties.is_empty() {
-        return Err(SigningAborted::InvalidPsiPrimePrime(faulty_parties).into());
+        return Err(
+            SigningAborted::new(SigningAbortReason::InvalidPsiPrimePrime, faulty_parties).into(),
+        );
     }
 
     // Step 2
@@ -1203,18 +2044,433 @@ where
     let Delta = Delta_i + round3_msgs.iter().map(|m| m.Delta).sum::<Point<E>>();
 
     if Point::generator() * delta != Delta {
-        // Following the protocol, party should broadcast additional proofs
-        // to convince others it didn't cheat. However, since identifiable
-        // abort is not implemented yet, this part of the protocol is missing
-        return Err(SigningAborted::MismatchedDelta.into());
+        // Identification of faulty parties: each party proves that its δ_i
+        // is a decryption of ciphertext C_i which can be computed publicly
+        tracer.named_round_begins("Identification of faulty parties (δ)");
+
+        tracer.stage("Compute H_i = enc_i(gamma_i * k_i)");
+        let nu_i = Integer::gen_invertible(N_i, rng);
+        let H_i = {
+            let gamma_i_times_K_i = dec_i
+                .omul(&utils::scalar_to_bignumber(&gamma_i), &K_i)
+                .map_err(|_| Bug::PaillierOp(BugSource::H_i))?;
+            let zero_enc = dec_i
+                .encrypt_with(&Integer::ZERO, &nu_i)
+                .map_err(|_| Bug::PaillierEnc(BugSource::H_i))?;
+            dec_i
+                .encryption_key()
+                .oadd(&gamma_i_times_K_i, &zero_enc)
+                .map_err(|_| Bug::PaillierOp(BugSource::H_i))?
+        };
+
+        tracer.stage("Prove psi_mul");
+        let psi_mul = pi_mul::prove::<D>(
+            &unambiguous::ProofMul { sid, prover: i },
+            pi_mul::Data {
+                key: N_i,
+                x: &G_i,
+                y: &K_i,
+                c: &H_i,
+            },
+            pi_mul::PrivateData {
+                x: &utils::scalar_to_bignumber(&gamma_i),
+                nonce_x: &v_i,
+                nonce: &nu_i,
+            },
+            &security_params.pi_mul,
+            &mut *rng,
+        )
+        .map_err(|e| Bug::PiMul(BugSource::psi_mul, e))?;
+
+        tracer.stage("Compute C_i");
+        let received_round2 = round2_msgs.iter().cloned().collect::<Vec<_>>();
+        let C_i = combine_ciphertexts(
+            dec_i.encryption_key(),
+            &H_i,
+            received_round2.iter().map(|msg| &msg.D),
+            sent_round2.iter().map(|msg| &msg.F),
+        )
+        .map_err(|_| Bug::PaillierOp(BugSource::C_i))?;
+        let (y_i, nonce_y_i) =
+            pi_dec::decrypt_with_nonce(&dec_i, &C_i).ok_or(Bug::PaillierDec(BugSource::C_i))?;
+        runtime.yield_now().await;
+
+        for j in utils::iter_peers(i, n) {
+            tracer.stage("Prove psi_dec");
+            let R_j = &R[usize::from(j)];
+            let psi_dec = pi_dec::prove::<E, D>(
+                &unambiguous::ProofDec {
+                    sid,
+                    prover: i,
+                    hat: false,
+                },
+                &R_j.into(),
+                pi_dec::Data {
+                    key: N_i,
+                    c: &C_i,
+                    x: &delta_i,
+                },
+                pi_dec::PrivateData {
+                    y: &y_i,
+                    nonce: &nonce_y_i,
+                },
+                &security_params.pi_dec,
+                &mut *rng,
+            )
+            .map_err(|e| Bug::PiDec(BugSource::psi_dec, e))?;
+
+            tracer.send_msg();
+            outgoings
+                .feed(Outgoing::p2p(
+                    j,
+                    Msg::Round3Blame(MsgRound3Blame {
+                        H: H_i.clone(),
+                        psi_mul: psi_mul.clone(),
+                        psi_dec,
+                        round2_msgs: received_round2.clone(),
+                    }),
+                ))
+                .await
+                .map_err(IoError::send_message)?;
+            tracer.msg_sent();
+        }
+        tracer.send_msg();
+        outgoings.flush().await.map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.receive_msgs();
+        let blame_msgs = rounds
+            .complete(round3_blame)
+            .await?
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        tracer.stage("Validate forwarded round 2 messages");
+        let mut K = ciphertexts.iter().map(|c| &c.K).collect::<Vec<_>>();
+        K.insert(usize::from(i), &K_i);
+        let mut Gammas = round2_msgs.iter().map(|msg| msg.Gamma).collect::<Vec<_>>();
+        Gammas.insert(usize::from(i), Gamma_i);
+        let faulty_parties = blame_msgs
+            .iter_indexed()
+            .filter(|(j, _, msg)| {
+                !forwarded_round2_valid::<E, D>(
+                    sid,
+                    i,
+                    *j,
+                    n,
+                    R,
+                    &K,
+                    &Gammas,
+                    &sent_round2,
+                    &msg.round2_msgs,
+                    false,
+                    &security_params,
+                )
+            })
+            .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        if !faulty_parties.is_empty() {
+            return Err(
+                SigningAborted::new(SigningAbortReason::MismatchedDelta, faulty_parties).into(),
+            );
+        }
+        runtime.yield_now().await;
+
+        tracer.stage("Validate psi_mul and psi_dec");
+        let mut received = blame_msgs
+            .iter()
+            .map(|msg| msg.round2_msgs.as_slice())
+            .collect::<Vec<_>>();
+        received.insert(usize::from(i), &received_round2);
+
+        let mut faulty_parties = vec![];
+        for ((j, blame_msg_id, blame_msg), ((_, msg_id, msg), ciphertexts_j)) in blame_msgs
+            .iter_indexed()
+            .zip(round3_msgs.iter_indexed().zip(ciphertexts.iter()))
+        {
+            let R_j = &R[usize::from(j)];
+            let enc_j = fast_paillier::EncryptionKey::from_n(R_j.N.clone());
+
+            let C_j = combine_ciphertexts(
+                &enc_j,
+                &blame_msg.H,
+                received[usize::from(j)].iter().map(|msg| &msg.D),
+                utils::iter_peers(j, n)
+                    .map(|l| &received[usize::from(l)][utils::peer_position(l, j)].F),
+            );
+            let Ok(C_j) = C_j else {
+                faulty_parties.push(AbortBlame::new(j, msg_id, blame_msg_id));
+                continue;
+            };
+
+            let psi_mul_invalid = pi_mul::verify::<D>(
+                &unambiguous::ProofMul { sid, prover: j },
+                pi_mul::Data {
+                    key: &R_j.N,
+                    x: &ciphertexts_j.G,
+                    y: &ciphertexts_j.K,
+                    c: &blame_msg.H,
+                },
+                &blame_msg.psi_mul.0,
+                &security_params.pi_mul,
+                &blame_msg.psi_mul.1,
+            )
+            .is_err();
+            let psi_dec_invalid = pi_dec::verify::<E, D>(
+                &unambiguous::ProofDec {
+                    sid,
+                    prover: j,
+                    hat: false,
+                },
+                &R_i.into(),
+                pi_dec::Data {
+                    key: &R_j.N,
+                    c: &C_j,
+                    x: &msg.delta,
+                },
+                &blame_msg.psi_dec.0,
+                &security_params.pi_dec,
+                &blame_msg.psi_dec.1,
+            )
+            .is_err();
+
+            if psi_mul_invalid || psi_dec_invalid {
+                faulty_parties.push(AbortBlame::new(j, msg_id, blame_msg_id));
+            }
+            runtime.yield_now().await;
+        }
+
+        if faulty_parties.is_empty() {
+            return Err(Bug::NoFaultyParties.into());
+        }
+        return Err(
+            SigningAborted::new(SigningAbortReason::MismatchedDelta, faulty_parties).into(),
+        );
+    }
+
+    tracer.stage("Compute commitments k_j R, chi_j R");
+    let delta_inv = delta.invert().ok_or(Bug::ZeroDelta)?;
+    let mut commitments = round3_msgs
+        .iter()
+        .map(|msg| SignerCommitment {
+            k_R: msg.Delta * delta_inv,
+            chi_R: msg.S * delta_inv,
+        })
+        .collect::<Vec<_>>();
+    commitments.insert(
+        usize::from(i),
+        SignerCommitment {
+            k_R: Delta_i * delta_inv,
+            chi_R: S_i * delta_inv,
+        },
+    );
+    // Sum of k_j R is guaranteed to be G by the check above, and sum of chi_j R
+    // must be a public key. Otherwise, someone sent inconsistent S_j.
+    if commitments.iter().map(|c| c.chi_R).sum::<Point<E>>() != pk {
+        // Identification of faulty parties: each party proves that S_i = y_i Γ where
+        // y_i is a plaintext of ciphertext hat_C_i which can be computed publicly
+        tracer.named_round_begins("Identification of faulty parties (χ)");
+
+        tracer.stage("Compute hat_H_i = enc_i(x_i * k_i)");
+        let hat_nu_i = Integer::gen_invertible(N_i, rng);
+        let hat_H_i = {
+            let x_i_times_K_i = dec_i
+                .omul(&utils::scalar_to_bignumber(x_i), &K_i)
+                .map_err(|_| Bug::PaillierOp(BugSource::hat_H_i))?;
+            let zero_enc = dec_i
+                .encrypt_with(&Integer::ZERO, &hat_nu_i)
+                .map_err(|_| Bug::PaillierEnc(BugSource::hat_H_i))?;
+            dec_i
+                .encryption_key()
+                .oadd(&x_i_times_K_i, &zero_enc)
+                .map_err(|_| Bug::PaillierOp(BugSource::hat_H_i))?
+        };
+
+        tracer.stage("Compute hat_C_i");
+        let received_round2 = round2_msgs.iter().cloned().collect::<Vec<_>>();
+        let hat_C_i = combine_ciphertexts(
+            dec_i.encryption_key(),
+            &hat_H_i,
+            received_round2.iter().map(|msg| &msg.hat_D),
+            sent_round2.iter().map(|msg| &msg.hat_F),
+        )
+        .map_err(|_| Bug::PaillierOp(BugSource::hat_C_i))?;
+        let (y_i, nonce_y_i) = pi_dec::decrypt_with_nonce(&dec_i, &hat_C_i)
+            .ok_or(Bug::PaillierDec(BugSource::hat_C_i))?;
+        runtime.yield_now().await;
+
+        for j in utils::iter_peers(i, n) {
+            let R_j = &R[usize::from(j)];
+
+            tracer.stage("Prove psi_mul_star");
+            let psi_mul_star = pi_mul_star::prove::<E, D>(
+                &unambiguous::ProofMulStar { sid, prover: i },
+                &R_j.into(),
+                pi_mul_star::Data {
+                    key: N_i,
+                    c: &K_i,
+                    d: &hat_H_i,
+                    x: &X[usize::from(i)],
+                },
+                pi_mul_star::PrivateData {
+                    x: &utils::scalar_to_bignumber(x_i),
+                    nonce: &hat_nu_i,
+                },
+                &security_params.pi_mul_star,
+                &mut *rng,
+            )
+            .map_err(|e| Bug::PiMulStar(BugSource::psi_mul_star, e))?;
+
+            tracer.stage("Prove psi_log");
+            let psi_log = pi_log::non_interactive::prove::<E, D>(
+                &unambiguous::ProofLogChi { sid, prover: i },
+                &R_j.into(),
+                pi_log::Data {
+                    key0: &dec_i,
+                    c: &hat_C_i,
+                    x: &S_i,
+                    b: &Gamma,
+                },
+                pi_log::PrivateData {
+                    x: &y_i,
+                    nonce: &nonce_y_i,
+                },
+                &security_params.pi_log_chi,
+                &mut *rng,
+            )
+            .map_err(|e| Bug::PiLog(BugSource::psi_log, e))?;
+
+            tracer.send_msg();
+            outgoings
+                .feed(Outgoing::p2p(
+                    j,
+                    Msg::ChiBlame(MsgChiBlame {
+                        hat_H: hat_H_i.clone(),
+                        psi_mul_star,
+                        psi_log,
+                        round2_msgs: received_round2.clone(),
+                    }),
+                ))
+                .await
+                .map_err(IoError::send_message)?;
+            tracer.msg_sent();
+        }
+        tracer.send_msg();
+        outgoings.flush().await.map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.receive_msgs();
+        let blame_msgs = rounds
+            .complete(chi_blame)
+            .await?
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        tracer.stage("Validate forwarded round 2 messages");
+        let mut K = ciphertexts.iter().map(|c| &c.K).collect::<Vec<_>>();
+        K.insert(usize::from(i), &K_i);
+        let Xs = X.iter().map(|X_j| **X_j).collect::<Vec<_>>();
+        let faulty_parties = blame_msgs
+            .iter_indexed()
+            .filter(|(j, _, msg)| {
+                !forwarded_round2_valid::<E, D>(
+                    sid,
+                    i,
+                    *j,
+                    n,
+                    R,
+                    &K,
+                    &Xs,
+                    &sent_round2,
+                    &msg.round2_msgs,
+                    true,
+                    &security_params,
+                )
+            })
+            .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        if !faulty_parties.is_empty() {
+            return Err(
+                SigningAborted::new(SigningAbortReason::MismatchedChi, faulty_parties).into(),
+            );
+        }
+        runtime.yield_now().await;
+
+        tracer.stage("Validate psi_mul_star and psi_log");
+        let mut received = blame_msgs
+            .iter()
+            .map(|msg| msg.round2_msgs.as_slice())
+            .collect::<Vec<_>>();
+        received.insert(usize::from(i), &received_round2);
+
+        let mut faulty_parties = vec![];
+        for ((j, blame_msg_id, blame_msg), (_, msg_id, msg)) in
+            blame_msgs.iter_indexed().zip(round3_msgs.iter_indexed())
+        {
+            let R_j = &R[usize::from(j)];
+            let enc_j = fast_paillier::EncryptionKey::from_n(R_j.N.clone());
+
+            let hat_C_j = combine_ciphertexts(
+                &enc_j,
+                &blame_msg.hat_H,
+                received[usize::from(j)].iter().map(|msg| &msg.hat_D),
+                utils::iter_peers(j, n)
+                    .map(|l| &received[usize::from(l)][utils::peer_position(l, j)].hat_F),
+            );
+            let Ok(hat_C_j) = hat_C_j else {
+                faulty_parties.push(AbortBlame::new(j, msg_id, blame_msg_id));
+                continue;
+            };
+
+            let psi_mul_star_invalid = pi_mul_star::verify::<E, D>(
+                &unambiguous::ProofMulStar { sid, prover: j },
+                &R_i.into(),
+                pi_mul_star::Data {
+                    key: &R_j.N,
+                    c: K[usize::from(j)],
+                    d: &blame_msg.hat_H,
+                    x: &X[usize::from(j)],
+                },
+                &blame_msg.psi_mul_star.0,
+                &security_params.pi_mul_star,
+                &blame_msg.psi_mul_star.1,
+            )
+            .is_err();
+            let psi_log_invalid = pi_log::non_interactive::verify::<E, D>(
+                &unambiguous::ProofLogChi { sid, prover: j },
+                &R_i.into(),
+                pi_log::Data {
+                    key0: &enc_j,
+                    c: &hat_C_j,
+                    x: &msg.S,
+                    b: &Gamma,
+                },
+                &blame_msg.psi_log.0,
+                &security_params.pi_log_chi,
+                &blame_msg.psi_log.1,
+            )
+            .is_err();
+
+            if psi_mul_star_invalid || psi_log_invalid {
+                faulty_parties.push(AbortBlame::new(j, msg_id, blame_msg_id));
+            }
+            runtime.yield_now().await;
+        }
+
+        if faulty_parties.is_empty() {
+            return Err(Bug::NoFaultyParties.into());
+        }
+        return Err(SigningAborted::new(SigningAbortReason::MismatchedChi, faulty_parties).into());
     }
 
-    let R = Gamma * delta.invert().ok_or(Bug::ZeroDelta)?;
-    let R = NonZero::from_point(R).ok_or(Bug::ZeroR)?;
     let presig = Presignature {
-        R,
+        R: NonZero::from_point(Gamma * delta_inv).ok_or(Bug::ZeroR)?,
         k: k_i,
         chi: SecretScalar::new(&mut chi_i.clone()),
+        signer: metadata.signers[usize::from(i)],
+        metadata,
+        commitments,
+        derived,
     };
 
     // If message is not specified, protocol terminates here and outputs partial
@@ -1228,13 +2484,14 @@ where
     tracer.named_round_begins("Partial signing");
 
     // Round 1
-    let partial_sig = presig.issue_partial_signature(message_to_sign);
+    let partial_sig = presig.issue_partial_signature_unchecked(message_to_sign);
 
+    let my_round4_msg = MsgRound4 {
+        sigma: partial_sig.sigma,
+    };
     tracer.send_msg();
     outgoings
-        .send(Outgoing::broadcast(Msg::Round4(MsgRound4 {
-            sigma: partial_sig.sigma,
-        })))
+        .send(Outgoing::broadcast(Msg::Round4(my_round4_msg.clone())))
         .await
         .map_err(IoError::send_message)?;
     tracer.msg_sent();
@@ -1242,35 +2499,383 @@ where
     // Output
     tracer.named_round_begins("Signature reconstruction");
 
-    tracer.receive_msgs();
-    let partial_sigs = rounds
-        .complete(round4)
-        .await
-        .map_err(IoError::receive_message)?;
-    tracer.msgs_received();
-    let sig = {
-        let r = NonZero::from_scalar(partial_sig.r);
-        let s = NonZero::from_scalar(
-            partial_sig.sigma + partial_sigs.iter().map(|m| m.sigma).sum::<Scalar<E>>(),
+    tracer.receive_msgs();
+    let partial_sigs = rounds
+        .complete(round4)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    // Reliability check (if enabled). All signers need to agree on σ_j, otherwise
+    // only some of them would proceed to identification of faulty parties
+    if enforce_reliable_broadcast {
+        tracer.stage("Hash received msgs (reliability check)");
+        let h_i = udigest::hash_iter::<D>(
+            partial_sigs
+                .iter_including_me(&my_round4_msg)
+                .map(|msg| unambiguous::EchoMsg { sid, round: 4, msg }),
+        );
+
+        tracer.send_msg();
+        outgoings
+            .send(Outgoing::broadcast(Msg::Round4ReliabilityCheck(
+                MsgRound4ReliabilityCheck(h_i),
+            )))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.round_begins();
+
+        tracer.receive_msgs();
+        let round4_hashes = rounds
+            .complete(round4_sync)
+            .await?
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+        tracer.stage("Assert other parties hashed messages (reliability check)");
+        let parties_have_different_hashes = round4_hashes
+            .into_iter_indexed()
+            .filter(|(_j, _msg_id, hash)| hash.0 != h_i)
+            .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        if !parties_have_different_hashes.is_empty() {
+            return Err(SigningAborted::new(
+                SigningAbortReason::BroadcastNotReliable { round: 4 },
+                parties_have_different_hashes,
+            )
+            .into());
+        }
+    }
+    let sig = {
+        let r = NonZero::from_scalar(partial_sig.r);
+        let s = NonZero::from_scalar(
+            partial_sig.sigma + partial_sigs.iter().map(|m| m.sigma).sum::<Scalar<E>>(),
+        );
+        Option::zip(r, s).map(|(r, s)| {
+            Signature::from_raw_parts(r, s)
+                .with_recovery_id(partial_sig.recovery_id)
+                .normalize_s()
+        })
+    };
+    let Some(sig) = sig.filter(|sig| sig.verify(&pk, &message_to_sign).is_ok()) else {
+        // Identification of faulty parties: each party proves that its σ_i
+        // is a decryption of ciphertext S_i which can be computed publicly
+        tracer.named_round_begins("Identification of faulty parties (σ)");
+
+        tracer.stage("Compute hat_H_i = enc_i(x_i * k_i)");
+        let hat_nu_i = Integer::gen_invertible(N_i, rng);
+        let hat_H_i = {
+            let x_i_times_K_i = dec_i
+                .omul(&utils::scalar_to_bignumber(x_i), &K_i)
+                .map_err(|_| Bug::PaillierOp(BugSource::hat_H_i))?;
+            let zero_enc = dec_i
+                .encrypt_with(&Integer::ZERO, &hat_nu_i)
+                .map_err(|_| Bug::PaillierEnc(BugSource::hat_H_i))?;
+            dec_i
+                .encryption_key()
+                .oadd(&x_i_times_K_i, &zero_enc)
+                .map_err(|_| Bug::PaillierOp(BugSource::hat_H_i))?
+        };
+
+        tracer.stage("Compute S_i");
+        let r = partial_sig.r;
+        let m = message_to_sign.to_scalar();
+        let received_round2 = round2_msgs.iter().cloned().collect::<Vec<_>>();
+        let S_i = partial_signature_ciphertext(
+            dec_i.encryption_key(),
+            &K_i,
+            &hat_H_i,
+            received_round2.iter().map(|msg| &msg.hat_D),
+            sent_round2.iter().map(|msg| &msg.hat_F),
+            &r,
+            &m,
+        )
+        .map_err(|_| Bug::PaillierOp(BugSource::S_i))?;
+        let (y_i, nonce_y_i) =
+            pi_dec::decrypt_with_nonce(&dec_i, &S_i).ok_or(Bug::PaillierDec(BugSource::S_i))?;
+        runtime.yield_now().await;
+
+        for j in utils::iter_peers(i, n) {
+            let R_j = &R[usize::from(j)];
+
+            tracer.stage("Prove psi_mul_star");
+            let psi_mul_star = pi_mul_star::prove::<E, D>(
+                &unambiguous::ProofMulStar { sid, prover: i },
+                &R_j.into(),
+                pi_mul_star::Data {
+                    key: N_i,
+                    c: &K_i,
+                    d: &hat_H_i,
+                    x: &X[usize::from(i)],
+                },
+                pi_mul_star::PrivateData {
+                    x: &utils::scalar_to_bignumber(x_i),
+                    nonce: &hat_nu_i,
+                },
+                &security_params.pi_mul_star,
+                &mut *rng,
+            )
+            .map_err(|e| Bug::PiMulStar(BugSource::psi_mul_star, e))?;
+
+            tracer.stage("Prove psi_dec");
+            let psi_dec = pi_dec::prove::<E, D>(
+                &unambiguous::ProofDec {
+                    sid,
+                    prover: i,
+                    hat: true,
+                },
+                &R_j.into(),
+                pi_dec::Data {
+                    key: N_i,
+                    c: &S_i,
+                    x: &partial_sig.sigma,
+                },
+                pi_dec::PrivateData {
+                    y: &y_i,
+                    nonce: &nonce_y_i,
+                },
+                &security_params.pi_dec,
+                &mut *rng,
+            )
+            .map_err(|e| Bug::PiDec(BugSource::psi_dec, e))?;
+
+            tracer.send_msg();
+            outgoings
+                .feed(Outgoing::p2p(
+                    j,
+                    Msg::Round4Blame(MsgRound4Blame {
+                        hat_H: hat_H_i.clone(),
+                        psi_mul_star,
+                        psi_dec,
+                        round2_msgs: received_round2.clone(),
+                    }),
+                ))
+                .await
+                .map_err(IoError::send_message)?;
+            tracer.msg_sent();
+        }
+        tracer.send_msg();
+        outgoings.flush().await.map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.receive_msgs();
+        let blame_msgs = rounds
+            .complete(round4_blame)
+            .await?
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        tracer.stage("Validate forwarded round 2 messages");
+        let mut K = ciphertexts.iter().map(|c| &c.K).collect::<Vec<_>>();
+        K.insert(usize::from(i), &K_i);
+        let Xs = X.iter().map(|X_j| **X_j).collect::<Vec<_>>();
+        let faulty_parties = blame_msgs
+            .iter_indexed()
+            .filter(|(j, _, msg)| {
+                !forwarded_round2_valid::<E, D>(
+                    sid,
+                    i,
+                    *j,
+                    n,
+                    R,
+                    &K,
+                    &Xs,
+                    &sent_round2,
+                    &msg.round2_msgs,
+                    true,
+                    &security_params,
+                )
+            })
+            .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        if !faulty_parties.is_empty() {
+            return Err(
+                SigningAborted::new(SigningAbortReason::SignatureInvalid, faulty_parties).into(),
+            );
+        }
+        runtime.yield_now().await;
+
+        tracer.stage("Validate psi_mul_star and psi_dec");
+        let mut received = blame_msgs
+            .iter()
+            .map(|msg| msg.round2_msgs.as_slice())
+            .collect::<Vec<_>>();
+        received.insert(usize::from(i), &received_round2);
+
+        let mut faulty_parties = vec![];
+        for ((j, blame_msg_id, blame_msg), (_, msg_id, msg)) in
+            blame_msgs.iter_indexed().zip(partial_sigs.iter_indexed())
+        {
+            let R_j = &R[usize::from(j)];
+            let enc_j = fast_paillier::EncryptionKey::from_n(R_j.N.clone());
+
+            let S_j = partial_signature_ciphertext(
+                &enc_j,
+                K[usize::from(j)],
+                &blame_msg.hat_H,
+                received[usize::from(j)].iter().map(|msg| &msg.hat_D),
+                utils::iter_peers(j, n)
+                    .map(|l| &received[usize::from(l)][utils::peer_position(l, j)].hat_F),
+                &r,
+                &m,
+            );
+            let Ok(S_j) = S_j else {
+                faulty_parties.push(AbortBlame::new(j, msg_id, blame_msg_id));
+                continue;
+            };
+
+            let psi_mul_star_invalid = pi_mul_star::verify::<E, D>(
+                &unambiguous::ProofMulStar { sid, prover: j },
+                &R_i.into(),
+                pi_mul_star::Data {
+                    key: &R_j.N,
+                    c: K[usize::from(j)],
+                    d: &blame_msg.hat_H,
+                    x: &X[usize::from(j)],
+                },
+                &blame_msg.psi_mul_star.0,
+                &security_params.pi_mul_star,
+                &blame_msg.psi_mul_star.1,
+            )
+            .is_err();
+            let psi_dec_invalid = pi_dec::verify::<E, D>(
+                &unambiguous::ProofDec {
+                    sid,
+                    prover: j,
+                    hat: true,
+                },
+                &R_i.into(),
+                pi_dec::Data {
+                    key: &R_j.N,
+                    c: &S_j,
+                    x: &msg.sigma,
+                },
+                &blame_msg.psi_dec.0,
+                &security_params.pi_dec,
+                &blame_msg.psi_dec.1,
+            )
+            .is_err();
+
+            if psi_mul_star_invalid || psi_dec_invalid {
+                faulty_parties.push(AbortBlame::new(j, msg_id, blame_msg_id));
+            }
+            runtime.yield_now().await;
+        }
+
+        if faulty_parties.is_empty() {
+            return Err(Bug::NoFaultyParties.into());
+        }
+        return Err(
+            SigningAborted::new(SigningAbortReason::SignatureInvalid, faulty_parties).into(),
         );
-        Option::zip(r, s).map(|(r, s)| Signature { r, s }.normalize_s())
     };
-    let sig_invalid = match &sig {
-        Some(sig) => sig.verify(&pk, &message_to_sign).is_err(),
-        None => true,
-    };
-    if sig_invalid {
-        // Following the protocol, party should broadcast additional proofs
-        // to convince others it didn't cheat. However, since identifiable
-        // abort is not implemented yet, this part of the protocol is missing
-        return Err(SigningAborted::SignatureInvalid.into());
-    }
-    let sig = sig.ok_or(SigningAborted::SignatureInvalid)?;
 
     tracer.protocol_ends();
     Ok(ProtocolOutput::Signature(sig))
 }
 
+/// Computes $H \oplus \sum_\ell D_\ell \ominus \sum_\ell F_\ell$
+///
+/// Used in identification of faulty parties to publicly derive ciphertext of $\delta_j$
+/// (or $\chi_j$) from $H_j$ and ciphertexts exchanged at round 2.
+fn combine_ciphertexts<'c>(
+    enc: &fast_paillier::EncryptionKey,
+    H: &fast_paillier::Ciphertext,
+    D: impl IntoIterator<Item = &'c fast_paillier::Ciphertext>,
+    F: impl IntoIterator<Item = &'c fast_paillier::Ciphertext>,
+) -> Result<fast_paillier::Ciphertext, fast_paillier::Error> {
+    let C = D
+        .into_iter()
+        .try_fold(H.clone(), |C, D_l| enc.oadd(&C, D_l))?;
+    F.into_iter().try_fold(C, |C, F_l| enc.osub(&C, F_l))
+}
+
+/// Computes $S = (m \odot K) \oplus (r \odot \hat C)$, ciphertext of partial signature $\sigma_j$,
+/// where $\hat C$ is ciphertext of $\chi_j$ derived via [`combine_ciphertexts`]
+fn partial_signature_ciphertext<'c, E: Curve>(
+    enc: &fast_paillier::EncryptionKey,
+    K: &fast_paillier::Ciphertext,
+    hat_H: &fast_paillier::Ciphertext,
+    hat_D: impl IntoIterator<Item = &'c fast_paillier::Ciphertext>,
+    hat_F: impl IntoIterator<Item = &'c fast_paillier::Ciphertext>,
+    r: &Scalar<E>,
+    m: &Scalar<E>,
+) -> Result<fast_paillier::Ciphertext, fast_paillier::Error> {
+    let hat_C = combine_ciphertexts(enc, hat_H, hat_D, hat_F)?;
+    let m_times_K = enc.omul(&utils::scalar_to_bignumber(m), K)?;
+    let r_times_hat_C = enc.omul(&utils::scalar_to_bignumber(r), &hat_C)?;
+    enc.oadd(&m_times_K, &r_times_hat_C)
+}
+
+/// Checks that round 2 messages forwarded by $j$-th party at identification of faulty
+/// parties are the messages it actually received
+///
+/// Messages sent by us are compared against what we sent. Messages sent by other parties
+/// are checked by verifying $\psi_{j,\ell}$ (or $\hat \psi_{j,\ell}$ if `hat` is set)
+/// against $\Gamma_\ell$ (or $X_\ell$) provided in `Y`.
+fn forwarded_round2_valid<E, D>(
+    sid: ExecutionId,
+    i: PartyIndex,
+    j: PartyIndex,
+    n: u16,
+    R: &[PartyAux],
+    K: &[&fast_paillier::Ciphertext],
+    Y: &[Point<E>],
+    sent_round2: &[MsgRound2<E>],
+    forwarded: &[MsgRound2<E>],
+    hat: bool,
+    security_params: &utils::SecurityParams,
+) -> bool
+where
+    E: Curve,
+    D: Digest<OutputSize = digest::typenum::U32> + Clone + 'static,
+{
+    if forwarded.len() != usize::from(n) - 1 {
+        return false;
+    }
+    let R_j = &R[usize::from(j)];
+    let enc_j = fast_paillier::EncryptionKey::from_n(R_j.N.clone());
+
+    utils::iter_peers(j, n).zip(forwarded).all(|(l, msg)| {
+        if l == i {
+            let sent = &sent_round2[utils::peer_position(i, j)];
+            return msg.Gamma == sent.Gamma
+                && msg.D == sent.D
+                && msg.F == sent.F
+                && msg.hat_D == sent.hat_D
+                && msg.hat_F == sent.hat_F;
+        }
+
+        let enc_l = fast_paillier::EncryptionKey::from_n(R[usize::from(l)].N.clone());
+        let (d, y, proof) = if hat {
+            (&msg.hat_D, &msg.hat_F, &msg.hat_psi)
+        } else {
+            (&msg.D, &msg.F, &msg.psi)
+        };
+        pi_aff::non_interactive::verify::<E, D>(
+            &unambiguous::ProofPsi {
+                sid,
+                prover: l,
+                hat,
+            },
+            &R_j.into(),
+            pi_aff::Data {
+                key0: &enc_j,
+                key1: &enc_l,
+                c: K[usize::from(j)],
+                d,
+                y,
+                x: &Y[usize::from(l)],
+            },
+            &proof.0,
+            &security_params.pi_aff,
+            &proof.1,
+        )
+        .is_ok()
+    })
+}
+
 impl<E> Presignature<E>
 where
     E: Curve,
@@ -1278,16 +2883,104 @@ where
 {
     /// Issues partial signature for given message
     ///
+    /// `key_share` must be the key share that was used to generate the presignature, otherwise
+    /// [`PresignatureMismatch`] error is returned. Note that it's the master key share even if
+    /// [derivation path](Self::set_derivation_path) was set.
+    ///
     /// **Never reuse presignatures!** If you use the same presignatures to sign two different
-    /// messages, it leaks the private key!
-    pub fn issue_partial_signature(self, message_to_sign: DataToSign<E>) -> PartialSignature<E> {
+    /// messages, it leaks the private key! Partial signatures are issued only by
+    /// [`store::PresignatureStore`] which guarantees that.
+    pub(crate) fn issue_partial_signature<L: SecurityLevel>(
+        self,
+        key_share: &KeyShare<E, L>,
+        message_to_sign: DataToSign<E>,
+    ) -> Result<PartialSignature<E>, PresignatureMismatch> {
+        if self.metadata.key_fingerprint != key_fingerprint(key_share) {
+            return Err(PresignatureMismatch::Key);
+        }
+        if self.metadata.aux_fingerprint != aux_fingerprint(key_share) {
+            return Err(PresignatureMismatch::AuxInfo);
+        }
+        if self.signer != key_share.core.i {
+            return Err(PresignatureMismatch::Signer {
+                presignature: self.signer,
+                key_share: key_share.core.i,
+            });
+        }
+        Ok(self.issue_partial_signature_unchecked(message_to_sign))
+    }
+
+    fn issue_partial_signature_unchecked(
+        self,
+        message_to_sign: DataToSign<E>,
+    ) -> PartialSignature<E> {
         let r = self.R.x().to_scalar();
         let m = message_to_sign.to_scalar();
         let sigma_i = self.k.as_ref() * m + r * self.chi.as_ref();
-        PartialSignature { r, sigma: sigma_i }
+        PartialSignature {
+            r,
+            sigma: sigma_i,
+            signer: self.signer,
+            metadata: self.metadata,
+            recovery_id: RecoveryId::of(&self.R),
+        }
+    }
+
+    /// Returns recovery id of the signature that will be produced from this presignature
+    /// before low-s normalization
+    pub fn recovery_id(&self) -> RecoveryId {
+        RecoveryId::of(&self.R)
+    }
+}
+
+impl PresignatureMetadata {
+    fn new<E: Curve, L: SecurityLevel>(
+        key_share: &KeyShare<E, L>,
+        signers: &[PartyIndex],
+        execution_id: ExecutionId,
+    ) -> Self {
+        Self {
+            signers: signers.to_vec(),
+            key_fingerprint: key_fingerprint(key_share),
+            aux_fingerprint: aux_fingerprint(key_share),
+            execution_id: execution_id.as_bytes().to_vec(),
+        }
     }
 }
 
+fn key_fingerprint<E: Curve, L: SecurityLevel>(key_share: &KeyShare<E, L>) -> [u8; 32] {
+    let vss_setup = key_share.core.vss_setup.as_ref();
+    udigest::hash::<sha2::Sha256>(&unambiguous::KeyFingerprint {
+        shared_public_key: &key_share.core.shared_public_key,
+        public_shares: &key_share.core.public_shares,
+        min_signers: vss_setup.map(|setup| setup.min_signers),
+        I: vss_setup.map(|setup| setup.I.as_slice()),
+        weights: vss_setup.and_then(|setup| setup.weights.as_deref()),
+        ranks: vss_setup.and_then(|setup| setup.ranks.as_deref()),
+        #[cfg(feature = "hd-wallet")]
+        chain_code: key_share.core.chain_code,
+        #[cfg(not(feature = "hd-wallet"))]
+        chain_code: None,
+    })
+    .into()
+}
+
+fn aux_fingerprint<E: Curve, L: SecurityLevel>(key_share: &KeyShare<E, L>) -> [u8; 32] {
+    udigest::hash::<sha2::Sha256>(&unambiguous::AuxFingerprint {
+        parties: key_share
+            .aux
+            .parties
+            .iter()
+            .map(|aux| unambiguous::PartyAuxFingerprint {
+                N: &aux.N,
+                s: &aux.s,
+                t: &aux.t,
+            })
+            .collect(),
+    })
+    .into()
+}
+
 impl<E: Curve> Presignature<E> {
     /// Specifies HD derivation path
     ///
@@ -1301,6 +2994,10 @@ impl<E: Curve> Presignature<E> {
     /// assoicated with the key share that was used to generate presignature.
     /// Using wrong `epub` will simply lead to invalid signature.
     ///
+    /// Derivation path can be set only once. Returns [`SetDerivationPathError::AlreadySet`] if
+    /// it was already set, including when it was specified via
+    /// [`SigningBuilder::set_derivation_path`] at presignature generation.
+    ///
     /// ## Derivation algorithm
     /// This method uses [`hd_wallet::Slip10`] derivation algorithm, which can only be used with secp256k1
     /// and secp256r1 curves. If you need to use another one, see
@@ -1310,7 +3007,7 @@ impl<E: Curve> Presignature<E> {
         self,
         epub: hd_wallet::ExtendedPublicKey<E>,
         derivation_path: impl IntoIterator<Item = Index>,
-    ) -> Result<Self, <Index as TryInto<hd_wallet::NonHardenedIndex>>::Error>
+    ) -> Result<Self, SetDerivationPathError<<Index as TryInto<hd_wallet::NonHardenedIndex>>::Error>>
     where
         hd_wallet::Slip10: hd_wallet::HdWallet<E>,
         hd_wallet::NonHardenedIndex: TryFrom<Index>,
@@ -1329,24 +3026,117 @@ impl<E: Curve> Presignature<E> {
     /// key](crate::key_share::DirtyIncompleteKeyShare::extended_public_key)
     /// assoicated with the key share that was used to generate presignature.
     /// Using wrong `epub` will simply lead to invalid signature.
+    ///
+    /// Derivation path can be set only once. Returns [`SetDerivationPathError::AlreadySet`] if
+    /// it was already set, including when it was specified via
+    /// [`SigningBuilder::set_derivation_path_with_algo`] at presignature generation.
     #[cfg(feature = "hd-wallet")]
     pub fn set_derivation_path_with_algo<Hd: hd_wallet::HdWallet<E>, Index>(
-        mut self,
+        self,
         epub: hd_wallet::ExtendedPublicKey<E>,
         derivation_path: impl IntoIterator<Item = Index>,
-    ) -> Result<Self, <Index as TryInto<hd_wallet::NonHardenedIndex>>::Error>
+    ) -> Result<Self, SetDerivationPathError<<Index as TryInto<hd_wallet::NonHardenedIndex>>::Error>>
     where
         hd_wallet::NonHardenedIndex: TryFrom<Index>,
     {
-        let additive_shift = derive_additive_shift::<E, Hd, _>(epub, derivation_path)?;
+        if self.derived {
+            return Err(SetDerivationPathError::AlreadySet);
+        }
+        let additive_shift = derive_additive_shift::<E, Hd, _>(epub, derivation_path)
+            .map_err(SetDerivationPathError::InvalidPath)?;
+        Ok(self.apply_additive_shift(additive_shift))
+    }
 
+    #[cfg(feature = "hd-wallet")]
+    fn apply_additive_shift(mut self, additive_shift: Scalar<E>) -> Self {
+        debug_assert!(!self.derived);
         let mut chi = self.chi + additive_shift * &self.k;
         self.chi = SecretScalar::new(&mut chi);
+        for commitment in &mut self.commitments {
+            commitment.apply_additive_shift(additive_shift);
+        }
+        self.derived = true;
+        self
+    }
+
+    /// Returns public part of the presignature
+    ///
+    /// Public data reflects derivation path only if it was set before obtaining public data.
+    /// If derivation path is set afterwards, e.g. by
+    /// [`PresignatureStore::consume_with_derivation_path`](store::PresignatureStore::consume_with_derivation_path),
+    /// the same path must be set on public data via
+    /// [`PresignaturePublicData::set_derivation_path_with_algo`].
+    pub fn public_data(&self) -> PresignaturePublicData<E> {
+        PresignaturePublicData {
+            R: self.R,
+            commitments: self.commitments.clone(),
+            metadata: self.metadata.clone(),
+        }
+    }
+}
+
+impl<E: Curve> PresignaturePublicData<E> {
+    /// Specifies HD derivation path
+    ///
+    /// Outputs public data matching presignature with derivation path set, so it can be
+    /// used to verify partial signatures issued by a child key derived from master `epub`
+    /// using `derivation_path`. Derivation path must be the same one that signers used.
+    ///
+    /// Derivation path must not be set if public data was obtained from a presignature that
+    /// already has derivation path set, otherwise commitments are shifted twice and all partial
+    /// signatures are deemed invalid by [`PartialSignature::combine_verified`].
+    ///
+    /// ## Derivation algorithm
+    /// This method uses [`hd_wallet::Slip10`] derivation algorithm, which can only be used with secp256k1
+    /// and secp256r1 curves. If you need to use another one, see
+    /// [`set_derivation_path_with_algo`](Self::set_derivation_path_with_algo)
+    #[cfg(all(feature = "hd-wallet", feature = "hd-slip10"))]
+    pub fn set_derivation_path<Index>(
+        self,
+        epub: hd_wallet::ExtendedPublicKey<E>,
+        derivation_path: impl IntoIterator<Item = Index>,
+    ) -> Result<Self, <Index as TryInto<hd_wallet::NonHardenedIndex>>::Error>
+    where
+        hd_wallet::Slip10: hd_wallet::HdWallet<E>,
+        hd_wallet::NonHardenedIndex: TryFrom<Index>,
+    {
+        self.set_derivation_path_with_algo::<hd_wallet::Slip10, _>(epub, derivation_path)
+    }
 
+    /// Specifies HD derivation path
+    ///
+    /// Outputs public data matching presignature with derivation path set, so it can be
+    /// used to verify partial signatures issued by a child key derived from master `epub`
+    /// using `derivation_path`. Derivation path must be the same one that signers used.
+    ///
+    /// Derivation path must not be set if public data was obtained from a presignature that
+    /// already has derivation path set, otherwise commitments are shifted twice and all partial
+    /// signatures are deemed invalid by [`PartialSignature::combine_verified`].
+    #[cfg(feature = "hd-wallet")]
+    pub fn set_derivation_path_with_algo<Hd: hd_wallet::HdWallet<E>, Index>(
+        mut self,
+        epub: hd_wallet::ExtendedPublicKey<E>,
+        derivation_path: impl IntoIterator<Item = Index>,
+    ) -> Result<Self, <Index as TryInto<hd_wallet::NonHardenedIndex>>::Error>
+    where
+        hd_wallet::NonHardenedIndex: TryFrom<Index>,
+    {
+        let additive_shift = derive_additive_shift::<E, Hd, _>(epub, derivation_path)?;
+        for commitment in &mut self.commitments {
+            commitment.apply_additive_shift(additive_shift);
+        }
         Ok(self)
     }
 }
 
+#[cfg(feature = "hd-wallet")]
+impl<E: Curve> SignerCommitment<E> {
+    /// Updates commitment for presignature share $\chi_j$ shifted to $\chi_j + \text{shift} \cdot k_j$
+    fn apply_additive_shift(&mut self, additive_shift: Scalar<E>) {
+        self.chi_R += self.k_R * additive_shift;
+    }
+}
+
 #[cfg(feature = "hd-wallet")]
 fn derive_additive_shift<E: Curve, Hd: hd_wallet::HdWallet<E>, Index>(
     mut epub: hd_wallet::ExtendedPublicKey<E>,
@@ -1369,21 +3159,131 @@ where
 }
 
 impl<E: Curve> PartialSignature<E> {
+    /// Index of the signer (at keygen) that issued the partial signature
+    pub fn signer(&self) -> PartyIndex {
+        self.signer
+    }
+
+    /// Metadata of the presignature partial signature was issued from
+    pub fn metadata(&self) -> &PresignatureMetadata {
+        &self.metadata
+    }
+
+    /// Recovery id of presignature $R$, before low-s normalization
+    pub fn recovery_id(&self) -> RecoveryId {
+        self.recovery_id
+    }
+
     /// Combines threshold amount of partial signatures into regular signature
     ///
-    /// Returns `None` if input is malformed.
+    /// Exactly one partial signature from every signer that generated the presignature must be
+    /// provided. Returns [`CombineError`] if partial signatures were issued from different
+    /// presignatures, or if set of signers doesn't match the one in presignature metadata.
     ///
     /// `combine` may return a signature that's invalid for public key and message it was issued for.
     /// This would mean that some of signers cheated and aborted the protocol. You need to validate
     /// resulting signature to be sure that no one aborted the protocol.
-    pub fn combine(partial_signatures: &[PartialSignature<E>]) -> Option<Signature<E>> {
-        if partial_signatures.is_empty() {
-            None
-        } else {
-            let r = NonZero::from_scalar(partial_signatures[0].r)?;
-            let s = NonZero::from_scalar(partial_signatures.iter().map(|s| s.sigma).sum())?;
-            Some(Signature { r, s }.normalize_s())
+    pub fn combine(
+        partial_signatures: &[PartialSignature<E>],
+    ) -> Result<Signature<E>, CombineError> {
+        let first = partial_signatures
+            .first()
+            .ok_or(CombineError::NoPartialSignatures)?;
+        for (index, partial_sig) in partial_signatures.iter().enumerate() {
+            if partial_sig.metadata != first.metadata {
+                return Err(CombineError::MismatchedMetadata { index });
+            }
+            if partial_sig.r != first.r || partial_sig.recovery_id != first.recovery_id {
+                return Err(CombineError::MismatchedR { index });
+            }
+        }
+
+        let signers = &first.metadata.signers;
+        for (index, partial_sig) in partial_signatures.iter().enumerate() {
+            if !signers.contains(&partial_sig.signer) {
+                return Err(CombineError::UnexpectedSigner {
+                    signer: partial_sig.signer,
+                });
+            }
+            if partial_signatures[..index]
+                .iter()
+                .any(|other| other.signer == partial_sig.signer)
+            {
+                return Err(CombineError::DuplicatedSigner {
+                    signer: partial_sig.signer,
+                });
+            }
+        }
+        if partial_signatures.len() != signers.len() {
+            return Err(CombineError::WrongAmount {
+                expected: signers.len(),
+                actual: partial_signatures.len(),
+            });
+        }
+
+        let r = NonZero::from_scalar(first.r).ok_or(CombineError::ZeroSignature)?;
+        let s = NonZero::from_scalar(partial_signatures.iter().map(|s| s.sigma).sum())
+            .ok_or(CombineError::ZeroSignature)?;
+        Ok(Signature::from_raw_parts(r, s)
+            .with_recovery_id(first.recovery_id)
+            .normalize_s())
+    }
+
+    /// Verifies each partial signature and combines them into regular signature
+    ///
+    /// Unlike [`combine`](Self::combine), every partial signature is checked against
+    /// commitments from `public_data`, so a signer who issued a wrong partial signature is
+    /// identified. If any of partial signatures is invalid, returns
+    /// [`CombineVerifiedError::InvalidPartialSignatures`] with positions of all invalid
+    /// partial signatures in the list.
+    ///
+    /// `public_data` must be obtained from the caller's own presignature via
+    /// [`Presignature::public_data`]. Public data received from other signers must not be used:
+    /// a faulty signer could send commitments that match its invalid partial signature.
+    /// `message_to_sign` must be the same message partial signatures were issued for.
+    ///
+    /// If partial signatures were issued with a child key, e.g. via
+    /// [`PresignatureStore::consume_with_derivation_path`](store::PresignatureStore::consume_with_derivation_path),
+    /// public data must have the same derivation path set via
+    /// [`PresignaturePublicData::set_derivation_path_with_algo`]. It's not needed if public data
+    /// was obtained after derivation path was set on the presignature itself.
+    pub fn combine_verified(
+        partial_signatures: &[PartialSignature<E>],
+        public_data: &PresignaturePublicData<E>,
+        message_to_sign: DataToSign<E>,
+    ) -> Result<Signature<E>, CombineVerifiedError>
+    where
+        NonZero<Point<E>>: AlwaysHasAffineX<E>,
+    {
+        let R = public_data.R;
+        let r = R.x().to_scalar();
+        let m = message_to_sign.to_scalar();
+
+        let invalid = partial_signatures
+            .iter()
+            .enumerate()
+            .filter(|(_, partial_sig)| {
+                if partial_sig.metadata != public_data.metadata || partial_sig.r != r {
+                    return true;
+                }
+                let commitment = public_data
+                    .metadata
+                    .signers
+                    .iter()
+                    .position(|signer| *signer == partial_sig.signer)
+                    .and_then(|j| public_data.commitments.get(j));
+                let Some(commitment) = commitment else {
+                    return true;
+                };
+                R * partial_sig.sigma != commitment.k_R * m + commitment.chi_R * r
+            })
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        if !invalid.is_empty() {
+            return Err(CombineVerifiedError::InvalidPartialSignatures { indices: invalid });
         }
+
+        Ok(Self::combine(partial_signatures)?)
     }
 }
 
@@ -1408,10 +3308,71 @@ where
     }
 }
 
+impl<E: Curve> Signature<E>
+where
+    Point<E>: HasAffineXAndParity<E>,
+{
+    /// Recovers public key from the signature and the message
+    ///
+    /// Signature must carry a [recovery id](Self::recovery_id). Recovered key is the only public
+    /// key the signature is valid for.
+    pub fn recover_public_key(
+        &self,
+        message: &DataToSign<E>,
+    ) -> Result<NonZero<Point<E>>, RecoverPublicKeyError> {
+        let recovery_id = self
+            .recovery_id
+            .ok_or(RecoverPublicKeyError::NoRecoveryId)?;
+
+        let mut x = utils::scalar_to_bignumber(self.r);
+        if recovery_id.is_x_reduced() {
+            x += utils::scalar_to_bignumber(-Scalar::<E>::one()) + 1;
+        }
+        let mut x_bytes = Coordinate::<E>::default();
+        let x_len = x_bytes.as_be_bytes().len();
+        let x = x.to_digits::<u8>(paillier_zk::rug::integer::Order::Msf);
+        if x.len() > x_len {
+            return Err(RecoverPublicKeyError::InvalidSignature);
+        }
+        x_bytes.as_mut()[x_len - x.len()..].copy_from_slice(&x);
+
+        let parity = if recovery_id.is_y_odd() {
+            Parity::Odd
+        } else {
+            Parity::Even
+        };
+        let R = Point::<E>::from_x_and_parity(&x_bytes, parity)
+            .ok_or(RecoverPublicKeyError::InvalidSignature)?;
+
+        let public_key = (R * self.s - Point::generator() * message.to_scalar()) * self.r.invert();
+        NonZero::from_point(public_key).ok_or(RecoverPublicKeyError::InvalidSignature)
+    }
+}
+
 impl<E: Curve> Signature<E> {
     /// Create signature struct from `r` and `s` values
     pub fn from_raw_parts(r: NonZero<Scalar<E>>, s: NonZero<Scalar<E>>) -> Self {
-        Self { r, s }
+        Self {
+            r,
+            s,
+            recovery_id: None,
+        }
+    }
+
+    /// Returns recovery id, which allows recovering public key from the signature and the message
+    ///
+    /// Signing protocol always outputs signature with recovery id. It's `None` if signature
+    /// was constructed from $r, s$ only, e.g. via [`Signature::read_from_slice`].
+    pub fn recovery_id(&self) -> Option<RecoveryId> {
+        self.recovery_id
+    }
+
+    /// Attaches recovery id to the signature
+    pub fn with_recovery_id(self, recovery_id: RecoveryId) -> Self {
+        Self {
+            recovery_id: Some(recovery_id),
+            ..self
+        }
     }
     /// Normilizes the signature
     ///
@@ -1420,10 +3381,16 @@ impl<E: Curve> Signature<E> {
     /// $s$ that is in lower half.
     ///
     /// Note that signing protocol implemented within this crate ouputs normalized signature by default.
+    ///
+    /// Negating $s$ corresponds to negating $R$, so recovery id (if present) is updated accordingly.
     pub fn normalize_s(self) -> Self {
         let neg_s = -self.s;
         if neg_s < self.s {
-            Signature { s: neg_s, ..self }
+            Signature {
+                s: neg_s,
+                recovery_id: self.recovery_id.map(RecoveryId::negate),
+                ..self
+            }
         } else {
             self
         }
@@ -1468,6 +3435,25 @@ impl<E: Curve> Signature<E> {
     pub fn serialized_len() -> usize {
         2 * Scalar::<E>::serialized_len()
     }
+
+    /// Encodes signature in DER format
+    ///
+    /// Output is ASN.1 `SEQUENCE { r INTEGER, s INTEGER }` as expected by OpenSSL, X.509, TLS
+    /// and most of KMS. Recovery id is not encoded.
+    pub fn to_der(&self) -> Vec<u8> {
+        der::encode_signature(&self.r.to_be_bytes(), &self.s.to_be_bytes())
+    }
+
+    /// Decodes signature from DER format
+    ///
+    /// Only strict DER encoding is accepted. Returns `None` if input is malformed, or if
+    /// any of the signature parts is zero or not less than curve order.
+    pub fn from_der(bytes: &[u8]) -> Option<Self> {
+        let (r, s) = der::decode_signature(bytes)?;
+        let r = Scalar::from_be_bytes(r).ok()?.try_into().ok()?;
+        let s = Scalar::from_be_bytes(s).ok()?.try_into().ok()?;
+        Some(Self::from_raw_parts(r, s))
+    }
 }
 
 enum ProtocolOutput<E: Curve> {
@@ -1480,12 +3466,36 @@ enum ProtocolOutput<E: Curve> {
 #[error("signing protocol failed")]
 pub struct SigningError(#[source] Reason);
 
+impl SigningError {
+    /// If protocol was aborted by malicious party, returns information about
+    /// the abort: its reason and parties to blame
+    pub fn aborted(&self) -> Option<&SigningAborted> {
+        match &self.0 {
+            Reason::Aborted(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// If protocol failed because some signers didn't send their messages before round
+    /// deadline, returns the round and signers that stalled
+    ///
+    /// Signers are identified by their index $i$ in the signing protocol, i.e. by position
+    /// in the list of signers.
+    pub fn timed_out(&self) -> Option<&RoundTimeout> {
+        match &self.0 {
+            Reason::Timeout(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 crate::errors::impl_from! {
     impl From for SigningError {
         err: InvalidArgs => SigningError(Reason::InvalidArgs(err)),
         err: InvalidKeyShare => SigningError(Reason::InvalidKeyShare(err)),
         err: SigningAborted => SigningError(Reason::Aborted(err)),
         err: IoError => SigningError(Reason::IoError(err)),
+        err: RoundTimeout => SigningError(Reason::Timeout(err)),
         err: Bug => SigningError(Reason::Bug(err)),
     }
 }
@@ -1514,50 +3524,70 @@ enum Reason {
     ),
     #[error("i/o error")]
     IoError(#[source] IoError),
+    /// Some signers didn't send their messages before round deadline
+    #[error("round deadline expired")]
+    Timeout(#[source] RoundTimeout),
     /// Bug occurred
     #[error("bug occurred")]
     Bug(Bug),
 }
 
-/// Error indicating that protocol was aborted by malicious party
-///
-/// It _can be_ cryptographically proven, but we do not support it yet.
-#[allow(clippy::type_complexity)]
-#[derive(Debug, Error)]
-enum SigningAborted {
+/// Error indicating that signing was aborted by malicious party
+pub type SigningAborted = Aborted<SigningAbortReason>;
+
+/// Reason for signing abort: which exact check has failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum SigningAbortReason {
+    /// Party provided invalid $\Pi^\text{enc}$ proof for $K_j$
     #[error("pi_enc::verify(K) failed")]
-    EncProofOfK(Vec<(PartyIndex, MsgId, MsgId)>),
+    EncProofOfK,
+    /// Party provided invalid $\psi$, $\hat \psi$, or $\psi'$ proof
     #[error("ψ, ψˆ, or ψ' proofs are invalid")]
-    InvalidPsi(
-        Vec<(
-            PartyIndex,
-            MsgId,
-            MsgId,
-            (
-                Option<paillier_zk::InvalidProof>,
-                Option<paillier_zk::InvalidProof>,
-                Option<paillier_zk::InvalidProof>,
-            ),
-        )>,
-    ),
+    InvalidPsi,
+    /// Party provided invalid $\psi''$ proof
     #[error("ψ'' proof is invalid")]
-    InvalidPsiPrimePrime(Vec<(PartyIndex, MsgId, MsgId)>),
+    InvalidPsiPrimePrime,
+    /// $\Delta \ne G \cdot \delta$, party sent inconsistent $\delta_j$
     #[error("Delta != G * delta")]
     MismatchedDelta,
+    /// Resulting signature is not valid, party sent invalid partial signature
     #[error("resulting signature is not valid")]
     SignatureInvalid,
+    /// Other parties received different broadcast messages at round 1a
     #[error("other parties received different broadcast messages at round1a")]
-    Round1aNotReliable(Vec<(PartyIndex, MsgId)>),
+    Round1aNotReliable,
+    /// Other parties received different broadcast messages at given round
+    #[error("other parties received different broadcast messages at round {round}")]
+    BroadcastNotReliable {
+        /// Round at which broadcast was not reliable
+        round: u16,
+    },
+    /// Party sent malformed broadcast message at round 2
+    #[error("round 2 broadcast message is malformed")]
+    MalformedRound2,
+    /// $\sum_j S_j \ne X$, party sent inconsistent $S_j$
+    #[error("sum of S_j != X")]
+    MismatchedChi,
+    /// Party sent a message carrying unexpected amount of batch instances
+    #[error("message contains unexpected amount of batch instances")]
+    InvalidBatchSize,
 }
 
 #[derive(Debug, Error)]
 enum InvalidArgs {
-    #[error("exactly `threshold` amount of parties should take part in signing")]
+    #[error("at least `threshold` amount of parties should take part in signing")]
     MismatchedAmountOfParties,
     #[error("signer index `i` is out of bounds (must be < n)")]
     SignerIndexOutOfBounds,
     #[error("party index in S is out of bounds (must be < n)")]
     InvalidS,
+    #[error("S contains duplicated party indexes")]
+    DuplicatedS,
+    #[error("batch size must be at least 1")]
+    EmptyBatch,
+    #[error("signers are not authorized to sign by access structure of hierarchical key")]
+    UnauthorizedSigners,
 }
 
 #[derive(Debug, Error)]
@@ -1576,6 +3606,12 @@ enum Bug {
     PiAffG(BugSource, paillier_zk::Error),
     #[error("π log* failed to prove statement: {0:?}")]
     PiLog(BugSource, paillier_zk::Error),
+    #[error("π mul failed to prove statement: {0:?}")]
+    PiMul(BugSource, paillier_zk::Error),
+    #[error("π mul* failed to prove statement: {0:?}")]
+    PiMulStar(BugSource, paillier_zk::Error),
+    #[error("π dec failed to prove statement: {0:?}")]
+    PiDec(BugSource, paillier_zk::Error),
     #[error("couldn't decrypt a message: {0:?}")]
     PaillierDec(BugSource),
     #[error("delta is zero")]
@@ -1584,14 +3620,16 @@ enum Bug {
     ZeroR,
     #[error("unexpected protocol output")]
     UnexpectedProtocolOutput,
-    #[error("derive lagrange coef")]
-    LagrangeCoef,
     #[error("subset function returned error")]
     Subset,
     #[error("derived child key is zero - probability of that is negligible")]
     DerivedChildKeyZero,
     #[error("derived child share is zero - probability of that is negligible")]
     DerivedChildShareZero,
+    #[error("additive share of a signer is zero - probability of that is negligible")]
+    ZeroAdditiveShare,
+    #[error("identification of faulty parties didn't find anyone to blame")]
+    NoFaultyParties,
 }
 
 #[derive(Debug)]
@@ -1614,6 +3652,15 @@ enum BugSource {
     alpha,
     hat_alpha,
     psi_prime_prime,
+    H_i,
+    hat_H_i,
+    C_i,
+    hat_C_i,
+    S_i,
+    psi_mul,
+    psi_mul_star,
+    psi_dec,
+    psi_log,
 }
 
 /// Error indicating that signature is not valid for given public key and message
@@ -1621,6 +3668,116 @@ enum BugSource {
 #[error("signature is not valid")]
 pub struct InvalidSignature;
 
+/// Error indicating that public key can't be recovered from signature
+#[derive(Debug, Error)]
+pub enum RecoverPublicKeyError {
+    /// Signature doesn't carry recovery id
+    #[error("signature doesn't carry recovery id")]
+    NoRecoveryId,
+    /// Signature is not valid, no public key corresponds to it
+    #[error("signature is not valid")]
+    InvalidSignature,
+}
+
+/// Error indicating that byte doesn't represent a recovery id
+#[derive(Debug, Error)]
+#[error("recovery id must be in range 0..=3")]
+pub struct InvalidRecoveryId;
+
+/// Error indicating that derivation path can't be set on presignature
+#[cfg(feature = "hd-wallet")]
+#[derive(Debug, Error)]
+pub enum SetDerivationPathError<E> {
+    /// Derivation path has already been set on the presignature
+    #[error("derivation path has already been set")]
+    AlreadySet,
+    /// Derivation path is not valid
+    #[error("invalid derivation path")]
+    InvalidPath(#[source] E),
+}
+
+/// Error indicating that presignature doesn't match the key share it's used with
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum PresignatureMismatch {
+    /// Presignature was generated with a different key
+    #[error("presignature was generated with a different key")]
+    Key,
+    /// Presignature was generated with different aux info, i.e. key share was refreshed since then
+    #[error("presignature was generated with different aux info (key share was refreshed?)")]
+    AuxInfo,
+    /// Presignature belongs to another signer
+    #[error("presignature belongs to signer {presignature}, but key share belongs to signer {key_share}")]
+    Signer {
+        /// Index of the signer that owns the presignature
+        presignature: PartyIndex,
+        /// Index of the signer that owns the key share
+        key_share: PartyIndex,
+    },
+}
+
+/// Error indicating that partial signatures can't be combined
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum CombineError {
+    /// No partial signatures were provided
+    #[error("no partial signatures provided")]
+    NoPartialSignatures,
+    /// Partial signature was issued from a presignature generated by different signers, with
+    /// different key, or in different protocol execution
+    #[error("partial signature at position {index} has mismatched presignature metadata")]
+    MismatchedMetadata {
+        /// Position of partial signature in the list
+        index: usize,
+    },
+    /// Partial signature was issued from a different presignature
+    #[error("partial signature at position {index} was issued from a different presignature")]
+    MismatchedR {
+        /// Position of partial signature in the list
+        index: usize,
+    },
+    /// Partial signature was issued by a signer that didn't take part in presignature generation
+    #[error("signer {signer} didn't take part in presignature generation")]
+    UnexpectedSigner {
+        /// Index of the signer
+        signer: PartyIndex,
+    },
+    /// Signer issued more than one partial signature
+    #[error("signer {signer} issued more than one partial signature")]
+    DuplicatedSigner {
+        /// Index of the signer
+        signer: PartyIndex,
+    },
+    /// Amount of partial signatures doesn't match amount of signers
+    #[error("expected {expected} partial signatures, got {actual}")]
+    WrongAmount {
+        /// Amount of signers that generated the presignature
+        expected: usize,
+        /// Amount of provided partial signatures
+        actual: usize,
+    },
+    /// Resulting signature is zero
+    #[error("resulting signature is zero")]
+    ZeroSignature,
+}
+
+/// Error indicating that verified partial signatures can't be combined
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum CombineVerifiedError {
+    /// Some of partial signatures are invalid
+    ///
+    /// Signers that issued them either cheated or used a wrong presignature
+    #[error("partial signatures at positions {indices:?} are invalid")]
+    InvalidPartialSignatures {
+        /// Positions of invalid partial signatures in the list
+        indices: Vec<usize>,
+    },
+    /// Partial signatures are valid but can't be combined
+    #[error(transparent)]
+    Combine(#[from] CombineError),
+}
+
 #[cfg(test)]
 mod test {
     fn read_write_signature<E: generic_ec::Curve>() {
@@ -1648,4 +3805,111 @@ mod test {
     fn read_write_signature_stark() {
         read_write_signature::<crate::supported_curves::Stark>()
     }
+
+    fn der_signature<E: generic_ec::Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        for _ in 0..100 {
+            let r = generic_ec::NonZero::<generic_ec::Scalar<E>>::random(&mut rng);
+            let s = generic_ec::NonZero::<generic_ec::Scalar<E>>::random(&mut rng);
+            let signature = super::Signature::from_raw_parts(r, s);
+
+            let der = signature.to_der();
+            let signature2 = super::Signature::from_der(&der).unwrap();
+            assert!(signature == signature2, "signatures equal");
+
+            // DER and fixed-width encodings are interchangeable
+            let mut bytes = vec![0; super::Signature::<E>::serialized_len()];
+            signature2.write_to_slice(&mut bytes);
+            let signature3 = super::Signature::<E>::read_from_slice(&bytes).unwrap();
+            assert_eq!(signature3.to_der(), der);
+
+            // Trailing bytes are not allowed
+            let mut der_with_trailing = der.clone();
+            der_with_trailing.push(0);
+            assert!(super::Signature::<E>::from_der(&der_with_trailing).is_none());
+        }
+
+        // Small integers are encoded minimally
+        let one = generic_ec::NonZero::<generic_ec::Scalar<E>>::one();
+        let signature = super::Signature::from_raw_parts(one, -one);
+        let der = signature.to_der();
+        assert_eq!(der[..5], [0x30, der[1], 0x02, 0x01, 0x01]);
+        assert!(super::Signature::from_der(&der).unwrap() == signature);
+
+        // Non-minimal, negative and zero integers are rejected
+        for malformed in [
+            &[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x00][..],
+            &[0x30, 0x07, 0x02, 0x02, 0x00, 0x01, 0x02, 0x01, 0x01],
+            &[0x30, 0x06, 0x02, 0x01, 0x81, 0x02, 0x01, 0x01],
+            &[0x30, 0x81, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x01],
+            &[0x30, 0x05, 0x02, 0x01, 0x01, 0x02, 0x01],
+        ] {
+            assert!(super::Signature::<E>::from_der(malformed).is_none());
+        }
+        assert!(
+            super::Signature::<E>::from_der(&[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x01])
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn der_signature_secp256k1() {
+        der_signature::<crate::supported_curves::Secp256k1>()
+    }
+    #[test]
+    fn der_signature_secp256r1() {
+        der_signature::<crate::supported_curves::Secp256r1>()
+    }
+    #[test]
+    fn der_signature_stark() {
+        der_signature::<crate::supported_curves::Stark>()
+    }
+
+    fn recover_public_key<E: generic_ec::Curve>()
+    where
+        generic_ec::NonZero<generic_ec::Point<E>>: generic_ec::coords::AlwaysHasAffineX<E>,
+        generic_ec::Point<E>: generic_ec::coords::HasAffineXAndParity<E>,
+    {
+        use generic_ec::{coords::AlwaysHasAffineX, NonZero, Point, Scalar};
+
+        let mut rng = rand_dev::DevRng::new();
+        for _ in 0..20 {
+            let x = NonZero::<Scalar<E>>::random(&mut rng);
+            let public_key = Point::generator() * x;
+            let k = NonZero::<Scalar<E>>::random(&mut rng);
+            let R = Point::generator() * k;
+            let message = super::DataToSign::from_scalar(Scalar::random(&mut rng));
+
+            let r = NonZero::from_scalar(R.x().to_scalar()).unwrap();
+            let s = NonZero::from_scalar(k.invert() * (message.to_scalar() + r * x)).unwrap();
+            let signature = super::Signature::from_raw_parts(r, s)
+                .with_recovery_id(super::RecoveryId::of(&R))
+                .normalize_s();
+
+            signature.verify(&public_key, &message).unwrap();
+            let recovered = signature.recover_public_key(&message).unwrap();
+            assert_eq!(recovered, public_key);
+
+            // Serialized recovery id survives a round trip
+            let id = signature.recovery_id.unwrap();
+            assert_eq!(super::RecoveryId::from_byte(id.to_byte()), Some(id));
+
+            // Recovery id doesn't affect equality
+            let without_id = super::Signature::from_raw_parts(signature.r, signature.s);
+            assert!(signature == without_id, "signatures equal");
+        }
+    }
+
+    #[test]
+    fn recover_public_key_secp256k1() {
+        recover_public_key::<crate::supported_curves::Secp256k1>()
+    }
+    #[test]
+    fn recover_public_key_secp256r1() {
+        recover_public_key::<crate::supported_curves::Secp256r1>()
+    }
+    #[test]
+    fn recover_public_key_stark() {
+        recover_public_key::<crate::supported_curves::Stark>()
+    }
 }
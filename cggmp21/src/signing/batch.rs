@@ -0,0 +1,1756 @@
+//! Batched presignature generation
+//!
+//! Generates many presignatures in a single run of (3+1)-round protocol. Every instance of the
+//! batch is carried out exactly as standalone presignature generation with its own execution id
+//! derived from execution id of the batch and index of the instance. Messages of all instances
+//! sent at the same round are combined into a single message, so round-trip latency and
+//! per-message overhead are paid once per batch.
+//!
+//! If $\Delta \ne \delta \cdot G$ or $\sum_j S_j \ne \delta \cdot X$ in some of the instances,
+//! the whole batch is discarded and signers carry out identification of faulty parties for
+//! these instances in the same way as (3+1)-round protocol does.
+//!
+//! When signing a batch of messages, presignatures are followed by one more round in which
+//! partial signatures for all messages are exchanged. If any of resulting signatures is invalid,
+//! faulty parties are identified by checking their partial signatures against commitments
+//! $k_j \cdot R$ and $\chi_j \cdot R$ obtained at presignature generation, so no additional
+//! communication is needed.
+
+use digest::Digest;
+use futures::SinkExt;
+use generic_ec::{coords::AlwaysHasAffineX, Curve, NonZero, Point, Scalar, SecretScalar};
+use paillier_zk::rug::Complete;
+use paillier_zk::{fast_paillier, rug::Integer};
+use paillier_zk::{
+    group_element_vs_paillier_encryption_in_range as pi_log,
+    paillier_affine_operation_in_range as pi_aff, paillier_encryption_in_range as pi_enc,
+    IntegerExt,
+};
+use rand_core::{CryptoRng, RngCore};
+use round_based::{
+    rounds_router::simple_store::RoundInput, runtime::AsyncRuntime, Delivery, Mpc, MpcParty,
+    Outgoing, PartyIndex, ProtocolMessage,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::IoError;
+use crate::key_share::{KeyShare, PartyAux};
+use crate::progress::Tracer;
+use crate::timeout::{RoundTimer, RoundsRouter};
+use crate::utils::AbortBlame;
+use crate::zk::{
+    paillier_decryption_modulo_q as pi_dec, paillier_multiplication as pi_mul,
+    paillier_multiplication_vs_group as pi_mul_star,
+};
+use crate::{security_level::SecurityLevel, utils, ExecutionId};
+
+use super::msg as single;
+use super::{
+    combine_ciphertexts, forwarded_round2_valid, map_t_out_of_n_to_t_out_of_t, Bug, BugSource,
+    DataToSign, InvalidArgs, Presignature, PresignatureMetadata, Signature, SignerCommitment,
+    SigningAbortReason, SigningAborted, SigningError, TOutOfT,
+};
+
+/// Batched presignature generation message
+///
+/// Enumerates messages from all rounds
+#[derive(Clone, ProtocolMessage, Serialize, Deserialize)]
+#[serde(bound = "")]
+#[allow(clippy::large_enum_variant)]
+pub enum Msg<E: Curve, D: Digest> {
+    /// Round 1a message
+    Round1a(MsgRound1a),
+    /// Round 1b message
+    Round1b(MsgRound1b),
+    /// Round 2 message
+    Round2(MsgRound2<E>),
+    /// Round 3a message
+    Round3a(MsgRound3a<E>),
+    /// Round 3b message
+    Round3b(MsgRound3b<E>),
+    /// Reliability check message (optional additional round)
+    ReliabilityCheck(single::MsgReliabilityCheck<D>),
+    /// Reliability check of round 3a messages (optional additional round)
+    Round3aReliabilityCheck(single::MsgRound3aReliabilityCheck<D>),
+    /// Message proving correctness of $\delta_i$ (sent only if $\Delta \ne \delta \cdot G$
+    /// in some of the instances)
+    Round3Blame(MsgRound3Blame<E>),
+    /// Message proving correctness of $S_i$ (sent only if $\sum_j S_j \ne \delta \cdot X$
+    /// in some of the instances)
+    ChiBlame(MsgChiBlame<E>),
+    /// Round 4 message, only sent when signing a batch of messages
+    Round4(MsgRound4<E>),
+}
+
+/// Message from round 1a
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[udigest(tag = prefixed!("batch.round1"))]
+pub struct MsgRound1a {
+    /// Round 1a message of every instance
+    pub instances: Vec<single::MsgRound1a>,
+}
+
+/// Message from round 1b
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MsgRound1b {
+    /// Round 1b message of every instance
+    pub instances: Vec<single::MsgRound1b>,
+}
+
+/// Message from round 2
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound2<E: Curve> {
+    /// Round 2 message of every instance
+    pub instances: Vec<single::MsgRound2<E>>,
+}
+
+/// Message from round 3a
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[udigest(tag = prefixed!("batch.round3"))]
+#[udigest(bound = "")]
+#[serde(bound = "")]
+pub struct MsgRound3a<E: Curve> {
+    /// Round 3a message of every instance
+    pub instances: Vec<single::MsgRound3a<E>>,
+}
+
+/// Message from round 3b
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound3b<E: Curve> {
+    /// Round 3b message of every instance
+    pub instances: Vec<single::MsgRound3b<E>>,
+}
+
+/// Message from identification round that follows round 3
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound3Blame<E: Curve> {
+    /// Identification message of every instance
+    ///
+    /// `None` for instances in which $\Delta = \delta \cdot G$
+    pub instances: Vec<Option<single::MsgRound3Blame<E>>>,
+}
+
+/// Message from identification round that follows round 3 if $S_j$ are inconsistent
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgChiBlame<E: Curve> {
+    /// Identification message of every instance
+    ///
+    /// `None` for instances in which $\sum_j S_j = \delta \cdot X$
+    pub instances: Vec<Option<single::MsgChiBlame<E>>>,
+}
+
+/// Message from round 4
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound4<E: Curve> {
+    /// Partial signature of every message in the batch
+    pub instances: Vec<single::MsgRound4<E>>,
+}
+
+mod unambiguous {
+    use crate::ExecutionId;
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("batch.instance"))]
+    pub struct Instance<'a> {
+        pub sid: ExecutionId<'a>,
+        pub instance: u64,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("batch.echo_round"))]
+    pub struct Echo<'a> {
+        pub sid: ExecutionId<'a>,
+        pub ciphertexts: &'a super::MsgRound1a,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("batch.echo_msg"))]
+    #[udigest(bound = "M: udigest::Digestable")]
+    pub struct EchoMsg<'a, M> {
+        pub sid: ExecutionId<'a>,
+        pub round: u16,
+        pub msg: &'a M,
+    }
+}
+
+/// Output of batched protocol
+pub(super) enum BatchOutput<E: Curve> {
+    Presignatures(Vec<Presignature<E>>),
+    Signatures(Vec<Signature<E>>),
+}
+
+/// Ephemeral secrets of a single instance generated at round 1
+struct Ephemeral<E: Curve> {
+    gamma_i: SecretScalar<E>,
+    k_i: SecretScalar<E>,
+    v_i: Integer,
+    rho_i: Integer,
+}
+
+/// t-out-of-n batched presignature generation
+///
+/// Maps t-out-of-n key share into t-out-of-t setting in the same way as (3+1)-round
+/// protocol does, and calls [`presignatures_n_out_of_n`].
+pub(super) async fn presignatures_t_out_of_n<M, E, L, D, R>(
+    mut tracer: Option<&mut dyn Tracer>,
+    round_timer: Option<&mut dyn RoundTimer>,
+    rng: &mut R,
+    party: M,
+    sid: ExecutionId<'_>,
+    i: PartyIndex,
+    key_share: &KeyShare<E, L>,
+    S: &[PartyIndex],
+    batch_size: usize,
+    messages_to_sign: Option<&[DataToSign<E>]>,
+    enforce_reliable_broadcast: bool,
+    additive_shift: Option<Scalar<E>>,
+) -> Result<BatchOutput<E>, SigningError>
+where
+    M: Mpc<ProtocolMessage = Msg<E, D>>,
+    E: Curve,
+    L: SecurityLevel,
+    D: Digest<OutputSize = digest::typenum::U32> + Clone + 'static,
+    R: RngCore + CryptoRng,
+    NonZero<Point<E>>: AlwaysHasAffineX<E>,
+{
+    tracer.protocol_begins();
+    if batch_size == 0 {
+        return Err(InvalidArgs::EmptyBatch.into());
+    }
+    tracer.stage("Map t-out-of-n protocol to t-out-of-t");
+    let TOutOfT {
+        t, x_i, X, R, pk, ..
+    } = map_t_out_of_n_to_t_out_of_t(key_share, i, S, additive_shift)?;
+    let (p_i, q_i) = (&key_share.aux.p, &key_share.aux.q);
+    let metadata = PresignatureMetadata::new(key_share, S, sid);
+
+    // t-out-of-t presignatures generation
+    presignatures_n_out_of_n::<_, _, L, _, _>(
+        tracer,
+        round_timer,
+        rng,
+        party,
+        sid,
+        i,
+        t,
+        &x_i,
+        &X,
+        pk,
+        p_i,
+        q_i,
+        &R,
+        metadata,
+        additive_shift.is_some(),
+        batch_size,
+        messages_to_sign,
+        enforce_reliable_broadcast,
+    )
+    .await
+}
+
+/// n-out-of-n batched presignature generation
+///
+/// If `messages_to_sign` are provided, their amount must be equal to `batch_size`
+async fn presignatures_n_out_of_n<M, E, L, D, R>(
+    mut tracer: Option<&mut dyn Tracer>,
+    round_timer: Option<&mut dyn RoundTimer>,
+    rng: &mut R,
+    party: M,
+    sid: ExecutionId<'_>,
+    i: PartyIndex,
+    n: u16,
+    x_i: &NonZero<SecretScalar<E>>,
+    X: &[NonZero<Point<E>>],
+    pk: Point<E>,
+    p_i: &Integer,
+    q_i: &Integer,
+    R: &[PartyAux],
+    metadata: PresignatureMetadata,
+    derived: bool,
+    batch_size: usize,
+    messages_to_sign: Option<&[DataToSign<E>]>,
+    enforce_reliable_broadcast: bool,
+) -> Result<BatchOutput<E>, SigningError>
+where
+    M: Mpc<ProtocolMessage = Msg<E, D>>,
+    E: Curve,
+    L: SecurityLevel,
+    D: Digest<OutputSize = digest::typenum::U32> + Clone + 'static,
+    R: RngCore + CryptoRng,
+    NonZero<Point<E>>: AlwaysHasAffineX<E>,
+{
+    let MpcParty {
+        delivery, runtime, ..
+    } = party.into_party();
+    let (incomings, mut outgoings) = delivery.split();
+
+    tracer.stage("Retrieve auxiliary data");
+    let R_i = &R[usize::from(i)];
+    let N_i = &R_i.N;
+    let dec_i: fast_paillier::DecryptionKey =
+        fast_paillier::DecryptionKey::from_primes(p_i.clone(), q_i.clone())
+            .map_err(|_| Bug::InvalidOwnPaillierKey)?;
+
+    tracer.stage("Precompute execution ids and security params");
+    let security_params = crate::utils::SecurityParams::new::<L>();
+    let instance_ids = (0u64..)
+        .take(batch_size)
+        .map(|instance| udigest::hash::<D>(&unambiguous::Instance { sid, instance }))
+        .collect::<Vec<_>>();
+    let sids = instance_ids
+        .iter()
+        .map(|id| ExecutionId::new(id))
+        .collect::<Vec<_>>();
+
+    tracer.stage("Setup networking");
+    let mut rounds = RoundsRouter::<Msg<E, D>>::builder(i, n);
+    let round1a = rounds.add_round(RoundInput::<MsgRound1a>::broadcast(i, n));
+    let round1b = rounds.add_round(RoundInput::<MsgRound1b>::p2p(i, n));
+    let round1a_sync = rounds.add_round(RoundInput::<single::MsgReliabilityCheck<D>>::broadcast(
+        i, n,
+    ));
+    let round2 = rounds.add_round(RoundInput::<MsgRound2<E>>::p2p(i, n));
+    let round3a = rounds.add_round(RoundInput::<MsgRound3a<E>>::broadcast(i, n));
+    let round3b = rounds.add_round(RoundInput::<MsgRound3b<E>>::p2p(i, n));
+    let round3a_sync =
+        rounds.add_round(RoundInput::<single::MsgRound3aReliabilityCheck<D>>::broadcast(i, n));
+    let round3_blame = rounds.add_round(RoundInput::<MsgRound3Blame<E>>::p2p(i, n));
+    let chi_blame = rounds.add_round(RoundInput::<MsgChiBlame<E>>::p2p(i, n));
+    let round4 = rounds.add_round(RoundInput::<MsgRound4<E>>::broadcast(i, n));
+    let mut rounds = rounds.listen(incomings, round_timer);
+
+    // Round 1
+    tracer.round_begins();
+
+    let mut ephemeral = Vec::with_capacity(batch_size);
+    let mut my_ciphertexts = Vec::with_capacity(batch_size);
+    for _ in 0..batch_size {
+        tracer.stage("Generate local ephemeral secrets (k_i, y_i, p_i, v_i)");
+        let gamma_i = SecretScalar::<E>::random(rng);
+        let k_i = SecretScalar::<E>::random(rng);
+
+        let v_i = Integer::gen_invertible(N_i, rng);
+        let rho_i = Integer::gen_invertible(N_i, rng);
+
+        tracer.stage("Encrypt G_i and K_i");
+        let G_i = dec_i
+            .encrypt_with(&utils::scalar_to_bignumber(&gamma_i), &v_i)
+            .map_err(|_| Bug::PaillierEnc(BugSource::G_i))?;
+        let K_i = dec_i
+            .encrypt_with(&utils::scalar_to_bignumber(&k_i), &rho_i)
+            .map_err(|_| Bug::PaillierEnc(BugSource::K_i))?;
+        runtime.yield_now().await;
+
+        ephemeral.push(Ephemeral {
+            gamma_i,
+            k_i,
+            v_i,
+            rho_i,
+        });
+        my_ciphertexts.push(single::MsgRound1a { K: K_i, G: G_i });
+    }
+    let my_ciphertexts = MsgRound1a {
+        instances: my_ciphertexts,
+    };
+
+    tracer.send_msg();
+    outgoings
+        .feed(Outgoing::broadcast(Msg::Round1a(my_ciphertexts.clone())))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    for j in utils::iter_peers(i, n) {
+        let R_j = &R[usize::from(j)];
+
+        let mut instances = Vec::with_capacity(batch_size);
+        for ((eph, ciphertexts_i), sid) in
+            ephemeral.iter().zip(&my_ciphertexts.instances).zip(&sids)
+        {
+            tracer.stage("Prove ψ0_j");
+            let psi0 = pi_enc::non_interactive::prove::<D>(
+                &super::unambiguous::ProofEnc {
+                    sid: *sid,
+                    prover: i,
+                },
+                &R_j.into(),
+                pi_enc::Data {
+                    key: &dec_i,
+                    ciphertext: &ciphertexts_i.K,
+                },
+                pi_enc::PrivateData {
+                    plaintext: &utils::scalar_to_bignumber(&eph.k_i),
+                    nonce: &eph.rho_i,
+                },
+                &security_params.pi_enc,
+                &mut *rng,
+            )
+            .map_err(|e| Bug::PiEnc(BugSource::psi0, e))?;
+            instances.push(single::MsgRound1b { psi0 });
+        }
+
+        tracer.send_msg();
+        outgoings
+            .feed(Outgoing::p2p(j, Msg::Round1b(MsgRound1b { instances })))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+    }
+    tracer.send_msg();
+    outgoings.flush().await.map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Round 2
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    // Contains G_j, K_j of every instance sent by other parties
+    let ciphertexts = rounds
+        .complete(round1a)
+        .await?
+        .map_err(IoError::receive_message)?;
+    let psi0 = rounds
+        .complete(round1b)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    // Reliability check (if enabled)
+    if enforce_reliable_broadcast {
+        tracer.stage("Hash received msgs (reliability check)");
+        let h_i = udigest::hash_iter::<D>(
+            ciphertexts
+                .iter_including_me(&my_ciphertexts)
+                .map(|ciphertexts| unambiguous::Echo { sid, ciphertexts }),
+        );
+
+        tracer.send_msg();
+        outgoings
+            .send(Outgoing::broadcast(Msg::ReliabilityCheck(
+                single::MsgReliabilityCheck(h_i),
+            )))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.round_begins();
+
+        tracer.receive_msgs();
+        let round1a_hashes = rounds
+            .complete(round1a_sync)
+            .await?
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+        tracer.stage("Assert other parties hashed messages (reliability check)");
+        let parties_have_different_hashes = round1a_hashes
+            .into_iter_indexed()
+            .filter(|(_j, _msg_id, hash)| hash.0 != h_i)
+            .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        if !parties_have_different_hashes.is_empty() {
+            return Err(SigningAborted::new(
+                SigningAbortReason::Round1aNotReliable,
+                parties_have_different_hashes,
+            )
+            .into());
+        }
+    }
+
+    tracer.stage("Validate batch size");
+    let faulty_parties = utils::collect_blame(&ciphertexts, &psi0, |_, ciphertexts, psi0| {
+        ciphertexts.instances.len() != batch_size || psi0.instances.len() != batch_size
+    });
+    if !faulty_parties.is_empty() {
+        return Err(
+            SigningAborted::new(SigningAbortReason::InvalidBatchSize, faulty_parties).into(),
+        );
+    }
+
+    // Step 1. Verify proofs
+    tracer.stage("Verify psi0 proofs");
+    {
+        let mut faulty_parties = vec![];
+        for ((j, msg1_id, ciphertexts_j), (_, msg2_id, proofs)) in
+            ciphertexts.iter_indexed().zip(psi0.iter_indexed())
+        {
+            let R_j = &R[usize::from(j)];
+            let enc_j = fast_paillier::EncryptionKey::from_n(R_j.N.clone());
+            let invalid = ciphertexts_j
+                .instances
+                .iter()
+                .zip(&proofs.instances)
+                .zip(&sids)
+                .any(|((ciphertext, proof), sid)| {
+                    pi_enc::non_interactive::verify::<D>(
+                        &super::unambiguous::ProofEnc {
+                            sid: *sid,
+                            prover: j,
+                        },
+                        &R_i.into(),
+                        pi_enc::Data {
+                            key: &enc_j,
+                            ciphertext: &ciphertext.K,
+                        },
+                        &proof.psi0.0,
+                        &security_params.pi_enc,
+                        &proof.psi0.1,
+                    )
+                    .is_err()
+                });
+            if invalid {
+                faulty_parties.push(AbortBlame::new(j, msg1_id, msg2_id))
+            }
+            runtime.yield_now().await;
+        }
+
+        if !faulty_parties.is_empty() {
+            return Err(
+                SigningAborted::new(SigningAbortReason::EncProofOfK, faulty_parties).into(),
+            );
+        }
+    }
+
+    // Step 2
+    let Gammas_i = ephemeral
+        .iter()
+        .map(|eph| Point::generator() * &eph.gamma_i)
+        .collect::<Vec<_>>();
+    let J = (Integer::ONE << L::ELL_PRIME).complete();
+
+    let mut beta_sums = vec![Scalar::<E>::zero(); batch_size];
+    let mut hat_beta_sums = vec![Scalar::<E>::zero(); batch_size];
+    // Messages sent in round 2 are kept as they might be needed to identify faulty parties,
+    // `sent_round2[l]` contains messages sent in `l`-th instance
+    let mut sent_round2 = vec![Vec::with_capacity(usize::from(n) - 1); batch_size];
+    for (j, _, ciphertexts_j) in ciphertexts.iter_indexed() {
+        let R_j = &R[usize::from(j)];
+        let N_j = &R_j.N;
+        let enc_j = fast_paillier::EncryptionKey::from_n(N_j.clone());
+
+        let mut instances = Vec::with_capacity(batch_size);
+        for (l, (eph, ciphertext_j)) in ephemeral.iter().zip(&ciphertexts_j.instances).enumerate() {
+            let sid = sids[l];
+            let Gamma_i = Gammas_i[l];
+            let G_i = &my_ciphertexts.instances[l].G;
+
+            tracer.stage("Sample random r, hat_r, s, hat_s, beta, hat_beta");
+            let r_ij = N_i.random_below_ref(&mut utils::external_rand(rng)).into();
+            let hat_r_ij = N_i.random_below_ref(&mut utils::external_rand(rng)).into();
+            let s_ij = N_j.random_below_ref(&mut utils::external_rand(rng)).into();
+            let hat_s_ij = N_j.random_below_ref(&mut utils::external_rand(rng)).into();
+
+            let beta_ij = Integer::from_rng_pm(&J, rng);
+            let hat_beta_ij = Integer::from_rng_pm(&J, rng);
+
+            beta_sums[l] += beta_ij.to_scalar();
+            hat_beta_sums[l] += hat_beta_ij.to_scalar();
+
+            tracer.stage("Encrypt D_ji");
+            // D_ji = (gamma_i * K_j) + enc_j(-beta_ij, s_ij)
+            let D_ji = {
+                let gamma_i_times_K_j = enc_j
+                    .omul(&utils::scalar_to_bignumber(&eph.gamma_i), &ciphertext_j.K)
+                    .map_err(|_| Bug::PaillierOp(BugSource::gamma_i_times_K_j))?;
+                let neg_beta_ij_enc = enc_j
+                    .encrypt_with(&(-&beta_ij).complete(), &s_ij)
+                    .map_err(|_| Bug::PaillierEnc(BugSource::neg_beta_ij_enc))?;
+                enc_j
+                    .oadd(&gamma_i_times_K_j, &neg_beta_ij_enc)
+                    .map_err(|_| Bug::PaillierOp(BugSource::D_ji))?
+            };
+
+            tracer.stage("Encrypt F_ji");
+            let F_ji = dec_i
+                .encrypt_with(&(-&beta_ij).complete(), &r_ij)
+                .map_err(|_| Bug::PaillierEnc(BugSource::F_ji))?;
+
+            tracer.stage("Encrypt hat_D_ji");
+            // Dˆ_ji = (x_i * K_j) + enc_j(-hat_beta_ij, hat_s_ij)
+            let hat_D_ji = {
+                let x_i_times_K_j = enc_j
+                    .omul(&utils::scalar_to_bignumber(x_i), &ciphertext_j.K)
+                    .map_err(|_| Bug::PaillierOp(BugSource::x_i_times_K_j))?;
+                let neg_hat_beta_ij_enc = enc_j
+                    .encrypt_with(&(-&hat_beta_ij).complete(), &hat_s_ij)
+                    .map_err(|_| Bug::PaillierEnc(BugSource::hat_beta_ij_enc))?;
+                enc_j
+                    .oadd(&x_i_times_K_j, &neg_hat_beta_ij_enc)
+                    .map_err(|_| Bug::PaillierOp(BugSource::hat_D))?
+            };
+            runtime.yield_now().await;
+
+            tracer.stage("Encrypt hat_F_ji");
+            let hat_F_ji = dec_i
+                .encrypt_with(&(-&hat_beta_ij).complete(), &hat_r_ij)
+                .map_err(|_| Bug::PaillierEnc(BugSource::hat_F))?;
+
+            tracer.stage("Prove psi_ji");
+            let psi_ji = pi_aff::non_interactive::prove::<E, D>(
+                &super::unambiguous::ProofPsi {
+                    sid,
+                    prover: i,
+                    hat: false,
+                },
+                &R_j.into(),
+                pi_aff::Data {
+                    key0: &enc_j,
+                    key1: &dec_i,
+                    c: &ciphertext_j.K,
+                    d: &D_ji,
+                    y: &F_ji,
+                    x: &Gamma_i,
+                },
+                pi_aff::PrivateData {
+                    x: &utils::scalar_to_bignumber(&eph.gamma_i),
+                    y: &(-&beta_ij).complete(),
+                    nonce: &s_ij,
+                    nonce_y: &r_ij,
+                },
+                &security_params.pi_aff,
+                &mut *rng,
+            )
+            .map_err(|e| Bug::PiAffG(BugSource::psi, e))?;
+            runtime.yield_now().await;
+
+            tracer.stage("Prove psiˆ_ji");
+            let hat_psi_ji = pi_aff::non_interactive::prove::<E, D>(
+                &super::unambiguous::ProofPsi {
+                    sid,
+                    prover: i,
+                    hat: true,
+                },
+                &R_j.into(),
+                pi_aff::Data {
+                    key0: &enc_j,
+                    key1: &dec_i,
+                    c: &ciphertext_j.K,
+                    d: &hat_D_ji,
+                    y: &hat_F_ji,
+                    x: &(Point::generator() * x_i),
+                },
+                pi_aff::PrivateData {
+                    x: &utils::scalar_to_bignumber(x_i),
+                    y: &(-&hat_beta_ij).complete(),
+                    nonce: &hat_s_ij,
+                    nonce_y: &hat_r_ij,
+                },
+                &security_params.pi_aff,
+                &mut *rng,
+            )
+            .map_err(|e| Bug::PiAffG(BugSource::hat_psi, e))?;
+
+            tracer.stage("Prove psi_prime_ji ");
+            let psi_prime_ji = pi_log::non_interactive::prove::<E, D>(
+                &super::unambiguous::ProofLog {
+                    sid,
+                    prover: i,
+                    prime_prime: false,
+                },
+                &R_j.into(),
+                pi_log::Data {
+                    key0: &dec_i,
+                    c: G_i,
+                    x: &Gamma_i,
+                    b: &Point::<E>::generator().to_point(),
+                },
+                pi_log::PrivateData {
+                    x: &utils::scalar_to_bignumber(&eph.gamma_i),
+                    nonce: &eph.v_i,
+                },
+                &security_params.pi_log,
+                &mut *rng,
+            )
+            .map_err(|e| Bug::PiLog(BugSource::psi_prime, e))?;
+            runtime.yield_now().await;
+
+            let msg = single::MsgRound2 {
+                Gamma: Gamma_i,
+                D: D_ji,
+                F: F_ji,
+                hat_D: hat_D_ji,
+                hat_F: hat_F_ji,
+                psi: psi_ji,
+                hat_psi: hat_psi_ji,
+                psi_prime: psi_prime_ji,
+            };
+            sent_round2[l].push(msg.clone());
+            instances.push(msg);
+        }
+
+        tracer.send_msg();
+        outgoings
+            .feed(Outgoing::p2p(j, Msg::Round2(MsgRound2 { instances })))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+    }
+    tracer.send_msg();
+    outgoings.flush().await.map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Round 3
+    tracer.round_begins();
+
+    // Step 1
+    tracer.receive_msgs();
+    let round2_msgs = rounds
+        .complete(round2)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    tracer.stage("Validate batch size");
+    let faulty_parties =
+        utils::collect_simple_blame(&round2_msgs, |msg| msg.instances.len() != batch_size);
+    if !faulty_parties.is_empty() {
+        return Err(
+            SigningAborted::new(SigningAbortReason::InvalidBatchSize, faulty_parties).into(),
+        );
+    }
+
+    let mut faulty_parties = vec![];
+    for ((j, msg_id, msgs), (_, ciphertext_msg_id, ciphertexts_j)) in
+        round2_msgs.iter_indexed().zip(ciphertexts.iter_indexed())
+    {
+        tracer.stage("Retrieve auxiliary data");
+        let X_j = X[usize::from(j)];
+        let R_j = &R[usize::from(j)];
+        let enc_j = fast_paillier::EncryptionKey::from_n(R_j.N.clone());
+
+        let mut invalid = false;
+        for (l, (msg, ciphertexts_j)) in msgs
+            .instances
+            .iter()
+            .zip(&ciphertexts_j.instances)
+            .enumerate()
+        {
+            let sid = sids[l];
+            let K_i = &my_ciphertexts.instances[l].K;
+
+            tracer.stage("Validate psi");
+            let psi_invalid = pi_aff::non_interactive::verify::<E, D>(
+                &super::unambiguous::ProofPsi {
+                    sid,
+                    prover: j,
+                    hat: false,
+                },
+                &R_i.into(),
+                pi_aff::Data {
+                    key0: &dec_i,
+                    key1: &enc_j,
+                    c: K_i,
+                    d: &msg.D,
+                    y: &msg.F,
+                    x: &msg.Gamma,
+                },
+                &msg.psi.0,
+                &security_params.pi_aff,
+                &msg.psi.1,
+            )
+            .is_err();
+
+            tracer.stage("Validate hat_psi");
+            let hat_psi_invalid = pi_aff::non_interactive::verify::<E, D>(
+                &super::unambiguous::ProofPsi {
+                    sid,
+                    prover: j,
+                    hat: true,
+                },
+                &R_i.into(),
+                pi_aff::Data {
+                    key0: &dec_i,
+                    key1: &enc_j,
+                    c: K_i,
+                    d: &msg.hat_D,
+                    y: &msg.hat_F,
+                    x: &X_j,
+                },
+                &msg.hat_psi.0,
+                &security_params.pi_aff,
+                &msg.hat_psi.1,
+            )
+            .is_err();
+
+            tracer.stage("Validate psi_prime");
+            let psi_prime_invalid = pi_log::non_interactive::verify::<E, D>(
+                &super::unambiguous::ProofLog {
+                    sid,
+                    prover: j,
+                    prime_prime: false,
+                },
+                &R_i.into(),
+                pi_log::Data {
+                    key0: &enc_j,
+                    c: &ciphertexts_j.G,
+                    x: &msg.Gamma,
+                    b: &Point::<E>::generator().to_point(),
+                },
+                &msg.psi_prime.0,
+                &security_params.pi_log,
+                &msg.psi_prime.1,
+            )
+            .is_err();
+
+            invalid |= psi_invalid || hat_psi_invalid || psi_prime_invalid;
+            runtime.yield_now().await;
+        }
+
+        if invalid {
+            faulty_parties.push(AbortBlame::new(j, ciphertext_msg_id, msg_id))
+        }
+    }
+
+    if !faulty_parties.is_empty() {
+        return Err(SigningAborted::new(SigningAbortReason::InvalidPsi, faulty_parties).into());
+    }
+
+    // Step 2
+    let mut Gammas = Vec::with_capacity(batch_size);
+    let mut Deltas_i = Vec::with_capacity(batch_size);
+    let mut deltas_i = Vec::with_capacity(batch_size);
+    let mut chis_i = Vec::with_capacity(batch_size);
+    let mut Ss_i = Vec::with_capacity(batch_size);
+    for (l, eph) in ephemeral.iter().enumerate() {
+        tracer.stage("Compute Gamma, Delta_i, delta_i, chi_i");
+        let Gamma = Gammas_i[l]
+            + round2_msgs
+                .iter()
+                .map(|msgs| msgs.instances[l].Gamma)
+                .sum::<Point<E>>();
+        let Delta_i = Gamma * &eph.k_i;
+
+        let alpha_sum = round2_msgs
+            .iter()
+            .map(|msgs| &msgs.instances[l].D)
+            .try_fold(Scalar::<E>::zero(), |sum, D_ij| {
+                let alpha_ij = dec_i
+                    .decrypt(D_ij)
+                    .map_err(|_| Bug::PaillierDec(BugSource::alpha))?;
+                Ok::<_, Bug>(sum + alpha_ij.to_scalar())
+            })?;
+        let hat_alpha_sum = round2_msgs
+            .iter()
+            .map(|msgs| &msgs.instances[l].hat_D)
+            .try_fold(Scalar::zero(), |sum, hat_D_ij| {
+                let hat_alpha_ij = dec_i
+                    .decrypt(hat_D_ij)
+                    .map_err(|_| Bug::PaillierDec(BugSource::hat_alpha))?;
+                Ok::<_, Bug>(sum + hat_alpha_ij.to_scalar())
+            })?;
+
+        let delta_i = eph.gamma_i.as_ref() * eph.k_i.as_ref() + alpha_sum + beta_sums[l];
+        let chi_i = x_i * eph.k_i.as_ref() + hat_alpha_sum + hat_beta_sums[l];
+        runtime.yield_now().await;
+
+        Gammas.push(Gamma);
+        Deltas_i.push(Delta_i);
+        deltas_i.push(delta_i);
+        chis_i.push(chi_i);
+        Ss_i.push(Gamma * chi_i);
+    }
+
+    let my_round3a_msg = MsgRound3a {
+        instances: (0..batch_size)
+            .map(|l| single::MsgRound3a {
+                delta: deltas_i[l],
+                Delta: Deltas_i[l],
+                S: Ss_i[l],
+            })
+            .collect(),
+    };
+    tracer.send_msg();
+    outgoings
+        .feed(Outgoing::broadcast(Msg::Round3a(my_round3a_msg.clone())))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    for j in utils::iter_peers(i, n) {
+        let R_j = &R[usize::from(j)];
+
+        let mut instances = Vec::with_capacity(batch_size);
+        for (l, eph) in ephemeral.iter().enumerate() {
+            tracer.stage("Prove psi_prime_prime");
+            let psi_prime_prime = pi_log::non_interactive::prove::<E, D>(
+                &super::unambiguous::ProofLog {
+                    sid: sids[l],
+                    prover: i,
+                    prime_prime: true,
+                },
+                &R_j.into(),
+                pi_log::Data {
+                    key0: &dec_i,
+                    c: &my_ciphertexts.instances[l].K,
+                    x: &Deltas_i[l],
+                    b: &Gammas[l],
+                },
+                pi_log::PrivateData {
+                    x: &utils::scalar_to_bignumber(&eph.k_i),
+                    nonce: &eph.rho_i,
+                },
+                &security_params.pi_log,
+                &mut *rng,
+            )
+            .map_err(|e| Bug::PiLog(BugSource::psi_prime_prime, e))?;
+
+            instances.push(single::MsgRound3b { psi_prime_prime });
+        }
+
+        tracer.send_msg();
+        outgoings
+            .feed(Outgoing::p2p(j, Msg::Round3b(MsgRound3b { instances })))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+    }
+    tracer.send_msg();
+    outgoings.flush().await.map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Output
+    tracer.named_round_begins("Presig output");
+
+    // Step 1
+    tracer.receive_msgs();
+    let round3_msgs = rounds
+        .complete(round3a)
+        .await?
+        .map_err(IoError::receive_message)?;
+    let psi_prime_prime = rounds
+        .complete(round3b)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    // Reliability check (if enabled). All signers need to agree on δ_j, Δ_j of every
+    // instance, otherwise only some of them would proceed to identification of faulty parties
+    if enforce_reliable_broadcast {
+        tracer.stage("Hash received msgs (reliability check)");
+        let h_i = udigest::hash_iter::<D>(
+            round3_msgs
+                .iter_including_me(&my_round3a_msg)
+                .map(|msg| unambiguous::EchoMsg { sid, round: 3, msg }),
+        );
+
+        tracer.send_msg();
+        outgoings
+            .send(Outgoing::broadcast(Msg::Round3aReliabilityCheck(
+                single::MsgRound3aReliabilityCheck(h_i),
+            )))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.round_begins();
+
+        tracer.receive_msgs();
+        let round3a_hashes = rounds
+            .complete(round3a_sync)
+            .await?
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+        tracer.stage("Assert other parties hashed messages (reliability check)");
+        let parties_have_different_hashes = round3a_hashes
+            .into_iter_indexed()
+            .filter(|(_j, _msg_id, hash)| hash.0 != h_i)
+            .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        if !parties_have_different_hashes.is_empty() {
+            return Err(SigningAborted::new(
+                SigningAbortReason::BroadcastNotReliable { round: 3 },
+                parties_have_different_hashes,
+            )
+            .into());
+        }
+    }
+
+    tracer.stage("Validate batch size");
+    let faulty_parties = utils::collect_blame(&round3_msgs, &psi_prime_prime, |_, msgs, proofs| {
+        msgs.instances.len() != batch_size || proofs.instances.len() != batch_size
+    });
+    if !faulty_parties.is_empty() {
+        return Err(
+            SigningAborted::new(SigningAbortReason::InvalidBatchSize, faulty_parties).into(),
+        );
+    }
+
+    tracer.stage("Validate psi_prime_prime");
+    let mut faulty_parties = vec![];
+    for (((j, msg_id, msgs_j), (_, proofs_id, proofs_j)), ciphertexts_j) in round3_msgs
+        .iter_indexed()
+        .zip(psi_prime_prime.iter_indexed())
+        .zip(ciphertexts.iter())
+    {
+        let R_j = &R[usize::from(j)];
+        let enc_j = fast_paillier::EncryptionKey::from_n(R_j.N.clone());
+
+        let invalid = msgs_j
+            .instances
+            .iter()
+            .zip(&proofs_j.instances)
+            .zip(&ciphertexts_j.instances)
+            .enumerate()
+            .any(|(l, ((msg_j, proof_j), ciphertext_j))| {
+                pi_log::non_interactive::verify::<E, D>(
+                    &super::unambiguous::ProofLog {
+                        sid: sids[l],
+                        prover: j,
+                        prime_prime: true,
+                    },
+                    &R_i.into(),
+                    pi_log::Data {
+                        key0: &enc_j,
+                        c: &ciphertext_j.K,
+                        x: &msg_j.Delta,
+                        b: &Gammas[l],
+                    },
+                    &proof_j.psi_prime_prime.0,
+                    &security_params.pi_log,
+                    &proof_j.psi_prime_prime.1,
+                )
+                .is_err()
+            });
+        if invalid {
+            faulty_parties.push(AbortBlame::new(j, msg_id, proofs_id))
+        }
+        runtime.yield_now().await;
+    }
+
+    if !faulty_parties.is_empty() {
+        return Err(
+            SigningAborted::new(SigningAbortReason::InvalidPsiPrimePrime, faulty_parties).into(),
+        );
+    }
+
+    // Step 2
+    tracer.stage("Calculate presignatures");
+    let deltas = (0..batch_size)
+        .map(|l| {
+            let delta = deltas_i[l]
+                + round3_msgs
+                    .iter()
+                    .map(|msgs| msgs.instances[l].delta)
+                    .sum::<Scalar<E>>();
+            let Delta = Deltas_i[l]
+                + round3_msgs
+                    .iter()
+                    .map(|msgs| msgs.instances[l].Delta)
+                    .sum::<Point<E>>();
+            (delta, Delta)
+        })
+        .collect::<Vec<_>>();
+    let mismatched = deltas
+        .iter()
+        .map(|(delta, Delta)| Point::generator() * delta != *Delta)
+        .collect::<Vec<_>>();
+
+    if mismatched.contains(&true) {
+        // Identification of faulty parties: in every instance where Δ ≠ δ⋅G, each party
+        // proves that its δ_i is a decryption of ciphertext C_i which can be computed publicly
+        tracer.named_round_begins("Identification of faulty parties (δ)");
+
+        let mut my_blame = Vec::with_capacity(batch_size);
+        for (l, eph) in ephemeral.iter().enumerate() {
+            if !mismatched[l] {
+                my_blame.push(None);
+                continue;
+            }
+            let (G_i, K_i) = (
+                &my_ciphertexts.instances[l].G,
+                &my_ciphertexts.instances[l].K,
+            );
+
+            tracer.stage("Compute H_i = enc_i(gamma_i * k_i)");
+            let nu_i = Integer::gen_invertible(N_i, rng);
+            let H_i = {
+                let gamma_i_times_K_i = dec_i
+                    .omul(&utils::scalar_to_bignumber(&eph.gamma_i), K_i)
+                    .map_err(|_| Bug::PaillierOp(BugSource::H_i))?;
+                let zero_enc = dec_i
+                    .encrypt_with(&Integer::ZERO, &nu_i)
+                    .map_err(|_| Bug::PaillierEnc(BugSource::H_i))?;
+                dec_i
+                    .encryption_key()
+                    .oadd(&gamma_i_times_K_i, &zero_enc)
+                    .map_err(|_| Bug::PaillierOp(BugSource::H_i))?
+            };
+
+            tracer.stage("Prove psi_mul");
+            let psi_mul = pi_mul::prove::<D>(
+                &super::unambiguous::ProofMul {
+                    sid: sids[l],
+                    prover: i,
+                },
+                pi_mul::Data {
+                    key: N_i,
+                    x: G_i,
+                    y: K_i,
+                    c: &H_i,
+                },
+                pi_mul::PrivateData {
+                    x: &utils::scalar_to_bignumber(&eph.gamma_i),
+                    nonce_x: &eph.v_i,
+                    nonce: &nu_i,
+                },
+                &security_params.pi_mul,
+                &mut *rng,
+            )
+            .map_err(|e| Bug::PiMul(BugSource::psi_mul, e))?;
+
+            tracer.stage("Compute C_i");
+            let received_round2 = round2_msgs
+                .iter()
+                .map(|msgs| msgs.instances[l].clone())
+                .collect::<Vec<_>>();
+            let C_i = combine_ciphertexts(
+                dec_i.encryption_key(),
+                &H_i,
+                received_round2.iter().map(|msg| &msg.D),
+                sent_round2[l].iter().map(|msg| &msg.F),
+            )
+            .map_err(|_| Bug::PaillierOp(BugSource::C_i))?;
+            let (y_i, nonce_y_i) =
+                pi_dec::decrypt_with_nonce(&dec_i, &C_i).ok_or(Bug::PaillierDec(BugSource::C_i))?;
+            runtime.yield_now().await;
+
+            my_blame.push(Some((H_i, psi_mul, C_i, y_i, nonce_y_i, received_round2)));
+        }
+
+        for j in utils::iter_peers(i, n) {
+            let R_j = &R[usize::from(j)];
+
+            let mut instances = Vec::with_capacity(batch_size);
+            for (l, blame) in my_blame.iter().enumerate() {
+                let Some((H_i, psi_mul, C_i, y_i, nonce_y_i, received_round2)) = blame else {
+                    instances.push(None);
+                    continue;
+                };
+
+                tracer.stage("Prove psi_dec");
+                let psi_dec = pi_dec::prove::<E, D>(
+                    &super::unambiguous::ProofDec {
+                        sid: sids[l],
+                        prover: i,
+                        hat: false,
+                    },
+                    &R_j.into(),
+                    pi_dec::Data {
+                        key: N_i,
+                        c: C_i,
+                        x: &deltas_i[l],
+                    },
+                    pi_dec::PrivateData {
+                        y: y_i,
+                        nonce: nonce_y_i,
+                    },
+                    &security_params.pi_dec,
+                    &mut *rng,
+                )
+                .map_err(|e| Bug::PiDec(BugSource::psi_dec, e))?;
+
+                instances.push(Some(single::MsgRound3Blame {
+                    H: H_i.clone(),
+                    psi_mul: psi_mul.clone(),
+                    psi_dec,
+                    round2_msgs: received_round2.clone(),
+                }));
+            }
+
+            tracer.send_msg();
+            outgoings
+                .feed(Outgoing::p2p(
+                    j,
+                    Msg::Round3Blame(MsgRound3Blame { instances }),
+                ))
+                .await
+                .map_err(IoError::send_message)?;
+            tracer.msg_sent();
+        }
+        tracer.send_msg();
+        outgoings.flush().await.map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.receive_msgs();
+        let blame_msgs = rounds
+            .complete(round3_blame)
+            .await?
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        tracer.stage("Validate batch size");
+        let faulty_parties =
+            utils::collect_simple_blame(&blame_msgs, |msg| msg.instances.len() != batch_size);
+        if !faulty_parties.is_empty() {
+            return Err(
+                SigningAborted::new(SigningAbortReason::InvalidBatchSize, faulty_parties).into(),
+            );
+        }
+
+        // All signers observed the same δ_j, Δ_j, so every signer must prove correctness of
+        // its δ_j in exactly those instances where Δ ≠ δ⋅G
+        let faulty_parties = utils::collect_simple_blame(&blame_msgs, |msg| {
+            !msg.instances
+                .iter()
+                .map(Option::is_some)
+                .eq(mismatched.iter().copied())
+        });
+        if !faulty_parties.is_empty() {
+            return Err(
+                SigningAborted::new(SigningAbortReason::MismatchedDelta, faulty_parties).into(),
+            );
+        }
+
+        let mut faulty_parties = vec![];
+        for (l, blame) in my_blame.iter().enumerate() {
+            let Some((.., received_round2)) = blame else {
+                continue;
+            };
+            let instance_blame = blame_msgs
+                .iter()
+                .filter_map(|msg| msg.instances[l].as_ref())
+                .collect::<Vec<_>>();
+            let sid = sids[l];
+
+            tracer.stage("Validate forwarded round 2 messages");
+            let mut K = ciphertexts
+                .iter()
+                .map(|c| &c.instances[l].K)
+                .collect::<Vec<_>>();
+            K.insert(usize::from(i), &my_ciphertexts.instances[l].K);
+            let mut Gammas = round2_msgs
+                .iter()
+                .map(|msgs| msgs.instances[l].Gamma)
+                .collect::<Vec<_>>();
+            Gammas.insert(usize::from(i), Gammas_i[l]);
+            let forwarded_invalid = blame_msgs
+                .iter_indexed()
+                .zip(&instance_blame)
+                .filter(|((j, _, _), blame)| {
+                    !forwarded_round2_valid::<E, D>(
+                        sid,
+                        i,
+                        *j,
+                        n,
+                        R,
+                        &K,
+                        &Gammas,
+                        &sent_round2[l],
+                        &blame.round2_msgs,
+                        false,
+                        &security_params,
+                    )
+                })
+                .map(|((j, msg_id, _), _)| AbortBlame::new(j, msg_id, msg_id))
+                .collect::<Vec<_>>();
+            if !forwarded_invalid.is_empty() {
+                faulty_parties.extend(forwarded_invalid);
+                continue;
+            }
+            runtime.yield_now().await;
+
+            tracer.stage("Validate psi_mul and psi_dec");
+            let mut received = instance_blame
+                .iter()
+                .map(|blame| blame.round2_msgs.as_slice())
+                .collect::<Vec<_>>();
+            received.insert(usize::from(i), received_round2);
+
+            for (((j, blame_msg_id, _), blame), ((_, msg_id, msgs), ciphertexts_j)) in blame_msgs
+                .iter_indexed()
+                .zip(&instance_blame)
+                .zip(round3_msgs.iter_indexed().zip(ciphertexts.iter()))
+            {
+                let R_j = &R[usize::from(j)];
+                let enc_j = fast_paillier::EncryptionKey::from_n(R_j.N.clone());
+                let ciphertexts_j = &ciphertexts_j.instances[l];
+
+                let C_j = combine_ciphertexts(
+                    &enc_j,
+                    &blame.H,
+                    received[usize::from(j)].iter().map(|msg| &msg.D),
+                    utils::iter_peers(j, n)
+                        .map(|m| &received[usize::from(m)][utils::peer_position(m, j)].F),
+                );
+                let Ok(C_j) = C_j else {
+                    faulty_parties.push(AbortBlame::new(j, msg_id, blame_msg_id));
+                    continue;
+                };
+
+                let psi_mul_invalid = pi_mul::verify::<D>(
+                    &super::unambiguous::ProofMul { sid, prover: j },
+                    pi_mul::Data {
+                        key: &R_j.N,
+                        x: &ciphertexts_j.G,
+                        y: &ciphertexts_j.K,
+                        c: &blame.H,
+                    },
+                    &blame.psi_mul.0,
+                    &security_params.pi_mul,
+                    &blame.psi_mul.1,
+                )
+                .is_err();
+                let psi_dec_invalid = pi_dec::verify::<E, D>(
+                    &super::unambiguous::ProofDec {
+                        sid,
+                        prover: j,
+                        hat: false,
+                    },
+                    &R_i.into(),
+                    pi_dec::Data {
+                        key: &R_j.N,
+                        c: &C_j,
+                        x: &msgs.instances[l].delta,
+                    },
+                    &blame.psi_dec.0,
+                    &security_params.pi_dec,
+                    &blame.psi_dec.1,
+                )
+                .is_err();
+
+                if psi_mul_invalid || psi_dec_invalid {
+                    faulty_parties.push(AbortBlame::new(j, msg_id, blame_msg_id));
+                }
+                runtime.yield_now().await;
+            }
+        }
+
+        if faulty_parties.is_empty() {
+            return Err(Bug::NoFaultyParties.into());
+        }
+        return Err(
+            SigningAborted::new(SigningAbortReason::MismatchedDelta, faulty_parties).into(),
+        );
+    }
+
+    tracer.stage("Compute commitments k_j R, chi_j R");
+    let deltas_inv = deltas
+        .iter()
+        .map(|(delta, _)| delta.invert().ok_or(Bug::ZeroDelta))
+        .collect::<Result<Vec<_>, _>>()?;
+    let commitments = (0..batch_size)
+        .map(|l| {
+            let delta_inv = deltas_inv[l];
+            let mut commitments = round3_msgs
+                .iter()
+                .map(|msgs| SignerCommitment {
+                    k_R: msgs.instances[l].Delta * delta_inv,
+                    chi_R: msgs.instances[l].S * delta_inv,
+                })
+                .collect::<Vec<_>>();
+            commitments.insert(
+                usize::from(i),
+                SignerCommitment {
+                    k_R: Deltas_i[l] * delta_inv,
+                    chi_R: Ss_i[l] * delta_inv,
+                },
+            );
+            commitments
+        })
+        .collect::<Vec<_>>();
+    // Same as in a single instance, sum of chi_j R must be a public key
+    let chi_mismatched = commitments
+        .iter()
+        .map(|c| c.iter().map(|c| c.chi_R).sum::<Point<E>>() != pk)
+        .collect::<Vec<_>>();
+
+    if chi_mismatched.contains(&true) {
+        // Identification of faulty parties: in every instance where S_j are inconsistent, each
+        // party proves that S_i = y_i Γ where y_i is a plaintext of ciphertext hat_C_i which
+        // can be computed publicly
+        tracer.named_round_begins("Identification of faulty parties (χ)");
+
+        let mut my_blame = Vec::with_capacity(batch_size);
+        for (l, mismatched) in chi_mismatched.iter().enumerate() {
+            if !mismatched {
+                my_blame.push(None);
+                continue;
+            }
+            let K_i = &my_ciphertexts.instances[l].K;
+
+            tracer.stage("Compute hat_H_i = enc_i(x_i * k_i)");
+            let hat_nu_i = Integer::gen_invertible(N_i, rng);
+            let hat_H_i = {
+                let x_i_times_K_i = dec_i
+                    .omul(&utils::scalar_to_bignumber(x_i), K_i)
+                    .map_err(|_| Bug::PaillierOp(BugSource::hat_H_i))?;
+                let zero_enc = dec_i
+                    .encrypt_with(&Integer::ZERO, &hat_nu_i)
+                    .map_err(|_| Bug::PaillierEnc(BugSource::hat_H_i))?;
+                dec_i
+                    .encryption_key()
+                    .oadd(&x_i_times_K_i, &zero_enc)
+                    .map_err(|_| Bug::PaillierOp(BugSource::hat_H_i))?
+            };
+
+            tracer.stage("Compute hat_C_i");
+            let received_round2 = round2_msgs
+                .iter()
+                .map(|msgs| msgs.instances[l].clone())
+                .collect::<Vec<_>>();
+            let hat_C_i = combine_ciphertexts(
+                dec_i.encryption_key(),
+                &hat_H_i,
+                received_round2.iter().map(|msg| &msg.hat_D),
+                sent_round2[l].iter().map(|msg| &msg.hat_F),
+            )
+            .map_err(|_| Bug::PaillierOp(BugSource::hat_C_i))?;
+            let (y_i, nonce_y_i) = pi_dec::decrypt_with_nonce(&dec_i, &hat_C_i)
+                .ok_or(Bug::PaillierDec(BugSource::hat_C_i))?;
+            runtime.yield_now().await;
+
+            my_blame.push(Some((
+                hat_H_i,
+                hat_nu_i,
+                hat_C_i,
+                y_i,
+                nonce_y_i,
+                received_round2,
+            )));
+        }
+
+        for j in utils::iter_peers(i, n) {
+            let R_j = &R[usize::from(j)];
+
+            let mut instances = Vec::with_capacity(batch_size);
+            for (l, blame) in my_blame.iter().enumerate() {
+                let Some((hat_H_i, hat_nu_i, hat_C_i, y_i, nonce_y_i, received_round2)) = blame
+                else {
+                    instances.push(None);
+                    continue;
+                };
+
+                tracer.stage("Prove psi_mul_star");
+                let psi_mul_star = pi_mul_star::prove::<E, D>(
+                    &super::unambiguous::ProofMulStar {
+                        sid: sids[l],
+                        prover: i,
+                    },
+                    &R_j.into(),
+                    pi_mul_star::Data {
+                        key: N_i,
+                        c: &my_ciphertexts.instances[l].K,
+                        d: hat_H_i,
+                        x: &X[usize::from(i)],
+                    },
+                    pi_mul_star::PrivateData {
+                        x: &utils::scalar_to_bignumber(x_i),
+                        nonce: hat_nu_i,
+                    },
+                    &security_params.pi_mul_star,
+                    &mut *rng,
+                )
+                .map_err(|e| Bug::PiMulStar(BugSource::psi_mul_star, e))?;
+
+                tracer.stage("Prove psi_log");
+                let psi_log = pi_log::non_interactive::prove::<E, D>(
+                    &super::unambiguous::ProofLogChi {
+                        sid: sids[l],
+                        prover: i,
+                    },
+                    &R_j.into(),
+                    pi_log::Data {
+                        key0: &dec_i,
+                        c: hat_C_i,
+                        x: &Ss_i[l],
+                        b: &Gammas[l],
+                    },
+                    pi_log::PrivateData {
+                        x: y_i,
+                        nonce: nonce_y_i,
+                    },
+                    &security_params.pi_log_chi,
+                    &mut *rng,
+                )
+                .map_err(|e| Bug::PiLog(BugSource::psi_log, e))?;
+
+                instances.push(Some(single::MsgChiBlame {
+                    hat_H: hat_H_i.clone(),
+                    psi_mul_star,
+                    psi_log,
+                    round2_msgs: received_round2.clone(),
+                }));
+            }
+
+            tracer.send_msg();
+            outgoings
+                .feed(Outgoing::p2p(j, Msg::ChiBlame(MsgChiBlame { instances })))
+                .await
+                .map_err(IoError::send_message)?;
+            tracer.msg_sent();
+        }
+        tracer.send_msg();
+        outgoings.flush().await.map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.receive_msgs();
+        let blame_msgs = rounds
+            .complete(chi_blame)
+            .await?
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        tracer.stage("Validate batch size");
+        let faulty_parties =
+            utils::collect_simple_blame(&blame_msgs, |msg| msg.instances.len() != batch_size);
+        if !faulty_parties.is_empty() {
+            return Err(
+                SigningAborted::new(SigningAbortReason::InvalidBatchSize, faulty_parties).into(),
+            );
+        }
+
+        // All signers observed the same S_j, so every signer must prove correctness of its
+        // S_j in exactly those instances where S_j are inconsistent
+        let faulty_parties = utils::collect_simple_blame(&blame_msgs, |msg| {
+            !msg.instances
+                .iter()
+                .map(Option::is_some)
+                .eq(chi_mismatched.iter().copied())
+        });
+        if !faulty_parties.is_empty() {
+            return Err(
+                SigningAborted::new(SigningAbortReason::MismatchedChi, faulty_parties).into(),
+            );
+        }
+
+        let Xs = X.iter().map(|X_j| **X_j).collect::<Vec<_>>();
+        let mut faulty_parties = vec![];
+        for (l, blame) in my_blame.iter().enumerate() {
+            let Some((.., received_round2)) = blame else {
+                continue;
+            };
+            let instance_blame = blame_msgs
+                .iter()
+                .filter_map(|msg| msg.instances[l].as_ref())
+                .collect::<Vec<_>>();
+            let sid = sids[l];
+
+            tracer.stage("Validate forwarded round 2 messages");
+            let mut K = ciphertexts
+                .iter()
+                .map(|c| &c.instances[l].K)
+                .collect::<Vec<_>>();
+            K.insert(usize::from(i), &my_ciphertexts.instances[l].K);
+            let forwarded_invalid = blame_msgs
+                .iter_indexed()
+                .zip(&instance_blame)
+                .filter(|((j, _, _), blame)| {
+                    !forwarded_round2_valid::<E, D>(
+                        sid,
+                        i,
+                        *j,
+                        n,
+                        R,
+                        &K,
+                        &Xs,
+                        &sent_round2[l],
+                        &blame.round2_msgs,
+                        true,
+                        &security_params,
+                    )
+                })
+                .map(|((j, msg_id, _), _)| AbortBlame::new(j, msg_id, msg_id))
+                .collect::<Vec<_>>();
+            if !forwarded_invalid.is_empty() {
+                faulty_parties.extend(forwarded_invalid);
+                continue;
+            }
+            runtime.yield_now().await;
+
+            tracer.stage("Validate psi_mul_star and psi_log");
+            let mut received = instance_blame
+                .iter()
+                .map(|blame| blame.round2_msgs.as_slice())
+                .collect::<Vec<_>>();
+            received.insert(usize::from(i), received_round2);
+
+            for (((j, blame_msg_id, _), blame), (_, msg_id, msgs)) in blame_msgs
+                .iter_indexed()
+                .zip(&instance_blame)
+                .zip(round3_msgs.iter_indexed())
+            {
+                let R_j = &R[usize::from(j)];
+                let enc_j = fast_paillier::EncryptionKey::from_n(R_j.N.clone());
+
+                let hat_C_j = combine_ciphertexts(
+                    &enc_j,
+                    &blame.hat_H,
+                    received[usize::from(j)].iter().map(|msg| &msg.hat_D),
+                    utils::iter_peers(j, n)
+                        .map(|m| &received[usize::from(m)][utils::peer_position(m, j)].hat_F),
+                );
+                let Ok(hat_C_j) = hat_C_j else {
+                    faulty_parties.push(AbortBlame::new(j, msg_id, blame_msg_id));
+                    continue;
+                };
+
+                let psi_mul_star_invalid = pi_mul_star::verify::<E, D>(
+                    &super::unambiguous::ProofMulStar { sid, prover: j },
+                    &R_i.into(),
+                    pi_mul_star::Data {
+                        key: &R_j.N,
+                        c: K[usize::from(j)],
+                        d: &blame.hat_H,
+                        x: &X[usize::from(j)],
+                    },
+                    &blame.psi_mul_star.0,
+                    &security_params.pi_mul_star,
+                    &blame.psi_mul_star.1,
+                )
+                .is_err();
+                let psi_log_invalid = pi_log::non_interactive::verify::<E, D>(
+                    &super::unambiguous::ProofLogChi { sid, prover: j },
+                    &R_i.into(),
+                    pi_log::Data {
+                        key0: &enc_j,
+                        c: &hat_C_j,
+                        x: &msgs.instances[l].S,
+                        b: &Gammas[l],
+                    },
+                    &blame.psi_log.0,
+                    &security_params.pi_log_chi,
+                    &blame.psi_log.1,
+                )
+                .is_err();
+
+                if psi_mul_star_invalid || psi_log_invalid {
+                    faulty_parties.push(AbortBlame::new(j, msg_id, blame_msg_id));
+                }
+                runtime.yield_now().await;
+            }
+        }
+
+        if faulty_parties.is_empty() {
+            return Err(Bug::NoFaultyParties.into());
+        }
+        return Err(SigningAborted::new(SigningAbortReason::MismatchedChi, faulty_parties).into());
+    }
+
+    let presigs = ephemeral
+        .into_iter()
+        .zip(deltas_inv)
+        .zip(chis_i)
+        .zip(Gammas)
+        .zip(commitments)
+        .map(|((((eph, delta_inv), mut chi_i), Gamma), commitments)| {
+            Ok(Presignature {
+                R: NonZero::from_point(Gamma * delta_inv).ok_or(Bug::ZeroR)?,
+                k: eph.k_i,
+                chi: SecretScalar::new(&mut chi_i),
+                signer: metadata.signers[usize::from(i)],
+                metadata: metadata.clone(),
+                commitments,
+                derived,
+            })
+        })
+        .collect::<Result<Vec<_>, Bug>>()?;
+
+    // If messages are not specified, protocol terminates here and outputs presignatures
+    let Some(messages_to_sign) = messages_to_sign else {
+        tracer.protocol_ends();
+        return Ok(BatchOutput::Presignatures(presigs));
+    };
+
+    // Signing
+    tracer.named_round_begins("Partial signing");
+
+    // Round 1
+    let (public_data, partial_sigs): (Vec<_>, Vec<_>) = presigs
+        .into_iter()
+        .zip(messages_to_sign)
+        .map(|(presig, message_to_sign)| {
+            (
+                presig.public_data(),
+                presig.issue_partial_signature_unchecked(*message_to_sign),
+            )
+        })
+        .unzip();
+
+    tracer.send_msg();
+    outgoings
+        .send(Outgoing::broadcast(Msg::Round4(MsgRound4 {
+            instances: partial_sigs
+                .iter()
+                .map(|partial_sig| single::MsgRound4 {
+                    sigma: partial_sig.sigma,
+                })
+                .collect(),
+        })))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Output
+    tracer.named_round_begins("Signatures reconstruction");
+
+    tracer.receive_msgs();
+    let round4_msgs = rounds
+        .complete(round4)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    tracer.stage("Validate batch size");
+    let faulty_parties =
+        utils::collect_simple_blame(&round4_msgs, |msg| msg.instances.len() != batch_size);
+    if !faulty_parties.is_empty() {
+        return Err(
+            SigningAborted::new(SigningAbortReason::InvalidBatchSize, faulty_parties).into(),
+        );
+    }
+
+    tracer.stage("Combine partial signatures");
+    let sigs = partial_sigs
+        .iter()
+        .enumerate()
+        .map(|(l, partial_sig)| {
+            let r = NonZero::from_scalar(partial_sig.r);
+            let s = NonZero::from_scalar(
+                partial_sig.sigma
+                    + round4_msgs
+                        .iter()
+                        .map(|msgs| msgs.instances[l].sigma)
+                        .sum::<Scalar<E>>(),
+            );
+            Option::zip(r, s)
+                .map(|(r, s)| {
+                    Signature::from_raw_parts(r, s)
+                        .with_recovery_id(partial_sig.recovery_id)
+                        .normalize_s()
+                })
+                .filter(|sig| sig.verify(&pk, &messages_to_sign[l]).is_ok())
+        })
+        .collect::<Option<Vec<_>>>();
+
+    let Some(sigs) = sigs else {
+        // Identification of faulty parties: σ_j = k_j m + χ_j r, so it can be checked
+        // against commitments k_j R and χ_j R
+        tracer.stage("Identify faulty parties (σ)");
+        let faulty_parties = round4_msgs
+            .iter_indexed()
+            .filter(|(j, _, msgs)| {
+                msgs.instances
+                    .iter()
+                    .zip(&public_data)
+                    .zip(messages_to_sign)
+                    .any(|((msg, public_data), message_to_sign)| {
+                        let r = public_data.R.x().to_scalar();
+                        let commitment = &public_data.commitments[usize::from(*j)];
+                        *public_data.R * msg.sigma
+                            != commitment.k_R * message_to_sign.to_scalar() + commitment.chi_R * r
+                    })
+            })
+            .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        return Err(
+            SigningAborted::new(SigningAbortReason::SignatureInvalid, faulty_parties).into(),
+        );
+    };
+
+    tracer.protocol_ends();
+    Ok(BatchOutput::Signatures(sigs))
+}
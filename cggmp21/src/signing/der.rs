@@ -0,0 +1,112 @@
+//! Minimal DER encoder and strict decoder for ECDSA signatures
+//!
+//! Signature is encoded as ASN.1 `SEQUENCE { r INTEGER, s INTEGER }`. Only non-negative
+//! integers are supported, which is all ECDSA needs.
+
+const INTEGER: u8 = 0x02;
+const SEQUENCE: u8 = 0x30;
+
+/// Encodes `SEQUENCE { r INTEGER, s INTEGER }`, where `r` and `s` are big-endian unsigned
+/// integers
+pub fn encode_signature(r: &[u8], s: &[u8]) -> Vec<u8> {
+    let mut content = vec![];
+    write_integer(&mut content, r);
+    write_integer(&mut content, s);
+
+    let mut out = vec![];
+    write_tlv(&mut out, SEQUENCE, &content);
+    out
+}
+
+/// Decodes `SEQUENCE { r INTEGER, s INTEGER }`, returns big-endian unsigned `r` and `s`
+///
+/// Returns `None` if input is not a strict DER encoding of such sequence, or if it
+/// has trailing bytes.
+pub fn decode_signature(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (content, rest) = read_tlv(bytes, SEQUENCE)?;
+    if !rest.is_empty() {
+        return None;
+    }
+    let (r, content) = read_integer(content)?;
+    let (s, content) = read_integer(content)?;
+    if !content.is_empty() {
+        return None;
+    }
+    Some((r, s))
+}
+
+fn write_integer(out: &mut Vec<u8>, int: &[u8]) {
+    let first_nonzero = int.iter().position(|b| *b != 0).unwrap_or(int.len());
+    let int = &int[first_nonzero..];
+
+    let mut content = Vec::with_capacity(int.len() + 1);
+    // Leading zero is required if most significant bit is set, otherwise
+    // integer would be negative
+    if !matches!(int.first(), Some(b) if b & 0x80 == 0) {
+        content.push(0);
+    }
+    content.extend_from_slice(int);
+    write_tlv(out, INTEGER, &content);
+}
+
+fn write_tlv(out: &mut Vec<u8>, tag: u8, content: &[u8]) {
+    out.push(tag);
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let first_nonzero = len_bytes.iter().position(|b| *b != 0).unwrap_or(0);
+        let len_bytes = &len_bytes[first_nonzero..];
+        out.push(0x80 | len_bytes.len() as u8);
+        out.extend_from_slice(len_bytes);
+    }
+    out.extend_from_slice(content);
+}
+
+/// Reads non-negative integer, returns its big-endian bytes without leading zeroes
+fn read_integer(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (content, rest) = read_tlv(bytes, INTEGER)?;
+    match content {
+        // Negative integer
+        [b, ..] if b & 0x80 != 0 => None,
+        // Non-minimal encoding
+        [0, b, ..] if b & 0x80 == 0 => None,
+        [0, int @ ..] => Some((int, rest)),
+        [] => None,
+        int => Some((int, rest)),
+    }
+}
+
+fn read_tlv(bytes: &[u8], expected_tag: u8) -> Option<(&[u8], &[u8])> {
+    let (&tag, bytes) = bytes.split_first()?;
+    if tag != expected_tag {
+        return None;
+    }
+    let (&len, mut bytes) = bytes.split_first()?;
+    let len = if len < 0x80 {
+        usize::from(len)
+    } else {
+        let len_size = usize::from(len & 0x7f);
+        if len_size == 0 || len_size > core::mem::size_of::<usize>() || bytes.len() < len_size {
+            return None;
+        }
+        let (len_bytes, rest) = bytes.split_at(len_size);
+        bytes = rest;
+        // Long form must be minimal
+        if len_bytes[0] == 0 {
+            return None;
+        }
+        let len = len_bytes
+            .iter()
+            .fold(0usize, |len, b| (len << 8) | usize::from(*b));
+        if len < 0x80 {
+            return None;
+        }
+        len
+    };
+    if bytes.len() < len {
+        return None;
+    }
+    Some(bytes.split_at(len))
+}
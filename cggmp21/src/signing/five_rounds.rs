@@ -0,0 +1,1432 @@
+//! (5+1)-round signing protocol
+//!
+//! Compared to (3+1)-round protocol, presignature generation takes two more rounds, but
+//! identification of faulty parties is much cheaper:
+//! * Ciphertexts produced by MtA are broadcast, and reliability of broadcast is checked
+//!   in the following round, so all signers have the same view on them
+//! * If $\Delta \ne \delta \cdot G$, signers reveal their ephemeral secrets $k_i, \gamma_i$
+//!   and MtA masks $\beta_{i,j}$ along with encryption nonces. That allows anyone to
+//!   recompute $\delta_j$ of every signer without any ZK proofs. Revealing these secrets
+//!   is safe as presignature gets discarded.
+//! * At round 5, each signer publishes $S_i = \chi_i \cdot R$. If $\sum_j S_j \ne X$, signers
+//!   prove correctness of $S_i$. That's the only case when identification requires ZK proofs.
+//! * Partial signature $\sigma_j$ can be verified against $\Delta_j$ and $S_j$, so faulty
+//!   signer is identified without additional communication.
+//!
+//! Rounds of presignature generation:
+//! 1. Broadcast $K_i, G_i$, send $\psi^0_{j,i}$ to each $j$
+//! 2. Broadcast $\Gamma_i$ and ciphertexts $D_{j,i}, F_{j,i}, \hat D_{j,i}, \hat F_{j,i}$ for
+//!    all $j$, send $\psi_{j,i}, \hat \psi_{j,i}, \psi'_{j,i}$ to each $j$
+//! 3. Broadcast $\delta_i, \Delta_i$
+//! 4. Send $\psi''_{j,i}$ to each $j$
+//! 5. Broadcast $S_i$
+//!
+//! Rounds 2, 3 and 4 also carry a hash of broadcast messages received at previous round,
+//! which is used to ensure reliability of broadcast.
+
+use digest::Digest;
+use futures::SinkExt;
+use generic_ec::{coords::AlwaysHasAffineX, Curve, NonZero, Point, Scalar, SecretScalar};
+use paillier_zk::rug::Complete;
+use paillier_zk::{fast_paillier, rug::Integer};
+use paillier_zk::{
+    group_element_vs_paillier_encryption_in_range as pi_log,
+    paillier_affine_operation_in_range as pi_aff, paillier_encryption_in_range as pi_enc,
+    IntegerExt,
+};
+use rand_core::{CryptoRng, RngCore};
+use round_based::{
+    rounds_router::simple_store::RoundInput, runtime::AsyncRuntime, Delivery, Mpc, MpcParty,
+    Outgoing, PartyIndex, ProtocolMessage,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::IoError;
+use crate::key_share::{KeyShare, PartyAux};
+use crate::progress::Tracer;
+use crate::timeout::{RoundTimer, RoundsRouter};
+use crate::utils::AbortBlame;
+use crate::zk::{
+    paillier_decryption_modulo_q as pi_dec, paillier_multiplication_vs_group as pi_mul_star,
+};
+use crate::{security_level::SecurityLevel, utils, ExecutionId};
+
+use super::{
+    combine_ciphertexts, map_t_out_of_n_to_t_out_of_t, Bug, BugSource, DataToSign, Presignature,
+    PresignatureMetadata, ProtocolOutput, Signature, SignerCommitment, SigningAbortReason,
+    SigningAborted, SigningError, TOutOfT,
+};
+
+/// Signing protocol message
+///
+/// Enumerates messages from all rounds
+#[derive(Clone, ProtocolMessage, Serialize, Deserialize)]
+#[serde(bound = "")]
+#[allow(clippy::large_enum_variant)]
+pub enum Msg<E: Curve, D: Digest> {
+    /// Round 1a message
+    Round1a(MsgRound1a),
+    /// Round 1b message
+    Round1b(MsgRound1b),
+    /// Round 2 broadcast message
+    Round2Broad(MsgRound2Broad<E, D>),
+    /// Round 2 p2p message
+    Round2Uni(MsgRound2Uni<E>),
+    /// Round 3 message
+    Round3(MsgRound3<E, D>),
+    /// Round 4 message
+    Round4(MsgRound4<E, D>),
+    /// Round 5 message
+    Round5(MsgRound5<E>),
+    /// Round 6 message (partial signature)
+    Round6(MsgRound6<E>),
+    /// Message revealing ephemeral secrets (sent only if $\Delta \ne \delta \cdot G$)
+    RevealDelta(MsgRevealDelta),
+    /// Message proving correctness of $S_i$ (sent only if $\sum_j S_j \ne X$)
+    ChiBlame(MsgChiBlame<E>),
+}
+
+/// Message from round 1a
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[udigest(tag = prefixed!("five_rounds.round1"))]
+pub struct MsgRound1a {
+    /// $K_i$
+    #[udigest(as = utils::encoding::Integer)]
+    pub K: fast_paillier::Ciphertext,
+    /// $G_i$
+    #[udigest(as = utils::encoding::Integer)]
+    pub G: fast_paillier::Ciphertext,
+}
+
+/// Message from round 1b
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MsgRound1b {
+    /// $\psi^0_{j,i}$
+    pub psi0: (pi_enc::Commitment, pi_enc::Proof),
+}
+
+/// Broadcast message from round 2
+///
+/// Ciphertexts are listed for every other signer $j$ in ascending order of $j$
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[udigest(tag = prefixed!("five_rounds.round2"))]
+#[udigest(bound = "")]
+#[serde(bound = "")]
+pub struct MsgRound2Broad<E: Curve, D: Digest> {
+    /// $\Gamma_i$
+    pub Gamma: Point<E>,
+    /// $D_{j,i}$
+    #[udigest(as = Vec<utils::encoding::Integer>)]
+    pub D: Vec<fast_paillier::Ciphertext>,
+    /// $F_{j,i}$
+    #[udigest(as = Vec<utils::encoding::Integer>)]
+    pub F: Vec<fast_paillier::Ciphertext>,
+    /// $\hat D_{j,i}$
+    #[udigest(as = Vec<utils::encoding::Integer>)]
+    pub hat_D: Vec<fast_paillier::Ciphertext>,
+    /// $\hat F_{j,i}$
+    #[udigest(as = Vec<utils::encoding::Integer>)]
+    pub hat_F: Vec<fast_paillier::Ciphertext>,
+    /// Hash of all messages received at round 1a
+    #[udigest(as_bytes)]
+    pub echo: digest::Output<D>,
+}
+
+/// P2P message from round 2
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound2Uni<E: Curve> {
+    /// $\psi_{j,i}$
+    pub psi: (pi_aff::Commitment<E>, pi_aff::Proof),
+    /// $\hat \psi_{j,i}$
+    pub hat_psi: (pi_aff::Commitment<E>, pi_aff::Proof),
+    /// $\psi'_{j,i}$
+    pub psi_prime: (pi_log::Commitment<E>, pi_log::Proof),
+}
+
+/// Message from round 3
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[udigest(tag = prefixed!("five_rounds.round3"))]
+#[udigest(bound = "")]
+#[serde(bound = "")]
+pub struct MsgRound3<E: Curve, D: Digest> {
+    /// $\delta_i$
+    pub delta: Scalar<E>,
+    /// $\Delta_i$
+    pub Delta: Point<E>,
+    /// Hash of all broadcast messages received at round 2
+    #[udigest(as_bytes)]
+    pub echo: digest::Output<D>,
+}
+
+/// Message from round 4
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound4<E: Curve, D: Digest> {
+    /// $\psi''_{j,i}$
+    pub psi_prime_prime: (pi_log::Commitment<E>, pi_log::Proof),
+    /// Hash of all broadcast messages received at round 3
+    pub echo: digest::Output<D>,
+}
+
+/// Message from round 5
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound5<E: Curve> {
+    /// $S_i = \chi_i \cdot R$
+    pub S: Point<E>,
+}
+
+/// Message from round 6
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgRound6<E: Curve> {
+    /// $\sigma_i$
+    pub sigma: Scalar<E>,
+}
+
+/// Message from identification round that follows round 4
+///
+/// Sent when $\Delta \ne \delta \cdot G$. Reveals ephemeral secrets of $i$-th party
+/// so anyone can recompute $\delta_i$. Vectors are listed for every other signer $j$
+/// in ascending order of $j$.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MsgRevealDelta {
+    /// $k_i$
+    pub k: Integer,
+    /// $\rho_i$, nonce of $K_i$
+    pub rho: Integer,
+    /// $\gamma_i$
+    pub gamma: Integer,
+    /// $\nu_i$, nonce of $G_i$
+    pub nu: Integer,
+    /// $\beta_{i,j}$
+    pub beta: Vec<Integer>,
+    /// $s_{i,j}$, nonce of $D_{j,i}$
+    pub s: Vec<Integer>,
+    /// $r_{i,j}$, nonce of $F_{j,i}$
+    pub r: Vec<Integer>,
+}
+
+/// Message from identification round that follows round 5
+///
+/// Sent when $\sum_j S_j \ne X$ to convince other parties that $S_i$ was computed honestly.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MsgChiBlame<E: Curve> {
+    /// $\hat H_i$, encryption of $x_i \cdot k_i$
+    pub hat_H: fast_paillier::Ciphertext,
+    /// $\psi^{mul*}_{j,i}$, proof that $\hat H_i$ is well-formed
+    pub psi_mul_star: (pi_mul_star::Commitment<E>, pi_mul_star::Proof),
+    /// $\psi^{log}_{j,i}$, proof that $S_i = y \cdot R$ where $y$ is plaintext of $\hat C_i$
+    pub psi_log: (pi_log::Commitment<E>, pi_log::Proof),
+}
+
+mod unambiguous {
+    use crate::ExecutionId;
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("five_rounds.proof_enc"))]
+    pub struct ProofEnc<'a> {
+        pub sid: ExecutionId<'a>,
+        pub prover: u16,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("five_rounds.proof_psi"))]
+    pub struct ProofPsi<'a> {
+        pub sid: ExecutionId<'a>,
+        pub prover: u16,
+        pub hat: bool,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("five_rounds.proof_log"))]
+    pub struct ProofLog<'a> {
+        pub sid: ExecutionId<'a>,
+        pub prover: u16,
+        pub prime_prime: bool,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("five_rounds.proof_mul_star"))]
+    pub struct ProofMulStar<'a> {
+        pub sid: ExecutionId<'a>,
+        pub prover: u16,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("five_rounds.proof_log_chi"))]
+    pub struct ProofLogChi<'a> {
+        pub sid: ExecutionId<'a>,
+        pub prover: u16,
+    }
+
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = prefixed!("five_rounds.echo"))]
+    #[udigest(bound = "M: udigest::Digestable")]
+    pub struct Echo<'a, M> {
+        pub sid: ExecutionId<'a>,
+        pub round: u16,
+        pub msg: &'a M,
+    }
+}
+
+/// t-out-of-n (5+1)-round signing
+///
+/// Maps t-out-of-n key share into t-out-of-t setting in the same way as (3+1)-round
+/// protocol does, and calls [`signing_n_out_of_n`].
+pub(super) async fn signing_t_out_of_n<M, E, L, D, R>(
+    mut tracer: Option<&mut dyn Tracer>,
+    round_timer: Option<&mut dyn RoundTimer>,
+    rng: &mut R,
+    party: M,
+    sid: ExecutionId<'_>,
+    i: PartyIndex,
+    key_share: &KeyShare<E, L>,
+    S: &[PartyIndex],
+    message_to_sign: Option<DataToSign<E>>,
+    additive_shift: Option<Scalar<E>>,
+) -> Result<ProtocolOutput<E>, SigningError>
+where
+    M: Mpc<ProtocolMessage = Msg<E, D>>,
+    E: Curve,
+    L: SecurityLevel,
+    D: Digest<OutputSize = digest::typenum::U32> + Clone + 'static,
+    R: RngCore + CryptoRng,
+    NonZero<Point<E>>: AlwaysHasAffineX<E>,
+{
+    tracer.protocol_begins();
+    tracer.stage("Map t-out-of-n protocol to t-out-of-t");
+    let TOutOfT { t, x_i, X, pk, R } =
+        map_t_out_of_n_to_t_out_of_t(key_share, i, S, additive_shift)?;
+    let (p_i, q_i) = (&key_share.aux.p, &key_share.aux.q);
+    let metadata = PresignatureMetadata::new(key_share, S, sid);
+
+    // t-out-of-t signing
+    signing_n_out_of_n::<_, _, L, _, _>(
+        tracer,
+        round_timer,
+        rng,
+        party,
+        sid,
+        i,
+        t,
+        &x_i,
+        &X,
+        pk,
+        p_i,
+        q_i,
+        &R,
+        metadata,
+        additive_shift.is_some(),
+        message_to_sign,
+    )
+    .await
+}
+
+/// n-out-of-n (5+1)-round signing
+async fn signing_n_out_of_n<M, E, L, D, R>(
+    mut tracer: Option<&mut dyn Tracer>,
+    round_timer: Option<&mut dyn RoundTimer>,
+    rng: &mut R,
+    party: M,
+    sid: ExecutionId<'_>,
+    i: PartyIndex,
+    n: u16,
+    x_i: &NonZero<SecretScalar<E>>,
+    X: &[NonZero<Point<E>>],
+    pk: Point<E>,
+    p_i: &Integer,
+    q_i: &Integer,
+    R: &[PartyAux],
+    metadata: PresignatureMetadata,
+    derived: bool,
+    message_to_sign: Option<DataToSign<E>>,
+) -> Result<ProtocolOutput<E>, SigningError>
+where
+    M: Mpc<ProtocolMessage = Msg<E, D>>,
+    E: Curve,
+    L: SecurityLevel,
+    D: Digest<OutputSize = digest::typenum::U32> + Clone + 'static,
+    R: RngCore + CryptoRng,
+    NonZero<Point<E>>: AlwaysHasAffineX<E>,
+{
+    let MpcParty {
+        delivery, runtime, ..
+    } = party.into_party();
+    let (incomings, mut outgoings) = delivery.split();
+
+    tracer.stage("Retrieve auxiliary data");
+    let R_i = &R[usize::from(i)];
+    let N_i = &R_i.N;
+    let dec_i: fast_paillier::DecryptionKey =
+        fast_paillier::DecryptionKey::from_primes(p_i.clone(), q_i.clone())
+            .map_err(|_| Bug::InvalidOwnPaillierKey)?;
+
+    tracer.stage("Precompute execution id and security params");
+    let security_params = crate::utils::SecurityParams::new::<L>();
+
+    tracer.stage("Setup networking");
+    let mut rounds = RoundsRouter::<Msg<E, D>>::builder(i, n);
+    let round1a = rounds.add_round(RoundInput::<MsgRound1a>::broadcast(i, n));
+    let round1b = rounds.add_round(RoundInput::<MsgRound1b>::p2p(i, n));
+    let round2_broad = rounds.add_round(RoundInput::<MsgRound2Broad<E, D>>::broadcast(i, n));
+    let round2_uni = rounds.add_round(RoundInput::<MsgRound2Uni<E>>::p2p(i, n));
+    let round3 = rounds.add_round(RoundInput::<MsgRound3<E, D>>::broadcast(i, n));
+    let round4 = rounds.add_round(RoundInput::<MsgRound4<E, D>>::p2p(i, n));
+    let round5 = rounds.add_round(RoundInput::<MsgRound5<E>>::broadcast(i, n));
+    let round6 = rounds.add_round(RoundInput::<MsgRound6<E>>::broadcast(i, n));
+    let reveal_delta = rounds.add_round(RoundInput::<MsgRevealDelta>::broadcast(i, n));
+    let chi_blame = rounds.add_round(RoundInput::<MsgChiBlame<E>>::p2p(i, n));
+    let mut rounds = rounds.listen(incomings, round_timer);
+
+    // Round 1
+    tracer.round_begins();
+
+    tracer.stage("Generate local ephemeral secrets (k_i, y_i, p_i, v_i)");
+    let gamma_i = SecretScalar::<E>::random(rng);
+    let k_i = SecretScalar::<E>::random(rng);
+
+    let v_i = Integer::gen_invertible(N_i, rng);
+    let rho_i = Integer::gen_invertible(N_i, rng);
+
+    tracer.stage("Encrypt G_i and K_i");
+    let G_i = dec_i
+        .encrypt_with(&utils::scalar_to_bignumber(&gamma_i), &v_i)
+        .map_err(|_| Bug::PaillierEnc(BugSource::G_i))?;
+    let K_i = dec_i
+        .encrypt_with(&utils::scalar_to_bignumber(&k_i), &rho_i)
+        .map_err(|_| Bug::PaillierEnc(BugSource::K_i))?;
+    let round1a_msg = MsgRound1a {
+        K: K_i.clone(),
+        G: G_i.clone(),
+    };
+    runtime.yield_now().await;
+
+    tracer.send_msg();
+    outgoings
+        .feed(Outgoing::broadcast(Msg::Round1a(round1a_msg.clone())))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    for j in utils::iter_peers(i, n) {
+        tracer.stage("Prove ψ0_j");
+        let R_j = &R[usize::from(j)];
+
+        let psi0 = pi_enc::non_interactive::prove::<D>(
+            &unambiguous::ProofEnc { sid, prover: i },
+            &R_j.into(),
+            pi_enc::Data {
+                key: &dec_i,
+                ciphertext: &K_i,
+            },
+            pi_enc::PrivateData {
+                plaintext: &utils::scalar_to_bignumber(&k_i),
+                nonce: &rho_i,
+            },
+            &security_params.pi_enc,
+            &mut *rng,
+        )
+        .map_err(|e| Bug::PiEnc(BugSource::psi0, e))?;
+
+        tracer.send_msg();
+        outgoings
+            .feed(Outgoing::p2p(j, Msg::Round1b(MsgRound1b { psi0 })))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+    }
+    tracer.send_msg();
+    outgoings.flush().await.map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Round 2
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    // Contains G_j, K_j sent by other parties
+    let ciphertexts = rounds
+        .complete(round1a)
+        .await?
+        .map_err(IoError::receive_message)?;
+    let psi0 = rounds
+        .complete(round1b)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    tracer.stage("Verify psi0 proofs");
+    {
+        let mut faulty_parties = vec![];
+        for ((j, msg1_id, ciphertext), (_, msg2_id, proof)) in
+            ciphertexts.iter_indexed().zip(psi0.iter_indexed())
+        {
+            let R_j = &R[usize::from(j)];
+            if pi_enc::non_interactive::verify::<D>(
+                &unambiguous::ProofEnc { sid, prover: j },
+                &R_i.into(),
+                pi_enc::Data {
+                    key: &fast_paillier::EncryptionKey::from_n(R_j.N.clone()),
+                    ciphertext: &ciphertext.K,
+                },
+                &proof.psi0.0,
+                &security_params.pi_enc,
+                &proof.psi0.1,
+            )
+            .is_err()
+            {
+                faulty_parties.push(AbortBlame::new(j, msg1_id, msg2_id))
+            }
+        }
+
+        if !faulty_parties.is_empty() {
+            return Err(
+                SigningAborted::new(SigningAbortReason::EncProofOfK, faulty_parties).into(),
+            );
+        }
+    }
+    runtime.yield_now().await;
+
+    tracer.stage("Hash received msgs (reliability check)");
+    let round1a_hash = udigest::hash_iter::<D>(
+        ciphertexts
+            .iter_including_me(&round1a_msg)
+            .map(|msg| unambiguous::Echo { sid, round: 1, msg }),
+    );
+    let round1a_msgs = ciphertexts
+        .iter_including_me(&round1a_msg)
+        .collect::<Vec<_>>();
+
+    let Gamma_i = Point::generator() * &gamma_i;
+    let J = (Integer::ONE << L::ELL_PRIME).complete();
+
+    let mut beta_sum = Scalar::zero();
+    let mut hat_beta_sum = Scalar::zero();
+    let mut round2_broad_msg = MsgRound2Broad {
+        Gamma: Gamma_i,
+        D: Vec::with_capacity(usize::from(n) - 1),
+        F: Vec::with_capacity(usize::from(n) - 1),
+        hat_D: Vec::with_capacity(usize::from(n) - 1),
+        hat_F: Vec::with_capacity(usize::from(n) - 1),
+        echo: round1a_hash,
+    };
+    let mut round2_uni_msgs = Vec::with_capacity(usize::from(n) - 1);
+    // Secrets that are revealed if Delta != G * delta
+    let mut beta = Vec::with_capacity(usize::from(n) - 1);
+    let mut s = Vec::with_capacity(usize::from(n) - 1);
+    let mut r = Vec::with_capacity(usize::from(n) - 1);
+    for (j, _, ciphertext_j) in ciphertexts.iter_indexed() {
+        tracer.stage("Sample random r, hat_r, s, hat_s, beta, hat_beta");
+        let R_j = &R[usize::from(j)];
+        let N_j = &R_j.N;
+        let enc_j = fast_paillier::EncryptionKey::from_n(N_j.clone());
+
+        let r_ij: Integer = N_i.random_below_ref(&mut utils::external_rand(rng)).into();
+        let hat_r_ij = N_i.random_below_ref(&mut utils::external_rand(rng)).into();
+        let s_ij: Integer = N_j.random_below_ref(&mut utils::external_rand(rng)).into();
+        let hat_s_ij = N_j.random_below_ref(&mut utils::external_rand(rng)).into();
+
+        let beta_ij = Integer::from_rng_pm(&J, rng);
+        let hat_beta_ij = Integer::from_rng_pm(&J, rng);
+
+        beta_sum += beta_ij.to_scalar();
+        hat_beta_sum += hat_beta_ij.to_scalar();
+
+        tracer.stage("Encrypt D_ji");
+        // D_ji = (gamma_i * K_j) + enc_j(-beta_ij, s_ij)
+        let D_ji = {
+            let gamma_i_times_K_j = enc_j
+                .omul(&utils::scalar_to_bignumber(&gamma_i), &ciphertext_j.K)
+                .map_err(|_| Bug::PaillierOp(BugSource::gamma_i_times_K_j))?;
+            let neg_beta_ij_enc = enc_j
+                .encrypt_with(&(-&beta_ij).complete(), &s_ij)
+                .map_err(|_| Bug::PaillierEnc(BugSource::neg_beta_ij_enc))?;
+            enc_j
+                .oadd(&gamma_i_times_K_j, &neg_beta_ij_enc)
+                .map_err(|_| Bug::PaillierOp(BugSource::D_ji))?
+        };
+
+        tracer.stage("Encrypt F_ji");
+        let F_ji = dec_i
+            .encrypt_with(&(-&beta_ij).complete(), &r_ij)
+            .map_err(|_| Bug::PaillierEnc(BugSource::F_ji))?;
+
+        tracer.stage("Encrypt hat_D_ji");
+        // Dˆ_ji = (x_i * K_j) + enc_j(-hat_beta_ij, hat_s_ij)
+        let hat_D_ji = {
+            let x_i_times_K_j = enc_j
+                .omul(&utils::scalar_to_bignumber(x_i), &ciphertext_j.K)
+                .map_err(|_| Bug::PaillierOp(BugSource::x_i_times_K_j))?;
+            let neg_hat_beta_ij_enc = enc_j
+                .encrypt_with(&(-&hat_beta_ij).complete(), &hat_s_ij)
+                .map_err(|_| Bug::PaillierEnc(BugSource::hat_beta_ij_enc))?;
+            enc_j
+                .oadd(&x_i_times_K_j, &neg_hat_beta_ij_enc)
+                .map_err(|_| Bug::PaillierOp(BugSource::hat_D))?
+        };
+        runtime.yield_now().await;
+
+        tracer.stage("Encrypt hat_F_ji");
+        let hat_F_ji = dec_i
+            .encrypt_with(&(-&hat_beta_ij).complete(), &hat_r_ij)
+            .map_err(|_| Bug::PaillierEnc(BugSource::hat_F))?;
+
+        tracer.stage("Prove psi_ji");
+        let psi_ji = pi_aff::non_interactive::prove::<E, D>(
+            &unambiguous::ProofPsi {
+                sid,
+                prover: i,
+                hat: false,
+            },
+            &R_j.into(),
+            pi_aff::Data {
+                key0: &enc_j,
+                key1: &dec_i,
+                c: &ciphertext_j.K,
+                d: &D_ji,
+                y: &F_ji,
+                x: &Gamma_i,
+            },
+            pi_aff::PrivateData {
+                x: &utils::scalar_to_bignumber(&gamma_i),
+                y: &(-&beta_ij).complete(),
+                nonce: &s_ij,
+                nonce_y: &r_ij,
+            },
+            &security_params.pi_aff,
+            &mut *rng,
+        )
+        .map_err(|e| Bug::PiAffG(BugSource::psi, e))?;
+        runtime.yield_now().await;
+
+        tracer.stage("Prove psiˆ_ji");
+        let hat_psi_ji = pi_aff::non_interactive::prove::<E, D>(
+            &unambiguous::ProofPsi {
+                sid,
+                prover: i,
+                hat: true,
+            },
+            &R_j.into(),
+            pi_aff::Data {
+                key0: &enc_j,
+                key1: &dec_i,
+                c: &ciphertext_j.K,
+                d: &hat_D_ji,
+                y: &hat_F_ji,
+                x: &(Point::generator() * x_i),
+            },
+            pi_aff::PrivateData {
+                x: &utils::scalar_to_bignumber(x_i),
+                y: &(-&hat_beta_ij).complete(),
+                nonce: &hat_s_ij,
+                nonce_y: &hat_r_ij,
+            },
+            &security_params.pi_aff,
+            &mut *rng,
+        )
+        .map_err(|e| Bug::PiAffG(BugSource::hat_psi, e))?;
+
+        tracer.stage("Prove psi_prime_ji ");
+        let psi_prime_ji = pi_log::non_interactive::prove::<E, D>(
+            &unambiguous::ProofLog {
+                sid,
+                prover: i,
+                prime_prime: false,
+            },
+            &R_j.into(),
+            pi_log::Data {
+                key0: &dec_i,
+                c: &G_i,
+                x: &Gamma_i,
+                b: &Point::<E>::generator().to_point(),
+            },
+            pi_log::PrivateData {
+                x: &utils::scalar_to_bignumber(&gamma_i),
+                nonce: &v_i,
+            },
+            &security_params.pi_log,
+            &mut *rng,
+        )
+        .map_err(|e| Bug::PiLog(BugSource::psi_prime, e))?;
+        runtime.yield_now().await;
+
+        round2_broad_msg.D.push(D_ji);
+        round2_broad_msg.F.push(F_ji);
+        round2_broad_msg.hat_D.push(hat_D_ji);
+        round2_broad_msg.hat_F.push(hat_F_ji);
+        round2_uni_msgs.push((
+            j,
+            MsgRound2Uni {
+                psi: psi_ji,
+                hat_psi: hat_psi_ji,
+                psi_prime: psi_prime_ji,
+            },
+        ));
+        beta.push(beta_ij);
+        s.push(s_ij);
+        r.push(r_ij);
+    }
+
+    tracer.send_msg();
+    outgoings
+        .feed(Outgoing::broadcast(Msg::Round2Broad(
+            round2_broad_msg.clone(),
+        )))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+    for (j, msg) in round2_uni_msgs {
+        tracer.send_msg();
+        outgoings
+            .feed(Outgoing::p2p(j, Msg::Round2Uni(msg)))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+    }
+    tracer.send_msg();
+    outgoings.flush().await.map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Round 3
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let round2_broad_msgs = rounds
+        .complete(round2_broad)
+        .await?
+        .map_err(IoError::receive_message)?;
+    let round2_uni_msgs = rounds
+        .complete(round2_uni)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    tracer.stage("Assert other parties hashed messages (reliability check)");
+    let parties_have_different_hashes = round2_broad_msgs
+        .iter_indexed()
+        .filter(|(_j, _msg_id, msg)| msg.echo != round1a_hash)
+        .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+        .collect::<Vec<_>>();
+    if !parties_have_different_hashes.is_empty() {
+        return Err(SigningAborted::new(
+            SigningAbortReason::BroadcastNotReliable { round: 1 },
+            parties_have_different_hashes,
+        )
+        .into());
+    }
+
+    tracer.stage("Validate round 2 broadcast msgs");
+    let malformed_msgs = round2_broad_msgs
+        .iter_indexed()
+        .filter(|(_j, _msg_id, msg)| {
+            [&msg.D, &msg.F, &msg.hat_D, &msg.hat_F]
+                .iter()
+                .any(|c| c.len() != usize::from(n) - 1)
+        })
+        .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+        .collect::<Vec<_>>();
+    if !malformed_msgs.is_empty() {
+        return Err(
+            SigningAborted::new(SigningAbortReason::MalformedRound2, malformed_msgs).into(),
+        );
+    }
+
+    let mut faulty_parties = vec![];
+    for ((j, broad_msg_id, broad_msg), (_, uni_msg_id, uni_msg)) in round2_broad_msgs
+        .iter_indexed()
+        .zip(round2_uni_msgs.iter_indexed())
+    {
+        tracer.stage("Retrieve auxiliary data");
+        let X_j = X[usize::from(j)];
+        let R_j = &R[usize::from(j)];
+        let enc_j = fast_paillier::EncryptionKey::from_n(R_j.N.clone());
+        let G_j = &round1a_msgs[usize::from(j)].G;
+        let position = utils::peer_position(j, i);
+
+        tracer.stage("Validate psi");
+        let psi_invalid = pi_aff::non_interactive::verify::<E, D>(
+            &unambiguous::ProofPsi {
+                sid,
+                prover: j,
+                hat: false,
+            },
+            &R_i.into(),
+            pi_aff::Data {
+                key0: &dec_i,
+                key1: &enc_j,
+                c: &K_i,
+                d: &broad_msg.D[position],
+                y: &broad_msg.F[position],
+                x: &broad_msg.Gamma,
+            },
+            &uni_msg.psi.0,
+            &security_params.pi_aff,
+            &uni_msg.psi.1,
+        )
+        .err();
+
+        tracer.stage("Validate hat_psi");
+        let hat_psi_invalid = pi_aff::non_interactive::verify::<E, D>(
+            &unambiguous::ProofPsi {
+                sid,
+                prover: j,
+                hat: true,
+            },
+            &R_i.into(),
+            pi_aff::Data {
+                key0: &dec_i,
+                key1: &enc_j,
+                c: &K_i,
+                d: &broad_msg.hat_D[position],
+                y: &broad_msg.hat_F[position],
+                x: &X_j,
+            },
+            &uni_msg.hat_psi.0,
+            &security_params.pi_aff,
+            &uni_msg.hat_psi.1,
+        )
+        .err();
+
+        tracer.stage("Validate psi_prime");
+        let psi_prime_invalid = pi_log::non_interactive::verify::<E, D>(
+            &unambiguous::ProofLog {
+                sid,
+                prover: j,
+                prime_prime: false,
+            },
+            &R_i.into(),
+            pi_log::Data {
+                key0: &enc_j,
+                c: G_j,
+                x: &broad_msg.Gamma,
+                b: &Point::<E>::generator().to_point(),
+            },
+            &uni_msg.psi_prime.0,
+            &security_params.pi_log,
+            &uni_msg.psi_prime.1,
+        )
+        .err();
+
+        if psi_invalid.is_some() || hat_psi_invalid.is_some() || psi_prime_invalid.is_some() {
+            faulty_parties.push(AbortBlame::new(j, broad_msg_id, uni_msg_id))
+        }
+        runtime.yield_now().await;
+    }
+
+    if !faulty_parties.is_empty() {
+        return Err(SigningAborted::new(SigningAbortReason::InvalidPsi, faulty_parties).into());
+    }
+
+    tracer.stage("Compute Gamma, delta_i, chi_i");
+    let Gamma = Gamma_i
+        + round2_broad_msgs
+            .iter()
+            .map(|msg| msg.Gamma)
+            .sum::<Point<E>>();
+
+    let alpha_sum = round2_broad_msgs
+        .iter_indexed()
+        .map(|(j, _, msg)| &msg.D[utils::peer_position(j, i)])
+        .try_fold(Scalar::<E>::zero(), |sum, D_ij| {
+            let alpha_ij = dec_i
+                .decrypt(D_ij)
+                .map_err(|_| Bug::PaillierDec(BugSource::alpha))?;
+            Ok::<_, Bug>(sum + alpha_ij.to_scalar())
+        })?;
+    let hat_alpha_sum = round2_broad_msgs
+        .iter_indexed()
+        .map(|(j, _, msg)| &msg.hat_D[utils::peer_position(j, i)])
+        .try_fold(Scalar::zero(), |sum, hat_D_ij| {
+            let hat_alpha_ij = dec_i
+                .decrypt(hat_D_ij)
+                .map_err(|_| Bug::PaillierDec(BugSource::hat_alpha))?;
+            Ok::<_, Bug>(sum + hat_alpha_ij.to_scalar())
+        })?;
+
+    let delta_i = gamma_i.as_ref() * k_i.as_ref() + alpha_sum + beta_sum;
+    let chi_i = x_i * k_i.as_ref() + hat_alpha_sum + hat_beta_sum;
+    runtime.yield_now().await;
+
+    tracer.stage("Hash received msgs (reliability check)");
+    let round2_hash = udigest::hash_iter::<D>(
+        round2_broad_msgs
+            .iter_including_me(&round2_broad_msg)
+            .map(|msg| unambiguous::Echo { sid, round: 2, msg }),
+    );
+
+    let Delta_i = Gamma * &k_i;
+    let round3_msg = MsgRound3 {
+        delta: delta_i,
+        Delta: Delta_i,
+        echo: round2_hash,
+    };
+
+    tracer.send_msg();
+    outgoings
+        .send(Outgoing::broadcast(Msg::Round3(round3_msg.clone())))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Round 4
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let round3_msgs = rounds
+        .complete(round3)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    tracer.stage("Assert other parties hashed messages (reliability check)");
+    let parties_have_different_hashes = round3_msgs
+        .iter_indexed()
+        .filter(|(_j, _msg_id, msg)| msg.echo != round2_hash)
+        .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+        .collect::<Vec<_>>();
+    if !parties_have_different_hashes.is_empty() {
+        return Err(SigningAborted::new(
+            SigningAbortReason::BroadcastNotReliable { round: 2 },
+            parties_have_different_hashes,
+        )
+        .into());
+    }
+
+    tracer.stage("Hash received msgs (reliability check)");
+    let round3_hash = udigest::hash_iter::<D>(
+        round3_msgs
+            .iter_including_me(&round3_msg)
+            .map(|msg| unambiguous::Echo { sid, round: 3, msg }),
+    );
+
+    for j in utils::iter_peers(i, n) {
+        tracer.stage("Prove psi_prime_prime");
+        let R_j = &R[usize::from(j)];
+        let psi_prime_prime = pi_log::non_interactive::prove::<E, D>(
+            &unambiguous::ProofLog {
+                sid,
+                prover: i,
+                prime_prime: true,
+            },
+            &R_j.into(),
+            pi_log::Data {
+                key0: &dec_i,
+                c: &K_i,
+                x: &Delta_i,
+                b: &Gamma,
+            },
+            pi_log::PrivateData {
+                x: &utils::scalar_to_bignumber(&k_i),
+                nonce: &rho_i,
+            },
+            &security_params.pi_log,
+            &mut *rng,
+        )
+        .map_err(|e| Bug::PiLog(BugSource::psi_prime_prime, e))?;
+
+        tracer.send_msg();
+        outgoings
+            .feed(Outgoing::p2p(
+                j,
+                Msg::Round4(MsgRound4 {
+                    psi_prime_prime,
+                    echo: round3_hash,
+                }),
+            ))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+    }
+    tracer.send_msg();
+    outgoings.flush().await.map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Round 5
+    tracer.round_begins();
+
+    tracer.receive_msgs();
+    let round4_msgs = rounds
+        .complete(round4)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    // All signers need to agree on δ_j, Δ_j, otherwise only some of them would proceed
+    // to identification of faulty parties
+    tracer.stage("Assert other parties hashed messages (reliability check)");
+    let parties_have_different_hashes = round4_msgs
+        .iter_indexed()
+        .filter(|(_j, _msg_id, msg)| msg.echo != round3_hash)
+        .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+        .collect::<Vec<_>>();
+    if !parties_have_different_hashes.is_empty() {
+        return Err(SigningAborted::new(
+            SigningAbortReason::BroadcastNotReliable { round: 3 },
+            parties_have_different_hashes,
+        )
+        .into());
+    }
+
+    tracer.stage("Validate psi_prime_prime");
+    let mut faulty_parties = vec![];
+    for (((j, proof_id, proof_j), (_, msg_id, msg_j)), ciphertext_j) in round4_msgs
+        .iter_indexed()
+        .zip(round3_msgs.iter_indexed())
+        .zip(ciphertexts.iter())
+    {
+        let R_j = &R[usize::from(j)];
+        let enc_j = fast_paillier::EncryptionKey::from_n(R_j.N.clone());
+
+        if pi_log::non_interactive::verify::<E, D>(
+            &unambiguous::ProofLog {
+                sid,
+                prover: j,
+                prime_prime: true,
+            },
+            &R_i.into(),
+            pi_log::Data {
+                key0: &enc_j,
+                c: &ciphertext_j.K,
+                x: &msg_j.Delta,
+                b: &Gamma,
+            },
+            &proof_j.psi_prime_prime.0,
+            &security_params.pi_log,
+            &proof_j.psi_prime_prime.1,
+        )
+        .is_err()
+        {
+            faulty_parties.push(AbortBlame::new(j, msg_id, proof_id))
+        }
+    }
+    runtime.yield_now().await;
+
+    if !faulty_parties.is_empty() {
+        return Err(
+            SigningAborted::new(SigningAbortReason::InvalidPsiPrimePrime, faulty_parties).into(),
+        );
+    }
+
+    tracer.stage("Compute delta, Delta");
+    let delta = delta_i + round3_msgs.iter().map(|m| m.delta).sum::<Scalar<E>>();
+    let Delta = Delta_i + round3_msgs.iter().map(|m| m.Delta).sum::<Point<E>>();
+    if Point::generator() * delta != Delta {
+        // Identification of faulty parties: the presignature is discarded, so each
+        // party reveals its ephemeral secrets, and δ_j can be recomputed publicly
+        tracer.named_round_begins("Identification of faulty parties (δ)");
+
+        let reveal_i = MsgRevealDelta {
+            k: utils::scalar_to_bignumber(&k_i),
+            rho: rho_i,
+            gamma: utils::scalar_to_bignumber(&gamma_i),
+            nu: v_i,
+            beta,
+            s,
+            r,
+        };
+        tracer.send_msg();
+        outgoings
+            .send(Outgoing::broadcast(Msg::RevealDelta(reveal_i.clone())))
+            .await
+            .map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.receive_msgs();
+        let reveal_msgs = rounds
+            .complete(reveal_delta)
+            .await?
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        tracer.stage("Validate revealed secrets");
+        let round2_msgs = round2_broad_msgs
+            .iter_including_me(&round2_broad_msg)
+            .collect::<Vec<_>>();
+        let faulty_parties = reveal_msgs
+            .iter_indexed()
+            .filter(|(j, _, reveal_j)| {
+                check_revealed_secrets(*j, n, R, &round1a_msgs, &round2_msgs, reveal_j).is_none()
+            })
+            .map(|(j, msg_id, _)| AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        if !faulty_parties.is_empty() {
+            return Err(
+                SigningAborted::new(SigningAbortReason::MismatchedDelta, faulty_parties).into(),
+            );
+        }
+        runtime.yield_now().await;
+
+        tracer.stage("Recompute delta_j and Delta_j");
+        let reveals = reveal_msgs.iter_including_me(&reveal_i).collect::<Vec<_>>();
+        let mut faulty_parties = vec![];
+        for ((j, reveal_msg_id, reveal_j), (_, msg3_id, msg3)) in
+            reveal_msgs.iter_indexed().zip(round3_msgs.iter_indexed())
+        {
+            if recompute_delta::<E>(j, n, R, &reveals) != msg3.delta
+                || Gamma * reveal_j.k.to_scalar::<E>() != msg3.Delta
+            {
+                faulty_parties.push(AbortBlame::new(j, msg3_id, reveal_msg_id));
+            }
+        }
+
+        return Err(
+            SigningAborted::new(SigningAbortReason::MismatchedDelta, faulty_parties).into(),
+        );
+    }
+
+    tracer.stage("Compute R, S_i");
+    let R_point =
+        NonZero::from_point(Gamma * delta.invert().ok_or(Bug::ZeroDelta)?).ok_or(Bug::ZeroR)?;
+    let S_i = *R_point * chi_i;
+
+    tracer.send_msg();
+    outgoings
+        .send(Outgoing::broadcast(Msg::Round5(MsgRound5 { S: S_i })))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Output
+    tracer.named_round_begins("Presig output");
+
+    tracer.receive_msgs();
+    let round5_msgs = rounds
+        .complete(round5)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+
+    if S_i + round5_msgs.iter().map(|m| m.S).sum::<Point<E>>() != pk {
+        // Identification of faulty parties: each party proves that S_i = y_i R where
+        // y_i is a plaintext of ciphertext hat_C_i which can be computed publicly
+        tracer.named_round_begins("Identification of faulty parties (χ)");
+
+        tracer.stage("Compute hat_H_i = enc_i(x_i * k_i)");
+        let hat_nu_i = Integer::gen_invertible(N_i, rng);
+        let hat_H_i = {
+            let x_i_times_K_i = dec_i
+                .omul(&utils::scalar_to_bignumber(x_i), &K_i)
+                .map_err(|_| Bug::PaillierOp(BugSource::hat_H_i))?;
+            let zero_enc = dec_i
+                .encrypt_with(&Integer::ZERO, &hat_nu_i)
+                .map_err(|_| Bug::PaillierEnc(BugSource::hat_H_i))?;
+            dec_i
+                .encryption_key()
+                .oadd(&x_i_times_K_i, &zero_enc)
+                .map_err(|_| Bug::PaillierOp(BugSource::hat_H_i))?
+        };
+
+        tracer.stage("Compute hat_C_i");
+        let hat_C_i = combine_ciphertexts(
+            dec_i.encryption_key(),
+            &hat_H_i,
+            round2_broad_msgs
+                .iter_indexed()
+                .map(|(j, _, msg)| &msg.hat_D[utils::peer_position(j, i)]),
+            &round2_broad_msg.hat_F,
+        )
+        .map_err(|_| Bug::PaillierOp(BugSource::hat_C_i))?;
+        let (y_i, nonce_y_i) = pi_dec::decrypt_with_nonce(&dec_i, &hat_C_i)
+            .ok_or(Bug::PaillierDec(BugSource::hat_C_i))?;
+        runtime.yield_now().await;
+
+        for j in utils::iter_peers(i, n) {
+            let R_j = &R[usize::from(j)];
+
+            tracer.stage("Prove psi_mul_star");
+            let psi_mul_star = pi_mul_star::prove::<E, D>(
+                &unambiguous::ProofMulStar { sid, prover: i },
+                &R_j.into(),
+                pi_mul_star::Data {
+                    key: N_i,
+                    c: &K_i,
+                    d: &hat_H_i,
+                    x: &X[usize::from(i)],
+                },
+                pi_mul_star::PrivateData {
+                    x: &utils::scalar_to_bignumber(x_i),
+                    nonce: &hat_nu_i,
+                },
+                &security_params.pi_mul_star,
+                &mut *rng,
+            )
+            .map_err(|e| Bug::PiMulStar(BugSource::psi_mul_star, e))?;
+
+            tracer.stage("Prove psi_log");
+            let psi_log = pi_log::non_interactive::prove::<E, D>(
+                &unambiguous::ProofLogChi { sid, prover: i },
+                &R_j.into(),
+                pi_log::Data {
+                    key0: &dec_i,
+                    c: &hat_C_i,
+                    x: &S_i,
+                    b: &*R_point,
+                },
+                pi_log::PrivateData {
+                    x: &y_i,
+                    nonce: &nonce_y_i,
+                },
+                &security_params.pi_log_chi,
+                &mut *rng,
+            )
+            .map_err(|e| Bug::PiLog(BugSource::psi_log, e))?;
+
+            tracer.send_msg();
+            outgoings
+                .feed(Outgoing::p2p(
+                    j,
+                    Msg::ChiBlame(MsgChiBlame {
+                        hat_H: hat_H_i.clone(),
+                        psi_mul_star,
+                        psi_log,
+                    }),
+                ))
+                .await
+                .map_err(IoError::send_message)?;
+            tracer.msg_sent();
+        }
+        tracer.send_msg();
+        outgoings.flush().await.map_err(IoError::send_message)?;
+        tracer.msg_sent();
+
+        tracer.receive_msgs();
+        let blame_msgs = rounds
+            .complete(chi_blame)
+            .await?
+            .map_err(IoError::receive_message)?;
+        tracer.msgs_received();
+
+        tracer.stage("Validate psi_mul_star and psi_log");
+        let round2_msgs = round2_broad_msgs
+            .iter_including_me(&round2_broad_msg)
+            .collect::<Vec<_>>();
+        let mut faulty_parties = vec![];
+        for ((j, blame_msg_id, blame_msg), (_, msg_id, msg)) in
+            blame_msgs.iter_indexed().zip(round5_msgs.iter_indexed())
+        {
+            let R_j = &R[usize::from(j)];
+            let enc_j = fast_paillier::EncryptionKey::from_n(R_j.N.clone());
+            let K_j = &round1a_msgs[usize::from(j)].K;
+
+            let hat_C_j = combine_ciphertexts(
+                &enc_j,
+                &blame_msg.hat_H,
+                utils::iter_peers(j, n)
+                    .map(|l| &round2_msgs[usize::from(l)].hat_D[utils::peer_position(l, j)]),
+                &round2_msgs[usize::from(j)].hat_F,
+            );
+            let Ok(hat_C_j) = hat_C_j else {
+                faulty_parties.push(AbortBlame::new(j, msg_id, blame_msg_id));
+                continue;
+            };
+
+            let psi_mul_star_invalid = pi_mul_star::verify::<E, D>(
+                &unambiguous::ProofMulStar { sid, prover: j },
+                &R_i.into(),
+                pi_mul_star::Data {
+                    key: &R_j.N,
+                    c: K_j,
+                    d: &blame_msg.hat_H,
+                    x: &X[usize::from(j)],
+                },
+                &blame_msg.psi_mul_star.0,
+                &security_params.pi_mul_star,
+                &blame_msg.psi_mul_star.1,
+            )
+            .is_err();
+            let psi_log_invalid = pi_log::non_interactive::verify::<E, D>(
+                &unambiguous::ProofLogChi { sid, prover: j },
+                &R_i.into(),
+                pi_log::Data {
+                    key0: &enc_j,
+                    c: &hat_C_j,
+                    x: &msg.S,
+                    b: &*R_point,
+                },
+                &blame_msg.psi_log.0,
+                &security_params.pi_log_chi,
+                &blame_msg.psi_log.1,
+            )
+            .is_err();
+
+            if psi_mul_star_invalid || psi_log_invalid {
+                faulty_parties.push(AbortBlame::new(j, msg_id, blame_msg_id));
+            }
+            runtime.yield_now().await;
+        }
+
+        return Err(SigningAborted::new(SigningAbortReason::MismatchedChi, faulty_parties).into());
+    }
+
+    tracer.stage("Compute commitments k_j R, chi_j R");
+    let delta_inv = delta.invert().ok_or(Bug::ZeroDelta)?;
+    let mut commitments = round3_msgs
+        .iter()
+        .zip(round5_msgs.iter())
+        .map(|(msg3, msg5)| SignerCommitment {
+            k_R: msg3.Delta * delta_inv,
+            chi_R: msg5.S,
+        })
+        .collect::<Vec<_>>();
+    commitments.insert(
+        usize::from(i),
+        SignerCommitment {
+            k_R: Delta_i * delta_inv,
+            chi_R: S_i,
+        },
+    );
+
+    let presig = Presignature {
+        R: R_point,
+        k: k_i,
+        chi: SecretScalar::new(&mut chi_i.clone()),
+        signer: metadata.signers[usize::from(i)],
+        metadata,
+        commitments,
+        derived,
+    };
+
+    // If message is not specified, protocol terminates here and outputs partial
+    // signature
+    let Some(message_to_sign) = message_to_sign else {
+        tracer.protocol_ends();
+        return Ok(ProtocolOutput::Presignature(presig));
+    };
+
+    // Signing
+    tracer.named_round_begins("Partial signing");
+
+    // Round 1
+    let partial_sig = presig.issue_partial_signature_unchecked(message_to_sign);
+
+    tracer.send_msg();
+    outgoings
+        .send(Outgoing::broadcast(Msg::Round6(MsgRound6 {
+            sigma: partial_sig.sigma,
+        })))
+        .await
+        .map_err(IoError::send_message)?;
+    tracer.msg_sent();
+
+    // Output
+    tracer.named_round_begins("Signature reconstruction");
+
+    tracer.receive_msgs();
+    let partial_sigs = rounds
+        .complete(round6)
+        .await?
+        .map_err(IoError::receive_message)?;
+    tracer.msgs_received();
+    let sig = {
+        let r = NonZero::from_scalar(partial_sig.r);
+        let s = NonZero::from_scalar(
+            partial_sig.sigma + partial_sigs.iter().map(|m| m.sigma).sum::<Scalar<E>>(),
+        );
+        Option::zip(r, s).map(|(r, s)| {
+            Signature::from_raw_parts(r, s)
+                .with_recovery_id(partial_sig.recovery_id)
+                .normalize_s()
+        })
+    };
+    let Some(sig) = sig.filter(|sig| sig.verify(&pk, &message_to_sign).is_ok()) else {
+        // Identification of faulty parties: σ_j = k_j m + χ_j r, so it can be checked
+        // against k_j R = δ^-1 Δ_j and S_j = χ_j R
+        tracer.stage("Identify faulty parties (σ)");
+        let m = message_to_sign.to_scalar();
+        let faulty_parties = partial_sigs
+            .iter_indexed()
+            .zip(round3_msgs.iter())
+            .zip(round5_msgs.iter())
+            .filter(|(((_, _, msg6), msg3), msg5)| {
+                *R_point * msg6.sigma != msg3.Delta * (delta_inv * m) + msg5.S * partial_sig.r
+            })
+            .map(|(((j, msg_id, _), _), _)| AbortBlame::new(j, msg_id, msg_id))
+            .collect::<Vec<_>>();
+        return Err(
+            SigningAborted::new(SigningAbortReason::SignatureInvalid, faulty_parties).into(),
+        );
+    };
+
+    tracer.protocol_ends();
+    Ok(ProtocolOutput::Signature(sig))
+}
+
+/// Checks that secrets revealed by $j$-th party are consistent with ciphertexts it sent
+/// at rounds 1 and 2
+///
+/// Returns `None` if revealed secrets are inconsistent
+fn check_revealed_secrets<E: Curve, D: Digest>(
+    j: PartyIndex,
+    n: u16,
+    R: &[PartyAux],
+    round1a_msgs: &[&MsgRound1a],
+    round2_msgs: &[&MsgRound2Broad<E, D>],
+    reveal: &MsgRevealDelta,
+) -> Option<()> {
+    let len = usize::from(n) - 1;
+    if reveal.beta.len() != len || reveal.s.len() != len || reveal.r.len() != len {
+        return None;
+    }
+    let enc_j = fast_paillier::EncryptionKey::from_n(R[usize::from(j)].N.clone());
+    let round1a_j = round1a_msgs[usize::from(j)];
+    let round2_j = round2_msgs[usize::from(j)];
+
+    (enc_j.encrypt_with(&reveal.k, &reveal.rho).ok()? == round1a_j.K).then_some(())?;
+    (enc_j.encrypt_with(&reveal.gamma, &reveal.nu).ok()? == round1a_j.G).then_some(())?;
+    (Point::generator() * reveal.gamma.to_scalar::<E>() == round2_j.Gamma).then_some(())?;
+
+    for (position, l) in utils::iter_peers(j, n).enumerate() {
+        let enc_l = fast_paillier::EncryptionKey::from_n(R[usize::from(l)].N.clone());
+        let neg_beta = (-&reveal.beta[position]).complete();
+
+        // D_lj = (gamma_j * K_l) + enc_l(-beta_jl, s_jl)
+        let D_lj = enc_l
+            .oadd(
+                &enc_l
+                    .omul(&reveal.gamma, &round1a_msgs[usize::from(l)].K)
+                    .ok()?,
+                &enc_l.encrypt_with(&neg_beta, &reveal.s[position]).ok()?,
+            )
+            .ok()?;
+        let F_lj = enc_j.encrypt_with(&neg_beta, &reveal.r[position]).ok()?;
+        (D_lj == round2_j.D[position] && F_lj == round2_j.F[position]).then_some(())?;
+    }
+
+    Some(())
+}
+
+/// Recomputes $\delta_j$ from secrets revealed by all signers
+///
+/// Revealed secrets must be validated via [`check_revealed_secrets`] beforehand
+fn recompute_delta<E: Curve>(
+    j: PartyIndex,
+    n: u16,
+    R: &[PartyAux],
+    reveals: &[&MsgRevealDelta],
+) -> Scalar<E> {
+    let reveal_j = reveals[usize::from(j)];
+    let N_j = &R[usize::from(j)].N;
+
+    let mut delta_j = (&reveal_j.gamma * &reveal_j.k).complete().to_scalar::<E>();
+    for (position, l) in utils::iter_peers(j, n).enumerate() {
+        let reveal_l = reveals[usize::from(l)];
+        let beta_lj = &reveal_l.beta[utils::peer_position(l, j)];
+
+        // alpha_jl is a decryption of D_jl = (gamma_l * K_j) + enc_j(-beta_lj)
+        let alpha_jl = signed_modulo((&reveal_l.gamma * &reveal_j.k).complete() - beta_lj, N_j);
+        delta_j += alpha_jl.to_scalar::<E>() + reveal_j.beta[position].to_scalar::<E>();
+    }
+    delta_j
+}
+
+/// Reduces `x` modulo `n` into `{-n/2, .., n/2}`, the same way as paillier decryption does
+fn signed_modulo(x: Integer, n: &Integer) -> Integer {
+    let x = x.modulo(n);
+    if (&x << 1u32).complete() >= *n {
+        x - n
+    } else {
+        x
+    }
+}
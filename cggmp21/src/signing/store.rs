@@ -0,0 +1,729 @@
+//! Presignature storage enforcing one-time use
+//!
+//! **Reusing a presignature leaks the private key.** [`PresignatureStore`] is the only way to
+//! issue a partial signature from a presignature: [`Presignature`] can't be used to sign, cloned
+//! or serialized by itself. A presignature is moved into the store right after it's generated,
+//! and the store hands out an opaque [`PresignatureHandle`] in exchange. The handle can be
+//! consumed by [`PresignatureStore::consume`] which marks the presignature consumed before
+//! partial signature is issued. Any further attempt to use the same presignature is refused.
+//!
+//! Every presignature is identified by [`PresignatureId`] derived from its public component $R$.
+//! $R$ is the same for all signers that generated the presignature, so signers agree on the id
+//! without any extra communication.
+//!
+//! Store keeps presignatures in a [`PresignatureStorage`]. [`MemoryStorage`] keeps them in
+//! memory, [`FileStorage`] is a reference implementation that keeps them on disk (requires
+//! `presignature-file-store` feature).
+
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+
+use generic_ec::{coords::AlwaysHasAffineX, Curve, NonZero, Point, SecretScalar};
+use round_based::PartyIndex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    DataToSign, PartialSignature, Presignature, PresignatureMetadata, PresignatureMismatch,
+    SignerCommitment,
+};
+use crate::key_share::KeyShare;
+use crate::security_level::SecurityLevel;
+
+/// Unique identifier of a presignature
+///
+/// Derived from public component $R$ of presignature via [`PresignatureId::new`], so all signers
+/// that took part in presignature generation obtain the same id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct PresignatureId(#[serde(with = "hex::serde")] [u8; 32]);
+
+impl PresignatureId {
+    /// Derives id of the presignature
+    pub fn new<E: Curve>(presignature: &Presignature<E>) -> Self {
+        Self::from_nonce(&presignature.R)
+    }
+
+    fn from_nonce<E: Curve>(R: &NonZero<Point<E>>) -> Self {
+        #[derive(udigest::Digestable)]
+        #[udigest(tag = prefixed!("presignature_id"))]
+        #[udigest(bound = "")]
+        struct Id<'a, E: Curve> {
+            R: &'a NonZero<Point<E>>,
+        }
+
+        Self(udigest::hash::<sha2::Sha256>(&Id { R }).into())
+    }
+
+    /// Returns bytes representation of the id
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl core::fmt::Display for PresignatureId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+/// Presignature as it's kept in [`PresignatureStorage`]
+///
+/// Opaque: it can only be saved to and taken from storage by [`PresignatureStore`]. Storage
+/// may serialize it in order to persist it.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct StoredPresignature<E: Curve> {
+    R: NonZero<Point<E>>,
+    k: SecretScalar<E>,
+    chi: SecretScalar<E>,
+    signer: PartyIndex,
+    metadata: PresignatureMetadata,
+    commitments: Vec<SignerCommitment<E>>,
+    #[serde(default)]
+    derived: bool,
+}
+
+impl<E: Curve> StoredPresignature<E> {
+    /// Returns id of the presignature
+    pub fn id(&self) -> PresignatureId {
+        PresignatureId::from_nonce(&self.R)
+    }
+}
+
+impl<E: Curve> From<Presignature<E>> for StoredPresignature<E> {
+    fn from(presignature: Presignature<E>) -> Self {
+        Self {
+            R: presignature.R,
+            k: presignature.k,
+            chi: presignature.chi,
+            signer: presignature.signer,
+            metadata: presignature.metadata,
+            commitments: presignature.commitments,
+            derived: presignature.derived,
+        }
+    }
+}
+
+impl<E: Curve> From<StoredPresignature<E>> for Presignature<E> {
+    fn from(stored: StoredPresignature<E>) -> Self {
+        Self {
+            R: stored.R,
+            k: stored.k,
+            chi: stored.chi,
+            signer: stored.signer,
+            metadata: stored.metadata,
+            commitments: stored.commitments,
+            derived: stored.derived,
+        }
+    }
+}
+
+/// Storage of presignatures that guarantees each of them is taken at most once
+///
+/// Implementations must guarantee that once [`take`](Self::take) returned a presignature,
+/// any further call to `take` or [`save`](Self::save) with the same id fails, including
+/// calls made after the application restarts or from another process sharing the same storage.
+/// Storage must never hand out a copy of presignature by other means.
+pub trait PresignatureStorage<E: Curve> {
+    /// Saves presignature under given id
+    ///
+    /// Must return [`PresignatureStoreError::AlreadyExists`] if presignature with the same id
+    /// is already stored, and [`PresignatureStoreError::AlreadyConsumed`] if it has been
+    /// taken before.
+    fn save(
+        &mut self,
+        id: PresignatureId,
+        presignature: StoredPresignature<E>,
+    ) -> Result<(), PresignatureStoreError>;
+
+    /// Atomically marks presignature as consumed and returns it
+    ///
+    /// Presignature must be persistently marked as consumed before it's returned. Returns
+    /// [`PresignatureStoreError::UnknownId`] if there's no such presignature, and
+    /// [`PresignatureStoreError::AlreadyConsumed`] if it has been taken before.
+    fn take(
+        &mut self,
+        id: &PresignatureId,
+    ) -> Result<StoredPresignature<E>, PresignatureStoreError>;
+
+    /// Checks whether presignature with given id is stored and not consumed yet
+    fn contains(&self, id: &PresignatureId) -> Result<bool, PresignatureStoreError>;
+}
+
+/// Handle of a presignature kept in [`PresignatureStore`]
+///
+/// Obtained from [`PresignatureStore::insert`] or [`PresignatureStore::get`], and consumed by
+/// [`PresignatureStore::consume`].
+pub struct PresignatureHandle<E: Curve> {
+    id: PresignatureId,
+    _curve: PhantomData<E>,
+}
+
+impl<E: Curve> PresignatureHandle<E> {
+    /// Returns id of the presignature
+    pub fn id(&self) -> PresignatureId {
+        self.id
+    }
+}
+
+/// Store of presignatures, the only way to issue a partial signature from a presignature
+///
+/// Presignatures are kept in storage `S`, see [`PresignatureStorage`].
+///
+/// ## Example
+/// ```rust,no_run
+/// # fn doc() -> Result<(), Box<dyn std::error::Error>> {
+/// # type E = cggmp21::supported_curves::Secp256k1;
+/// # let presignature: cggmp21::signing::Presignature<E> = unimplemented!();
+/// # let data_to_sign: cggmp21::signing::DataToSign<E> = unimplemented!();
+/// # let key_share: cggmp21::KeyShare<E> = unimplemented!();
+/// use cggmp21::signing::store::{MemoryStorage, PresignatureStore};
+///
+/// let mut store = PresignatureStore::new(MemoryStorage::default());
+/// // Presignature is moved into the store right after it's generated
+/// let handle = store.insert(presignature)?;
+///
+/// // Later, when signing request is received
+/// let partial_sig = store.consume(handle, &key_share, data_to_sign)?;
+/// # Ok(()) }
+/// ```
+pub struct PresignatureStore<S> {
+    storage: S,
+}
+
+impl<S> PresignatureStore<S> {
+    /// Constructs a store that keeps presignatures in given storage
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    /// Saves presignature, returns its handle
+    pub fn insert<E: Curve>(
+        &mut self,
+        presignature: Presignature<E>,
+    ) -> Result<PresignatureHandle<E>, PresignatureStoreError>
+    where
+        S: PresignatureStorage<E>,
+    {
+        let id = PresignatureId::new(&presignature);
+        self.storage.save(id, presignature.into())?;
+        Ok(PresignatureHandle {
+            id,
+            _curve: PhantomData,
+        })
+    }
+
+    /// Returns handle of a stored presignature
+    ///
+    /// Used to refer to presignature saved before the application restarted, or chosen by
+    /// another signer. Returns [`PresignatureStoreError::UnknownId`] if there's no such
+    /// presignature or it has already been consumed.
+    pub fn get<E: Curve>(
+        &self,
+        id: PresignatureId,
+    ) -> Result<PresignatureHandle<E>, PresignatureStoreError>
+    where
+        S: PresignatureStorage<E>,
+    {
+        if !self.storage.contains(&id)? {
+            return Err(PresignatureStoreError::UnknownId);
+        }
+        Ok(PresignatureHandle {
+            id,
+            _curve: PhantomData,
+        })
+    }
+
+    /// Consumes presignature and issues partial signature for given message
+    ///
+    /// Presignature is marked as consumed before partial signature is issued. Any further
+    /// attempt to use it returns [`PresignatureStoreError::AlreadyConsumed`].
+    ///
+    /// `key_share` must be the key share that was used to generate the presignature, otherwise
+    /// [`PresignatureStoreError::Mismatch`] error is returned. Note that it's the master key
+    /// share even if [derivation path](Presignature::set_derivation_path_with_algo) was set.
+    /// Presignature is consumed even if it doesn't match the key share.
+    pub fn consume<E, L>(
+        &mut self,
+        handle: PresignatureHandle<E>,
+        key_share: &KeyShare<E, L>,
+        message_to_sign: DataToSign<E>,
+    ) -> Result<PartialSignature<E>, PresignatureStoreError>
+    where
+        E: Curve,
+        L: SecurityLevel,
+        S: PresignatureStorage<E>,
+        NonZero<Point<E>>: AlwaysHasAffineX<E>,
+    {
+        let presignature = Presignature::from(self.storage.take(&handle.id)?);
+        Ok(presignature.issue_partial_signature(key_share, message_to_sign)?)
+    }
+
+    /// Consumes presignature and issues partial signature for given message signed by a child
+    /// key derived using `derivation_path`
+    ///
+    /// Derivation path is validated before presignature is consumed. See
+    /// [`Presignature::set_derivation_path_with_algo`] for details. Derivation path can be set
+    /// only once: if it was already set before presignature was inserted into the store,
+    /// [`PresignatureStoreError::DerivationPathAlreadySet`] is returned and presignature is
+    /// consumed.
+    ///
+    /// Partial signatures issued this way are verified by
+    /// [`PartialSignature::combine_verified`](super::PartialSignature::combine_verified)
+    /// against public data with the same derivation path set, see
+    /// [`PresignaturePublicData::set_derivation_path_with_algo`](super::PresignaturePublicData::set_derivation_path_with_algo).
+    #[cfg(feature = "hd-wallet")]
+    pub fn consume_with_derivation_path<E, Hd, L, Index>(
+        &mut self,
+        handle: PresignatureHandle<E>,
+        key_share: &KeyShare<E, L>,
+        derivation_path: impl IntoIterator<Item = Index>,
+        message_to_sign: DataToSign<E>,
+    ) -> Result<PartialSignature<E>, PresignatureStoreError>
+    where
+        E: Curve,
+        Hd: hd_wallet::HdWallet<E>,
+        L: SecurityLevel,
+        S: PresignatureStorage<E>,
+        hd_wallet::NonHardenedIndex: TryFrom<Index>,
+        NonZero<Point<E>>: AlwaysHasAffineX<E>,
+    {
+        let epub = key_share
+            .extended_public_key()
+            .ok_or(PresignatureStoreError::InvalidDerivationPath)?;
+        let additive_shift = super::derive_additive_shift::<E, Hd, _>(epub, derivation_path)
+            .map_err(|_| PresignatureStoreError::InvalidDerivationPath)?;
+        let presignature = Presignature::from(self.storage.take(&handle.id)?);
+        if presignature.derived {
+            return Err(PresignatureStoreError::DerivationPathAlreadySet);
+        }
+        let presignature = presignature.apply_additive_shift(additive_shift);
+        Ok(presignature.issue_partial_signature(key_share, message_to_sign)?)
+    }
+}
+
+/// Storage that keeps presignatures in memory
+///
+/// Consumed presignatures are remembered until the storage is dropped. Presignatures are lost
+/// when the application restarts, which is safe, but wasteful.
+pub struct MemoryStorage<E: Curve> {
+    presignatures: HashMap<PresignatureId, StoredPresignature<E>>,
+    consumed: HashSet<PresignatureId>,
+}
+
+impl<E: Curve> Default for MemoryStorage<E> {
+    fn default() -> Self {
+        Self {
+            presignatures: HashMap::new(),
+            consumed: HashSet::new(),
+        }
+    }
+}
+
+impl<E: Curve> PresignatureStorage<E> for MemoryStorage<E> {
+    fn save(
+        &mut self,
+        id: PresignatureId,
+        presignature: StoredPresignature<E>,
+    ) -> Result<(), PresignatureStoreError> {
+        if self.consumed.contains(&id) {
+            return Err(PresignatureStoreError::AlreadyConsumed);
+        }
+        match self.presignatures.entry(id) {
+            std::collections::hash_map::Entry::Occupied(_) => {
+                Err(PresignatureStoreError::AlreadyExists)
+            }
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(presignature);
+                Ok(())
+            }
+        }
+    }
+
+    fn take(
+        &mut self,
+        id: &PresignatureId,
+    ) -> Result<StoredPresignature<E>, PresignatureStoreError> {
+        match self.presignatures.remove(id) {
+            Some(presignature) => {
+                self.consumed.insert(*id);
+                Ok(presignature)
+            }
+            None if self.consumed.contains(id) => Err(PresignatureStoreError::AlreadyConsumed),
+            None => Err(PresignatureStoreError::UnknownId),
+        }
+    }
+
+    fn contains(&self, id: &PresignatureId) -> Result<bool, PresignatureStoreError> {
+        Ok(self.presignatures.contains_key(id))
+    }
+}
+
+/// Error returned by [`PresignatureStore`]
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum PresignatureStoreError {
+    /// There's no presignature with such id
+    #[error("unknown presignature id")]
+    UnknownId,
+    /// Presignature has already been consumed
+    #[error("presignature has already been consumed")]
+    AlreadyConsumed,
+    /// Presignature with the same id is already stored
+    #[error("presignature with the same id is already stored")]
+    AlreadyExists,
+    /// Derivation path is not valid, or key doesn't support HD derivation
+    #[error("invalid derivation path")]
+    InvalidDerivationPath,
+    /// Derivation path was already set on presignature before it was inserted into the store
+    #[error("derivation path has already been set")]
+    DerivationPathAlreadySet,
+    /// Presignature doesn't match the key share
+    #[error("presignature doesn't match the key share")]
+    Mismatch(#[from] PresignatureMismatch),
+    /// Underlying storage failed
+    #[error("storage error")]
+    Storage(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+#[cfg(feature = "presignature-file-store")]
+pub use file::{FilePresignatureStore, FileStorage};
+
+#[cfg(feature = "presignature-file-store")]
+mod file {
+    use std::fs;
+    use std::io::{self, Write};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use generic_ec::Curve;
+
+    use super::{
+        PresignatureId, PresignatureStorage, PresignatureStore, PresignatureStoreError,
+        StoredPresignature,
+    };
+
+    /// Presignature store that keeps every presignature in a separate file
+    ///
+    /// ## Example
+    /// ```rust,no_run
+    /// # fn doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// # type E = cggmp21::supported_curves::Secp256k1;
+    /// # let presignature: cggmp21::signing::Presignature<E> = unimplemented!();
+    /// # let data_to_sign: cggmp21::signing::DataToSign<E> = unimplemented!();
+    /// # let key_share: cggmp21::KeyShare<E> = unimplemented!();
+    /// use cggmp21::signing::store::FilePresignatureStore;
+    ///
+    /// let mut store = FilePresignatureStore::open("./presignatures")?;
+    /// // Presignature is moved into the store right after it's generated
+    /// let id = store.insert(presignature)?.id();
+    ///
+    /// // Later, when signing request is received (possibly, after restart)
+    /// let handle = store.get(id)?;
+    /// let partial_sig = store.consume(handle, &key_share, data_to_sign)?;
+    /// # Ok(()) }
+    /// ```
+    pub type FilePresignatureStore = PresignatureStore<FileStorage>;
+
+    impl FilePresignatureStore {
+        /// Opens a store at given directory, creates the directory if it doesn't exist
+        pub fn open(dir: impl AsRef<Path>) -> Result<Self, PresignatureStoreError> {
+            FileStorage::open(dir).map(Self::new)
+        }
+    }
+
+    /// Storage that keeps every presignature in a separate file
+    ///
+    /// Presignature with id `{id}` is stored at `{dir}/{id}.presig`. When it's taken, a
+    /// marker `{dir}/{id}.consumed` is created exclusively (i.e. creation fails if marker
+    /// already exists), and both the marker and the directory entry are flushed to disk before
+    /// the presignature is read. Markers are never removed, so presignature can't be taken
+    /// twice, even by several processes sharing the same directory or after a crash.
+    ///
+    /// Presignatures contain secret data and are stored unencrypted. Files are created readable
+    /// by owner only (on Unix), but make sure that the directory is protected accordingly.
+    pub struct FileStorage {
+        dir: PathBuf,
+    }
+
+    impl FileStorage {
+        /// Opens a storage at given directory, creates the directory if it doesn't exist
+        pub fn open(dir: impl AsRef<Path>) -> Result<Self, PresignatureStoreError> {
+            let dir = dir.as_ref().to_path_buf();
+            fs::create_dir_all(&dir).map_err(storage_error)?;
+            Ok(Self { dir })
+        }
+
+        fn presig_path(&self, id: &PresignatureId) -> PathBuf {
+            self.dir.join(format!("{id}.presig"))
+        }
+
+        fn marker_path(&self, id: &PresignatureId) -> PathBuf {
+            self.dir.join(format!("{id}.consumed"))
+        }
+
+        /// Returns path of a temporary file that's not used by any other save in progress
+        fn tmp_path(&self, id: &PresignatureId) -> PathBuf {
+            static COUNTER: AtomicU64 = AtomicU64::new(0);
+            let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+            self.dir
+                .join(format!("{id}.presig.{}.{n}.tmp", std::process::id()))
+        }
+
+        /// Flushes directory entries to disk
+        fn sync_dir(&self) -> Result<(), PresignatureStoreError> {
+            // Directories can't be opened for syncing on non-Unix platforms
+            #[cfg(unix)]
+            fs::File::open(&self.dir)
+                .and_then(|dir| dir.sync_all())
+                .map_err(storage_error)?;
+            Ok(())
+        }
+    }
+
+    impl<E: Curve> PresignatureStorage<E> for FileStorage {
+        fn save(
+            &mut self,
+            id: PresignatureId,
+            presignature: StoredPresignature<E>,
+        ) -> Result<(), PresignatureStoreError> {
+            if self.marker_path(&id).exists() {
+                return Err(PresignatureStoreError::AlreadyConsumed);
+            }
+            let bytes = serde_json::to_vec(&presignature).map_err(storage_error)?;
+
+            // Presignature is written to a temporary file first, and then hard-linked to its
+            // final location: linking fails if the file already exists, and the presignature
+            // never appears partially written
+            let tmp_path = self.tmp_path(&id);
+            let mut tmp = create_secret_file(&tmp_path).map_err(storage_error)?;
+            let written = tmp.write_all(&bytes).and_then(|()| tmp.sync_all());
+            let linked = written.and_then(|()| fs::hard_link(&tmp_path, self.presig_path(&id)));
+            fs::remove_file(&tmp_path).map_err(storage_error)?;
+            match linked {
+                Ok(()) => self.sync_dir(),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    Err(PresignatureStoreError::AlreadyExists)
+                }
+                Err(err) => Err(storage_error(err)),
+            }
+        }
+
+        fn take(
+            &mut self,
+            id: &PresignatureId,
+        ) -> Result<StoredPresignature<E>, PresignatureStoreError> {
+            let presig_path = self.presig_path(id);
+            if !presig_path.exists() {
+                return Err(if self.marker_path(id).exists() {
+                    PresignatureStoreError::AlreadyConsumed
+                } else {
+                    PresignatureStoreError::UnknownId
+                });
+            }
+
+            let marker = fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(self.marker_path(id));
+            let marker = match marker {
+                Ok(marker) => marker,
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    return Err(PresignatureStoreError::AlreadyConsumed)
+                }
+                Err(err) => return Err(storage_error(err)),
+            };
+            marker.sync_all().map_err(storage_error)?;
+            // Marker must survive a crash, which requires its directory entry to be persisted
+            self.sync_dir()?;
+
+            let bytes = fs::read(&presig_path).map_err(storage_error)?;
+            let presignature = serde_json::from_slice(&bytes).map_err(storage_error)?;
+            fs::remove_file(&presig_path).map_err(storage_error)?;
+            Ok(presignature)
+        }
+
+        fn contains(&self, id: &PresignatureId) -> Result<bool, PresignatureStoreError> {
+            Ok(self.presig_path(id).exists() && !self.marker_path(id).exists())
+        }
+    }
+
+    /// Creates a new file that's readable and writable by owner only
+    fn create_secret_file(path: &Path) -> io::Result<fs::File> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options.open(path)
+    }
+
+    fn storage_error(
+        err: impl std::error::Error + Send + Sync + 'static,
+    ) -> PresignatureStoreError {
+        PresignatureStoreError::Storage(Box::new(err))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generic_ec::{Curve, NonZero, Point, SecretScalar};
+
+    use super::{MemoryStorage, PresignatureStorage, PresignatureStoreError, StoredPresignature};
+
+    fn random_presignature<E: Curve>(rng: &mut rand_dev::DevRng) -> StoredPresignature<E> {
+        StoredPresignature {
+            R: Point::generator() * NonZero::<SecretScalar<E>>::random(rng),
+            k: SecretScalar::random(rng),
+            chi: SecretScalar::random(rng),
+            signer: 0,
+            metadata: crate::signing::PresignatureMetadata {
+                signers: vec![0, 1],
+                key_fingerprint: [0; 32],
+                aux_fingerprint: [0; 32],
+                execution_id: b"test".to_vec(),
+            },
+            commitments: vec![],
+            derived: false,
+        }
+    }
+
+    fn copy<E: Curve>(presig: &StoredPresignature<E>) -> StoredPresignature<E> {
+        StoredPresignature {
+            R: presig.R,
+            k: presig.k.clone(),
+            chi: presig.chi.clone(),
+            signer: presig.signer,
+            metadata: presig.metadata.clone(),
+            commitments: presig.commitments.clone(),
+            derived: presig.derived,
+        }
+    }
+
+    /// Checks that storage refuses to hand out the same presignature twice; `reopen` returns
+    /// storage sharing the same state as the original one
+    fn storage_refuses_reuse<E: Curve, S: PresignatureStorage<E>>(
+        mut storage: S,
+        reopen: impl FnOnce(S) -> S,
+    ) {
+        let mut rng = rand_dev::DevRng::new();
+
+        let presig = random_presignature::<E>(&mut rng);
+        let id = presig.id();
+        storage.save(id, copy(&presig)).unwrap();
+        assert!(storage.contains(&id).unwrap());
+        assert!(matches!(
+            storage.save(id, copy(&presig)),
+            Err(PresignatureStoreError::AlreadyExists)
+        ));
+
+        let taken = storage.take(&id).unwrap();
+        assert_eq!(taken.R, presig.R);
+        assert_eq!(taken.k.as_ref(), presig.k.as_ref());
+        assert_eq!(taken.chi.as_ref(), presig.chi.as_ref());
+        assert_eq!(taken.metadata, presig.metadata);
+        assert!(!storage.contains(&id).unwrap());
+
+        // Presignature can be taken only once, even if reopened or saved again
+        let mut reopened = reopen(storage);
+        assert!(matches!(
+            reopened.take(&id),
+            Err(PresignatureStoreError::AlreadyConsumed)
+        ));
+        assert!(matches!(
+            reopened.save(id, presig),
+            Err(PresignatureStoreError::AlreadyConsumed)
+        ));
+
+        let unknown = random_presignature::<E>(&mut rng).id();
+        assert!(!reopened.contains(&unknown).unwrap());
+        assert!(matches!(
+            reopened.take(&unknown),
+            Err(PresignatureStoreError::UnknownId)
+        ));
+    }
+
+    fn memory_storage_refuses_reuse<E: Curve>() {
+        storage_refuses_reuse::<E, _>(MemoryStorage::default(), |storage| storage)
+    }
+
+    #[cfg(feature = "presignature-file-store")]
+    fn file_storage_refuses_reuse<E: Curve>() {
+        use super::FileStorage;
+
+        let mut rng = rand_dev::DevRng::new();
+        let dir = std::env::temp_dir().join(format!(
+            "cggmp21-presignatures-{}",
+            hex::encode(rand_core::RngCore::next_u64(&mut rng).to_be_bytes())
+        ));
+
+        storage_refuses_reuse::<E, _>(FileStorage::open(&dir).unwrap(), |storage| {
+            drop(storage);
+            FileStorage::open(&dir).unwrap()
+        });
+
+        // Only markers of consumed presignatures are left, and no temporary files
+        let files = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with(".consumed"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(all(unix, feature = "presignature-file-store"))]
+    #[test]
+    fn file_storage_keeps_presignatures_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        type E = crate::supported_curves::Secp256k1;
+        let mut rng = rand_dev::DevRng::new();
+        let dir = std::env::temp_dir().join(format!(
+            "cggmp21-presignatures-{}",
+            hex::encode(rand_core::RngCore::next_u64(&mut rng).to_be_bytes())
+        ));
+        let mut storage = super::FileStorage::open(&dir).unwrap();
+
+        let presig = random_presignature::<E>(&mut rng);
+        let id = presig.id();
+        storage.save(id, presig).unwrap();
+
+        let mode = std::fs::metadata(dir.join(format!("{id}.presig")))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn memory_storage_refuses_reuse_secp256k1() {
+        memory_storage_refuses_reuse::<crate::supported_curves::Secp256k1>()
+    }
+    #[test]
+    fn memory_storage_refuses_reuse_secp256r1() {
+        memory_storage_refuses_reuse::<crate::supported_curves::Secp256r1>()
+    }
+    #[test]
+    fn memory_storage_refuses_reuse_stark() {
+        memory_storage_refuses_reuse::<crate::supported_curves::Stark>()
+    }
+
+    #[cfg(feature = "presignature-file-store")]
+    #[test]
+    fn file_storage_refuses_reuse_secp256k1() {
+        file_storage_refuses_reuse::<crate::supported_curves::Secp256k1>()
+    }
+    #[cfg(feature = "presignature-file-store")]
+    #[test]
+    fn file_storage_refuses_reuse_secp256r1() {
+        file_storage_refuses_reuse::<crate::supported_curves::Secp256r1>()
+    }
+    #[cfg(feature = "presignature-file-store")]
+    #[test]
+    fn file_storage_refuses_reuse_stark() {
+        file_storage_refuses_reuse::<crate::supported_curves::Stark>()
+    }
+}
@@ -5,9 +5,13 @@ use paillier_zk::{
     paillier_affine_operation_in_range as pi_aff, paillier_encryption_in_range as pi_enc,
 };
 use round_based::rounds_router::simple_store::RoundMsgs;
-use round_based::{MsgId, PartyIndex};
+use round_based::PartyIndex;
 
 use crate::security_level::SecurityLevel;
+use crate::zk::{
+    paillier_decryption_modulo_q as pi_dec, paillier_multiplication as pi_mul,
+    paillier_multiplication_vs_group as pi_mul_star,
+};
 
 pub use paillier_zk::fast_paillier::utils::external_rand;
 
@@ -19,7 +23,11 @@ pub fn scalar_to_bignumber<E: Curve>(scalar: impl AsRef<Scalar<E>>) -> Integer {
 pub struct SecurityParams {
     pub pi_aff: pi_aff::SecurityParams,
     pub pi_log: pi_log::SecurityParams,
+    pub pi_log_chi: pi_log::SecurityParams,
     pub pi_enc: pi_enc::SecurityParams,
+    pub pi_mul: pi_mul::SecurityParams,
+    pub pi_mul_star: pi_mul_star::SecurityParams,
+    pub pi_dec: pi_dec::SecurityParams,
 }
 
 impl SecurityParams {
@@ -36,11 +44,29 @@ impl SecurityParams {
                 epsilon: L::EPSILON,
                 q: L::q(),
             },
+            // Plaintext of `hat_C_i` (ciphertext of `chi_i`) can be as big as `n * 2^ell_prime`
+            pi_log_chi: pi_log::SecurityParams {
+                l: 2 * L::ELL + L::ELL_PRIME,
+                epsilon: L::EPSILON,
+                q: L::q(),
+            },
             pi_enc: pi_enc::SecurityParams {
                 l: L::ELL,
                 epsilon: L::EPSILON,
                 q: L::q(),
             },
+            pi_mul: pi_mul::SecurityParams { q: L::q() },
+            pi_mul_star: pi_mul_star::SecurityParams {
+                l: L::ELL,
+                epsilon: L::EPSILON,
+                q: L::q(),
+            },
+            // Decrypted plaintext can be as big as `r * chi_i` where `r` is a scalar
+            pi_dec: pi_dec::SecurityParams {
+                l: 2 * L::ELL + L::ELL_PRIME,
+                epsilon: L::EPSILON,
+                q: L::q(),
+            },
         }
     }
 }
@@ -57,32 +83,7 @@ where
     a
 }
 
-/// For some messages it is possible to precisely identify where the fault
-/// happened and which party is to blame. Use this struct to collect present the
-/// blame.
-///
-/// In the future we might want to replace the data_message and proof_message
-/// with a generic vec of messages.
-#[derive(Debug)]
-#[allow(dead_code)] // removes false-positive warnings
-pub struct AbortBlame {
-    /// Party which can be blamed for breaking the protocol
-    pub faulty_party: PartyIndex,
-    /// Message with initial data
-    pub data_message: MsgId,
-    /// Message with some kind of proof related to the data
-    pub proof_message: MsgId,
-}
-
-impl AbortBlame {
-    pub fn new(faulty_party: PartyIndex, data_message: MsgId, proof_message: MsgId) -> Self {
-        Self {
-            faulty_party,
-            data_message,
-            proof_message,
-        }
-    }
-}
+pub use cggmp21_keygen::{AbortBlame, Aborted};
 
 /// Filter returns `true` for every __faulty__ message pair
 pub fn collect_blame<D, P, F>(
@@ -152,6 +153,18 @@ pub fn iter_peers(i: u16, n: u16) -> impl Iterator<Item = u16> {
     (0..n).filter(move |x| *x != i)
 }
 
+/// Position of `j`-th party in [`iter_peers(i, n)`](iter_peers)
+///
+/// `i` and `j` must not be equal
+pub fn peer_position(i: u16, j: u16) -> usize {
+    debug_assert_ne!(i, j);
+    if j < i {
+        usize::from(j)
+    } else {
+        usize::from(j) - 1
+    }
+}
+
 /// Drop n-th item from iteration
 pub fn but_nth<T, I: IntoIterator<Item = T>>(n: u16, iter: I) -> impl Iterator<Item = T> {
     iter.into_iter()
@@ -1 +1,9 @@
+pub mod paillier_decryption_modulo_q;
+pub mod paillier_multiplication;
+pub mod paillier_multiplication_vs_group;
 pub mod ring_pedersen_parameters;
+
+/// Witness that proof is invalid
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("proof is invalid")]
+pub struct InvalidProof;
@@ -0,0 +1,344 @@
+//! Пdec or Rdec in the paper. Proof that plaintext of ciphertext $C$ is equal to
+//! $x$ modulo $q$ (curve order). Non-interactive version only.
+//!
+//! Prover knows $y, \rho$ such that $C = (1 + N_0)^y \rho^{N_0} \mod N_0^2$ and
+//! $x = y \mod q$.
+use digest::Digest;
+use generic_ec::{Curve, Scalar};
+use paillier_zk::{
+    fast_paillier::{utils, Ciphertext, Nonce},
+    paillier_encryption_in_range::Aux,
+    rug::{Complete, Integer},
+    IntegerExt,
+};
+use serde::{Deserialize, Serialize};
+
+use super::InvalidProof;
+
+/// Security parameters for the proof
+#[derive(Debug, Clone)]
+pub struct SecurityParams {
+    /// $\ell$ in the paper, bit size of plaintext $y$
+    ///
+    /// Plaintext is not checked to be in range, this parameter only affects the
+    /// size of masks that hide the plaintext.
+    pub l: usize,
+    /// $\varepsilon$ in the paper, slackness parameter
+    pub epsilon: usize,
+    /// Challenge is sampled from $\pm q$
+    pub q: Integer,
+}
+
+/// Public data known to both prover and verifier
+#[derive(Clone, Copy, udigest::Digestable)]
+#[udigest(bound = "")]
+pub struct Data<'a, E: Curve> {
+    /// Prover's paillier public key $N_0$
+    #[udigest(as = &crate::utils::encoding::Integer)]
+    pub key: &'a Integer,
+    /// $C$
+    #[udigest(as = &crate::utils::encoding::Integer)]
+    pub c: &'a Ciphertext,
+    /// $x$
+    pub x: &'a Scalar<E>,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// $y$, plaintext of $C$
+    pub y: &'a Integer,
+    /// $\rho$, nonce of $C$
+    pub nonce: &'a Nonce,
+}
+
+/// Prover's first message
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[serde(bound = "")]
+#[udigest(bound = "")]
+pub struct Commitment<E: Curve> {
+    /// $S = s^y t^\mu \mod \hat N$
+    #[udigest(as = crate::utils::encoding::Integer)]
+    pub S: Integer,
+    /// $T = s^\alpha t^\nu \mod \hat N$
+    #[udigest(as = crate::utils::encoding::Integer)]
+    pub T: Integer,
+    /// $A = (1 + N_0)^\alpha r^{N_0} \mod N_0^2$
+    #[udigest(as = crate::utils::encoding::Integer)]
+    pub A: Integer,
+    /// $\gamma = \alpha \mod q$
+    pub gamma: Scalar<E>,
+}
+
+/// The ZK proof. Computed by [`prove`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Proof {
+    /// $z_1 = \alpha + e y$
+    pub z1: Integer,
+    /// $z_2 = \nu + e \mu$
+    pub z2: Integer,
+    /// $w = r \rho^e \mod N_0$
+    pub w: Integer,
+}
+
+fn challenge<E: Curve, D: Digest>(
+    shared_state: &impl udigest::Digestable,
+    aux: &Aux,
+    data: Data<E>,
+    commitment: &Commitment<E>,
+    security: &SecurityParams,
+) -> Integer {
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = "dfns.paillier_decryption_modulo_q.seed")]
+    #[udigest(bound = "S: udigest::Digestable, A: udigest::Digestable")]
+    struct Seed<'a, S, A, E: Curve> {
+        shared_state: &'a S,
+        aux: A,
+        data: Data<'a, E>,
+        commitment: &'a Commitment<E>,
+    }
+
+    let mut rng = rand_hash::HashRng::<D, _>::from_seed(Seed {
+        shared_state,
+        aux: aux.digest_public_data(),
+        data,
+        commitment,
+    });
+    Integer::from_rng_pm(&security.q, &mut rng)
+}
+
+/// Computes $(1 + N)^x \rho^N \mod N^2$ for arbitrary integer $x$
+fn encrypt(n: &Integer, nn: &Integer, x: &Integer, nonce: &Integer) -> Option<Integer> {
+    let a = (Integer::ONE + (x * n).complete()).modulo(nn);
+    let b: Integer = nonce.pow_mod_ref(n, nn)?.into();
+    Some((a * b).modulo(nn))
+}
+
+/// Compute the proof for the given data, producing random commitment and
+/// deriving deterministic challenge based on `shared_state` and `data`
+///
+/// `aux` must contain ring-pedersen parameters of the verifier
+pub fn prove<E: Curve, D: Digest>(
+    shared_state: &impl udigest::Digestable,
+    aux: &Aux,
+    data: Data<E>,
+    pdata: PrivateData,
+    security: &SecurityParams,
+    rng: &mut impl rand_core::RngCore,
+) -> Result<(Commitment<E>, Proof), paillier_zk::Error> {
+    let n0 = data.key;
+    let nn0 = (n0 * n0).complete();
+    let hat_n = &aux.rsa_modulo;
+
+    let two_to_l = (Integer::ONE << security.l).complete();
+    let two_to_l_plus_e = (Integer::ONE << (security.l + security.epsilon)).complete();
+
+    let alpha = Integer::from_rng_pm(&two_to_l_plus_e, rng);
+    let mu = Integer::from_rng_pm(&(&two_to_l * hat_n).complete(), rng);
+    let nu = Integer::from_rng_pm(&(&two_to_l_plus_e * hat_n).complete(), rng);
+    let r = utils::sample_in_mult_group(rng, n0);
+
+    let S = hat_n.combine(&aux.s, pdata.y, &aux.t, &mu)?;
+    let T = hat_n.combine(&aux.s, &alpha, &aux.t, &nu)?;
+    let A = encrypt(n0, &nn0, &alpha, &r).ok_or_else(paillier_zk::BadExponent::undefined)?;
+    let gamma = alpha.to_scalar();
+    let commitment = Commitment { S, T, A, gamma };
+
+    let e = challenge::<E, D>(shared_state, aux, data, &commitment, security);
+
+    let z1 = &alpha + (&e * pdata.y).complete();
+    let z2 = &nu + (&e * &mu).complete();
+    let w = pdata
+        .nonce
+        .pow_mod_ref(&e, n0)
+        .map(|rho_to_e| (Integer::from(rho_to_e) * &r).modulo(n0))
+        .ok_or_else(paillier_zk::BadExponent::undefined)?;
+
+    Ok((commitment, Proof { z1, z2, w }))
+}
+
+/// Verify the proof, deriving challenge independently from same data
+///
+/// `aux` must contain ring-pedersen parameters of the verifier
+pub fn verify<E: Curve, D: Digest>(
+    shared_state: &impl udigest::Digestable,
+    aux: &Aux,
+    data: Data<E>,
+    commitment: &Commitment<E>,
+    security: &SecurityParams,
+    proof: &Proof,
+) -> Result<(), InvalidProof> {
+    let n0 = data.key;
+    let nn0 = (n0 * n0).complete();
+    let hat_n = &aux.rsa_modulo;
+
+    if !utils::in_mult_group(data.c, &nn0) || !utils::in_mult_group(&commitment.A, &nn0) {
+        return Err(InvalidProof);
+    }
+
+    let e = challenge::<E, D>(shared_state, aux, data, commitment, security);
+
+    {
+        let lhs = encrypt(n0, &nn0, &proof.z1, &proof.w).ok_or(InvalidProof)?;
+        let c_to_e: Integer = data.c.pow_mod_ref(&e, &nn0).ok_or(InvalidProof)?.into();
+        let rhs = (c_to_e * &commitment.A).modulo(&nn0);
+        if lhs != rhs {
+            return Err(InvalidProof);
+        }
+    }
+
+    if proof.z1.to_scalar::<E>() != commitment.gamma + e.to_scalar::<E>() * data.x {
+        return Err(InvalidProof);
+    }
+
+    {
+        let lhs = hat_n
+            .combine(&aux.s, &proof.z1, &aux.t, &proof.z2)
+            .map_err(|_| InvalidProof)?;
+        let s_to_e: Integer = commitment
+            .S
+            .pow_mod_ref(&e, hat_n)
+            .ok_or(InvalidProof)?
+            .into();
+        let rhs = (s_to_e * &commitment.T).modulo(hat_n);
+        if lhs != rhs {
+            return Err(InvalidProof);
+        }
+    }
+
+    Ok(())
+}
+
+/// Decrypts the ciphertext and extracts the nonce it was encrypted with
+///
+/// Returns `(y, rho)` such that $C = (1 + N)^y \rho^N \mod N^2$, where `y` is in `{-N/2, .., N/2}`.
+/// It can be used to obtain [`PrivateData`] for a ciphertext produced by homomorphic operations.
+pub fn decrypt_with_nonce(
+    key: &paillier_zk::fast_paillier::DecryptionKey,
+    c: &Ciphertext,
+) -> Option<(Integer, Nonce)> {
+    let n = key.n();
+    let nn = (n * n).complete();
+
+    let y = key.decrypt(c).ok()?;
+    // rho^N = C (1 + N)^{-y} = C (1 - y N) mod N^2
+    let rho_to_n = (c * (Integer::ONE - (&y * n).complete())).modulo(&nn);
+    // rho = (rho^N mod N)^{N^{-1} mod phi(N)} mod N
+    let phi = (key.p() - Integer::ONE).complete() * (key.q() - Integer::ONE).complete();
+    let n_inv: Integer = n.invert_ref(&phi)?.into();
+    let rho = rho_to_n.modulo(n).pow_mod(&n_inv, n).ok()?;
+
+    Some((y, rho))
+}
+
+#[cfg(test)]
+mod test {
+    use generic_ec::{curves::Secp256k1, Scalar};
+    use paillier_zk::{
+        fast_paillier,
+        rug::{Complete, Integer},
+        IntegerExt,
+    };
+
+    use crate::utils;
+
+    type D = sha2::Sha256;
+    type E = Secp256k1;
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams {
+            l: 1024,
+            epsilon: 128,
+            q: (Integer::ONE << 128_u32).complete(),
+        }
+    }
+
+    fn setup(
+        rng: &mut rand_dev::DevRng,
+    ) -> (
+        fast_paillier::DecryptionKey,
+        paillier_zk::paillier_encryption_in_range::Aux,
+    ) {
+        let p = utils::generate_blum_prime(rng, 768);
+        let q = utils::generate_blum_prime(rng, 768);
+        let key = fast_paillier::DecryptionKey::from_primes(p, q).unwrap();
+
+        let p = utils::generate_blum_prime(rng, 512);
+        let q = utils::generate_blum_prime(rng, 512);
+        let hat_n = (&p * &q).complete();
+        let phi = (&p - 1u8).complete() * (&q - 1u8).complete();
+        let r = Integer::gen_invertible(&hat_n, rng);
+        let lambda = phi.random_below_ref(&mut utils::external_rand(rng)).into();
+        let t = r.square().modulo(&hat_n);
+        let s = t.pow_mod_ref(&lambda, &hat_n).unwrap().into();
+        let aux = paillier_zk::paillier_encryption_in_range::Aux {
+            s,
+            t,
+            rsa_modulo: hat_n,
+            multiexp: None,
+            crt: None,
+        };
+        (key, aux)
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        let shared_state = "shared state";
+        let (key, aux) = setup(&mut rng);
+
+        // Ciphertext obtained via homomorphic operations, nonce is unknown
+        let a = Integer::from_rng_pm(&(Integer::ONE << 512_u32).complete(), &mut rng);
+        let b = Integer::from_rng_pm(&(Integer::ONE << 512_u32).complete(), &mut rng);
+        let (a_enc, _) = key.encrypt_with_random(&mut rng, &a).unwrap();
+        let (b_enc, _) = key.encrypt_with_random(&mut rng, &b).unwrap();
+        let c = key.encryption_key().oadd(&a_enc, &b_enc).unwrap();
+
+        let (y, nonce) = super::decrypt_with_nonce(&key, &c).unwrap();
+        assert_eq!(y, (&a + &b).complete());
+        assert_eq!(key.encrypt_with(&y, &nonce).unwrap(), c);
+
+        let x: Scalar<E> = y.to_scalar();
+        let data = super::Data {
+            key: key.n(),
+            c: &c,
+            x: &x,
+        };
+        let pdata = super::PrivateData {
+            y: &y,
+            nonce: &nonce,
+        };
+        let (commitment, proof) =
+            super::prove::<E, D>(&shared_state, &aux, data, pdata, &security(), &mut rng).unwrap();
+        super::verify::<E, D>(&shared_state, &aux, data, &commitment, &security(), &proof)
+            .expect("proof should pass");
+    }
+
+    #[test]
+    fn failing() {
+        let mut rng = rand_dev::DevRng::new();
+        let shared_state = "shared state";
+        let (key, aux) = setup(&mut rng);
+
+        let y = Integer::from_rng_pm(&(Integer::ONE << 512_u32).complete(), &mut rng);
+        let (c, nonce) = key.encrypt_with_random(&mut rng, &y).unwrap();
+
+        let x: Scalar<E> = y.to_scalar::<E>() + Scalar::one();
+        let data = super::Data {
+            key: key.n(),
+            c: &c,
+            x: &x,
+        };
+        let pdata = super::PrivateData {
+            y: &y,
+            nonce: &nonce,
+        };
+        let (commitment, proof) =
+            super::prove::<E, D>(&shared_state, &aux, data, pdata, &security(), &mut rng).unwrap();
+        if super::verify::<E, D>(&shared_state, &aux, data, &commitment, &security(), &proof)
+            .is_ok()
+        {
+            panic!("proof should fail");
+        }
+    }
+}
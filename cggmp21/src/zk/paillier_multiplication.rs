@@ -0,0 +1,294 @@
+//! Пmul or Rmul in the paper. Proof that plaintext of ciphertext $C$ is a product
+//! of plaintexts of ciphertexts $X$ and $Y$. Non-interactive version only.
+//!
+//! Prover knows $x, \rho, \rho_x$ such that $X = (1 + N)^x \rho_x^N \mod N^2$
+//! and $C = Y^x \rho^N \mod N^2$.
+use digest::Digest;
+use paillier_zk::{
+    fast_paillier::{utils, Ciphertext, Nonce},
+    rug::{Complete, Integer},
+    IntegerExt,
+};
+use serde::{Deserialize, Serialize};
+
+use super::InvalidProof;
+
+/// Security parameters for the proof
+#[derive(Debug, Clone)]
+pub struct SecurityParams {
+    /// Challenge is sampled from $\pm q$
+    pub q: Integer,
+}
+
+/// Public data known to both prover and verifier
+#[derive(Clone, Copy, udigest::Digestable)]
+pub struct Data<'a> {
+    /// Prover's paillier public key $N$
+    #[udigest(as = &crate::utils::encoding::Integer)]
+    pub key: &'a Integer,
+    /// $X$, encryption of $x$
+    #[udigest(as = &crate::utils::encoding::Integer)]
+    pub x: &'a Ciphertext,
+    /// $Y$
+    #[udigest(as = &crate::utils::encoding::Integer)]
+    pub y: &'a Ciphertext,
+    /// $C = Y^x \rho^N \mod N^2$
+    #[udigest(as = &crate::utils::encoding::Integer)]
+    pub c: &'a Ciphertext,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// $x$, plaintext of $X$
+    pub x: &'a Integer,
+    /// $\rho_x$, nonce of $X$
+    pub nonce_x: &'a Nonce,
+    /// $\rho$, nonce used in $C$
+    pub nonce: &'a Nonce,
+}
+
+/// Prover's first message
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+pub struct Commitment {
+    /// $A = Y^\alpha r^N \mod N^2$
+    #[udigest(as = crate::utils::encoding::Integer)]
+    pub a: Integer,
+    /// $B = (1 + N)^\alpha s^N \mod N^2$
+    #[udigest(as = crate::utils::encoding::Integer)]
+    pub b: Integer,
+}
+
+/// The ZK proof. Computed by [`prove`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Proof {
+    /// $z = \alpha + e x$
+    pub z: Integer,
+    /// $u = r \rho^e \mod N$
+    pub u: Integer,
+    /// $v = s \rho_x^e \mod N$
+    pub v: Integer,
+}
+
+fn challenge<D: Digest>(
+    shared_state: &impl udigest::Digestable,
+    data: Data,
+    commitment: &Commitment,
+    security: &SecurityParams,
+) -> Integer {
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = "dfns.paillier_multiplication.seed")]
+    struct Seed<'a, S: udigest::Digestable> {
+        shared_state: &'a S,
+        data: Data<'a>,
+        commitment: &'a Commitment,
+    }
+
+    let mut rng = rand_hash::HashRng::<D, _>::from_seed(Seed {
+        shared_state,
+        data,
+        commitment,
+    });
+    Integer::from_rng_pm(&security.q, &mut rng)
+}
+
+/// Computes $(1 + N)^x \rho^N \mod N^2$ for arbitrary integer $x$
+fn encrypt(n: &Integer, nn: &Integer, x: &Integer, nonce: &Integer) -> Option<Integer> {
+    let a = (Integer::ONE + (x * n).complete()).modulo(nn);
+    let b: Integer = nonce.pow_mod_ref(n, nn)?.into();
+    Some((a * b).modulo(nn))
+}
+
+/// Compute the proof for the given data, producing random commitment and
+/// deriving deterministic challenge based on `shared_state` and `data`
+pub fn prove<D: Digest>(
+    shared_state: &impl udigest::Digestable,
+    data: Data,
+    pdata: PrivateData,
+    security: &SecurityParams,
+    rng: &mut impl rand_core::RngCore,
+) -> Result<(Commitment, Proof), paillier_zk::Error> {
+    let n = data.key;
+    let nn = (n * n).complete();
+
+    let alpha = utils::sample_in_mult_group(rng, n);
+    let r = utils::sample_in_mult_group(rng, n);
+    let s = utils::sample_in_mult_group(rng, n);
+
+    let a = {
+        let y_to_alpha: Integer = data
+            .y
+            .pow_mod_ref(&alpha, &nn)
+            .ok_or_else(paillier_zk::BadExponent::undefined)?
+            .into();
+        let r_to_n: Integer = r
+            .pow_mod_ref(n, &nn)
+            .ok_or_else(paillier_zk::BadExponent::undefined)?
+            .into();
+        (y_to_alpha * r_to_n).modulo(&nn)
+    };
+    let b = encrypt(n, &nn, &alpha, &s).ok_or_else(paillier_zk::BadExponent::undefined)?;
+    let commitment = Commitment { a, b };
+
+    let e = challenge::<D>(shared_state, data, &commitment, security);
+
+    let z = &alpha + (&e * pdata.x).complete();
+    let u = pdata
+        .nonce
+        .pow_mod_ref(&e, n)
+        .map(|rho_to_e| (Integer::from(rho_to_e) * &r).modulo(n))
+        .ok_or_else(paillier_zk::BadExponent::undefined)?;
+    let v = pdata
+        .nonce_x
+        .pow_mod_ref(&e, n)
+        .map(|rho_x_to_e| (Integer::from(rho_x_to_e) * &s).modulo(n))
+        .ok_or_else(paillier_zk::BadExponent::undefined)?;
+
+    Ok((commitment, Proof { z, u, v }))
+}
+
+/// Verify the proof, deriving challenge independently from same data
+pub fn verify<D: Digest>(
+    shared_state: &impl udigest::Digestable,
+    data: Data,
+    commitment: &Commitment,
+    security: &SecurityParams,
+    proof: &Proof,
+) -> Result<(), InvalidProof> {
+    let n = data.key;
+    let nn = (n * n).complete();
+
+    for c in [data.x, data.y, data.c, &commitment.a, &commitment.b] {
+        if !utils::in_mult_group(c, &nn) {
+            return Err(InvalidProof);
+        }
+    }
+
+    let e = challenge::<D>(shared_state, data, commitment, security);
+
+    {
+        let y_to_z: Integer = data
+            .y
+            .pow_mod_ref(&proof.z, &nn)
+            .ok_or(InvalidProof)?
+            .into();
+        let u_to_n: Integer = proof.u.pow_mod_ref(n, &nn).ok_or(InvalidProof)?.into();
+        let lhs = (y_to_z * u_to_n).modulo(&nn);
+
+        let c_to_e: Integer = data.c.pow_mod_ref(&e, &nn).ok_or(InvalidProof)?.into();
+        let rhs = (c_to_e * &commitment.a).modulo(&nn);
+
+        if lhs != rhs {
+            return Err(InvalidProof);
+        }
+    }
+
+    {
+        let lhs = encrypt(n, &nn, &proof.z, &proof.v).ok_or(InvalidProof)?;
+
+        let x_to_e: Integer = data.x.pow_mod_ref(&e, &nn).ok_or(InvalidProof)?.into();
+        let rhs = (x_to_e * &commitment.b).modulo(&nn);
+
+        if lhs != rhs {
+            return Err(InvalidProof);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use paillier_zk::{
+        fast_paillier,
+        rug::{Complete, Integer},
+        IntegerExt,
+    };
+
+    use crate::utils;
+
+    type D = sha2::Sha256;
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams {
+            q: (Integer::ONE << 128_u32).complete(),
+        }
+    }
+
+    fn random_key(rng: &mut rand_dev::DevRng) -> fast_paillier::DecryptionKey {
+        let p = utils::generate_blum_prime(rng, 512);
+        let q = utils::generate_blum_prime(rng, 512);
+        fast_paillier::DecryptionKey::from_primes(p, q).unwrap()
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        let shared_state = "shared state";
+        let key = random_key(&mut rng);
+
+        let x = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng);
+        let (x_enc, nonce_x) = key.encrypt_with_random(&mut rng, &x).unwrap();
+        let (y_enc, _) = key.encrypt_with_random(&mut rng, &y).unwrap();
+        let nonce = Integer::gen_invertible(key.n(), &mut rng);
+        let c = {
+            let x_times_y = key.omul(&x, &y_enc).unwrap();
+            let zero = key.encrypt_with(&Integer::ZERO, &nonce).unwrap();
+            key.encryption_key().oadd(&x_times_y, &zero).unwrap()
+        };
+        assert_eq!(key.decrypt(&c).unwrap(), (&x * &y).complete());
+
+        let data = super::Data {
+            key: key.n(),
+            x: &x_enc,
+            y: &y_enc,
+            c: &c,
+        };
+        let pdata = super::PrivateData {
+            x: &x,
+            nonce_x: &nonce_x,
+            nonce: &nonce,
+        };
+        let (commitment, proof) =
+            super::prove::<D>(&shared_state, data, pdata, &security(), &mut rng).unwrap();
+        super::verify::<D>(&shared_state, data, &commitment, &security(), &proof)
+            .expect("proof should pass");
+    }
+
+    #[test]
+    fn failing() {
+        let mut rng = rand_dev::DevRng::new();
+        let shared_state = "shared state";
+        let key = random_key(&mut rng);
+
+        let x = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng);
+        let (x_enc, nonce_x) = key.encrypt_with_random(&mut rng, &x).unwrap();
+        let (y_enc, _) = key.encrypt_with_random(&mut rng, &y).unwrap();
+        let nonce = Integer::gen_invertible(key.n(), &mut rng);
+        let c = {
+            let x_plus_one = (&x + 1u8).complete();
+            let x_times_y = key.omul(&x_plus_one, &y_enc).unwrap();
+            let zero = key.encrypt_with(&Integer::ZERO, &nonce).unwrap();
+            key.encryption_key().oadd(&x_times_y, &zero).unwrap()
+        };
+
+        let data = super::Data {
+            key: key.n(),
+            x: &x_enc,
+            y: &y_enc,
+            c: &c,
+        };
+        let pdata = super::PrivateData {
+            x: &x,
+            nonce_x: &nonce_x,
+            nonce: &nonce,
+        };
+        let (commitment, proof) =
+            super::prove::<D>(&shared_state, data, pdata, &security(), &mut rng).unwrap();
+        if super::verify::<D>(&shared_state, data, &commitment, &security(), &proof).is_ok() {
+            panic!("proof should fail");
+        }
+    }
+}
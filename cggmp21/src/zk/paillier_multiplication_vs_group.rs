@@ -0,0 +1,337 @@
+//! Пmul* or Rmul* in the paper. Proof that plaintext of ciphertext $D$ is a product
+//! of plaintext of ciphertext $C$ and discrete logarithm of a point $X$. Non-interactive
+//! version only.
+//!
+//! Prover knows $x, \rho$ such that $D = C^x \rho^{N_0} \mod N_0^2$, $X = x G$, and
+//! $x \in \pm 2^\ell$.
+use digest::Digest;
+use generic_ec::{Curve, Point};
+use paillier_zk::{
+    fast_paillier::{utils, Ciphertext, Nonce},
+    paillier_encryption_in_range::Aux,
+    rug::{Complete, Integer},
+    IntegerExt,
+};
+use serde::{Deserialize, Serialize};
+
+use super::InvalidProof;
+
+/// Security parameters for the proof
+#[derive(Debug, Clone)]
+pub struct SecurityParams {
+    /// $\ell$ in the paper, bit size of $x$
+    pub l: usize,
+    /// $\varepsilon$ in the paper, slackness parameter
+    pub epsilon: usize,
+    /// Challenge is sampled from $\pm q$
+    pub q: Integer,
+}
+
+/// Public data known to both prover and verifier
+#[derive(Clone, Copy, udigest::Digestable)]
+#[udigest(bound = "")]
+pub struct Data<'a, E: Curve> {
+    /// Prover's paillier public key $N_0$
+    #[udigest(as = &crate::utils::encoding::Integer)]
+    pub key: &'a Integer,
+    /// $C$
+    #[udigest(as = &crate::utils::encoding::Integer)]
+    pub c: &'a Ciphertext,
+    /// $D = C^x \rho^{N_0} \mod N_0^2$
+    #[udigest(as = &crate::utils::encoding::Integer)]
+    pub d: &'a Ciphertext,
+    /// $X = x G$
+    pub x: &'a Point<E>,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// $x$
+    pub x: &'a Integer,
+    /// $\rho$, nonce used in $D$
+    pub nonce: &'a Nonce,
+}
+
+/// Prover's first message
+#[derive(Clone, Serialize, Deserialize, udigest::Digestable)]
+#[serde(bound = "")]
+#[udigest(bound = "")]
+pub struct Commitment<E: Curve> {
+    /// $A = C^\alpha r^{N_0} \mod N_0^2$
+    #[udigest(as = crate::utils::encoding::Integer)]
+    pub A: Integer,
+    /// $B_x = \alpha G$
+    pub B_x: Point<E>,
+    /// $E = s^\alpha t^\gamma \mod \hat N$
+    #[udigest(as = crate::utils::encoding::Integer)]
+    pub E: Integer,
+    /// $S = s^x t^\mu \mod \hat N$
+    #[udigest(as = crate::utils::encoding::Integer)]
+    pub S: Integer,
+}
+
+/// The ZK proof. Computed by [`prove`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Proof {
+    /// $z_1 = \alpha + e x$
+    pub z1: Integer,
+    /// $z_2 = \gamma + e \mu$
+    pub z2: Integer,
+    /// $w = r \rho^e \mod N_0$
+    pub w: Integer,
+}
+
+fn challenge<E: Curve, D: Digest>(
+    shared_state: &impl udigest::Digestable,
+    aux: &Aux,
+    data: Data<E>,
+    commitment: &Commitment<E>,
+    security: &SecurityParams,
+) -> Integer {
+    #[derive(udigest::Digestable)]
+    #[udigest(tag = "dfns.paillier_multiplication_vs_group.seed")]
+    #[udigest(bound = "S: udigest::Digestable, A: udigest::Digestable")]
+    struct Seed<'a, S, A, E: Curve> {
+        shared_state: &'a S,
+        aux: A,
+        data: Data<'a, E>,
+        commitment: &'a Commitment<E>,
+    }
+
+    let mut rng = rand_hash::HashRng::<D, _>::from_seed(Seed {
+        shared_state,
+        aux: aux.digest_public_data(),
+        data,
+        commitment,
+    });
+    Integer::from_rng_pm(&security.q, &mut rng)
+}
+
+/// Compute the proof for the given data, producing random commitment and
+/// deriving deterministic challenge based on `shared_state` and `data`
+///
+/// `aux` must contain ring-pedersen parameters of the verifier
+pub fn prove<E: Curve, D: Digest>(
+    shared_state: &impl udigest::Digestable,
+    aux: &Aux,
+    data: Data<E>,
+    pdata: PrivateData,
+    security: &SecurityParams,
+    rng: &mut impl rand_core::RngCore,
+) -> Result<(Commitment<E>, Proof), paillier_zk::Error> {
+    let n0 = data.key;
+    let nn0 = (n0 * n0).complete();
+    let hat_n = &aux.rsa_modulo;
+
+    let two_to_l = (Integer::ONE << security.l).complete();
+    let two_to_l_plus_e = (Integer::ONE << (security.l + security.epsilon)).complete();
+
+    let alpha = Integer::from_rng_pm(&two_to_l_plus_e, rng);
+    let mu = Integer::from_rng_pm(&(&two_to_l * hat_n).complete(), rng);
+    let r = utils::sample_in_mult_group(rng, n0);
+    let gamma = Integer::from_rng_pm(&(&two_to_l_plus_e * hat_n).complete(), rng);
+
+    let A = {
+        let c_to_alpha: Integer = data
+            .c
+            .pow_mod_ref(&alpha, &nn0)
+            .ok_or_else(paillier_zk::BadExponent::undefined)?
+            .into();
+        let r_to_n0: Integer = r
+            .pow_mod_ref(n0, &nn0)
+            .ok_or_else(paillier_zk::BadExponent::undefined)?
+            .into();
+        (c_to_alpha * r_to_n0).modulo(&nn0)
+    };
+    let B_x = Point::generator() * alpha.to_scalar::<E>();
+    let commitment = Commitment {
+        A,
+        B_x,
+        E: hat_n.combine(&aux.s, &alpha, &aux.t, &gamma)?,
+        S: hat_n.combine(&aux.s, pdata.x, &aux.t, &mu)?,
+    };
+
+    let e = challenge::<E, D>(shared_state, aux, data, &commitment, security);
+
+    let z1 = &alpha + (&e * pdata.x).complete();
+    let z2 = &gamma + (&e * &mu).complete();
+    let w = pdata
+        .nonce
+        .pow_mod_ref(&e, n0)
+        .map(|rho_to_e| (Integer::from(rho_to_e) * &r).modulo(n0))
+        .ok_or_else(paillier_zk::BadExponent::undefined)?;
+
+    Ok((commitment, Proof { z1, z2, w }))
+}
+
+/// Verify the proof, deriving challenge independently from same data
+///
+/// `aux` must contain ring-pedersen parameters of the verifier
+pub fn verify<E: Curve, D: Digest>(
+    shared_state: &impl udigest::Digestable,
+    aux: &Aux,
+    data: Data<E>,
+    commitment: &Commitment<E>,
+    security: &SecurityParams,
+    proof: &Proof,
+) -> Result<(), InvalidProof> {
+    let n0 = data.key;
+    let nn0 = (n0 * n0).complete();
+    let hat_n = &aux.rsa_modulo;
+
+    for c in [data.c, data.d, &commitment.A] {
+        if !utils::in_mult_group(c, &nn0) {
+            return Err(InvalidProof);
+        }
+    }
+
+    let e = challenge::<E, D>(shared_state, aux, data, commitment, security);
+
+    {
+        let c_to_z1: Integer = data
+            .c
+            .pow_mod_ref(&proof.z1, &nn0)
+            .ok_or(InvalidProof)?
+            .into();
+        let w_to_n0: Integer = proof.w.pow_mod_ref(n0, &nn0).ok_or(InvalidProof)?.into();
+        let lhs = (c_to_z1 * w_to_n0).modulo(&nn0);
+
+        let d_to_e: Integer = data.d.pow_mod_ref(&e, &nn0).ok_or(InvalidProof)?.into();
+        let rhs = (d_to_e * &commitment.A).modulo(&nn0);
+
+        if lhs != rhs {
+            return Err(InvalidProof);
+        }
+    }
+
+    if Point::generator() * proof.z1.to_scalar::<E>()
+        != commitment.B_x + data.x * e.to_scalar::<E>()
+    {
+        return Err(InvalidProof);
+    }
+
+    {
+        let lhs = hat_n
+            .combine(&aux.s, &proof.z1, &aux.t, &proof.z2)
+            .map_err(|_| InvalidProof)?;
+        let s_to_e: Integer = commitment
+            .S
+            .pow_mod_ref(&e, hat_n)
+            .ok_or(InvalidProof)?
+            .into();
+        let rhs = (s_to_e * &commitment.E).modulo(hat_n);
+        if lhs != rhs {
+            return Err(InvalidProof);
+        }
+    }
+
+    if !proof
+        .z1
+        .is_in_pm(&(Integer::ONE << (security.l + security.epsilon)).complete())
+    {
+        return Err(InvalidProof);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use generic_ec::{curves::Secp256k1, Point};
+    use paillier_zk::{
+        fast_paillier,
+        rug::{Complete, Integer},
+        IntegerExt,
+    };
+
+    use crate::utils;
+
+    type D = sha2::Sha256;
+    type E = Secp256k1;
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams {
+            l: 256,
+            // Slack must exceed bit size of the challenge, otherwise honest z1
+            // falls out of range with noticeable probability
+            epsilon: 230,
+            q: (Integer::ONE << 128_u32).complete(),
+        }
+    }
+
+    fn setup(
+        rng: &mut rand_dev::DevRng,
+    ) -> (
+        fast_paillier::DecryptionKey,
+        paillier_zk::paillier_encryption_in_range::Aux,
+    ) {
+        let p = utils::generate_blum_prime(rng, 768);
+        let q = utils::generate_blum_prime(rng, 768);
+        let key = fast_paillier::DecryptionKey::from_primes(p, q).unwrap();
+
+        let p = utils::generate_blum_prime(rng, 512);
+        let q = utils::generate_blum_prime(rng, 512);
+        let hat_n = (&p * &q).complete();
+        let phi = (&p - 1u8).complete() * (&q - 1u8).complete();
+        let r = Integer::gen_invertible(&hat_n, rng);
+        let lambda = phi.random_below_ref(&mut utils::external_rand(rng)).into();
+        let t = r.square().modulo(&hat_n);
+        let s = t.pow_mod_ref(&lambda, &hat_n).unwrap().into();
+        let aux = paillier_zk::paillier_encryption_in_range::Aux {
+            s,
+            t,
+            rsa_modulo: hat_n,
+            multiexp: None,
+            crt: None,
+        };
+        (key, aux)
+    }
+
+    fn run(x: Integer, X: Point<E>) -> Result<(), super::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let shared_state = "shared state";
+        let (key, aux) = setup(&mut rng);
+
+        let k = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng);
+        let (c, _) = key.encrypt_with_random(&mut rng, &k).unwrap();
+        let nonce = Integer::gen_invertible(key.n(), &mut rng);
+        let d = {
+            let x_times_c = key.omul(&x, &c).unwrap();
+            let zero = key.encrypt_with(&Integer::ZERO, &nonce).unwrap();
+            key.encryption_key().oadd(&x_times_c, &zero).unwrap()
+        };
+
+        let data = super::Data {
+            key: key.n(),
+            c: &c,
+            d: &d,
+            x: &X,
+        };
+        let pdata = super::PrivateData {
+            x: &x,
+            nonce: &nonce,
+        };
+        let (commitment, proof) =
+            super::prove::<E, D>(&shared_state, &aux, data, pdata, &security(), &mut rng).unwrap();
+        super::verify::<E, D>(&shared_state, &aux, data, &commitment, &security(), &proof)
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        let x = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng);
+        let X = Point::generator() * x.to_scalar::<E>();
+        run(x, X).expect("proof should pass")
+    }
+
+    #[test]
+    fn failing() {
+        let mut rng = rand_dev::DevRng::new();
+        let x = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng);
+        let X = Point::generator() * (x.to_scalar::<E>() + generic_ec::Scalar::one());
+        if run(x, X).is_ok() {
+            panic!("proof should fail");
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Birkhoff interpolation used by hierarchical keys
+//!
+//! Key share of hierarchical key at position $k$ is $x_k = F^{(r_k)}(I_k)$, i.e. value of $r_k$-th
+//! derivative of the shared polynomial $F(x) = \sum_{m < t} c_m x^m$. Relation between key shares
+//! and coefficients of the polynomial is given by Birkhoff matrix $M$ such that $x_k = \sum_m M_{k,m} c_m$.
+//! Unlike Lagrange interpolation, not every set of $t$ key shares determines the polynomial, which is
+//! what makes the access structure hierarchical.
+
+use alloc::{vec, vec::Vec};
+use core::ops;
+
+use generic_ec::{Curve, NonZero, Point, Scalar};
+
+/// Returns row of Birkhoff matrix corresponding to key share of rank `r` at point `x`
+///
+/// $M_{k,m} = m (m-1) \dots (m-r+1) x^{m-r}$ for $m \ge r$, and $0$ otherwise
+pub fn matrix_row<E: Curve>(x: &Scalar<E>, r: u16, t: u16) -> Vec<Scalar<E>> {
+    let mut row = vec![Scalar::zero(); usize::from(t)];
+    let mut x_pow = Scalar::one();
+    for m in r..t {
+        let falling_factorial = (m - r + 1..=m).fold(Scalar::one(), |acc, j| acc * Scalar::from(j));
+        row[usize::from(m)] = falling_factorial * x_pow;
+        x_pow *= x;
+    }
+    row
+}
+
+/// Reduces `matrix` to reduced row echelon form, applying the same row operations to `rhs`
+///
+/// Returns indexes of pivot columns. `i`-th pivot column has its only non-zero element (equal
+/// to one) in `i`-th row.
+fn gauss_jordan<E, T>(matrix: &mut [Vec<Scalar<E>>], rhs: &mut [T]) -> Vec<usize>
+where
+    E: Curve,
+    T: Copy + ops::Sub<Output = T> + ops::Mul<Scalar<E>, Output = T>,
+{
+    let columns = matrix.first().map(|row| row.len()).unwrap_or(0);
+    let mut pivots = Vec::new();
+    for col in 0..columns {
+        let row = pivots.len();
+        let Some(pivot) = (row..matrix.len()).find(|&r| !matrix[r][col].is_zero()) else {
+            continue;
+        };
+        matrix.swap(row, pivot);
+        rhs.swap(row, pivot);
+
+        let Some(inv) = matrix[row][col].invert() else {
+            continue;
+        };
+        matrix[row].iter_mut().for_each(|a| *a *= inv);
+        rhs[row] = rhs[row] * inv;
+
+        let pivot_row = matrix[row].clone();
+        for r in 0..matrix.len() {
+            let factor = matrix[r][col];
+            if r == row || factor.is_zero() {
+                continue;
+            }
+            for (a, p) in matrix[r][col..].iter_mut().zip(&pivot_row[col..]) {
+                *a -= factor * p;
+            }
+            rhs[r] = rhs[r] - rhs[row] * factor;
+        }
+
+        pivots.push(col);
+        if pivots.len() == matrix.len() {
+            break;
+        }
+    }
+    pivots
+}
+
+/// Returns coefficients $\lambda_k$ such that $F(0) = \sum_k \lambda_k x_k$ for any polynomial
+/// $F$ of degree $t-1$, where $x_k = F^{(r_k)}(I_k)$
+///
+/// Returns `None` if key shares don't determine $F(0)$. If there's more than one solution,
+/// any of them is returned.
+pub fn coefficients_at_zero<E: Curve>(
+    I: &[impl AsRef<Scalar<E>>],
+    ranks: &[u16],
+    t: u16,
+) -> Option<Vec<Scalar<E>>> {
+    if I.len() != ranks.len() {
+        return None;
+    }
+    // We're solving equation M^T λ = (1, 0, ..., 0)
+    let rows = I
+        .iter()
+        .zip(ranks)
+        .map(|(I_k, r_k)| matrix_row(I_k.as_ref(), *r_k, t))
+        .collect::<Vec<_>>();
+    let mut matrix = (0..usize::from(t))
+        .map(|m| rows.iter().map(|row| row[m]).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut rhs = vec![Scalar::<E>::zero(); usize::from(t)];
+    *rhs.first_mut()? = Scalar::one();
+
+    let pivots = gauss_jordan(&mut matrix, &mut rhs);
+    if rhs[pivots.len()..].iter().any(|v| !v.is_zero()) {
+        // Equation has no solutions
+        return None;
+    }
+
+    // Free variables are set to one, so every key share contributes to the result
+    let mut lambda = vec![Scalar::one(); I.len()];
+    for (row, &col) in pivots.iter().enumerate() {
+        lambda[col] = (0..I.len())
+            .filter(|c| !pivots.contains(c))
+            .fold(rhs[row], |acc, c| acc - matrix[row][c]);
+    }
+    Some(lambda)
+}
+
+/// Reasons why public shares can't be interpolated
+pub enum InterpolationError {
+    /// Public shares don't determine the polynomial
+    Undetermined,
+    /// Public shares don't lie on the same polynomial of degree $t-1$
+    Inconsistent,
+}
+
+/// Interpolates public polynomial $F(x) \cdot G$ from public shares and returns $F(0) \cdot G$
+///
+/// Checks that all public shares lie on the same polynomial of degree $t-1$
+pub fn interpolate_at_zero<E: Curve>(
+    I: &[impl AsRef<Scalar<E>>],
+    ranks: &[u16],
+    t: u16,
+    public_shares: &[NonZero<Point<E>>],
+) -> Result<Point<E>, InterpolationError> {
+    if I.len() != ranks.len() || I.len() != public_shares.len() {
+        return Err(InterpolationError::Inconsistent);
+    }
+    // We're solving equation M c = X
+    let mut matrix = I
+        .iter()
+        .zip(ranks)
+        .map(|(I_k, r_k)| matrix_row(I_k.as_ref(), *r_k, t))
+        .collect::<Vec<_>>();
+    let mut rhs = public_shares.iter().map(|X_k| **X_k).collect::<Vec<_>>();
+
+    let pivots = gauss_jordan(&mut matrix, &mut rhs);
+    if pivots.len() != usize::from(t) {
+        return Err(InterpolationError::Undetermined);
+    }
+    if rhs[pivots.len()..].iter().any(|X| !X.is_zero()) {
+        return Err(InterpolationError::Inconsistent);
+    }
+    rhs.first().copied().ok_or(InterpolationError::Undetermined)
+}
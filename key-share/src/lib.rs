@@ -28,6 +28,7 @@ use core::ops;
 use generic_ec::{serde::CurveName, Curve, NonZero, Point, Scalar, SecretScalar};
 use generic_ec_zkp::polynomial::lagrange_coefficient;
 
+pub mod birkhoff;
 #[cfg(feature = "serde")]
 mod serde_fix;
 #[cfg(feature = "spof")]
@@ -83,6 +84,10 @@ use serde_with::As;
 ///   If key share is polynomial, [`vss_setup`](DirtyKeyInfo::vss_setup) fiels should be `Some(_)`.
 ///
 ///   $I_j$ mentioned above is defined in [`VssSetup::I`]. Reasonable default would be $I_j = j+1$.
+///
+///   Polynomial key may be weighted (see [`VssSetup::weights`]): signer of weight $w$ holds $w$
+///   secret shares at distinct points, and any set of signers with total weight of at least $t$
+///   can sign.
 /// * Additive key share:
 ///   * Always non-threshold (i.e. $t=n$)
 ///   * Signer with index $i$ holds a secret share $x_i$
@@ -123,6 +128,11 @@ pub struct DirtyCoreKeyShare<E: Curve> {
     pub key_info: DirtyKeyInfo<E>,
     /// Secret share $x_i$
     pub x: NonZero<SecretScalar<E>>,
+    /// Secret shares at the remaining points of local party, if key is weighted
+    ///
+    /// Signer of weight $w$ holds $w$ secret shares: the first one is [`x`](Self::x), the other
+    /// $w-1$ are listed here. Empty if key is not weighted.
+    pub extra_x: Vec<NonZero<SecretScalar<E>>>,
 }
 
 #[cfg(feature = "serde")]
@@ -144,6 +154,7 @@ impl<E: Curve> serde::Serialize for DirtyCoreKeyShare<E> {
                     chain_code,
                 },
             x,
+            extra_x,
         } = &self;
         serde_fix::ser::CoreKeyShare {
             i,
@@ -152,6 +163,7 @@ impl<E: Curve> serde::Serialize for DirtyCoreKeyShare<E> {
             public_shares,
             vss_setup,
             x,
+            extra_x,
             #[cfg(feature = "hd-wallet")]
             chain_code,
         }
@@ -173,6 +185,7 @@ impl<'de, E: Curve> serde::Deserialize<'de> for DirtyCoreKeyShare<E> {
             public_shares,
             vss_setup,
             x,
+            extra_x,
             #[cfg(feature = "hd-wallet")]
             chain_code,
         } = serde::Deserialize::deserialize(deserializer)?;
@@ -187,6 +200,7 @@ impl<'de, E: Curve> serde::Deserialize<'de> for DirtyCoreKeyShare<E> {
                 chain_code,
             },
             x,
+            extra_x,
         })
     }
 }
@@ -212,6 +226,8 @@ pub struct DirtyKeyInfo<E: Curve> {
     /// Public shares of all signers sharing the key
     ///
     /// `public_shares[i]` corresponds to public share (or public commitment) of $\ith$ party.
+    /// If key is weighted, the list contains public shares of all key shares grouped by
+    /// signers, see [`VssSetup::weights`].
     #[cfg_attr(
         feature = "serde",
         serde(with = "As::<Vec<generic_ec::serde::Compact>>")
@@ -247,23 +263,61 @@ pub struct VssSetup<E: Curve> {
     pub min_signers: u16,
     /// Key shares indexes
     ///
-    /// `I[i]` corresponds to key share index of a $\ith$ signer
+    /// `I[i]` corresponds to key share index of a $\ith$ signer (or to $\ith$ key share
+    /// if key is weighted)
     #[cfg_attr(
         feature = "serde",
         serde(with = "As::<Vec<generic_ec::serde::PreferCompact>>")
     )]
     pub I: Vec<NonZero<Scalar<E>>>,
+    /// Weights of signers, present if key is weighted
+    ///
+    /// `weights[i]` is amount of key shares held by $\ith$ signer. [`I`](Self::I) and
+    /// [`public_shares`](DirtyKeyInfo::public_shares) list key shares of all signers in order:
+    /// first `weights[0]` key shares belong to signer 0, next `weights[1]` to signer 1, and so on.
+    /// [`min_signers`](Self::min_signers) is the minimal total weight of signers able to sign.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub weights: Option<Vec<u16>>,
+    /// Ranks of key shares, present if key is hierarchical
+    ///
+    /// Hierarchical key implements Tassa's threshold access structure: key share at position $k$
+    /// is $x_k = F^{(r_k)}(I_k)$, value of $r_k$-th derivative of the shared polynomial, where
+    /// $r_k$ = `ranks[k]`. Key shares of lower rank belong to a higher tier. Set of key shares
+    /// can sign only if it contains `min_signers` key shares satisfying Pólya condition: when
+    /// their ranks are sorted in ascending order, `k`-th of them (counting from 0) doesn't
+    /// exceed `k`. For instance, if `min_signers = 3`, key shares of the highest tier have
+    /// rank 0, and rest of key shares have rank 1, then any 3 key shares can sign as long as
+    /// at least one of them is from the highest tier: ranks `{0, 1, 1}` satisfy the condition,
+    /// and ranks `{1, 1, 1}` don't.
+    ///
+    /// Key shares are interpolated via Birkhoff interpolation, see
+    /// [`interpolation_coefficients`](DirtyKeyInfo::interpolation_coefficients).
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub ranks: Option<Vec<u16>>,
 }
 
 impl<E: Curve> Validate for DirtyCoreKeyShare<E> {
     type Error = InvalidCoreShare;
 
     fn is_valid(&self) -> Result<(), Self::Error> {
-        let party_public_share = self
-            .public_shares
-            .get(usize::from(self.i))
+        let party_public_shares = self
+            .shares_of(self.i)
+            .and_then(|shares| self.public_shares.get(shares))
             .ok_or(InvalidShareReason::PartyIndexOutOfBounds)?;
-        if *party_public_share != Point::generator() * &self.x {
+        if party_public_shares.len() != self.extra_x.len() + 1 {
+            return Err(InvalidShareReason::ExtraXLen.into());
+        }
+        if party_public_shares
+            .iter()
+            .zip(self.secret_shares())
+            .any(|(X, x)| *X != Point::generator() * x)
+        {
             return Err(InvalidShareReason::PartySecretShareDoesntMatchPublicShare.into());
         }
 
@@ -279,10 +333,14 @@ impl<E: Curve> ValidateFromParts<(u16, DirtyKeyInfo<E>, NonZero<SecretScalar<E>>
     fn validate_parts(
         (i, key_info, x): &(u16, DirtyKeyInfo<E>, NonZero<SecretScalar<E>>),
     ) -> Result<(), Self::Error> {
-        let party_public_share = key_info
-            .public_shares
-            .get(usize::from(*i))
+        let party_public_shares = key_info
+            .shares_of(*i)
+            .and_then(|shares| key_info.public_shares.get(shares))
             .ok_or(InvalidShareReason::PartyIndexOutOfBounds)?;
+        // Parts carry only one secret share, so the party can't have weight above 1
+        let [party_public_share] = party_public_shares else {
+            return Err(InvalidShareReason::ExtraXLen.into());
+        };
         if *party_public_share != Point::generator() * x {
             return Err(InvalidShareReason::PartySecretShareDoesntMatchPublicShare.into());
         }
@@ -291,7 +349,12 @@ impl<E: Curve> ValidateFromParts<(u16, DirtyKeyInfo<E>, NonZero<SecretScalar<E>>
     }
 
     fn from_parts((i, key_info, x): (u16, DirtyKeyInfo<E>, NonZero<SecretScalar<E>>)) -> Self {
-        Self { i, key_info, x }
+        Self {
+            i,
+            key_info,
+            x,
+            extra_x: Vec::new(),
+        }
     }
 }
 
@@ -321,6 +384,17 @@ fn validate_vss_key_info<E: Curve>(
     if n < 2 {
         return Err(InvalidShareReason::TooFewParties.into());
     }
+    if let Some(weights) = &vss_setup.weights {
+        if weights.len() < 2 {
+            return Err(InvalidShareReason::TooFewParties.into());
+        }
+        if weights.contains(&0) {
+            return Err(InvalidShareReason::ZeroWeight.into());
+        }
+        if weights.iter().map(|w| usize::from(*w)).sum::<usize>() != public_shares.len() {
+            return Err(InvalidShareReason::WeightsDontMatchShares.into());
+        }
+    }
 
     let t = vss_setup.min_signers;
     if !(2 <= t) {
@@ -333,6 +407,27 @@ fn validate_vss_key_info<E: Curve>(
         return Err(InvalidShareReason::ILen.into());
     }
 
+    if let Some(ranks) = &vss_setup.ranks {
+        // Hierarchical key: public shares must determine the polynomial, and
+        // its constant term must be the public key
+        if ranks.len() != usize::from(n) {
+            return Err(InvalidShareReason::RanksLen.into());
+        }
+        let reconstructed_pk = birkhoff::interpolate_at_zero(&vss_setup.I, ranks, t, public_shares)
+            .map_err(|err| match err {
+                birkhoff::InterpolationError::Undetermined => {
+                    InvalidShareReason::UnsatisfiableRanks
+                }
+                birkhoff::InterpolationError::Inconsistent => {
+                    InvalidShareReason::SharesDontMatchPublicKey
+                }
+            })?;
+        if reconstructed_pk != shared_public_key {
+            return Err(InvalidShareReason::SharesDontMatchPublicKey.into());
+        }
+        return Ok(());
+    }
+
     // Now we need to check that public key shares indeed form a public key.
     // We do that in two steps:
     // 1. Take `t` first public key shares, derive a public key and compare
@@ -383,6 +478,81 @@ fn validate_non_vss_key_info<E: Curve>(
 }
 
 impl<E: Curve> DirtyKeyInfo<E> {
+    /// Returns weight of j-th signer
+    ///
+    /// Weight is amount of key shares held by the signer. It's always 1 unless the key is
+    /// weighted (see [`VssSetup::weights`]). Returns `None` if `j` is out of bounds.
+    pub fn weight(&self, j: u16) -> Option<u16> {
+        match self.vss_setup.as_ref().and_then(|s| s.weights.as_ref()) {
+            Some(weights) => weights.get(usize::from(j)).copied(),
+            None => (usize::from(j) < self.public_shares.len()).then_some(1),
+        }
+    }
+
+    /// Returns positions of key shares held by j-th signer
+    ///
+    /// Positions index [`public_shares`](Self::public_shares) and [`VssSetup::I`]. Unless
+    /// the key is weighted, the only position is `j`. Returns `None` if `j` is out of bounds.
+    pub fn shares_of(&self, j: u16) -> Option<ops::Range<usize>> {
+        match self.vss_setup.as_ref().and_then(|s| s.weights.as_ref()) {
+            Some(weights) => {
+                let weight = usize::from(*weights.get(usize::from(j))?);
+                let start = weights[..usize::from(j)]
+                    .iter()
+                    .map(|w| usize::from(*w))
+                    .sum::<usize>();
+                Some(start..start + weight)
+            }
+            None => (usize::from(j) < self.public_shares.len())
+                .then(|| usize::from(j)..usize::from(j) + 1),
+        }
+    }
+
+    /// Returns interpolation coefficients of key shares held by signers `S`
+    ///
+    /// Coefficients $\lambda_k$ correspond to key shares of signers in `S` listed in order (see
+    /// [`shares_of`](Self::shares_of)), and satisfy $\sk = \sum_k \lambda_k x_k$. Lagrange
+    /// interpolation is used, or Birkhoff interpolation if key is hierarchical (see
+    /// [`VssSetup::ranks`]).
+    ///
+    /// Returns `None` if key is not polynomial, `S` contains out of bounds or duplicated indexes,
+    /// or signers `S` are not authorized to reconstruct the key.
+    pub fn interpolation_coefficients(&self, S: &[u16]) -> Option<Vec<Scalar<E>>> {
+        let vss_setup = self.vss_setup.as_ref()?;
+        if (1..S.len()).any(|j| S[..j].contains(&S[j])) {
+            return None;
+        }
+        let shares = S
+            .iter()
+            .map(|&j| self.shares_of(j))
+            .collect::<Option<Vec<_>>>()?;
+        let I = shares
+            .iter()
+            .map(|s| vss_setup.I.get(s.clone()))
+            .collect::<Option<Vec<_>>>()?
+            .concat();
+        if I.len() < usize::from(vss_setup.min_signers) {
+            return None;
+        }
+
+        match &vss_setup.ranks {
+            Some(ranks) => {
+                let ranks = shares
+                    .iter()
+                    .map(|s| ranks.get(s.clone()))
+                    .collect::<Option<Vec<_>>>()?
+                    .concat();
+                birkhoff::coefficients_at_zero(&I, &ranks, vss_setup.min_signers)
+            }
+            None => (0..I.len())
+                .map(|k| {
+                    generic_ec_zkp::polynomial::lagrange_coefficient_at_zero(k, &I)
+                        .map(|lambda_k| *lambda_k)
+                })
+                .collect(),
+        }
+    }
+
     /// Returns share preimage associated with j-th signer
     ///
     /// * For additive shares, share preimage is defined as `j+1`
@@ -390,6 +560,9 @@ impl<E: Curve> DirtyKeyInfo<E> {
     ///   $F(x)$ is polynomial co-shared by the signers and $x_j$ is secret share of j-th
     ///   signer
     ///
+    /// If key is weighted, `j` is position of the key share rather than index of the signer,
+    /// see [`shares_of`](Self::shares_of).
+    ///
     /// Note: if you have no idea what it is, probably you don't need it.
     pub fn share_preimage(&self, j: u16) -> Option<NonZero<Scalar<E>>> {
         if let Some(vss_setup) = self.vss_setup.as_ref() {
@@ -404,6 +577,38 @@ impl<E: Curve> DirtyKeyInfo<E> {
             None
         }
     }
+
+    /// Encodes shared public key as DER SubjectPublicKeyInfo ([RFC 5480])
+    ///
+    /// The output can be consumed by OpenSSL, X.509 tooling and cloud KMS. Point is
+    /// encoded in uncompressed form.
+    ///
+    /// Only secp256k1 and secp256r1 curves are supported, returns `None` for any other curve.
+    ///
+    /// [RFC 5480]: https://www.rfc-editor.org/rfc/rfc5480
+    pub fn shared_public_key_spki_der(&self) -> Option<Vec<u8>> {
+        // SEQUENCE { SEQUENCE { id-ecPublicKey, namedCurve }, BIT STRING }, followed by
+        // 65 bytes of uncompressed point
+        let prefix: &[u8] = match E::CURVE_NAME {
+            "secp256k1" => &[
+                0x30, 0x56, 0x30, 0x10, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06,
+                0x05, 0x2b, 0x81, 0x04, 0x00, 0x0a, 0x03, 0x42, 0x00,
+            ],
+            "secp256r1" => &[
+                0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06,
+                0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+            ],
+            _ => return None,
+        };
+        let point = self.shared_public_key.to_bytes(false);
+        if point.len() != 65 {
+            return None;
+        }
+        let mut spki = Vec::with_capacity(prefix.len() + point.len());
+        spki.extend_from_slice(prefix);
+        spki.extend_from_slice(&point);
+        Some(spki)
+    }
 }
 
 #[cfg(feature = "hd-wallet")]
@@ -471,17 +676,19 @@ impl<E: Curve> DirtyCoreKeyShare<E> {
 impl<E: Curve> CoreKeyShare<E> {
     /// Returns amount of key co-holders
     pub fn n(&self) -> u16 {
+        let n = match self.vss_setup.as_ref().and_then(|s| s.weights.as_ref()) {
+            Some(weights) => weights.len(),
+            None => self.public_shares.len(),
+        };
         #[allow(clippy::expect_used)]
-        self.public_shares
-            .len()
-            .try_into()
+        n.try_into()
             .expect("valid key share is guaranteed to have amount of signers fitting into u16")
     }
 
     /// Returns threshold
     ///
     /// Threshold is an amount of signers required to cooperate in order to sign a message
-    /// and/or generate presignature
+    /// and/or generate presignature. If key is weighted, it's minimal total weight of signers.
     pub fn min_signers(&self) -> u16 {
         self.vss_setup
             .as_ref()
@@ -495,6 +702,16 @@ impl<E: Curve> CoreKeyShare<E> {
     }
 }
 
+impl<E: Curve> DirtyCoreKeyShare<E> {
+    /// Returns all secret shares held by local party
+    ///
+    /// Yields [`x`](Self::x) followed by [`extra_x`](Self::extra_x). Shares correspond to
+    /// public shares at positions [`shares_of(i)`](DirtyKeyInfo::shares_of).
+    pub fn secret_shares(&self) -> impl Iterator<Item = &NonZero<SecretScalar<E>>> {
+        core::iter::once(&self.x).chain(&self.extra_x)
+    }
+}
+
 impl<E: Curve> ops::Deref for DirtyCoreKeyShare<E> {
     type Target = DirtyKeyInfo<E>;
     fn deref(&self) -> &Self::Target {
@@ -542,6 +759,16 @@ enum InvalidShareReason {
     ILen,
     #[displaydoc("indexes of shares in I are not pairwise distinct")]
     INotPairwiseDistinct,
+    #[displaydoc("weight of a signer is zero")]
+    ZeroWeight,
+    #[displaydoc("weights don't sum up to amount of public shares")]
+    WeightsDontMatchShares,
+    #[displaydoc("amount of secret shares doesn't match weight of the party")]
+    ExtraXLen,
+    #[displaydoc("mismatched length of ranks: ranks.len() != n")]
+    RanksLen,
+    #[displaydoc("ranks of key shares don't allow any set of signers to sign")]
+    UnsatisfiableRanks,
 }
 
 impl From<InvalidShareReason> for InvalidCoreShare {
@@ -597,25 +824,25 @@ pub fn reconstruct_secret_key<E: Curve>(
         return Err(ReconstructErrorReason::DifferentKeyShares.into());
     }
 
-    if key_shares.len() < usize::from(t) {
-        return Err(ReconstructErrorReason::TooFewKeyShares {
-            len: key_shares.len(),
-            t,
-        }
-        .into());
+    // Weighted key shares contribute as many shares as their weight is
+    let shares_len = key_shares
+        .iter()
+        .map(|s| s.as_ref().secret_shares().count())
+        .sum::<usize>();
+    if shares_len < usize::from(t) {
+        return Err(ReconstructErrorReason::TooFewKeyShares { len: shares_len, t }.into());
     }
 
-    if let Some(VssSetup { I, .. }) = vss {
+    if vss.is_some() {
         let S = key_shares.iter().map(|s| s.as_ref().i).collect::<Vec<_>>();
-        let I = crate::utils::subset(&S, I).ok_or(ReconstructErrorReason::Subset)?;
-        let lagrange_coefficients =
-            (0..).map(|j| generic_ec_zkp::polynomial::lagrange_coefficient_at_zero(j, &I));
-        let mut sk = lagrange_coefficients
-            .zip(key_shares)
-            .try_fold(Scalar::zero(), |acc, (lambda_j, key_share_j)| {
-                Some(acc + lambda_j? * &key_share_j.as_ref().x)
-            })
+        let lambda = key_shares[0]
+            .as_ref()
+            .interpolation_coefficients(&S)
             .ok_or(ReconstructErrorReason::Interpolation)?;
+        let mut sk = lambda
+            .iter()
+            .zip(key_shares.iter().flat_map(|s| s.as_ref().secret_shares()))
+            .fold(Scalar::zero(), |acc, (lambda_k, x_k)| acc + lambda_k * x_k);
         Ok(SecretScalar::new(&mut sk))
     } else {
         let mut sk = key_shares
@@ -649,9 +876,7 @@ enum ReconstructErrorReason {
         key shares were provided"
     )]
     TooFewKeyShares { len: usize, t: u16 },
-    #[displaydoc("subset function returned error (seems like a bug)")]
-    Subset,
-    #[displaydoc("interpolation failed (seems like a bug)")]
+    #[displaydoc("interpolation failed: key shares are not authorized to reconstruct the key")]
     Interpolation,
 }
 
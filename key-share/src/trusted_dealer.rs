@@ -199,6 +199,8 @@ impl<E: Curve> TrustedDealerBuilder<E> {
         let vss_setup = self.t.map(|t| VssSetup {
             min_signers: t,
             I: preimages,
+            weights: None,
+            ranks: None,
         });
 
         #[cfg(feature = "hd-wallet")]
@@ -224,6 +226,7 @@ impl<E: Curve> TrustedDealerBuilder<E> {
                         chain_code,
                     },
                     x: x_i,
+                    extra_x: Vec::new(),
                 })
                 .map_err(|err| Reason::InvalidKeyShare(err.into_error()))
             })
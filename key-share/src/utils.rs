@@ -22,17 +22,3 @@ pub mod encoding {
         }
     }
 }
-
-/// Returns `[list[indexes[0]], list[indexes[1]], ..., list[indexes[n-1]]]`
-///
-/// Result is `None` if any of `indexes[i]` is out of range of `list`
-#[cfg(feature = "spof")]
-pub fn subset<T: Clone, I: Into<usize> + Copy>(
-    indexes: &[I],
-    list: &[T],
-) -> Option<alloc::vec::Vec<T>> {
-    indexes
-        .iter()
-        .map(|&i| list.get(i.into()).cloned())
-        .collect()
-}
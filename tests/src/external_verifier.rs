@@ -45,9 +45,17 @@ pub mod blockchains {
 
             let mut signature_bytes = [0u8; 64];
             signature.write_to_slice(&mut signature_bytes);
+            let signature_der = signature.to_der();
             let signature = secp256k1::ecdsa::Signature::from_compact(&signature_bytes)
                 .context("malformed signature")?;
 
+            let der_signature = secp256k1::ecdsa::Signature::from_der(&signature_der)
+                .context("malformed DER signature")?;
+            anyhow::ensure!(
+                der_signature == signature,
+                "DER and fixed-width encodings mismatch"
+            );
+
             signature
                 .verify(&message, &public_key)
                 .context("invalid siganture")
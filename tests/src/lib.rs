@@ -247,6 +247,24 @@ pub fn convert_from_stark_scalar(
     generic_ec::Scalar::from_be_bytes(bytes).context("Can't read bytes")
 }
 
+/// Issues partial signature from a presignature kept in a fresh in-memory store
+pub fn issue_partial_signature<E, L>(
+    presignature: cggmp21::signing::Presignature<E>,
+    key_share: &KeyShare<E, L>,
+    message_to_sign: cggmp21::DataToSign<E>,
+) -> Result<cggmp21::PartialSignature<E>, cggmp21::signing::store::PresignatureStoreError>
+where
+    E: Curve,
+    L: SecurityLevel,
+    generic_ec::NonZero<generic_ec::Point<E>>: generic_ec::coords::AlwaysHasAffineX<E>,
+{
+    use cggmp21::signing::store::{MemoryStorage, PresignatureStore};
+
+    let mut store = PresignatureStore::new(MemoryStorage::default());
+    let handle = store.insert(presignature)?;
+    store.consume(handle, key_share, message_to_sign)
+}
+
 #[cfg(feature = "hd-wallet")]
 pub fn random_derivation_path(rng: &mut impl rand::RngCore) -> Vec<u32> {
     use rand::Rng;
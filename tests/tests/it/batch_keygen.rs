@@ -0,0 +1,95 @@
+use generic_ec::{Curve, Point};
+use rand::{seq::SliceRandom, Rng};
+use rand_dev::DevRng;
+
+use cggmp21::{key_share::reconstruct_secret_key, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: batch_keygen_works,
+    generics: stark,
+    suites: {
+        n3: (None, 3, 3, false),
+        t2n3: (Some(2), 3, 3, false),
+        t3n5: (Some(3), 5, 2, false),
+        #[cfg(feature = "hd-wallet")]
+        t3n5_hd: (Some(3), 5, 2, true),
+    }
+}
+
+fn batch_keygen_works<E: Curve>(t: Option<u16>, n: u16, batch_size: u16, hd_wallet: bool) {
+    #[cfg(not(feature = "hd-wallet"))]
+    assert!(!hd_wallet);
+
+    let mut rng = DevRng::new();
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let outputs = if let Some(t) = t {
+        round_based::sim::run(n, |i, party| {
+            let party = cggmp21_tests::buffer_outgoing(party);
+            let mut party_rng = rng.fork();
+            async move {
+                let builder = cggmp21::keygen::<E>(eid, i, n).set_threshold(t);
+                #[cfg(feature = "hd-wallet")]
+                let builder = builder.hd_wallet(hd_wallet);
+                builder.start_batch(batch_size, &mut party_rng, party).await
+            }
+        })
+        .unwrap()
+        .expect_ok()
+        .into_vec()
+    } else {
+        round_based::sim::run(n, |i, party| {
+            let party = cggmp21_tests::buffer_outgoing(party);
+            let mut party_rng = rng.fork();
+            async move {
+                let builder = cggmp21::keygen::<E>(eid, i, n);
+                #[cfg(feature = "hd-wallet")]
+                let builder = builder.hd_wallet(hd_wallet);
+                builder.start_batch(batch_size, &mut party_rng, party).await
+            }
+        })
+        .unwrap()
+        .expect_ok()
+        .into_vec()
+    };
+
+    assert!(outputs
+        .iter()
+        .all(|shares| shares.len() == usize::from(batch_size)));
+
+    let mut public_keys = vec![];
+    for k in 0..usize::from(batch_size) {
+        let key_shares = outputs
+            .iter()
+            .map(|shares| shares[k].clone())
+            .collect::<Vec<_>>();
+
+        for (i, key_share) in (0u16..).zip(&key_shares) {
+            assert_eq!(key_share.i, i);
+            assert_eq!(key_share.min_signers(), t.unwrap_or(n));
+            assert_eq!(key_share.shared_public_key, key_shares[0].shared_public_key);
+            assert_eq!(key_share.public_shares, key_shares[0].public_shares);
+            #[cfg(feature = "hd-wallet")]
+            {
+                assert_eq!(key_share.chain_code, key_shares[0].chain_code);
+                assert_eq!(key_share.chain_code.is_some(), hd_wallet);
+            }
+        }
+
+        let signers = key_shares
+            .choose_multiple(&mut rng, t.unwrap_or(n).into())
+            .cloned()
+            .collect::<Vec<_>>();
+        let sk = reconstruct_secret_key(&signers).unwrap();
+        assert_eq!(Point::generator() * sk, key_shares[0].shared_public_key);
+
+        public_keys.push(key_shares[0].shared_public_key);
+    }
+
+    // Keys generated in one batch are independent
+    for (k, pk) in public_keys.iter().enumerate() {
+        assert!(!public_keys[..k].contains(pk));
+    }
+}
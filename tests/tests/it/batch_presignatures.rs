@@ -0,0 +1,374 @@
+use futures::StreamExt;
+use generic_ec::{coords::HasAffineX, Curve, Point, Scalar};
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use rand_dev::DevRng;
+use sha2::Sha256;
+
+use cggmp21::key_share::AnyKeyShare;
+use cggmp21::signing::{msg::batch::Msg, DataToSign, SigningAbortReason};
+use cggmp21::{security_level::SecurityLevel128, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: batch_presignatures_work,
+    generics: stark,
+    suites: {
+        t2n3: (Some(2), 3, 3, false),
+        n2_reliable: (None, 2, 2, true),
+    }
+}
+
+fn batch_presignatures_work<E>(t: Option<u16>, n: u16, batch_size: usize, reliable_broadcast: bool)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(t, n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let t = shares[0].min_signers();
+    let mut participants = (0..n).collect::<Vec<_>>();
+    participants.shuffle(&mut rng);
+    let participants = &participants[..usize::from(t)];
+    println!("Signers: {participants:?}");
+
+    let participants_shares = participants.iter().map(|i| &shares[usize::from(*i)]);
+
+    let presigs = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+
+        async move {
+            cggmp21::signing(eid, i, participants, share)
+                .enforce_reliable_broadcast(reliable_broadcast)
+                .generate_presignatures(&mut party_rng, party, batch_size)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    for presigs in &presigs {
+        assert_eq!(presigs.len(), batch_size);
+    }
+
+    // Every presignature in the batch must be usable on its own
+    let mut presigs = presigs
+        .into_iter()
+        .map(|presigs| presigs.into_iter())
+        .collect::<Vec<_>>();
+    let mut nonces = vec![];
+    for _ in 0..batch_size {
+        let mut message_to_sign = [0u8; 100];
+        rng.fill_bytes(&mut message_to_sign);
+        let message_to_sign = DataToSign::digest::<Sha256>(&message_to_sign);
+
+        let partial_signatures = presigs
+            .iter_mut()
+            .zip(participants)
+            .map(|(presigs, j)| {
+                let presig = presigs.next().unwrap();
+                nonces.push(presig.public_data().R);
+                cggmp21_tests::issue_partial_signature(
+                    presig,
+                    &shares[usize::from(*j)],
+                    message_to_sign,
+                )
+                .expect("presignature mismatches key share")
+            })
+            .collect::<Vec<_>>();
+        let signature = cggmp21::PartialSignature::combine(&partial_signatures)
+            .expect("invalid partial signatures");
+        signature
+            .verify(&shares[0].shared_public_key, &message_to_sign)
+            .expect("signature is not valid");
+    }
+
+    // Instances must be independent
+    nonces.sort_by_key(|nonce| nonce.to_bytes(true).to_vec());
+    nonces.dedup();
+    assert_eq!(nonces.len(), batch_size);
+}
+
+cggmp21_tests::test_suite! {
+    test: empty_batch_is_rejected,
+    generics: stark,
+    suites: {
+        n2: (2),
+    }
+}
+
+fn empty_batch_is_rejected<E>(n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(None, n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+    let participants = &(0..n).collect::<Vec<_>>();
+
+    let results = round_based::sim::run_with_setup(&shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::signing(eid, i, participants, share)
+                .generate_presignatures(&mut party_rng, party, 0)
+                .await
+        }
+    })
+    .unwrap();
+
+    for result in results {
+        let Err(err) = result else {
+            panic!("empty batch must be rejected")
+        };
+        assert!(err.aborted().is_none());
+    }
+}
+
+cggmp21_tests::test_suite! {
+    test: batch_signing_works,
+    generics: stark,
+    suites: {
+        t2n3: (Some(2), 3, 3),
+        n2: (None, 2, 2),
+    }
+}
+
+fn batch_signing_works<E>(t: Option<u16>, n: u16, batch_size: usize)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(t, n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let t = shares[0].min_signers();
+    let mut participants = (0..n).collect::<Vec<_>>();
+    participants.shuffle(&mut rng);
+    let participants = &participants[..usize::from(t)];
+    println!("Signers: {participants:?}");
+
+    let participants_shares = participants.iter().map(|i| &shares[usize::from(*i)]);
+
+    let messages_to_sign = (0..batch_size)
+        .map(|_| {
+            let mut message_to_sign = [0u8; 100];
+            rng.fill_bytes(&mut message_to_sign);
+            DataToSign::digest::<Sha256>(&message_to_sign)
+        })
+        .collect::<Vec<_>>();
+    let messages_to_sign = &messages_to_sign;
+
+    let signatures = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+
+        async move {
+            cggmp21::signing(eid, i, participants, share)
+                .sign_batch(&mut party_rng, party, messages_to_sign)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .expect_eq();
+
+    assert_eq!(signatures.len(), batch_size);
+    for (signature, message_to_sign) in signatures.iter().zip(messages_to_sign) {
+        signature
+            .verify(&shares[0].shared_public_key, message_to_sign)
+            .expect("signature is not valid");
+    }
+}
+
+cggmp21_tests::test_suite! {
+    test: faulty_batch_signer_is_identified,
+    generics: stark,
+    suites: {
+        n3: (3),
+    }
+}
+
+fn faulty_batch_signer_is_identified<E>(n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(None, n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+    let participants = &(0..n).collect::<Vec<_>>();
+
+    let messages_to_sign = (0..2)
+        .map(|_| {
+            let mut message_to_sign = [0u8; 100];
+            rng.fill_bytes(&mut message_to_sign);
+            DataToSign::digest::<Sha256>(&message_to_sign)
+        })
+        .collect::<Vec<_>>();
+    let messages_to_sign = &messages_to_sign;
+
+    // Partial signature of the second message sent by faulty signer is tampered
+    let faulty = rng.gen_range(0..n);
+    let victim = (faulty + 1) % n;
+    println!("Faulty signer: {faulty}");
+
+    let results = round_based::sim::run_with_setup(&shares, |i, party, share| {
+        let tampered_sender = if i == faulty { victim } else { faulty };
+        let party = party.map_delivery(|delivery| {
+            let (incomings, outgoings) = round_based::Delivery::split(delivery);
+            let incomings = incomings.map(move |incoming| {
+                incoming.map(|mut incoming| {
+                    if let (true, Msg::Round4(msg)) =
+                        (incoming.sender == tampered_sender, &mut incoming.msg)
+                    {
+                        msg.instances[1].sigma += Scalar::one();
+                    }
+                    incoming
+                })
+            });
+            (incomings, outgoings)
+        });
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+
+        async move {
+            cggmp21::signing(eid, i, participants, share)
+                .sign_batch(&mut party_rng, party, messages_to_sign)
+                .await
+        }
+    })
+    .unwrap();
+
+    for (i, result) in (0..).zip(results) {
+        if i == faulty {
+            continue;
+        }
+        let err = result.expect_err("signing must fail");
+        let aborted = err
+            .aborted()
+            .unwrap_or_else(|| panic!("signing must be aborted by malicious party: {err:?}"));
+        assert_eq!(aborted.reason(), SigningAbortReason::SignatureInvalid);
+        assert_eq!(aborted.faulty_parties(), [faulty]);
+    }
+}
+
+cggmp21_tests::test_suite! {
+    test: faulty_batch_instance_is_identified,
+    generics: stark,
+    suites: {
+        n3_delta: (3, false, false),
+        n3_delta_split_view: (3, false, true),
+        n3_chi: (3, true, false),
+    }
+}
+
+/// $\delta_i$ (or $S_i$ if `chi` is set) of the second instance sent by faulty signer is
+/// tampered. If `split_view` is set, only one honest signer receives tampered message, and all
+/// honest signers must abort at reliability check instead of splitting on whether to carry out
+/// identification.
+fn faulty_batch_instance_is_identified<E>(n: u16, chi: bool, split_view: bool)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(None, n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+    let participants = &(0..n).collect::<Vec<_>>();
+
+    let faulty = rng.gen_range(0..n);
+    let victim = (faulty + 1) % n;
+    println!("Faulty signer: {faulty}");
+
+    let results = round_based::sim::run_with_setup(&shares, |i, party, share| {
+        let tampered_sender = if i == faulty { victim } else { faulty };
+        let tamper = !split_view || i == victim;
+        let party = party.map_delivery(|delivery| {
+            let (incomings, outgoings) = round_based::Delivery::split(delivery);
+            let incomings = incomings.map(move |incoming| {
+                incoming.map(|mut incoming| {
+                    if let (true, true, Msg::Round3a(msg)) = (
+                        tamper,
+                        incoming.sender == tampered_sender,
+                        &mut incoming.msg,
+                    ) {
+                        if chi {
+                            msg.instances[1].S += Point::generator();
+                        } else {
+                            msg.instances[1].delta += Scalar::one();
+                        }
+                    }
+                    incoming
+                })
+            });
+            (incomings, outgoings)
+        });
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+
+        async move {
+            // If tampered message is the same for all honest signers, it models a faulty
+            // signer that sent it over reliable broadcast channel
+            cggmp21::signing(eid, i, participants, share)
+                .enforce_reliable_broadcast(split_view)
+                .generate_presignatures(&mut party_rng, party, 2)
+                .await
+        }
+    })
+    .unwrap();
+
+    for (i, result) in (0..).zip(results) {
+        if i == faulty {
+            continue;
+        }
+        let Err(err) = result else {
+            panic!("presignature generation must fail")
+        };
+        let aborted = err
+            .aborted()
+            .unwrap_or_else(|| panic!("signing must be aborted by malicious party: {err:?}"));
+        if split_view {
+            assert_eq!(
+                aborted.reason(),
+                SigningAbortReason::BroadcastNotReliable { round: 3 }
+            );
+        } else if chi {
+            assert_eq!(aborted.reason(), SigningAbortReason::MismatchedChi);
+            assert_eq!(aborted.faulty_parties(), [faulty]);
+        } else {
+            assert_eq!(aborted.reason(), SigningAbortReason::MismatchedDelta);
+            assert_eq!(aborted.faulty_parties(), [faulty]);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use generic_ec::{coords::HasAffineX, Curve, NonZero, Point};
+use rand::{seq::SliceRandom, Rng, RngCore};
+use rand_dev::DevRng;
+use sha2::Sha256;
+
+use cggmp21::key_share::{AnyKeyShare, KeyShare, Validate};
+use cggmp21::signing::DataToSign;
+use cggmp21::{security_level::SecurityLevel128, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: chain_code_gen_works,
+    generics: stark,
+    suites: {
+        n3: (None, 3),
+        t2n3: (Some(2), 3),
+        t3n5: (Some(3), 5),
+    }
+}
+
+fn chain_code_gen_works<E>(t: Option<u16>, n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(t, n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let new_shares = round_based::sim::run_with_setup(&shares, |_i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::chain_code_gen(eid, share)
+                .start(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    for (new_share, old_share) in new_shares.iter().zip(&shares) {
+        assert!(new_share.is_hd_wallet());
+        assert_eq!(new_share.chain_code, new_shares[0].chain_code);
+        assert_eq!(new_share.i, old_share.i);
+        assert_eq!(new_share.shared_public_key, old_share.shared_public_key);
+        assert_eq!(new_share.public_shares, old_share.public_shares);
+    }
+
+    // Aux info of the original key shares completes the new ones
+    let new_shares = new_shares
+        .into_iter()
+        .zip(shares)
+        .map(|(core, old)| {
+            let aux = old.into_inner().aux.validate().expect("invalid aux info");
+            KeyShare::from_parts((core, aux)).expect("invalid key share")
+        })
+        .collect::<Vec<KeyShare<E>>>();
+
+    // Sign with a child key
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let mut message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&message_to_sign);
+
+    let derivation_path = cggmp21_tests::random_derivation_path(&mut rng);
+
+    let mut signers = (0..n).collect::<Vec<_>>();
+    signers.shuffle(&mut rng);
+    let signers = &signers[..usize::from(new_shares[0].min_signers())];
+    let signers_shares = signers.iter().map(|j| &new_shares[usize::from(*j)]);
+
+    let signature = round_based::sim::run_with_setup(signers_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        let signing = cggmp21::signing(eid, i, signers, share)
+            .set_derivation_path_with_algo::<E::HdAlgo, _>(derivation_path.clone())
+            .unwrap();
+        async move { signing.sign(&mut party_rng, party, message_to_sign).await }
+    })
+    .unwrap()
+    .expect_ok()
+    .expect_eq();
+
+    let public_key = NonZero::from_point(
+        new_shares[0]
+            .derive_child_public_key::<E::HdAlgo, _>(derivation_path.iter().cloned())
+            .unwrap()
+            .public_key,
+    )
+    .unwrap();
+    signature
+        .verify(&public_key, &message_to_sign)
+        .expect("signature is not valid");
+}
+
+cggmp21_tests::test_suite! {
+    test: hd_key_is_rejected,
+    generics: stark,
+    suites: {
+        t3n5: (Some(3), 5),
+    }
+}
+
+fn hd_key_is_rejected<E>(t: Option<u16>, n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(t, n, true)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let results = round_based::sim::run_with_setup(&shares, |_i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::chain_code_gen(eid, share)
+                .start(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap();
+    assert!(results.iter().all(|result| result.is_err()));
+}
@@ -0,0 +1,76 @@
+use generic_ec::{Curve, NonZero, Point, Scalar, SecretScalar};
+use rand::RngCore;
+use rand_dev::DevRng;
+use sha2::{Digest, Sha256};
+
+use cggmp21::supported_curves::{Secp256k1, Secp256r1, Stark};
+use cggmp21::{security_level::SecurityLevel128, DataToSign, Signature};
+
+#[test]
+fn der_signature_is_compatible_with_libsecp256k1() {
+    let mut rng = DevRng::new();
+    let secp = secp256k1::Secp256k1::signing_only();
+
+    for _ in 0..20 {
+        let secret_key = NonZero::<SecretScalar<Secp256k1>>::random(&mut rng);
+        let secret_key = secp256k1::SecretKey::from_slice(
+            &AsRef::<Scalar<Secp256k1>>::as_ref(&secret_key).to_be_bytes(),
+        )
+        .unwrap();
+        let public_key =
+            Point::<Secp256k1>::from_bytes(secret_key.public_key(&secp).serialize()).unwrap();
+
+        let mut message = [0u8; 100];
+        rng.fill_bytes(&mut message);
+        let message_hash: [u8; 32] = Sha256::digest(message).into();
+        let expected_signature = secp.sign_ecdsa(
+            &secp256k1::Message::from_slice(&message_hash).unwrap(),
+            &secret_key,
+        );
+
+        let der = expected_signature.serialize_der();
+        let signature = Signature::<Secp256k1>::from_der(&der).expect("DER is not accepted");
+        signature
+            .verify(&public_key, &DataToSign::digest::<Sha256>(&message))
+            .expect("signature is not valid");
+
+        assert_eq!(signature.to_der(), &*der);
+        assert_eq!(
+            signature.to_der(),
+            Signature::<Secp256k1>::read_from_slice(&expected_signature.serialize_compact())
+                .unwrap()
+                .to_der()
+        );
+    }
+}
+
+#[test]
+fn spki_encoding() {
+    let mut rng = DevRng::new();
+
+    let key_info = shared_key_info::<Secp256k1>(&mut rng);
+    let spki = key_info.shared_public_key_spki_der().unwrap();
+    assert_eq!(spki.len(), 88);
+    assert_eq!(spki[..2], [0x30, 0x56]);
+    let public_key = secp256k1::PublicKey::from_slice(&spki[23..]).unwrap();
+    assert_eq!(
+        public_key.serialize(),
+        &*key_info.shared_public_key.to_bytes(true)
+    );
+
+    let key_info = shared_key_info::<Secp256r1>(&mut rng);
+    let spki = key_info.shared_public_key_spki_der().unwrap();
+    assert_eq!(spki.len(), 91);
+    assert_eq!(spki[..2], [0x30, 0x59]);
+    assert_eq!(spki[26..], *key_info.shared_public_key.to_bytes(false));
+
+    let key_info = shared_key_info::<Stark>(&mut rng);
+    assert!(key_info.shared_public_key_spki_der().is_none());
+}
+
+fn shared_key_info<E: Curve>(rng: &mut DevRng) -> cggmp21::key_share::DirtyKeyInfo<E> {
+    let shares = cggmp21::trusted_dealer::builder::<E, SecurityLevel128>(3)
+        .generate_core_shares(rng)
+        .unwrap();
+    shares[0].key_info.clone()
+}
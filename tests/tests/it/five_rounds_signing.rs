@@ -0,0 +1,402 @@
+use std::sync::{Arc, Mutex};
+
+use futures::{Sink, SinkExt, StreamExt};
+use generic_ec::{
+    coords::{HasAffineX, HasAffineXAndParity},
+    Curve, Point, Scalar,
+};
+use rand::{seq::SliceRandom, Rng, RngCore};
+use rand_dev::DevRng;
+use round_based::Outgoing;
+use sha2::{digest::Output, Sha256};
+
+use cggmp21::key_share::AnyKeyShare;
+use cggmp21::signing::{msg::five_rounds::Msg, DataToSign, PartialSignature, SigningAbortReason};
+use cggmp21::{security_level::SecurityLevel128, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: signing_works,
+    generics: stark,
+    suites: {
+        n3: (None, 3, false),
+        t3n5: (Some(3), 5, false),
+        #[cfg(feature = "hd-wallet")]
+        t3n5_hd: (Some(3), 5, true),
+    }
+}
+
+fn signing_works<E>(t: Option<u16>, n: u16, hd_wallet: bool)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineXAndParity<E>,
+{
+    #[cfg(not(feature = "hd-wallet"))]
+    assert!(!hd_wallet);
+
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(t, n, hd_wallet)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let mut original_message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut original_message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&original_message_to_sign);
+
+    #[cfg(feature = "hd-wallet")]
+    let derivation_path = if hd_wallet {
+        Some(cggmp21_tests::random_derivation_path(&mut rng))
+    } else {
+        None
+    };
+
+    let t = shares[0].min_signers();
+    let mut participants = (0..n).collect::<Vec<_>>();
+    participants.shuffle(&mut rng);
+    let participants = &participants[..usize::from(t)];
+    println!("Signers: {participants:?}");
+    let participants_shares = participants.iter().map(|i| &shares[usize::from(*i)]);
+
+    let sig = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+
+        let signing = cggmp21::signing(eid, i, participants, share).set_five_rounds_protocol();
+
+        #[cfg(feature = "hd-wallet")]
+        let signing = if let Some(derivation_path) = derivation_path.clone() {
+            signing
+                .set_derivation_path_with_algo::<E::HdAlgo, _>(derivation_path)
+                .unwrap()
+        } else {
+            signing
+        };
+
+        async move { signing.sign(&mut party_rng, party, message_to_sign).await }
+    })
+    .unwrap()
+    .expect_ok()
+    .expect_eq();
+
+    #[cfg(feature = "hd-wallet")]
+    let public_key = if let Some(path) = &derivation_path {
+        generic_ec::NonZero::from_point(
+            shares[0]
+                .derive_child_public_key::<E::HdAlgo, _>(path.iter().cloned())
+                .unwrap()
+                .public_key,
+        )
+        .unwrap()
+    } else {
+        shares[0].shared_public_key
+    };
+    #[cfg(not(feature = "hd-wallet"))]
+    let public_key = shares[0].shared_public_key;
+
+    sig.verify(&public_key, &message_to_sign)
+        .expect("signature is not valid");
+    let recovered_key = sig
+        .recover_public_key(&message_to_sign)
+        .expect("public key can't be recovered");
+    assert_eq!(recovered_key, public_key);
+}
+
+cggmp21_tests::test_suite! {
+    test: signing_with_presigs,
+    generics: stark,
+    suites: {
+        t3n5: (Some(3), 5),
+    }
+}
+
+fn signing_with_presigs<E>(t: Option<u16>, n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(t, n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let t = shares[0].min_signers();
+    let mut participants = (0..n).collect::<Vec<_>>();
+    participants.shuffle(&mut rng);
+    let participants = &participants[..usize::from(t)];
+    println!("Signers: {participants:?}");
+    let participants_shares = participants.iter().map(|i| &shares[usize::from(*i)]);
+
+    let presignatures = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+
+        async move {
+            cggmp21::signing(eid, i, participants, share)
+                .set_five_rounds_protocol()
+                .generate_presignature(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    let mut original_message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut original_message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&original_message_to_sign);
+
+    let public_data = presignatures[0].public_data();
+    let partial_signatures = presignatures
+        .into_iter()
+        .zip(participants)
+        .map(|(presig, j)| {
+            cggmp21_tests::issue_partial_signature(
+                presig,
+                &shares[usize::from(*j)],
+                message_to_sign,
+            )
+            .expect("presignature mismatches key share")
+        })
+        .collect::<Vec<_>>();
+    let sig = PartialSignature::combine(&partial_signatures).expect("invalid partial signatures");
+    let verified_sig =
+        PartialSignature::combine_verified(&partial_signatures, &public_data, message_to_sign)
+            .expect("invalid partial signatures");
+    assert_eq!(sig, verified_sig);
+
+    sig.verify(&shares[0].shared_public_key, &message_to_sign)
+        .expect("signature is not valid");
+}
+
+/// Which message is tampered by the faulty party
+#[derive(Clone, Copy, Debug)]
+enum Fault {
+    /// $\delta_i$ sent in round 3
+    Delta,
+    /// $S_i$ sent in round 5
+    Chi,
+    /// $\sigma_i$ sent in round 6
+    Sigma,
+}
+
+impl Fault {
+    /// Reason of abort which honest parties should report
+    fn abort_reason(self) -> SigningAbortReason {
+        match self {
+            Fault::Delta => SigningAbortReason::MismatchedDelta,
+            Fault::Chi => SigningAbortReason::MismatchedChi,
+            Fault::Sigma => SigningAbortReason::SignatureInvalid,
+        }
+    }
+}
+
+cggmp21_tests::test_suite! {
+    test: faulty_signer_is_identified,
+    generics: stark,
+    suites: {
+        n3_delta: (None, 3, crate::five_rounds_signing::Fault::Delta),
+        n3_chi: (None, 3, crate::five_rounds_signing::Fault::Chi),
+        n3_sigma: (None, 3, crate::five_rounds_signing::Fault::Sigma),
+        t3n5_delta: (Some(3), 5, crate::five_rounds_signing::Fault::Delta),
+        t3n5_chi: (Some(3), 5, crate::five_rounds_signing::Fault::Chi),
+        t3n5_sigma: (Some(3), 5, crate::five_rounds_signing::Fault::Sigma),
+    }
+}
+
+fn faulty_signer_is_identified<E>(t: Option<u16>, n: u16, fault: Fault)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(t, n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let mut original_message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut original_message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&original_message_to_sign);
+
+    let t = shares[0].min_signers();
+    let mut participants = (0..n).collect::<Vec<_>>();
+    participants.shuffle(&mut rng);
+    let participants = &participants[..usize::from(t)];
+    println!("Signers: {participants:?}");
+    let participants_shares = participants.iter().map(|i| &shares[usize::from(*i)]);
+
+    // Index of faulty signer and of honest signer whose message is tampered
+    // at faulty signer side, so faulty signer also proceeds to identification
+    let faulty = rng.gen_range(0..t);
+    let victim = (faulty + 1) % t;
+    println!("Faulty signer: {faulty}");
+
+    let results = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+        let tampered_sender = if i == faulty { victim } else { faulty };
+        // Hash of round 3 messages sent by the party at round 4
+        let own_echo = Arc::new(Mutex::new(None));
+        let party = party.map_delivery(|delivery| {
+            let (incomings, outgoings) = round_based::Delivery::split(delivery);
+            let outgoings = record_echo(outgoings, own_echo.clone());
+            let incomings = incomings.map(move |incoming| {
+                incoming.map(|mut incoming| {
+                    if incoming.sender == tampered_sender {
+                        tamper(&mut incoming.msg, fault);
+                    }
+                    // Faulty signer sends the same tampered message to everyone, so it echoes
+                    // back the view of round 3 that honest signers have. Faulty signer itself
+                    // accepts any echo, so it proceeds to identification as well. Round 4
+                    // messages are received after the party sent its own one, so its hash
+                    // is known at this point.
+                    if let Msg::Round4(msg) = &mut incoming.msg {
+                        if i == faulty || incoming.sender == faulty {
+                            msg.echo = own_echo.lock().unwrap().expect("round 4 msg not sent");
+                        }
+                    }
+                    incoming
+                })
+            });
+            (incomings, outgoings)
+        });
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+
+        async move {
+            cggmp21::signing(eid, i, participants, share)
+                .set_five_rounds_protocol()
+                .sign(&mut party_rng, party, message_to_sign)
+                .await
+        }
+    })
+    .unwrap();
+
+    for (i, result) in (0..).zip(results) {
+        if i == faulty {
+            continue;
+        }
+        let err = result.expect_err("signing must fail");
+        let aborted = err
+            .aborted()
+            .unwrap_or_else(|| panic!("signing must be aborted by malicious party: {err:?}"));
+        assert_eq!(aborted.reason(), fault.abort_reason());
+        assert_eq!(aborted.faulty_parties(), [faulty]);
+        assert_eq!(aborted.blame().len(), 1);
+    }
+}
+
+cggmp21_tests::test_suite! {
+    test: split_view_of_delta_is_detected,
+    generics: stark,
+    suites: {
+        n3: (None, 3),
+        t3n5: (Some(3), 5),
+    }
+}
+
+/// Faulty party sends tampered $\delta_i$ to one of honest parties only. Honest parties must
+/// not split on whether to proceed to identification of faulty parties: all of them abort
+/// at reliability check.
+fn split_view_of_delta_is_detected<E>(t: Option<u16>, n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(t, n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let mut original_message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut original_message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&original_message_to_sign);
+
+    let t = shares[0].min_signers();
+    let mut participants = (0..n).collect::<Vec<_>>();
+    participants.shuffle(&mut rng);
+    let participants = &participants[..usize::from(t)];
+    println!("Signers: {participants:?}");
+    let participants_shares = participants.iter().map(|i| &shares[usize::from(*i)]);
+
+    // Only `victim` receives tampered message from faulty signer
+    let faulty = rng.gen_range(0..t);
+    let victim = (faulty + 1) % t;
+    println!("Faulty signer: {faulty}, victim: {victim}");
+
+    let results = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+        let party = party.map_delivery(|delivery| {
+            let (incomings, outgoings) = round_based::Delivery::split(delivery);
+            let incomings = incomings.map(move |incoming| {
+                incoming.map(|mut incoming| {
+                    if i == victim && incoming.sender == faulty {
+                        tamper(&mut incoming.msg, Fault::Delta);
+                    }
+                    incoming
+                })
+            });
+            (incomings, outgoings)
+        });
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+
+        async move {
+            cggmp21::signing(eid, i, participants, share)
+                .set_five_rounds_protocol()
+                .sign(&mut party_rng, party, message_to_sign)
+                .await
+        }
+    })
+    .unwrap();
+
+    for (i, result) in (0..).zip(results) {
+        if i == faulty {
+            continue;
+        }
+        let err = result.expect_err("signing must fail");
+        let aborted = err
+            .aborted()
+            .unwrap_or_else(|| panic!("signing must be aborted by malicious party: {err:?}"));
+        assert_eq!(
+            aborted.reason(),
+            SigningAbortReason::BroadcastNotReliable { round: 3 }
+        );
+    }
+}
+
+/// Saves hash of round 3 messages that the party sends at round 4 into `echo`
+fn record_echo<E: Curve, S>(
+    sink: S,
+    echo: Arc<Mutex<Option<Output<Sha256>>>>,
+) -> impl Sink<Outgoing<Msg<E, Sha256>>, Error = S::Error> + Unpin
+where
+    S: Sink<Outgoing<Msg<E, Sha256>>> + Unpin,
+{
+    sink.with(move |outgoing: Outgoing<Msg<E, Sha256>>| {
+        if let Msg::Round4(msg) = &outgoing.msg {
+            *echo.lock().unwrap() = Some(msg.echo);
+        }
+        futures::future::ready(Ok::<_, S::Error>(outgoing))
+    })
+}
+
+fn tamper<E: Curve>(msg: &mut Msg<E, Sha256>, fault: Fault) {
+    match (msg, fault) {
+        (Msg::Round3(msg), crate::five_rounds_signing::Fault::Delta) => msg.delta += Scalar::one(),
+        (Msg::Round5(msg), crate::five_rounds_signing::Fault::Chi) => msg.S += Point::generator(),
+        (Msg::Round6(msg), crate::five_rounds_signing::Fault::Sigma) => msg.sigma += Scalar::one(),
+        _ => (),
+    }
+}
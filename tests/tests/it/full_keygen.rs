@@ -0,0 +1,102 @@
+use generic_ec::{coords::HasAffineX, Curve, Point};
+use rand::{seq::SliceRandom, Rng, RngCore};
+use rand_dev::DevRng;
+use sha2::Sha256;
+
+use cggmp21::key_share::{reconstruct_secret_key, AnyKeyShare};
+use cggmp21::{security_level::SecurityLevel128, DataToSign, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: full_keygen_works,
+    generics: stark,
+    suites: {
+        n3: (None, 3),
+        t2n3: (Some(2), 3),
+    }
+}
+
+fn full_keygen_works<E>(t: Option<u16>, n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let mut primes = cggmp21_tests::CACHED_PRIMES.iter::<SecurityLevel128>();
+    let pregenerated_primes = (0..n)
+        .map(|_| primes.next().expect("Can't fetch primes"))
+        .collect::<Vec<_>>();
+
+    let key_shares = match t {
+        Some(t) => round_based::sim::run_with_setup(pregenerated_primes, |i, party, primes| {
+            let party = cggmp21_tests::buffer_outgoing(party);
+            let mut party_rng = rng.fork();
+            async move {
+                cggmp21::full_keygen::<E, SecurityLevel128>(eid, i, n, primes)
+                    .set_threshold(t)
+                    .start(&mut party_rng, party)
+                    .await
+            }
+        })
+        .unwrap()
+        .expect_ok()
+        .into_vec(),
+        None => round_based::sim::run_with_setup(pregenerated_primes, |i, party, primes| {
+            let party = cggmp21_tests::buffer_outgoing(party);
+            let mut party_rng = rng.fork();
+            async move {
+                cggmp21::full_keygen::<E, SecurityLevel128>(eid, i, n, primes)
+                    .start(&mut party_rng, party)
+                    .await
+            }
+        })
+        .unwrap()
+        .expect_ok()
+        .into_vec(),
+    };
+
+    for (i, key_share) in (0u16..).zip(&key_shares) {
+        assert_eq!(key_share.core.i, i);
+        assert_eq!(key_share.min_signers(), t.unwrap_or(n));
+        assert_eq!(key_share.shared_public_key, key_shares[0].shared_public_key);
+        assert_eq!(key_share.public_shares, key_shares[0].public_shares);
+        assert_eq!(key_share.aux.parties.len(), usize::from(n));
+    }
+
+    let mut participants = (0..n).collect::<Vec<_>>();
+    participants.shuffle(&mut rng);
+    let participants = &participants[..usize::from(t.unwrap_or(n))];
+    println!("Signers: {participants:?}");
+    let participants_shares = participants.iter().map(|i| &key_shares[usize::from(*i)]);
+
+    let sk = reconstruct_secret_key(&participants_shares.clone().collect::<Vec<_>>()).unwrap();
+    assert_eq!(Point::generator() * sk, key_shares[0].shared_public_key);
+
+    // Key shares are complete, so they can be used for signing right away
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let mut message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&message_to_sign);
+
+    let signature = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::signing(eid, i, participants, share)
+                .sign(&mut party_rng, party, message_to_sign)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .expect_eq();
+
+    signature
+        .verify(&key_shares[0].shared_public_key, &message_to_sign)
+        .expect("signature is not valid");
+}
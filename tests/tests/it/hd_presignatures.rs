@@ -0,0 +1,235 @@
+use generic_ec::{coords::HasAffineXAndParity, Curve, NonZero, Point};
+use rand::{Rng, RngCore};
+use rand_dev::DevRng;
+use sha2::Sha256;
+
+use cggmp21::signing::store::{MemoryStorage, PresignatureStore, PresignatureStoreError};
+use cggmp21::signing::{CombineVerifiedError, DataToSign, SetDerivationPathError};
+use cggmp21::{security_level::SecurityLevel128, ExecutionId, PartialSignature};
+
+cggmp21_tests::test_suite! {
+    test: store_signs_with_derivation_path,
+    generics: stark,
+    suites: {
+        t2n3: (2, 3),
+    }
+}
+
+fn store_signs_with_derivation_path<E>(t: u16, n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineXAndParity<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(Some(t), n, true)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let participants = &(0..t).collect::<Vec<_>>();
+    let participants_shares = participants.iter().map(|i| &shares[usize::from(*i)]);
+
+    // First instance is used to sign, second one is used to check that derivation path
+    // can't be set twice
+    let presigs = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+
+        async move {
+            cggmp21::signing(eid, i, participants, share)
+                .generate_presignatures(&mut party_rng, party, 2)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    let epub = shares[0].extended_public_key().unwrap();
+    let derivation_path = cggmp21_tests::random_derivation_path(&mut rng);
+    let public_key = NonZero::from_point(
+        shares[0]
+            .derive_child_public_key::<E::HdAlgo, _>(derivation_path.iter().cloned())
+            .unwrap()
+            .public_key,
+    )
+    .unwrap();
+
+    // Presignature is moved into the store right after it's generated, so public data is
+    // obtained before derivation path is known
+    let master_public_data = presigs[0][0].public_data();
+    let public_data = master_public_data
+        .clone()
+        .set_derivation_path_with_algo::<E::HdAlgo, _>(epub, derivation_path.iter().cloned())
+        .unwrap();
+
+    let mut stores = vec![];
+    let mut handles = vec![];
+    for (j, mut presigs) in presigs.into_iter().enumerate() {
+        let mut store = PresignatureStore::new(MemoryStorage::default());
+        let derived = presigs
+            .pop()
+            .unwrap()
+            .set_derivation_path_with_algo::<E::HdAlgo, _>(epub, derivation_path.iter().cloned())
+            .unwrap();
+        if j == 0 {
+            let result = derived.set_derivation_path_with_algo::<E::HdAlgo, _>(
+                epub,
+                derivation_path.iter().cloned(),
+            );
+            assert!(matches!(result, Err(SetDerivationPathError::AlreadySet)));
+        } else {
+            // Store refuses to apply derivation path to presignature that already has it
+            let handle = store.insert(derived).unwrap();
+            let id = handle.id();
+            let mut message_to_sign = [0u8; 100];
+            rng.fill_bytes(&mut message_to_sign);
+            let err = store
+                .consume_with_derivation_path::<_, E::HdAlgo, _, _>(
+                    handle,
+                    &shares[j],
+                    derivation_path.iter().cloned(),
+                    DataToSign::digest::<Sha256>(&message_to_sign),
+                )
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                PresignatureStoreError::DerivationPathAlreadySet
+            ));
+            assert!(matches!(
+                store.get::<E>(id),
+                Err(PresignatureStoreError::UnknownId)
+            ));
+        }
+        handles.push(store.insert(presigs.pop().unwrap()).unwrap());
+        stores.push(store);
+    }
+
+    let mut message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&message_to_sign);
+
+    let partial_signatures = stores
+        .iter_mut()
+        .zip(handles)
+        .zip(participants)
+        .map(|((store, handle), j)| {
+            store
+                .consume_with_derivation_path::<_, E::HdAlgo, _, _>(
+                    handle,
+                    &shares[usize::from(*j)],
+                    derivation_path.iter().cloned(),
+                    message_to_sign,
+                )
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    // Partial signatures issued by child key don't match commitments of master key
+    let err = PartialSignature::combine_verified(
+        &partial_signatures,
+        &master_public_data,
+        message_to_sign,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        CombineVerifiedError::InvalidPartialSignatures { indices } if indices.len() == usize::from(t)
+    ));
+
+    let signature =
+        PartialSignature::combine_verified(&partial_signatures, &public_data, message_to_sign)
+            .unwrap();
+    signature
+        .verify(&public_key, &message_to_sign)
+        .expect("signature is not valid");
+    let recovered_key = signature.recover_public_key(&message_to_sign).unwrap();
+    assert_eq!(recovered_key, public_key);
+}
+
+cggmp21_tests::test_suite! {
+    test: faulty_signer_is_identified_with_derivation_path,
+    generics: stark,
+    suites: {
+        t3n5: (3, 5),
+    }
+}
+
+fn faulty_signer_is_identified_with_derivation_path<E>(t: u16, n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineXAndParity<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(Some(t), n, true)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let participants = &(0..t).collect::<Vec<_>>();
+    let participants_shares = participants.iter().map(|i| &shares[usize::from(*i)]);
+
+    let presigs = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+
+        async move {
+            cggmp21::signing(eid, i, participants, share)
+                .generate_presignature(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    let epub = shares[0].extended_public_key().unwrap();
+    let derivation_path = cggmp21_tests::random_derivation_path(&mut rng);
+    let public_data = presigs[0]
+        .public_data()
+        .set_derivation_path_with_algo::<E::HdAlgo, _>(epub, derivation_path.iter().cloned())
+        .unwrap();
+
+    let mut message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&message_to_sign);
+
+    // Faulty signer ignores derivation path and issues partial signature with master key
+    let faulty = rng.gen_range(0..usize::from(t));
+    let partial_signatures = presigs
+        .into_iter()
+        .zip(participants)
+        .enumerate()
+        .map(|(index, (presig, j))| {
+            let mut store = PresignatureStore::new(MemoryStorage::default());
+            let handle = store.insert(presig).unwrap();
+            let key_share = &shares[usize::from(*j)];
+            if index == faulty {
+                store.consume(handle, key_share, message_to_sign).unwrap()
+            } else {
+                store
+                    .consume_with_derivation_path::<_, E::HdAlgo, _, _>(
+                        handle,
+                        key_share,
+                        derivation_path.iter().cloned(),
+                        message_to_sign,
+                    )
+                    .unwrap()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let err =
+        PartialSignature::combine_verified(&partial_signatures, &public_data, message_to_sign)
+            .unwrap_err();
+    assert!(matches!(
+        err,
+        CombineVerifiedError::InvalidPartialSignatures { indices } if indices == [faulty]
+    ));
+}
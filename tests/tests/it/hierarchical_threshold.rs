@@ -0,0 +1,192 @@
+use generic_ec::{coords::HasAffineX, Curve, Point};
+use rand::{Rng, RngCore};
+use rand_dev::DevRng;
+use sha2::Sha256;
+
+use cggmp21::key_share::{
+    reconstruct_secret_key, AnyKeyShare, IncompleteKeyShare, KeyShare, Validate,
+};
+use cggmp21::signing::DataToSign;
+use cggmp21::{security_level::SecurityLevel128, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: hierarchical_signing_works,
+    generics: stark,
+    suites: {
+        r011_t2: (&[0, 1, 1], 2, &[2, 0]),
+        r00111_t3: (&[0, 0, 1, 1, 1], 3, &[4, 1, 2]),
+        r00111_t3_all: (&[0, 0, 1, 1, 1], 3, &[3, 1, 0, 4, 2]),
+        r012_t3: (&[0, 1, 2], 3, &[0, 1, 2]),
+    }
+}
+
+fn hierarchical_signing_works<E>(ranks: &[u16], t: u16, signers: &[u16])
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+    let shares = hierarchical_keygen::<E>(ranks, t, &mut rng);
+
+    for share in &shares {
+        assert_eq!(share.min_signers(), t);
+        assert_eq!(
+            share.core.vss_setup.as_ref().unwrap().ranks.as_deref(),
+            Some(ranks)
+        );
+
+        // Hierarchical key share survives serialization
+        let serialized = serde_json::to_vec(&share.core).unwrap();
+        let deserialized: IncompleteKeyShare<E> = serde_json::from_slice(&serialized).unwrap();
+        assert_eq!(deserialized.vss_setup, share.core.vss_setup);
+    }
+
+    // Secret key can be reconstructed by authorized set of parties
+    let signers_shares = signers
+        .iter()
+        .map(|j| &shares[usize::from(*j)])
+        .collect::<Vec<_>>();
+    let sk = reconstruct_secret_key(&signers_shares).unwrap();
+    assert_eq!(Point::generator() * sk, shares[0].shared_public_key);
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let mut message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&message_to_sign);
+
+    let signature = round_based::sim::run_with_setup(signers_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::signing(eid, i, signers, share)
+                .sign(&mut party_rng, party, message_to_sign)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .expect_eq();
+
+    signature
+        .verify(&shares[0].shared_public_key, &message_to_sign)
+        .expect("signature is not valid");
+}
+
+cggmp21_tests::test_suite! {
+    test: unauthorized_signers_fail,
+    generics: stark,
+    suites: {
+        r011_t2: (&[0, 1, 1], 2, &[1, 2]),
+        r00111_t3: (&[0, 0, 1, 1, 1], 3, &[2, 3, 4]),
+    }
+}
+
+fn unauthorized_signers_fail<E>(ranks: &[u16], t: u16, signers: &[u16])
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+    let shares = hierarchical_keygen::<E>(ranks, t, &mut rng);
+
+    let signers_shares = signers
+        .iter()
+        .map(|j| &shares[usize::from(*j)])
+        .collect::<Vec<_>>();
+    assert!(reconstruct_secret_key(&signers_shares).is_err());
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let results = round_based::sim::run_with_setup(signers_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::signing(eid, i, signers, share)
+                .generate_presignature(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap();
+    assert!(results.iter().all(|result| result.is_err()));
+}
+
+cggmp21_tests::test_suite! {
+    test: keygen_rejects_unsatisfiable_ranks,
+    generics: stark,
+    suites: {
+        all_rank_1: (&[1, 1, 1], 2),
+        rank_too_large: (&[0, 2, 1], 2),
+        mismatched_amount: (&[0, 1], 2),
+    }
+}
+
+fn keygen_rejects_unsatisfiable_ranks<E>(ranks: &[u16], t: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+{
+    let mut rng = DevRng::new();
+    let n = 3;
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let results = round_based::sim::run(n, |i, party| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::keygen::<E>(eid, i, n)
+                .set_threshold(t)
+                .set_ranks(ranks.to_vec())
+                .start(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap();
+    assert!(results.iter().all(|result| result.is_err()));
+}
+
+/// Carries out hierarchical keygen and completes key shares with cached aux info
+fn hierarchical_keygen<E>(ranks: &[u16], t: u16, rng: &mut DevRng) -> Vec<KeyShare<E>>
+where
+    E: Curve + cggmp21_tests::CurveParams,
+{
+    let n = u16::try_from(ranks.len()).unwrap();
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let incomplete_shares: Vec<IncompleteKeyShare<E>> = round_based::sim::run(n, |i, party| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::keygen::<E>(eid, i, n)
+                .set_threshold(t)
+                .set_ranks(ranks.to_vec())
+                .start(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    // Aux info doesn't depend on the key, so we take it from cached key shares
+    let cached_shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(None, n, false)
+        .expect("retrieve cached shares");
+    incomplete_shares
+        .into_iter()
+        .zip(cached_shares)
+        .map(|(core, cached)| {
+            let aux = cached
+                .into_inner()
+                .aux
+                .validate()
+                .expect("invalid aux info");
+            KeyShare::from_parts((core, aux)).expect("invalid key share")
+        })
+        .collect()
+}
@@ -0,0 +1,216 @@
+use futures::StreamExt;
+use generic_ec::{coords::HasAffineX, Curve, Point, Scalar};
+use rand::{seq::SliceRandom, Rng, RngCore};
+use rand_dev::DevRng;
+use sha2::Sha256;
+
+use cggmp21::key_share::AnyKeyShare;
+use cggmp21::signing::{msg::Msg, DataToSign, SigningAbortReason};
+use cggmp21::{security_level::SecurityLevel128, ExecutionId};
+
+/// Which message is tampered by the faulty party
+#[derive(Clone, Copy, Debug)]
+enum Fault {
+    /// $\delta_i$ sent in round 3
+    Delta,
+    /// $S_i$ sent in round 3
+    Chi,
+    /// $\sigma_i$ sent in round 4
+    Sigma,
+}
+
+impl Fault {
+    /// Reason of abort which honest parties should report
+    fn abort_reason(self) -> SigningAbortReason {
+        match self {
+            Fault::Delta => SigningAbortReason::MismatchedDelta,
+            Fault::Chi => SigningAbortReason::MismatchedChi,
+            Fault::Sigma => SigningAbortReason::SignatureInvalid,
+        }
+    }
+
+    /// Reason of abort which honest parties should report if faulty party sent tampered
+    /// message only to some of them
+    fn split_view_abort_reason(self) -> SigningAbortReason {
+        match self {
+            Fault::Delta | Fault::Chi => SigningAbortReason::BroadcastNotReliable { round: 3 },
+            Fault::Sigma => SigningAbortReason::BroadcastNotReliable { round: 4 },
+        }
+    }
+}
+
+cggmp21_tests::test_suite! {
+    test: faulty_signer_is_identified,
+    generics: stark,
+    suites: {
+        n3_delta: (None, 3, crate::identifiable_abort::Fault::Delta),
+        n3_chi: (None, 3, crate::identifiable_abort::Fault::Chi),
+        n3_sigma: (None, 3, crate::identifiable_abort::Fault::Sigma),
+        t3n5_delta: (Some(3), 5, crate::identifiable_abort::Fault::Delta),
+        t3n5_chi: (Some(3), 5, crate::identifiable_abort::Fault::Chi),
+        t3n5_sigma: (Some(3), 5, crate::identifiable_abort::Fault::Sigma),
+    }
+}
+
+fn faulty_signer_is_identified<E>(t: Option<u16>, n: u16, fault: Fault)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(t, n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let mut original_message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut original_message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&original_message_to_sign);
+
+    let t = shares[0].min_signers();
+    let mut participants = (0..n).collect::<Vec<_>>();
+    participants.shuffle(&mut rng);
+    let participants = &participants[..usize::from(t)];
+    println!("Signers: {participants:?}");
+    let participants_shares = participants.iter().map(|i| &shares[usize::from(*i)]);
+
+    // Index of faulty signer and of honest signer whose message is tampered
+    // at faulty signer side, so faulty signer also proceeds to identification
+    let faulty = rng.gen_range(0..t);
+    let victim = (faulty + 1) % t;
+    println!("Faulty signer: {faulty}");
+
+    let results = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+        let tampered_sender = if i == faulty { victim } else { faulty };
+        let party = party.map_delivery(|delivery| {
+            let (incomings, outgoings) = round_based::Delivery::split(delivery);
+            let incomings = incomings.map(move |incoming| {
+                incoming.map(|mut incoming| {
+                    if incoming.sender == tampered_sender {
+                        tamper(&mut incoming.msg, fault);
+                    }
+                    incoming
+                })
+            });
+            (incomings, outgoings)
+        });
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+
+        async move {
+            // Simulated network delivers broadcast messages reliably, and tampered message
+            // is the same for all honest parties, so reliability check can be skipped
+            cggmp21::signing(eid, i, participants, share)
+                .enforce_reliable_broadcast(false)
+                .sign(&mut party_rng, party, message_to_sign)
+                .await
+        }
+    })
+    .unwrap();
+
+    for (i, result) in (0..).zip(results) {
+        if i == faulty {
+            continue;
+        }
+        let err = result.expect_err("signing must fail");
+        let aborted = err
+            .aborted()
+            .unwrap_or_else(|| panic!("signing must be aborted by malicious party: {err:?}"));
+        assert_eq!(aborted.reason(), fault.abort_reason());
+        assert_eq!(aborted.faulty_parties(), [faulty]);
+        assert_eq!(aborted.blame().len(), 1);
+    }
+}
+
+cggmp21_tests::test_suite! {
+    test: split_view_is_detected,
+    generics: stark,
+    suites: {
+        n3_delta: (None, 3, crate::identifiable_abort::Fault::Delta),
+        n3_chi: (None, 3, crate::identifiable_abort::Fault::Chi),
+        n3_sigma: (None, 3, crate::identifiable_abort::Fault::Sigma),
+        t3n5_delta: (Some(3), 5, crate::identifiable_abort::Fault::Delta),
+        t3n5_sigma: (Some(3), 5, crate::identifiable_abort::Fault::Sigma),
+    }
+}
+
+/// Faulty party sends tampered message to one of honest parties only. Honest parties must
+/// not split on whether to proceed to identification of faulty parties: all of them abort
+/// at reliability check.
+fn split_view_is_detected<E>(t: Option<u16>, n: u16, fault: Fault)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(t, n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let mut original_message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut original_message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&original_message_to_sign);
+
+    let t = shares[0].min_signers();
+    let mut participants = (0..n).collect::<Vec<_>>();
+    participants.shuffle(&mut rng);
+    let participants = &participants[..usize::from(t)];
+    println!("Signers: {participants:?}");
+    let participants_shares = participants.iter().map(|i| &shares[usize::from(*i)]);
+
+    // Only `victim` receives tampered message from faulty signer
+    let faulty = rng.gen_range(0..t);
+    let victim = (faulty + 1) % t;
+    println!("Faulty signer: {faulty}, victim: {victim}");
+
+    let results = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+        let party = party.map_delivery(|delivery| {
+            let (incomings, outgoings) = round_based::Delivery::split(delivery);
+            let incomings = incomings.map(move |incoming| {
+                incoming.map(|mut incoming| {
+                    if i == victim && incoming.sender == faulty {
+                        tamper(&mut incoming.msg, fault);
+                    }
+                    incoming
+                })
+            });
+            (incomings, outgoings)
+        });
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+
+        async move {
+            cggmp21::signing(eid, i, participants, share)
+                .sign(&mut party_rng, party, message_to_sign)
+                .await
+        }
+    })
+    .unwrap();
+
+    for (i, result) in (0..).zip(results) {
+        if i == faulty {
+            continue;
+        }
+        let err = result.expect_err("signing must fail");
+        let aborted = err
+            .aborted()
+            .unwrap_or_else(|| panic!("signing must be aborted by malicious party: {err:?}"));
+        assert_eq!(aborted.reason(), fault.split_view_abort_reason());
+    }
+}
+
+fn tamper<E: Curve>(msg: &mut Msg<E, Sha256>, fault: Fault) {
+    match (msg, fault) {
+        (Msg::Round3a(msg), crate::identifiable_abort::Fault::Delta) => msg.delta += Scalar::one(),
+        (Msg::Round3a(msg), crate::identifiable_abort::Fault::Chi) => msg.S += Point::generator(),
+        (Msg::Round4(msg), crate::identifiable_abort::Fault::Sigma) => msg.sigma += Scalar::one(),
+        _ => (),
+    }
+}
@@ -0,0 +1,211 @@
+use std::sync::{Arc, Mutex};
+
+use futures::StreamExt;
+use generic_ec::{Curve, NonZero, Point, Scalar, SecretScalar};
+use rand::{seq::SliceRandom, Rng};
+use rand_dev::DevRng;
+use sha2::Sha256;
+
+use cggmp21::key_export::{ExportAbortReason, Msg, MsgRound1};
+use cggmp21::{security_level::SecurityLevel128, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: key_export_works,
+    generics: stark,
+    suites: {
+        t3n5: (Some(3), 5, 3),
+        t3n5_all: (Some(3), 5, 5),
+        n3: (None, 3, 3),
+    }
+}
+
+fn key_export_works<E>(t: Option<u16>, n: u16, exporters_n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(t, n, false)
+        .expect("retrieve cached shares");
+    let key_info = &shares[0].core.key_info;
+
+    let mut exporters = (0..n).collect::<Vec<_>>();
+    exporters.shuffle(&mut rng);
+    let exporters = &exporters[..usize::from(exporters_n)];
+    println!("Exporters: {exporters:?}");
+
+    let decryption_key = NonZero::<SecretScalar<E>>::random(&mut rng);
+    let recipient_key = Point::generator() * &decryption_key;
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    // Exporters have indexes `0..exporters_n`, recipient has index `exporters_n`
+    let inputs = exporters
+        .iter()
+        .map(|j| Some(&shares[usize::from(*j)]))
+        .chain([None]);
+    let outputs = round_based::sim::run_with_setup(inputs, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        let decryption_key = decryption_key.clone();
+        async move {
+            let builder = cggmp21::key_export(eid, i, exporters, key_info, recipient_key);
+            let builder = match share {
+                Some(share) => builder.set_key_share(share),
+                None => builder.set_decryption_key(decryption_key),
+            };
+            builder.start(&mut party_rng, party).await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    // Exporters obtain nothing
+    let (recipient_output, exporters_outputs) = outputs.split_last().unwrap();
+    assert!(exporters_outputs.iter().all(|output| output.is_none()));
+    let sk = recipient_output
+        .as_ref()
+        .expect("recipient obtains the key");
+
+    assert_eq!(Point::generator() * sk, key_info.shared_public_key);
+    let reconstructed = cggmp21::key_share::reconstruct_secret_key(&shares).unwrap();
+    assert_eq!(AsRef::<Scalar<E>>::as_ref(sk), reconstructed.as_ref());
+}
+
+cggmp21_tests::test_suite! {
+    test: faulty_exporter_is_provably_blamed,
+    generics: stark,
+    suites: {
+        t3n5: (Some(3), 5),
+        n3: (None, 3),
+    }
+}
+
+fn faulty_exporter_is_provably_blamed<E>(t: Option<u16>, n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(t, n, false)
+        .expect("retrieve cached shares");
+    let key_info = &shares[0].core.key_info;
+
+    let exporters_n = t.unwrap_or(n);
+    let mut exporters = (0..n).collect::<Vec<_>>();
+    exporters.shuffle(&mut rng);
+    let exporters = &exporters[..usize::from(exporters_n)];
+
+    let decryption_key = NonZero::<SecretScalar<E>>::random(&mut rng);
+    let recipient_key = Point::generator() * &decryption_key;
+    // Faulty exporter encrypts its share to another key, so its proof of knowledge is valid,
+    // but recipient can't decrypt the share
+    let wrong_recipient_key = Point::generator() * NonZero::<Scalar<E>>::random(&mut rng);
+
+    let faulty = rng.gen_range(0..exporters_n);
+    println!("Exporters: {exporters:?}, faulty: {faulty}");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    // Messages received by the recipient, indexed by sender
+    let received = Arc::new(Mutex::new(vec![None; usize::from(exporters_n)]));
+
+    let inputs = exporters
+        .iter()
+        .map(|j| Some(&shares[usize::from(*j)]))
+        .chain([None]);
+    let outputs = round_based::sim::run_with_setup(inputs, |i, party, share| {
+        let received = received.clone();
+        let party = party.map_delivery(|delivery| {
+            let (incomings, outgoings) = round_based::Delivery::split(delivery);
+            let incomings = incomings.inspect(move |incoming| {
+                if let Ok(incoming) = incoming {
+                    let Msg::Round1(msg) = &incoming.msg;
+                    received.lock().unwrap()[usize::from(incoming.sender)] = Some(msg.clone());
+                }
+            });
+            (incomings, outgoings)
+        });
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        let decryption_key = decryption_key.clone();
+        let recipient_key = if i == faulty {
+            wrong_recipient_key
+        } else {
+            recipient_key
+        };
+        async move {
+            let builder = cggmp21::key_export(eid, i, exporters, key_info, recipient_key);
+            let builder = match share {
+                Some(share) => builder.set_key_share(share),
+                None => builder.set_decryption_key(decryption_key),
+            };
+            builder.start(&mut party_rng, party).await
+        }
+    })
+    .unwrap()
+    .into_vec();
+
+    let (recipient_output, exporters_outputs) = outputs.split_last().unwrap();
+    assert!(exporters_outputs
+        .iter()
+        .all(|output| matches!(output, Ok(None))));
+    let err = recipient_output.as_ref().unwrap_err();
+    let aborted = err.aborted().expect("protocol must be aborted");
+    assert_eq!(aborted.reason(), ExportAbortReason::InvalidCiphertext);
+    assert_eq!(aborted.faulty_parties(), [faulty]);
+
+    let received: Vec<MsgRound1<E>> = received
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|msg| msg.clone().expect("message was received"))
+        .collect();
+    let [invalid_ciphertext] = err.invalid_ciphertexts() else {
+        panic!("expected exactly one verifiable decryption")
+    };
+    assert_eq!(invalid_ciphertext.exporter, faulty);
+
+    // Anyone can check that the faulty exporter is indeed faulty
+    invalid_ciphertext
+        .verify::<Sha256>(
+            eid,
+            exporters,
+            key_info,
+            recipient_key,
+            &received[usize::from(faulty)],
+        )
+        .expect("verifiable decryption is invalid");
+
+    // But it can't be used to blame an honest exporter
+    let honest = (faulty + 1) % exporters_n;
+    let mut forged = invalid_ciphertext.clone();
+    forged.exporter = honest;
+    assert!(forged
+        .verify::<Sha256>(
+            eid,
+            exporters,
+            key_info,
+            recipient_key,
+            &received[usize::from(honest)],
+        )
+        .is_err());
+
+    // Revealed shared secret must match the proof
+    let mut forged = invalid_ciphertext.clone();
+    forged.shared_secret += Point::generator();
+    assert!(forged
+        .verify::<Sha256>(
+            eid,
+            exporters,
+            key_info,
+            recipient_key,
+            &received[usize::from(faulty)],
+        )
+        .is_err());
+}
@@ -0,0 +1,83 @@
+use generic_ec::{Curve, NonZero, Point, Scalar, SecretScalar};
+use rand::{seq::SliceRandom, Rng};
+use rand_dev::DevRng;
+
+use cggmp21::ExecutionId;
+
+cggmp21_tests::test_suite! {
+    test: key_import_works,
+    generics: stark,
+    suites: {
+        t2n3: (2, 3, false),
+        t3n5: (3, 5, false),
+        #[cfg(feature = "hd-wallet")]
+        t3n5_hd: (3, 5, true),
+    }
+}
+
+fn key_import_works<E>(t: u16, n: u16, hd_wallet: bool)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+{
+    #[cfg(not(feature = "hd-wallet"))]
+    assert!(!hd_wallet);
+
+    let mut rng = DevRng::new();
+
+    let sk = NonZero::<SecretScalar<E>>::random(&mut rng);
+    let pk = Point::generator() * &sk;
+    #[cfg(feature = "hd-wallet")]
+    let chain_code: Option<cggmp21::hd_wallet::ChainCode> = hd_wallet.then(|| rng.gen());
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    // Members of the committee have indexes `0..n`, key owner has index `n`
+    let secret_keys = (0..=n).map(|i| (i == n).then(|| sk.clone()));
+    let outputs = round_based::sim::run_with_setup(secret_keys, |i, party, sk| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            let builder = cggmp21::key_import::<E>(eid, i, n, t);
+            let builder = match sk {
+                Some(sk) => builder.set_secret_key(sk),
+                None => builder,
+            };
+            #[cfg(feature = "hd-wallet")]
+            let builder = match chain_code {
+                Some(chain_code) if i == n => builder.set_chain_code(chain_code),
+                _ => builder,
+            };
+            builder.start(&mut party_rng, party).await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    // Key owner obtains nothing
+    assert!(outputs[usize::from(n)].is_none());
+    let key_shares = outputs.into_iter().flatten().collect::<Vec<_>>();
+    assert_eq!(key_shares.len(), usize::from(n));
+
+    for (i, key_share) in key_shares.iter().enumerate() {
+        assert_eq!(key_share.i, u16::try_from(i).unwrap());
+        assert_eq!(key_share.min_signers(), t);
+        assert_eq!(key_share.n(), n);
+        assert_eq!(key_share.shared_public_key, pk);
+        assert_eq!(key_share.public_shares, key_shares[0].public_shares);
+        assert_eq!(
+            Point::<E>::generator() * &key_share.x,
+            key_share.public_shares[i]
+        );
+        #[cfg(feature = "hd-wallet")]
+        assert_eq!(key_share.chain_code, chain_code);
+    }
+
+    // Any `t` members of the committee can reconstruct the imported key
+    let mut subset = key_shares.iter().collect::<Vec<_>>();
+    subset.shuffle(&mut rng);
+    let subset = &subset[..usize::from(t)];
+    let reconstructed = cggmp21::key_share::reconstruct_secret_key(subset).unwrap();
+    assert_eq!(reconstructed.as_ref(), AsRef::<Scalar<E>>::as_ref(&sk));
+}
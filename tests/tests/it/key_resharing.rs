@@ -0,0 +1,98 @@
+use generic_ec::{Curve, Point};
+use rand::{seq::SliceRandom, Rng};
+use rand_dev::DevRng;
+
+use cggmp21::key_resharing::Participant;
+use cggmp21::{security_level::SecurityLevel128, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: key_resharing_works,
+    generics: stark,
+    suites: {
+        t3n5_to_t4n5: (Some(3), 5, &[(Some(0), false), (Some(1), true), (Some(3), true), (None, true), (None, true), (None, true)], 4, false),
+        t3n5_to_t2n3: (Some(3), 5, &[(Some(4), true), (Some(2), false), (Some(0), true), (None, true)], 2, false),
+        n3_to_t2n4: (None, 3, &[(Some(0), true), (Some(1), false), (Some(2), true), (None, true), (None, true)], 2, false),
+        #[cfg(feature = "hd-wallet")]
+        t3n5_to_t4n5_hd: (Some(3), 5, &[(Some(0), false), (Some(1), true), (Some(3), true), (None, true), (None, true), (None, true)], 4, true),
+    }
+}
+
+/// `participants` lists `(old_index, new_member)` of every party, see [`Participant`]
+fn key_resharing_works<E>(
+    t: Option<u16>,
+    n: u16,
+    participants: &[(Option<u16>, bool)],
+    new_t: u16,
+    hd_wallet: bool,
+) where
+    E: Curve + cggmp21_tests::CurveParams,
+{
+    #[cfg(not(feature = "hd-wallet"))]
+    assert!(!hd_wallet);
+
+    let mut rng = DevRng::new();
+
+    let participants = &participants
+        .iter()
+        .map(|&(old_index, new_member)| Participant {
+            old_index,
+            new_member,
+        })
+        .collect::<Vec<_>>();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(t, n, hd_wallet)
+        .expect("retrieve cached shares");
+    let old_key_info = &shares[0].core.key_info;
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let old_shares = participants
+        .iter()
+        .map(|p| p.old_index.map(|j| &shares[usize::from(j)]));
+    let outputs = round_based::sim::run_with_setup(old_shares, |i, party, old_share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            let builder = cggmp21::key_resharing(eid, i, participants, new_t, old_key_info);
+            let builder = match old_share {
+                Some(share) => builder.set_old_key_share(share),
+                None => builder,
+            };
+            builder.start(&mut party_rng, party).await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    // Parties that left the committee obtain nothing
+    for (output, participant) in outputs.iter().zip(participants) {
+        assert_eq!(output.is_some(), participant.new_member);
+    }
+    let new_shares = outputs.into_iter().flatten().collect::<Vec<_>>();
+    let new_n = participants.iter().filter(|p| p.new_member).count();
+    assert_eq!(new_shares.len(), new_n);
+
+    for (i, key_share) in new_shares.iter().enumerate() {
+        assert_eq!(key_share.i, u16::try_from(i).unwrap());
+        assert_eq!(key_share.min_signers(), new_t);
+        assert_eq!(usize::from(key_share.n()), new_n);
+        assert_eq!(key_share.shared_public_key, old_key_info.shared_public_key);
+        assert_eq!(key_share.public_shares, new_shares[0].public_shares);
+        assert_eq!(
+            Point::<E>::generator() * &key_share.x,
+            key_share.public_shares[i]
+        );
+        #[cfg(feature = "hd-wallet")]
+        assert_eq!(key_share.chain_code, old_key_info.chain_code);
+    }
+
+    // Any `new_t` members of the new committee can reconstruct the key
+    let mut subset = new_shares.iter().collect::<Vec<_>>();
+    subset.shuffle(&mut rng);
+    let subset = &subset[..usize::from(new_t)];
+    let sk = cggmp21::key_share::reconstruct_secret_key(subset).unwrap();
+    assert_eq!(Point::generator() * sk, *old_key_info.shared_public_key);
+}
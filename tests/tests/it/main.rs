@@ -1,7 +1,30 @@
 // mod key_refresh;
 // mod keygen;
 // mod old_shares;
+mod batch_keygen;
+mod batch_presignatures;
+#[cfg(feature = "hd-wallet")]
+mod chain_code_gen;
+mod der_encoding;
+mod five_rounds_signing;
+mod full_keygen;
+#[cfg(feature = "hd-wallet")]
+mod hd_presignatures;
+mod hierarchical_threshold;
+mod identifiable_abort;
+mod key_export;
+mod key_import;
+mod key_resharing;
 mod pipeline;
+mod presignature_metadata;
+mod robust_keygen;
+mod round_timeout;
+mod share_preimages;
+mod share_recovery;
+mod signing_quorum;
+mod threshold_conversion;
+mod threshold_key_refresh;
+mod weighted_threshold;
 // mod signing;
 // mod stark_prehashed;
 // mod trusted_dealer;
@@ -0,0 +1,200 @@
+use generic_ec::{coords::HasAffineXAndParity, Curve, Point, Scalar};
+use rand::{Rng, RngCore};
+use rand_dev::DevRng;
+use sha2::Sha256;
+
+use cggmp21::signing::store::{MemoryStorage, PresignatureStore, PresignatureStoreError};
+use cggmp21::signing::{CombineError, CombineVerifiedError, DataToSign, PresignatureMismatch};
+use cggmp21::{security_level::SecurityLevel128, ExecutionId, PartialSignature};
+
+cggmp21_tests::test_suite! {
+    test: presignature_mismatches_are_rejected,
+    generics: stark,
+    suites: {
+        t2n3: (2, 3),
+    }
+}
+
+fn presignature_mismatches_are_rejected<E>(t: u16, n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineXAndParity<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(Some(t), n, false)
+        .expect("retrieve cached shares");
+    let other_key_shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(None, n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let participants = &(0..t).collect::<Vec<_>>();
+    let participants_shares = participants.iter().map(|i| &shares[usize::from(*i)]);
+
+    // First instances are burned by mismatch checks, the last one is used to sign
+    let batch_size = 4;
+    let presigs = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+
+        async move {
+            cggmp21::signing(eid, i, participants, share)
+                .generate_presignatures(&mut party_rng, party, batch_size)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    let public_data = presigs[0][batch_size - 1].public_data();
+    for instance in 0..batch_size {
+        let instance_data = presigs[0][instance].public_data();
+        for presigs in &presigs {
+            assert_eq!(presigs[instance].public_data(), instance_data);
+        }
+        assert_eq!(instance_data.metadata.signers, *participants);
+        assert_eq!(instance_data.metadata.execution_id, eid.as_bytes());
+    }
+
+    // Every signer keeps its presignatures in its own store
+    let mut stores = vec![];
+    let mut handles = vec![];
+    for presigs in presigs {
+        let mut store = PresignatureStore::new(MemoryStorage::default());
+        let signer_handles = presigs
+            .into_iter()
+            .map(|presig| store.insert(presig).unwrap())
+            .collect::<Vec<_>>();
+        stores.push(store);
+        handles.push(signer_handles.into_iter());
+    }
+
+    let mut message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&message_to_sign);
+
+    // Key share of a different signer
+    let handle = handles[0].next().unwrap();
+    let id = handle.id();
+    let err = stores[0]
+        .consume(handle, &shares[usize::from(n - 1)], message_to_sign)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        PresignatureStoreError::Mismatch(PresignatureMismatch::Signer {
+            presignature: 0,
+            key_share
+        }) if key_share == n - 1
+    ));
+    // Mismatched presignature is consumed anyway
+    assert!(matches!(
+        stores[0].get::<E>(id),
+        Err(PresignatureStoreError::UnknownId)
+    ));
+
+    // Key share of a different key
+    let err = stores[0]
+        .consume(
+            handles[0].next().unwrap(),
+            &other_key_shares[0],
+            message_to_sign,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        PresignatureStoreError::Mismatch(PresignatureMismatch::Key)
+    ));
+
+    // Key share with different aux info
+    let mut refreshed_share = shares[0].clone().into_inner();
+    let aux = &mut refreshed_share.aux.parties[1];
+    std::mem::swap(&mut aux.s, &mut aux.t);
+    let refreshed_share = cggmp21::KeyShare::validate(refreshed_share).unwrap();
+    let err = stores[0]
+        .consume(
+            handles[0].next().unwrap(),
+            &refreshed_share,
+            message_to_sign,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        PresignatureStoreError::Mismatch(PresignatureMismatch::AuxInfo)
+    ));
+
+    // Combine rejects incomplete or duplicated set of partial signatures
+    let last_handles = handles
+        .into_iter()
+        .map(|handles| handles.last().unwrap())
+        .collect::<Vec<_>>();
+    let second_handle = stores[0].get::<E>(last_handles[0].id()).unwrap();
+    let partial_signatures = stores
+        .iter_mut()
+        .zip(last_handles)
+        .zip(participants)
+        .map(|((store, handle), j)| {
+            let partial_signature = store
+                .consume(handle, &shares[usize::from(*j)], message_to_sign)
+                .unwrap();
+            assert_eq!(partial_signature.signer(), *j);
+            partial_signature
+        })
+        .collect::<Vec<_>>();
+
+    // Presignature can't be used twice
+    let err = stores[0]
+        .consume(second_handle, &shares[0], message_to_sign)
+        .unwrap_err();
+    assert!(matches!(err, PresignatureStoreError::AlreadyConsumed));
+
+    let err = PartialSignature::combine(&partial_signatures[1..]).unwrap_err();
+    assert!(matches!(err, CombineError::WrongAmount { .. }));
+
+    let mut duplicated = partial_signatures.clone();
+    duplicated[1] = duplicated[0].clone();
+    let err = PartialSignature::combine(&duplicated).unwrap_err();
+    assert!(matches!(err, CombineError::DuplicatedSigner { signer: 0 }));
+
+    let mut mismatched = partial_signatures.clone();
+    let mut tampered_metadata = serde_json::to_value(&mismatched[1]).unwrap();
+    tampered_metadata["metadata"]["execution_id"] = hex::encode(b"other execution").into();
+    mismatched[1] = serde_json::from_value(tampered_metadata).unwrap();
+    let err = PartialSignature::combine(&mismatched).unwrap_err();
+    assert!(matches!(err, CombineError::MismatchedMetadata { index: 1 }));
+
+    // Combine with verification identifies invalid partial signatures
+    let mut tampered = partial_signatures.clone();
+    tampered[1].sigma += Scalar::one();
+    let err =
+        PartialSignature::combine_verified(&tampered, &public_data, message_to_sign).unwrap_err();
+    assert!(matches!(
+        err,
+        CombineVerifiedError::InvalidPartialSignatures { indices } if indices == [1]
+    ));
+
+    let mut other_message = [0u8; 100];
+    rng.fill_bytes(&mut other_message);
+    let other_message = DataToSign::digest::<Sha256>(&other_message);
+    let err = PartialSignature::combine_verified(&partial_signatures, &public_data, other_message)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        CombineVerifiedError::InvalidPartialSignatures { indices } if indices.len() == usize::from(t)
+    ));
+
+    let signature = PartialSignature::combine(&partial_signatures).unwrap();
+    let verified_signature =
+        PartialSignature::combine_verified(&partial_signatures, &public_data, message_to_sign)
+            .unwrap();
+    assert_eq!(signature, verified_signature);
+    signature
+        .verify(&shares[0].core.shared_public_key, &message_to_sign)
+        .expect("signature is not valid");
+    let recovered_key = signature.recover_public_key(&message_to_sign).unwrap();
+    assert_eq!(recovered_key, shares[0].core.shared_public_key);
+}
@@ -0,0 +1,219 @@
+use futures::StreamExt;
+use generic_ec::{Curve, Point, Scalar};
+use rand::{seq::SliceRandom, Rng};
+use rand_dev::DevRng;
+use sha2::Sha256;
+
+use cggmp21::keygen::{msg::threshold::Msg, KeygenAbortReason, RobustKeygenOutput};
+use cggmp21::{key_share::reconstruct_secret_key, security_level::SecurityLevel128, ExecutionId};
+
+/// Misbehavior of the faulty dealer
+#[derive(Clone, Copy, Debug)]
+enum Fault {
+    /// Honest execution
+    None,
+    /// Dealer sends invalid share to a victim, but reveals valid share when accused
+    InvalidShare,
+    /// Dealer sends invalid share to a victim, and reveals invalid share when accused
+    InvalidShareAndJustification,
+}
+
+cggmp21_tests::test_suite! {
+    test: robust_keygen_works,
+    generics: stark,
+    suites: {
+        t2n3: (2, 3, crate::robust_keygen::Fault::None),
+        t3n5: (3, 5, crate::robust_keygen::Fault::None),
+        t2n3_invalid_share: (2, 3, crate::robust_keygen::Fault::InvalidShare),
+        t3n5_invalid_share: (3, 5, crate::robust_keygen::Fault::InvalidShare),
+        t2n3_disqualified: (2, 3, crate::robust_keygen::Fault::InvalidShareAndJustification),
+        t3n5_disqualified: (3, 5, crate::robust_keygen::Fault::InvalidShareAndJustification),
+    }
+}
+
+fn robust_keygen_works<E: Curve>(t: u16, n: u16, fault: Fault) {
+    let mut rng = DevRng::new();
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let faulty = rng.gen_range(0..n);
+    let victim = (faulty + 1) % n;
+    println!("Faulty dealer: {faulty}, victim: {victim}");
+
+    let results = round_based::sim::run(n, |i, party| {
+        let party = party.map_delivery(|delivery| {
+            let (incomings, outgoings) = round_based::Delivery::split(delivery);
+            let incomings = incomings.map(move |incoming| {
+                incoming.map(|mut incoming| {
+                    if incoming.sender == faulty {
+                        tamper(&mut incoming.msg, fault, i == victim);
+                    }
+                    incoming
+                })
+            });
+            (incomings, outgoings)
+        });
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        // Tampered justification is the same for all honest parties, but faulty dealer doesn't
+        // see it, so it would fail reliability check. Simulated network delivers broadcast
+        // messages reliably, so reliability check can be skipped
+        let reliable_broadcast = !matches!(fault, Fault::InvalidShareAndJustification);
+        async move {
+            cggmp21::keygen::<E>(eid, i, n)
+                .set_threshold(t)
+                .enforce_reliable_broadcast(reliable_broadcast)
+                .start_robust(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap();
+
+    let expected_disqualified: &[u16] = match fault {
+        Fault::None | Fault::InvalidShare => &[],
+        Fault::InvalidShareAndJustification => &[faulty],
+    };
+    // Faulty dealer doesn't see its own tampered messages, so we don't check its output
+    // unless it behaved honestly
+    let outputs = (0..)
+        .zip(results)
+        .filter(|(i, _)| matches!(fault, Fault::None | Fault::InvalidShare) || *i != faulty)
+        .map(|(_, result)| result.expect("keygen failed"))
+        .collect::<Vec<RobustKeygenOutput<E>>>();
+
+    for output in &outputs {
+        assert_eq!(output.disqualified, expected_disqualified);
+        assert_eq!(
+            output.key_share.shared_public_key,
+            outputs[0].key_share.shared_public_key
+        );
+        assert_eq!(
+            output.key_share.public_shares,
+            outputs[0].key_share.public_shares
+        );
+    }
+
+    // Key can be reconstructed from any `t` key shares of honest parties
+    let mut key_shares = outputs
+        .iter()
+        .map(|output| &output.key_share)
+        .collect::<Vec<_>>();
+    key_shares.shuffle(&mut rng);
+    let sk = reconstruct_secret_key(&key_shares[..usize::from(t)]).unwrap();
+    assert_eq!(
+        Point::generator() * sk,
+        outputs[0].key_share.shared_public_key
+    );
+}
+
+fn tamper<E: Curve>(msg: &mut Msg<E, SecurityLevel128, Sha256>, fault: Fault, to_victim: bool) {
+    match (msg, fault) {
+        (Msg::Round2Uni(msg), Fault::InvalidShare | Fault::InvalidShareAndJustification)
+            if to_victim =>
+        {
+            msg.sigma += Scalar::one()
+        }
+        (Msg::Justification(msg), Fault::InvalidShareAndJustification) => msg
+            .revealed
+            .iter_mut()
+            .for_each(|(_, sigmas)| sigmas[0] += Scalar::one()),
+        _ => (),
+    }
+}
+
+/// Faulty party sends its message of robust keygen round to some of honest parties only
+#[derive(Clone, Copy, Debug)]
+enum SplitView {
+    /// Faulty party complains about the victim to the victim only
+    Complaints,
+    /// Faulty dealer sends invalid share to the victim, and reveals invalid share to
+    /// a witness only
+    Justification,
+}
+
+impl SplitView {
+    /// Reason of abort which honest parties should report
+    fn abort_reason(self) -> KeygenAbortReason {
+        match self {
+            SplitView::Complaints => KeygenAbortReason::ComplaintsNotReliable,
+            SplitView::Justification => KeygenAbortReason::JustificationNotReliable,
+        }
+    }
+}
+
+cggmp21_tests::test_suite! {
+    test: split_view_is_detected,
+    generics: stark,
+    suites: {
+        t2n3_complaints: (2, 3, crate::robust_keygen::SplitView::Complaints),
+        t3n5_complaints: (3, 5, crate::robust_keygen::SplitView::Complaints),
+        t2n3_justification: (2, 3, crate::robust_keygen::SplitView::Justification),
+        t3n5_justification: (3, 5, crate::robust_keygen::SplitView::Justification),
+    }
+}
+
+/// Honest parties must not disqualify different dealers: all of them abort at reliability
+/// check instead
+fn split_view_is_detected<E: Curve>(t: u16, n: u16, split_view: SplitView) {
+    let mut rng = DevRng::new();
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let faulty = rng.gen_range(0..n);
+    let victim = (faulty + 1) % n;
+    let witness = (faulty + 2) % n;
+    println!("Faulty party: {faulty}, victim: {victim}, witness: {witness}");
+
+    let results = round_based::sim::run(n, |i, party| {
+        let party = party.map_delivery(|delivery| {
+            let (incomings, outgoings) = round_based::Delivery::split(delivery);
+            let incomings = incomings.map(move |incoming| {
+                incoming.map(|mut incoming| {
+                    if incoming.sender != faulty {
+                        return incoming;
+                    }
+                    match (&mut incoming.msg, split_view) {
+                        (Msg::Complaints(msg), SplitView::Complaints) if i == victim => {
+                            msg.accused = vec![victim]
+                        }
+                        (Msg::Round2Uni(msg), SplitView::Justification) if i == victim => {
+                            msg.sigma += Scalar::one()
+                        }
+                        (Msg::Justification(msg), SplitView::Justification) if i == witness => msg
+                            .revealed
+                            .iter_mut()
+                            .for_each(|(_, sigmas)| sigmas[0] += Scalar::one()),
+                        _ => (),
+                    }
+                    incoming
+                })
+            });
+            (incomings, outgoings)
+        });
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::keygen::<E>(eid, i, n)
+                .set_threshold(t)
+                .start_robust(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap();
+
+    for (i, result) in (0..).zip(results) {
+        if i == faulty {
+            continue;
+        }
+        let err = match result {
+            Ok(_) => panic!("keygen must fail"),
+            Err(err) => err,
+        };
+        let aborted = err
+            .aborted()
+            .unwrap_or_else(|| panic!("keygen must be aborted by malicious party: {err:?}"));
+        assert_eq!(aborted.reason(), split_view.abort_reason());
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::VecDeque;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use generic_ec::{coords::HasAffineX, Curve, Point};
+use rand::{Rng, RngCore};
+use rand_dev::DevRng;
+use round_based::state_machine::{ProceedResult, StateMachine};
+use round_based::{Incoming, MessageDestination, MessageType, PartyIndex};
+use sha2::Sha256;
+
+use cggmp21::signing::DataToSign;
+use cggmp21::{security_level::SecurityLevel128, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: keygen_reports_stalled_party,
+    generics: stark,
+    suites: {
+        t2n3: (2, 3),
+    }
+}
+
+cggmp21_tests::test_suite! {
+    test: signing_reports_stalled_party,
+    generics: stark,
+    suites: {
+        t2n3: (2, 3),
+    }
+}
+
+fn keygen_reports_stalled_party<E: Curve>(t: u16, n: u16) {
+    let mut rng = DevRng::new();
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let silent = rng.gen_range(0..n);
+    println!("Silent party: {silent}");
+
+    let expired = Arc::new(AtomicBool::new(false));
+    let mut timers = (0..n).map(|_| timer(&expired)).collect::<Vec<_>>();
+    let mut rngs = (0..n).map(|_| rng.fork()).collect::<Vec<_>>();
+
+    let parties = (0..n)
+        .zip(&mut timers)
+        .zip(&mut rngs)
+        .filter(|((i, _), _)| *i != silent)
+        .map(|((i, timer), party_rng)| {
+            let party = cggmp21::keygen::<E>(eid, i, n)
+                .set_threshold(t)
+                .set_round_timer(timer)
+                .into_state_machine(party_rng);
+            (i, party)
+        })
+        .collect();
+
+    for result in run_until_stalled(parties, &expired) {
+        let Err(err) = result else {
+            panic!("keygen must fail")
+        };
+        let timeout = err
+            .timed_out()
+            .unwrap_or_else(|| panic!("keygen must time out: {err:?}"));
+        assert_eq!(timeout.round(), 0);
+        assert_eq!(timeout.missing_parties(), [silent]);
+    }
+}
+
+#[test]
+fn aux_gen_reports_stalled_party() {
+    let mut rng = DevRng::new();
+    let n = 3;
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let silent = rng.gen_range(0..n);
+    println!("Silent party: {silent}");
+
+    let expired = Arc::new(AtomicBool::new(false));
+    let mut timers = (0..n).map(|_| timer(&expired)).collect::<Vec<_>>();
+    let mut rngs = (0..n).map(|_| rng.fork()).collect::<Vec<_>>();
+    let mut primes = cggmp21_tests::CACHED_PRIMES.iter::<SecurityLevel128>();
+
+    let parties = (0..n)
+        .zip(&mut timers)
+        .zip(&mut rngs)
+        .filter(|((i, _), _)| *i != silent)
+        .map(|((i, timer), party_rng)| {
+            let pregenerated_primes = primes.next().expect("Can't fetch primes");
+            let party = cggmp21::aux_info_gen(eid, i, n, pregenerated_primes)
+                .set_round_timer(timer)
+                .into_state_machine(party_rng);
+            (i, party)
+        })
+        .collect();
+
+    for result in run_until_stalled(parties, &expired) {
+        let Err(err) = result else {
+            panic!("aux gen must fail")
+        };
+        let timeout = err
+            .timed_out()
+            .unwrap_or_else(|| panic!("aux gen must time out: {err:?}"));
+        assert_eq!(timeout.round(), 0);
+        assert_eq!(timeout.missing_parties(), [silent]);
+    }
+}
+
+fn signing_reports_stalled_party<E>(t: u16, n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(Some(t), n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let mut message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&message_to_sign);
+
+    // Silent signer is a part of signing quorum, so other signers wait for its messages
+    let participants = &(0..n).collect::<Vec<_>>();
+    let silent = rng.gen_range(0..n);
+    println!("Silent signer: {silent}");
+
+    let expired = Arc::new(AtomicBool::new(false));
+    let mut timers = (0..n).map(|_| timer(&expired)).collect::<Vec<_>>();
+    let mut rngs = (0..n).map(|_| rng.fork()).collect::<Vec<_>>();
+
+    let parties = (0..n)
+        .zip(&mut timers)
+        .zip(&mut rngs)
+        .filter(|((i, _), _)| *i != silent)
+        .map(|((i, timer), party_rng)| {
+            let party = cggmp21::signing(eid, i, participants, &shares[usize::from(i)])
+                .set_round_timer(timer)
+                .sign_sync(party_rng, message_to_sign);
+            (i, party)
+        })
+        .collect();
+
+    for result in run_until_stalled(parties, &expired) {
+        let Err(err) = result else {
+            panic!("signing must fail")
+        };
+        let timeout = err
+            .timed_out()
+            .unwrap_or_else(|| panic!("signing must time out: {err:?}"));
+        assert_eq!(timeout.round(), 0);
+        assert_eq!(timeout.missing_parties(), [silent]);
+    }
+}
+
+/// Round timer which deadlines expire once `expired` flag is set
+fn timer(expired: &Arc<AtomicBool>) -> impl FnMut() -> Deadline + Send + Sync {
+    let expired = expired.clone();
+    move || Deadline(expired.clone())
+}
+
+struct Deadline(Arc<AtomicBool>);
+
+impl Future for Deadline {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<()> {
+        if self.0.load(Ordering::Relaxed) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Carries out the protocol between given parties until none of them can proceed, then
+/// expires round deadlines and runs the parties until they all terminate
+///
+/// Messages sent to parties that aren't in the list are dropped.
+fn run_until_stalled<P>(mut parties: Vec<(PartyIndex, P)>, expired: &AtomicBool) -> Vec<P::Output>
+where
+    P: StateMachine,
+    P::Msg: Clone,
+{
+    let indexes = parties.iter().map(|(i, _)| *i).collect::<Vec<_>>();
+    let mut queues = parties
+        .iter()
+        .map(|_| VecDeque::<Incoming<P::Msg>>::new())
+        .collect::<Vec<_>>();
+    let mut wants_msg = vec![false; parties.len()];
+    let mut outputs = parties.iter().map(|_| None).collect::<Vec<_>>();
+    let mut next_id = 0;
+
+    while outputs.iter().any(Option::is_none) {
+        let mut progressed = false;
+        for (pos, (i, party)) in parties.iter_mut().enumerate() {
+            if outputs[pos].is_some() {
+                continue;
+            }
+            if wants_msg[pos] {
+                match queues[pos].pop_front() {
+                    Some(msg) => {
+                        if party.received_msg(msg).is_err() {
+                            panic!("party {i} doesn't accept message");
+                        }
+                        wants_msg[pos] = false;
+                        progressed = true;
+                    }
+                    // Once deadline expired, party needs to be resumed to notice it
+                    None if expired.load(Ordering::Relaxed) => (),
+                    None => continue,
+                }
+            }
+
+            match party.proceed() {
+                ProceedResult::SendMsg(outgoing) => {
+                    let msg_type = match outgoing.recipient {
+                        MessageDestination::AllParties => MessageType::Broadcast,
+                        MessageDestination::OneParty(_) => MessageType::P2P,
+                    };
+                    for (queue, j) in queues.iter_mut().zip(&indexes) {
+                        let is_recipient = match outgoing.recipient {
+                            MessageDestination::AllParties => j != i,
+                            MessageDestination::OneParty(recipient) => *j == recipient,
+                        };
+                        if is_recipient {
+                            queue.push_back(Incoming {
+                                id: next_id,
+                                sender: *i,
+                                msg_type,
+                                msg: outgoing.msg.clone(),
+                            });
+                            next_id += 1;
+                        }
+                    }
+                    progressed = true;
+                }
+                ProceedResult::NeedsOneMoreMessage => wants_msg[pos] = true,
+                ProceedResult::Output(output) => {
+                    outputs[pos] = Some(output);
+                    progressed = true;
+                }
+                ProceedResult::Yielded => progressed = true,
+                ProceedResult::Error(err) => panic!("party {i} failed: {err}"),
+            }
+        }
+
+        if !progressed {
+            assert!(
+                !expired.swap(true, Ordering::Relaxed),
+                "parties didn't terminate after round deadline expired"
+            );
+        }
+    }
+
+    outputs.into_iter().map(Option::unwrap).collect()
+}
@@ -0,0 +1,98 @@
+use generic_ec::{Curve, NonZero, Point, Scalar};
+use rand::{seq::SliceRandom, Rng};
+use rand_dev::DevRng;
+
+use cggmp21::{key_share::reconstruct_secret_key, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: keygen_at_custom_preimages,
+    generics: stark,
+    suites: {
+        t2n3: (2, 3),
+        t3n5: (3, 5),
+    }
+}
+
+fn keygen_at_custom_preimages<E: Curve>(t: u16, n: u16) {
+    let mut rng = DevRng::new();
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let preimages = (0..n)
+        .map(|_| NonZero::<Scalar<E>>::random(&mut rng))
+        .collect::<Vec<_>>();
+
+    let key_shares = round_based::sim::run(n, |i, party| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        let preimages = preimages.clone();
+
+        async move {
+            cggmp21::keygen::<E>(eid, i, n)
+                .set_threshold(t)
+                .set_share_preimages(preimages)
+                .start(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    for (i, key_share) in (0u16..).zip(&key_shares) {
+        assert_eq!(key_share.i, i);
+        assert_eq!(key_share.shared_public_key, key_shares[0].shared_public_key);
+        assert_eq!(key_share.public_shares, key_shares[0].public_shares);
+        assert_eq!(
+            key_share.vss_setup.as_ref().map(|setup| &setup.I),
+            Some(&preimages)
+        );
+        assert_eq!(key_share.share_preimage(i), Some(preimages[usize::from(i)]));
+    }
+
+    let t_shares = key_shares
+        .choose_multiple(&mut rng, t.into())
+        .cloned()
+        .collect::<Vec<_>>();
+    let sk = reconstruct_secret_key(&t_shares).unwrap();
+    assert_eq!(Point::generator() * sk, key_shares[0].shared_public_key);
+}
+
+cggmp21_tests::test_suite! {
+    test: invalid_preimages_are_rejected,
+    generics: stark,
+    suites: {
+        t2n3: (2, 3),
+    }
+}
+
+fn invalid_preimages_are_rejected<E: Curve>(t: u16, n: u16) {
+    let mut rng = DevRng::new();
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let preimages = (0..n)
+        .map(|_| NonZero::<Scalar<E>>::random(&mut rng))
+        .collect::<Vec<_>>();
+    let mut duplicated = preimages.clone();
+    duplicated[1] = duplicated[0];
+
+    for invalid in [preimages[1..].to_vec(), duplicated] {
+        let results = round_based::sim::run(n, |i, party| {
+            let mut party_rng = rng.fork();
+            let invalid = invalid.clone();
+
+            async move {
+                cggmp21::keygen::<E>(eid, i, n)
+                    .set_threshold(t)
+                    .set_share_preimages(invalid)
+                    .start(&mut party_rng, party)
+                    .await
+            }
+        })
+        .unwrap();
+        assert!(results.into_vec().iter().all(|result| result.is_err()));
+    }
+}
@@ -0,0 +1,121 @@
+use generic_ec::{Curve, Scalar};
+use rand::Rng;
+use rand_dev::DevRng;
+
+use cggmp21::{security_level::SecurityLevel128, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: share_recovery_works,
+    generics: stark,
+    suites: {
+        t3n5: (3, 5, &[4, 0, 1], 2, false),
+        t3n5_more_helpers: (3, 5, &[1, 2, 3, 4], 0, false),
+        t2n3: (2, 3, &[2, 0], 1, false),
+        #[cfg(feature = "hd-wallet")]
+        t3n5_hd: (3, 5, &[4, 0, 1], 2, true),
+    }
+}
+
+fn share_recovery_works<E>(t: u16, n: u16, helpers: &[u16], lost_index: u16, hd_wallet: bool)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+{
+    #[cfg(not(feature = "hd-wallet"))]
+    assert!(!hd_wallet);
+
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(Some(t), n, hd_wallet)
+        .expect("retrieve cached shares");
+    let key_info = &shares[0].core.key_info;
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    // Helpers are followed by the recovering party which has no key share
+    let parties_shares = helpers
+        .iter()
+        .map(|h| Some(&shares[usize::from(*h)]))
+        .chain([None]);
+    let outputs = round_based::sim::run_with_setup(parties_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            let builder = cggmp21::share_recovery(eid, i, helpers, lost_index, key_info);
+            let builder = match share {
+                Some(share) => builder.set_key_share(share),
+                None => builder,
+            };
+            builder.start(&mut party_rng, party).await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    let (recovered, helpers_outputs) = outputs.split_last().unwrap();
+    assert!(helpers_outputs.iter().all(|output| output.is_none()));
+
+    let recovered = recovered
+        .as_ref()
+        .expect("recovering party must obtain key share");
+    let lost_share = &shares[usize::from(lost_index)].core;
+    assert_eq!(recovered.i, lost_index);
+    let (recovered_x, lost_x): (&Scalar<E>, &Scalar<E>) =
+        (recovered.x.as_ref(), lost_share.x.as_ref());
+    assert_eq!(recovered_x, lost_x);
+    assert_eq!(recovered.shared_public_key, lost_share.shared_public_key);
+    assert_eq!(recovered.public_shares, lost_share.public_shares);
+    assert_eq!(recovered.vss_setup, lost_share.vss_setup);
+    #[cfg(feature = "hd-wallet")]
+    assert_eq!(recovered.chain_code, lost_share.chain_code);
+}
+
+cggmp21_tests::test_suite! {
+    test: share_recovery_rejects_non_threshold_key,
+    generics: stark,
+    suites: {
+        n3: (3, &[0, 1], 2),
+    }
+}
+
+fn share_recovery_rejects_non_threshold_key<E>(n: u16, helpers: &[u16], lost_index: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(None, n, false)
+        .expect("retrieve cached shares");
+    let key_info = &shares[0].core.key_info;
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let parties_shares = helpers
+        .iter()
+        .map(|h| Some(&shares[usize::from(*h)]))
+        .chain([None]);
+    let results = round_based::sim::run_with_setup(parties_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            let builder = cggmp21::share_recovery(eid, i, helpers, lost_index, key_info);
+            let builder = match share {
+                Some(share) => builder.set_key_share(share),
+                None => builder,
+            };
+            builder.start(&mut party_rng, party).await
+        }
+    })
+    .unwrap();
+
+    for result in results {
+        let Err(err) = result else {
+            panic!("recovery of non-threshold key share must fail")
+        };
+        assert!(err.aborted().is_none());
+    }
+}
@@ -176,7 +176,8 @@ where
 
     let partial_signatures = presigs
         .into_iter()
-        .map(|presig| {
+        .zip(participants)
+        .map(|(presig, j)| {
             #[cfg(feature = "hd-wallet")]
             let presig = if let Some(derivation_path) = &derivation_path {
                 let epub = shares[0].extended_public_key().expect("not hd wallet");
@@ -189,7 +190,8 @@ where
             } else {
                 presig
             };
-            presig.issue_partial_signature(message_to_sign)
+            cggmp21_tests::issue_partial_signature(presig, &shares[usize::from(*j)], message_to_sign)
+                .expect("presignature mismatches key share")
         })
         .collect::<Vec<_>>();
 
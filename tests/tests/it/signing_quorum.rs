@@ -0,0 +1,135 @@
+use generic_ec::{coords::HasAffineX, Curve, Point};
+use rand::{seq::SliceRandom, Rng, RngCore};
+use rand_dev::DevRng;
+use sha2::Sha256;
+
+use cggmp21::key_share::AnyKeyShare;
+use cggmp21::signing::{DataToSign, PartialSignature};
+use cggmp21::{security_level::SecurityLevel128, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: signing_with_more_than_threshold_signers,
+    generics: stark,
+    suites: {
+        t2n3: (2, 3, 3, false),
+        t3n5: (3, 5, 4, false),
+        t3n5_five_rounds: (3, 5, 5, true),
+    }
+}
+
+fn signing_with_more_than_threshold_signers<E>(t: u16, n: u16, signers: u16, five_rounds: bool)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(Some(t), n, false)
+        .expect("retrieve cached shares");
+    assert_eq!(shares[0].min_signers(), t);
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let mut participants = (0..n).collect::<Vec<_>>();
+    participants.shuffle(&mut rng);
+    let participants = &participants[..usize::from(signers)];
+    println!("Signers: {participants:?}");
+    let participants_shares = participants.iter().map(|i| &shares[usize::from(*i)]);
+
+    let mut message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&message_to_sign);
+
+    let signature = if five_rounds {
+        let presigs = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+            let party = cggmp21_tests::buffer_outgoing(party);
+            let mut party_rng = rng.fork();
+            async move {
+                cggmp21::signing(eid, i, participants, share)
+                    .set_five_rounds_protocol()
+                    .generate_presignature(&mut party_rng, party)
+                    .await
+            }
+        })
+        .unwrap()
+        .expect_ok()
+        .into_vec();
+        let public_data = presigs[0].public_data();
+        let partial_signatures = presigs
+            .into_iter()
+            .zip(participants)
+            .map(|(presig, j)| {
+                cggmp21_tests::issue_partial_signature(
+                    presig,
+                    &shares[usize::from(*j)],
+                    message_to_sign,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        PartialSignature::combine_verified(&partial_signatures, &public_data, message_to_sign)
+            .expect("invalid partial signatures")
+    } else {
+        round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+            let party = cggmp21_tests::buffer_outgoing(party);
+            let mut party_rng = rng.fork();
+            async move {
+                cggmp21::signing(eid, i, participants, share)
+                    .sign(&mut party_rng, party, message_to_sign)
+                    .await
+            }
+        })
+        .unwrap()
+        .expect_ok()
+        .expect_eq()
+    };
+
+    signature
+        .verify(&shares[0].shared_public_key, &message_to_sign)
+        .expect("signature is not valid");
+}
+
+cggmp21_tests::test_suite! {
+    test: signing_with_less_than_threshold_signers_fails,
+    generics: stark,
+    suites: {
+        t3n5: (3, 5),
+    }
+}
+
+fn signing_with_less_than_threshold_signers_fails<E>(t: u16, n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(Some(t), n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+    let participants = &(0..t - 1).collect::<Vec<_>>();
+    let participants_shares = participants.iter().map(|i| &shares[usize::from(*i)]);
+
+    let results = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::signing(eid, i, participants, share)
+                .generate_presignature(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap();
+
+    for result in results {
+        let Err(err) = result else {
+            panic!("signing with less than threshold signers must fail")
+        };
+        assert!(err.aborted().is_none());
+    }
+}
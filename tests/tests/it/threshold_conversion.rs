@@ -0,0 +1,139 @@
+use generic_ec::{coords::HasAffineX, Curve, Point};
+use rand::{seq::SliceRandom, Rng, RngCore};
+use rand_dev::DevRng;
+use sha2::Sha256;
+
+use cggmp21::key_share::{reconstruct_secret_key, KeyShare, Validate};
+use cggmp21::signing::DataToSign;
+use cggmp21::{security_level::SecurityLevel128, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: conversion_to_threshold_works,
+    generics: stark,
+    suites: {
+        n3_to_t2: (3, 2, false),
+        n5_to_t3: (5, 3, false),
+        #[cfg(feature = "hd-wallet")]
+        n5_to_t3_hd: (5, 3, true),
+    }
+}
+
+fn conversion_to_threshold_works<E>(n: u16, t: u16, hd_wallet: bool)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    #[cfg(not(feature = "hd-wallet"))]
+    assert!(!hd_wallet);
+
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(None, n, hd_wallet)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let new_shares = round_based::sim::run_with_setup(&shares, |_i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::convert_to_threshold(eid, share, t)
+                .start(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    for (i, (new_share, old_share)) in new_shares.iter().zip(&shares).enumerate() {
+        assert_eq!(new_share.i, u16::try_from(i).unwrap());
+        assert_eq!(new_share.n(), n);
+        assert_eq!(new_share.min_signers(), t);
+        assert_eq!(new_share.shared_public_key, old_share.shared_public_key);
+        #[cfg(feature = "hd-wallet")]
+        assert_eq!(new_share.chain_code, old_share.chain_code);
+    }
+
+    // Any `t` parties can reconstruct the key
+    let mut signers = (0..n).collect::<Vec<_>>();
+    signers.shuffle(&mut rng);
+    let signers = &signers[..usize::from(t)];
+    let signers_shares = signers
+        .iter()
+        .map(|j| &new_shares[usize::from(*j)])
+        .collect::<Vec<_>>();
+    let sk = reconstruct_secret_key(&signers_shares).unwrap();
+    assert_eq!(Point::generator() * sk, shares[0].shared_public_key);
+
+    // Aux info of the original key shares completes the converted ones
+    let new_shares = new_shares
+        .into_iter()
+        .zip(shares)
+        .map(|(core, old)| {
+            let aux = old.into_inner().aux.validate().expect("invalid aux info");
+            KeyShare::from_parts((core, aux)).expect("invalid key share")
+        })
+        .collect::<Vec<KeyShare<E>>>();
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let mut message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&message_to_sign);
+
+    let signers_shares = signers.iter().map(|j| &new_shares[usize::from(*j)]);
+    let signature = round_based::sim::run_with_setup(signers_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::signing(eid, i, signers, share)
+                .sign(&mut party_rng, party, message_to_sign)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .expect_eq();
+
+    signature
+        .verify(&new_shares[0].shared_public_key, &message_to_sign)
+        .expect("signature is not valid");
+}
+
+cggmp21_tests::test_suite! {
+    test: threshold_key_cant_be_converted,
+    generics: stark,
+    suites: {
+        t3n5: (3, 5),
+    }
+}
+
+fn threshold_key_cant_be_converted<E>(t: u16, n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(Some(t), n, false)
+        .expect("retrieve cached shares");
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let results = round_based::sim::run_with_setup(&shares, |_i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::convert_to_threshold(eid, share, t)
+                .start(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap();
+    assert!(results.iter().all(|result| result.is_err()));
+}
@@ -0,0 +1,152 @@
+use generic_ec::{coords::HasAffineX, Curve, Point};
+use rand::{seq::SliceRandom, Rng, RngCore};
+use rand_dev::DevRng;
+use sha2::Sha256;
+
+use cggmp21::key_share::AnyKeyShare;
+use cggmp21::signing::DataToSign;
+use cggmp21::{security_level::SecurityLevel128, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: threshold_key_refresh_works,
+    generics: stark,
+    suites: {
+        t3n5: (3, 5, false, false),
+        t3n5_reliable: (3, 5, true, false),
+        #[cfg(feature = "hd-wallet")]
+        t3n5_hd: (3, 5, false, true),
+    }
+}
+
+fn threshold_key_refresh_works<E>(t: u16, n: u16, reliable_broadcast: bool, hd_wallet: bool)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    #[cfg(not(feature = "hd-wallet"))]
+    assert!(!hd_wallet);
+
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(Some(t), n, hd_wallet)
+        .expect("retrieve cached shares");
+    let mut primes = cggmp21_tests::CACHED_PRIMES.iter::<SecurityLevel128>();
+
+    // Perform refresh
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let key_shares = round_based::sim::run_with_setup(&shares, |_i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        let pregenerated_data = primes.next().expect("Can't fetch primes");
+        async move {
+            cggmp21::threshold_key_refresh(eid, share, pregenerated_data)
+                .enforce_reliable_broadcast(reliable_broadcast)
+                .start(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    // Validate key shares
+
+    for (i, (key_share, old_share)) in key_shares.iter().zip(&shares).enumerate() {
+        assert_eq!(key_share.core.i, u16::try_from(i).unwrap());
+        assert_eq!(key_share.min_signers(), t);
+        assert_eq!(
+            key_share.core.shared_public_key,
+            old_share.core.shared_public_key
+        );
+        assert_eq!(key_share.core.vss_setup, old_share.core.vss_setup);
+        assert_eq!(
+            key_share.core.public_shares,
+            key_shares[0].core.public_shares
+        );
+        assert_ne!(key_share.core.public_shares, old_share.core.public_shares);
+        assert_eq!(
+            Point::<E>::generator() * &key_share.core.x,
+            key_share.core.public_shares[i]
+        );
+        #[cfg(feature = "hd-wallet")]
+        assert_eq!(key_share.core.chain_code, old_share.core.chain_code);
+    }
+
+    // Sign with refreshed key shares
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let mut message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&message_to_sign);
+
+    let mut participants = (0..n).collect::<Vec<_>>();
+    participants.shuffle(&mut rng);
+    let participants = &participants[..usize::from(t)];
+    println!("Signers: {participants:?}");
+    let participants_shares = participants.iter().map(|i| &key_shares[usize::from(*i)]);
+
+    let sig = round_based::sim::run_with_setup(participants_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::signing(eid, i, participants, share)
+                .sign(&mut party_rng, party, message_to_sign)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .expect_eq();
+
+    sig.verify(&key_shares[0].core.shared_public_key, &message_to_sign)
+        .expect("signature is not valid");
+}
+
+cggmp21_tests::test_suite! {
+    test: non_threshold_key_refresh_rejects_threshold_share,
+    generics: stark,
+    suites: {
+        t3n5: (3, 5),
+    }
+}
+
+fn non_threshold_key_refresh_rejects_threshold_share<E>(t: u16, n: u16)
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+
+    let shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(Some(t), n, false)
+        .expect("retrieve cached shares");
+    let mut primes = cggmp21_tests::CACHED_PRIMES.iter::<SecurityLevel128>();
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let results = round_based::sim::run_with_setup(&shares, |_i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        let pregenerated_data = primes.next().expect("Can't fetch primes");
+        async move {
+            cggmp21::key_refresh(eid, share, pregenerated_data)
+                .start(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap();
+
+    for result in results {
+        let Err(err) = result else {
+            panic!("refresh of threshold share must fail")
+        };
+        assert!(err.aborted().is_none());
+    }
+}
@@ -0,0 +1,155 @@
+use generic_ec::{coords::HasAffineX, Curve, Point};
+use rand::{Rng, RngCore};
+use rand_dev::DevRng;
+use sha2::Sha256;
+
+use cggmp21::key_share::{
+    reconstruct_secret_key, AnyKeyShare, IncompleteKeyShare, KeyShare, Validate,
+};
+use cggmp21::signing::DataToSign;
+use cggmp21::{security_level::SecurityLevel128, ExecutionId};
+
+cggmp21_tests::test_suite! {
+    test: weighted_signing_works,
+    generics: stark,
+    suites: {
+        w211_t3: (&[2, 1, 1], 3, &[0, 2]),
+        w321_t4: (&[3, 2, 1], 4, &[0, 2]),
+        w321_t4_all: (&[3, 2, 1], 4, &[2, 1, 0]),
+    }
+}
+
+fn weighted_signing_works<E>(weights: &[u16], t: u16, signers: &[u16])
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+    let shares = weighted_keygen::<E>(weights, t, &mut rng);
+
+    for (i, share) in (0u16..).zip(&shares) {
+        assert_eq!(share.n(), u16::try_from(weights.len()).unwrap());
+        assert_eq!(share.min_signers(), t);
+        assert_eq!(share.core.weight(i), Some(weights[usize::from(i)]));
+        assert_eq!(
+            share.core.secret_shares().count(),
+            usize::from(weights[usize::from(i)])
+        );
+
+        // Weighted key share survives serialization
+        let serialized = serde_json::to_vec(&share.core).unwrap();
+        let deserialized: IncompleteKeyShare<E> = serde_json::from_slice(&serialized).unwrap();
+        assert_eq!(
+            deserialized.secret_shares().count(),
+            share.core.secret_shares().count()
+        );
+    }
+
+    // Secret key can be reconstructed from shares of parties with enough total weight
+    let signers_shares = signers
+        .iter()
+        .map(|j| &shares[usize::from(*j)])
+        .collect::<Vec<_>>();
+    let sk = reconstruct_secret_key(&signers_shares).unwrap();
+    assert_eq!(Point::generator() * sk, shares[0].shared_public_key);
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let mut message_to_sign = [0u8; 100];
+    rng.fill_bytes(&mut message_to_sign);
+    let message_to_sign = DataToSign::digest::<Sha256>(&message_to_sign);
+
+    let signature = round_based::sim::run_with_setup(signers_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::signing(eid, i, signers, share)
+                .sign(&mut party_rng, party, message_to_sign)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .expect_eq();
+
+    signature
+        .verify(&shares[0].shared_public_key, &message_to_sign)
+        .expect("signature is not valid");
+}
+
+cggmp21_tests::test_suite! {
+    test: signing_with_not_enough_weight_fails,
+    generics: stark,
+    suites: {
+        w321_t4: (&[3, 2, 1], 4, &[1, 2]),
+    }
+}
+
+fn signing_with_not_enough_weight_fails<E>(weights: &[u16], t: u16, signers: &[u16])
+where
+    E: Curve + cggmp21_tests::CurveParams,
+    Point<E>: HasAffineX<E>,
+{
+    let mut rng = DevRng::new();
+    let shares = weighted_keygen::<E>(weights, t, &mut rng);
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let signers_shares = signers.iter().map(|j| &shares[usize::from(*j)]);
+    let results = round_based::sim::run_with_setup(signers_shares, |i, party, share| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::signing(eid, i, signers, share)
+                .generate_presignature(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap();
+    assert!(results.iter().all(|result| result.is_err()));
+}
+
+/// Carries out weighted keygen and completes key shares with cached aux info
+fn weighted_keygen<E>(weights: &[u16], t: u16, rng: &mut DevRng) -> Vec<KeyShare<E>>
+where
+    E: Curve + cggmp21_tests::CurveParams,
+{
+    let n = u16::try_from(weights.len()).unwrap();
+
+    let eid: [u8; 32] = rng.gen();
+    let eid = ExecutionId::new(&eid);
+
+    let incomplete_shares: Vec<IncompleteKeyShare<E>> = round_based::sim::run(n, |i, party| {
+        let party = cggmp21_tests::buffer_outgoing(party);
+        let mut party_rng = rng.fork();
+        async move {
+            cggmp21::keygen::<E>(eid, i, n)
+                .set_threshold(t)
+                .set_weights(weights.to_vec())
+                .start(&mut party_rng, party)
+                .await
+        }
+    })
+    .unwrap()
+    .expect_ok()
+    .into_vec();
+
+    // Aux info doesn't depend on the key, so we take it from cached key shares
+    let cached_shares = cggmp21_tests::CACHED_SHARES
+        .get_shares::<E, SecurityLevel128>(None, n, false)
+        .expect("retrieve cached shares");
+    incomplete_shares
+        .into_iter()
+        .zip(cached_shares)
+        .map(|(core, cached)| {
+            let aux = cached
+                .into_inner()
+                .aux
+                .validate()
+                .expect("invalid aux info");
+            KeyShare::from_parts((core, aux)).expect("invalid key share")
+        })
+        .collect()
+}